@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: MIT
+
+//! Accessors and map lookups on `Value`.
+//!
+//! Each accessor returns `Some` only for the matching variant, so a caller can inspect a
+//! decoded tree without writing out a `match`. The `Index` implementations follow the
+//! convention of JSON libraries, returning `null` rather than panicking when an item is
+//! missing, so lookups can be chained.
+
+use core::ops::Index;
+
+use crate::Value;
+
+/// The value returned by `Index` for missing items.
+static NULL: Value<'static> = Value::null();
+
+impl<'a> Value<'a> {
+    /// Returns the value of an unsigned integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// assert_eq!(Value::unsigned(7).as_u64(), Some(7));
+    /// assert_eq!(Value::negative(-7).as_u64(), None);
+    /// ```
+    #[inline]
+    pub const fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Unsigned(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an unsigned or negative integer.
+    ///
+    /// Every CBOR integer, from -2^64 to 2^64-1, fits in an `i128`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// assert_eq!(Value::unsigned(7).as_i128(), Some(7));
+    /// assert_eq!(Value::Negative(u64::MAX).as_i128(), Some(-18446744073709551616));
+    /// ```
+    #[inline]
+    pub const fn as_i128(&self) -> Option<i128> {
+        match self {
+            Value::Unsigned(n) => Some(*n as i128),
+            Value::Negative(n) => Some(-1 - *n as i128),
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of a byte string.
+    #[inline]
+    pub const fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(*bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of a text string.
+    #[inline]
+    pub const fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Value::Text(text) => Some(*text),
+            _ => None,
+        }
+    }
+
+    /// Returns the items of an array.
+    #[inline]
+    pub const fn as_array(&self) -> Option<&'a [Value<'a>]> {
+        match self {
+            Value::Array(items) => Some(*items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a map, in their stored order.
+    #[inline]
+    pub const fn as_map(&self) -> Option<&'a [(Value<'a>, Value<'a>)]> {
+        match self {
+            Value::Map(pairs) => Some(*pairs),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a boolean (simple value 20 or 21).
+    #[inline]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Simple(20) => Some(false),
+            Value::Simple(21) => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is null (simple value 22).
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Value::Simple(22))
+    }
+
+    /// Returns the value of a float.
+    ///
+    /// Integers are not converted, since most of them cannot be represented exactly.
+    #[inline]
+    pub const fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of the outermost tag.
+    #[inline]
+    pub const fn tag_number(&self) -> Option<u64> {
+        match self {
+            Value::Tag(tag, _) => Some(*tag),
+            _ => None,
+        }
+    }
+
+    /// Returns the item inside any number of tags, or the value itself if it is untagged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let time = Value::unsigned(1700000000);
+    /// let tagged = Value::tag(1, &time);
+    /// let self_described = Value::tag(55799, &tagged);
+    /// assert_eq!(self_described.untagged(), &time);
+    /// assert_eq!(time.untagged(), &time);
+    /// ```
+    #[inline]
+    pub const fn untagged(&self) -> &Value<'a> {
+        let mut value = self;
+        while let Value::Tag(_, item) = value {
+            value = item;
+        }
+        value
+    }
+
+    /// Looks up a key in a map.
+    ///
+    /// Keys are compared with `PartialEq`, so an integer key does not match a float key
+    /// of the same value. The first matching entry wins.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Value)` - The value stored under `key`.
+    /// * `None` - If the value is not a map or has no such key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let pairs = [(Value::bool(true), Value::text("yes"))];
+    /// let map = Value::map(&pairs);
+    /// assert_eq!(map.get(&Value::bool(true)), Some(&Value::text("yes")));
+    /// assert_eq!(map.get(&Value::null()), None);
+    /// ```
+    pub fn get(&self, key: &Value) -> Option<&'a Value<'a>> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Looks up a text key in a map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let pairs = [(Value::text("name"), Value::text("Alex"))];
+    /// let map = Value::map(&pairs);
+    /// assert_eq!(map.get_str("name").and_then(Value::as_str), Some("Alex"));
+    /// ```
+    pub const fn get_str(&self, key: &str) -> Option<&'a Value<'a>> {
+        let pairs = match self {
+            Value::Map(pairs) => *pairs,
+            _ => return None,
+        };
+
+        let mut i = 0;
+        while i < pairs.len() {
+            if let Value::Text(text) = pairs[i].0 {
+                if bytes_eq(text.as_bytes(), key.as_bytes()) {
+                    return Some(&pairs[i].1);
+                }
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Looks up an integer key in a map, as used by COSE and CWT labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let pairs = [
+    ///     (Value::unsigned(1), Value::unsigned(2)),
+    ///     (Value::negative(-1), Value::unsigned(1)),
+    /// ];
+    /// let key = Value::map(&pairs);
+    /// assert_eq!(key.get_int(-1).and_then(Value::as_u64), Some(1));
+    /// assert_eq!(key.get_int(3), None);
+    /// ```
+    pub const fn get_int(&self, key: i64) -> Option<&'a Value<'a>> {
+        let pairs = match self {
+            Value::Map(pairs) => *pairs,
+            _ => return None,
+        };
+
+        let mut i = 0;
+        while i < pairs.len() {
+            if let Some(n) = pairs[i].0.as_i128() {
+                if n == key as i128 {
+                    return Some(&pairs[i].1);
+                }
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+/// Compares two byte slices in a `const fn`.
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl<'a> Index<usize> for Value<'a> {
+    type Output = Value<'a>;
+
+    /// Returns an array item, or null if the value is not an array or is too short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let items = [Value::unsigned(1), Value::unsigned(2)];
+    /// let array = Value::array(&items);
+    /// assert_eq!(array[1], Value::unsigned(2));
+    /// assert!(array[2].is_null());
+    /// ```
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.as_array().and_then(|items| items.get(index)) {
+            Some(item) => item,
+            None => &NULL,
+        }
+    }
+}
+
+impl<'a> Index<&str> for Value<'a> {
+    type Output = Value<'a>;
+
+    /// Returns the value under a text key, or null if the value is not a map or has no
+    /// such key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let items = [Value::text("rust")];
+    /// let pairs = [(Value::text("tags"), Value::array(&items))];
+    /// let map = Value::map(&pairs);
+    /// assert_eq!(map["tags"][0], Value::text("rust"));
+    /// assert!(map["missing"][0].is_null());
+    /// ```
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get_str(key).unwrap_or(&NULL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn test_scalar_accessors() {
+        let bytes = [0x01];
+        assert_eq!(Value::unsigned(1).as_u64(), Some(1));
+        assert_eq!(Value::float(1.0).as_u64(), None);
+        assert_eq!(Value::Unsigned(u64::MAX).as_i128(), Some(u64::MAX as i128));
+        assert_eq!(Value::negative(-1).as_i128(), Some(-1));
+        assert_eq!(Value::text("1").as_i128(), None);
+        assert_eq!(Value::bytes(&bytes).as_bytes(), Some(&bytes[..]));
+        assert_eq!(Value::text("a").as_bytes(), None);
+        assert_eq!(Value::text("a").as_str(), Some("a"));
+        assert_eq!(Value::bytes(b"a").as_str(), None);
+        assert_eq!(Value::bool(true).as_bool(), Some(true));
+        assert_eq!(Value::bool(false).as_bool(), Some(false));
+        assert_eq!(Value::null().as_bool(), None);
+        assert!(Value::null().is_null());
+        assert!(!Value::Simple(23).is_null());
+        assert_eq!(Value::float(1.5).as_f64(), Some(1.5));
+        assert_eq!(Value::unsigned(1).as_f64(), None);
+    }
+
+    #[test]
+    fn test_container_accessors() {
+        let items = [Value::unsigned(1)];
+        let pairs = [(Value::unsigned(1), Value::null())];
+        assert_eq!(Value::array(&items).as_array(), Some(&items[..]));
+        assert_eq!(Value::map(&pairs).as_array(), None);
+        assert_eq!(Value::map(&pairs).as_map(), Some(&pairs[..]));
+        assert_eq!(Value::array(&items).as_map(), None);
+
+        let tagged = Value::tag(24, &items[0]);
+        assert_eq!(tagged.tag_number(), Some(24));
+        assert_eq!(items[0].tag_number(), None);
+        assert_eq!(tagged.untagged(), &items[0]);
+    }
+
+    #[test]
+    fn test_const_accessors() {
+        const PAIRS: [(Value, Value); 2] = [
+            (Value::text("id"), Value::unsigned(7)),
+            (Value::negative(-1), Value::text("key")),
+        ];
+        const MAP: Value = Value::map(&PAIRS);
+        const ID: Option<u64> = match MAP.get_str("id") {
+            Some(value) => value.as_u64(),
+            None => None,
+        };
+        assert_eq!(ID, Some(7));
+        assert_eq!(MAP.get_int(-1), Some(&Value::text("key")));
+    }
+
+    #[test]
+    fn test_map_lookup() {
+        let pairs = [
+            (Value::text("a"), Value::unsigned(1)),
+            (Value::unsigned(1), Value::unsigned(2)),
+            (Value::float(1.0), Value::unsigned(3)),
+            (Value::text("a"), Value::unsigned(4)),
+        ];
+        let map = Value::map(&pairs);
+
+        assert_eq!(map.get(&Value::text("a")), Some(&Value::unsigned(1)));
+        assert_eq!(map.get(&Value::float(1.0)), Some(&Value::unsigned(3)));
+        assert_eq!(map.get(&Value::text("b")), None);
+        assert_eq!(map.get_str("a"), Some(&Value::unsigned(1)));
+        assert_eq!(map.get_str("A"), None);
+        assert_eq!(map.get_int(1), Some(&Value::unsigned(2)));
+        assert_eq!(map.get_int(-2), None);
+
+        // Lookups on anything but a map find nothing
+        assert_eq!(Value::unsigned(1).get(&Value::unsigned(1)), None);
+        assert_eq!(Value::text("a").get_str("a"), None);
+        assert_eq!(Value::array(&[]).get_int(0), None);
+    }
+
+    #[test]
+    fn test_index() {
+        let inner = [(Value::text("b"), Value::unsigned(2))];
+        let items = [Value::unsigned(1), Value::map(&inner)];
+        let pairs = [(Value::text("a"), Value::array(&items))];
+        let map = Value::map(&pairs);
+
+        assert_eq!(map["a"][0], Value::unsigned(1));
+        assert_eq!(map["a"][1]["b"], Value::unsigned(2));
+        assert!(map["b"].is_null());
+        assert!(map["a"][2].is_null());
+        assert!(map[0].is_null());
+        assert!(map["a"]["b"].is_null());
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MIT
+
+//! Caller-supplied storage for building `Value` trees.
+//!
+//! A [`Value`] borrows its child nodes rather than owning them, so building a tree at
+//! runtime needs somewhere for those nodes to live. An [`Arena`] hands out slices of two
+//! caller-provided buffers, one for array items and tagged items, and one for map
+//! entries, without allocating.
+
+use crate::{Value, error::Error, result::Result};
+
+/// Fixed storage from which the child nodes of decoded `Value` trees are allocated.
+///
+/// Every array item and tagged item takes one slot in the value buffer, and every map
+/// entry takes one slot in the pair buffer. Allocation fails with
+/// `Error::BufferOverflow` once either buffer is exhausted.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, Value, decode::decode};
+///
+/// let bytes = [0x82, 0x01, 0xA1, 0x61, 0x61, 0x02];
+///
+/// let mut values = [Value::null(); 4];
+/// let mut pairs = [(Value::null(), Value::null()); 2];
+/// let mut arena = Arena::new(&mut values, &mut pairs);
+///
+/// let value = decode(&bytes, &mut arena).unwrap();
+/// let map = [(Value::text("a"), Value::unsigned(2))];
+/// let items = [Value::unsigned(1), Value::map(&map)];
+/// assert_eq!(value, Value::array(&items));
+/// assert_eq!(arena.remaining_values(), 2);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Arena<'a> {
+    values: &'a mut [Value<'a>],
+    pairs: &'a mut [(Value<'a>, Value<'a>)],
+}
+
+impl<'a> Arena<'a> {
+    /// Creates an arena over the given value and map entry buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Storage for array items and tagged items.
+    /// * `pairs` - Storage for map entries.
+    #[inline]
+    pub const fn new(values: &'a mut [Value<'a>], pairs: &'a mut [(Value<'a>, Value<'a>)]) -> Self {
+        Self { values, pairs }
+    }
+
+    /// Returns the number of unused value slots.
+    #[inline]
+    pub const fn remaining_values(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of unused map entry slots.
+    #[inline]
+    pub const fn remaining_pairs(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Allocates `len` value slots.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut [Value])` - The slots, which live as long as the arena's buffers.
+    /// * `Err(Error::BufferOverflow)` - If fewer than `len` slots remain.
+    #[inline]
+    pub fn alloc_values(&mut self, len: usize) -> Result<&'a mut [Value<'a>]> {
+        if len > self.values.len() {
+            return Err(Error::BufferOverflow);
+        }
+
+        let (head, tail) = core::mem::take(&mut self.values).split_at_mut(len);
+        self.values = tail;
+        Ok(head)
+    }
+
+    /// Allocates `len` map entry slots.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&mut [(Value, Value)])` - The slots, which live as long as the arena's buffers.
+    /// * `Err(Error::BufferOverflow)` - If fewer than `len` slots remain.
+    #[inline]
+    pub fn alloc_pairs(&mut self, len: usize) -> Result<&'a mut [(Value<'a>, Value<'a>)]> {
+        if len > self.pairs.len() {
+            return Err(Error::BufferOverflow);
+        }
+
+        let (head, tail) = core::mem::take(&mut self.pairs).split_at_mut(len);
+        self.pairs = tail;
+        Ok(head)
+    }
+
+    /// Allocates a single value slot holding `value`, for use as a tagged item.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&Value)` - The stored value.
+    /// * `Err(Error::BufferOverflow)` - If no value slots remain.
+    #[inline]
+    pub fn alloc(&mut self, value: Value<'a>) -> Result<&'a Value<'a>> {
+        let slot = self.alloc_values(1)?;
+        slot[0] = value;
+        let slot: &'a [Value<'a>] = slot;
+        Ok(&slot[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+    use crate::{Value, error::Error};
+
+    #[test]
+    fn test_alloc_values() {
+        let mut values = [Value::null(); 3];
+        let mut pairs = [];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+
+        let first = arena.alloc_values(2).unwrap();
+        first[0] = Value::unsigned(1);
+        first[1] = Value::unsigned(2);
+        assert_eq!(arena.remaining_values(), 1);
+
+        assert_eq!(arena.alloc_values(2), Err(Error::BufferOverflow));
+        assert_eq!(arena.remaining_values(), 1);
+
+        let tagged = arena.alloc(Value::text("x")).unwrap();
+        assert_eq!(tagged, &Value::text("x"));
+        assert_eq!(arena.remaining_values(), 0);
+        assert_eq!(arena.alloc(Value::null()), Err(Error::BufferOverflow));
+
+        // Earlier allocations are unaffected by later ones
+        assert_eq!(first, &[Value::unsigned(1), Value::unsigned(2)]);
+    }
+
+    #[test]
+    fn test_alloc_pairs() {
+        let mut values = [];
+        let mut pairs = [(Value::null(), Value::null()); 1];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+
+        assert_eq!(arena.alloc_pairs(0).map(|pairs| pairs.len()), Ok(0));
+        assert_eq!(arena.alloc_pairs(1).map(|pairs| pairs.len()), Ok(1));
+        assert_eq!(arena.remaining_pairs(), 0);
+        assert_eq!(arena.alloc_pairs(1), Err(Error::BufferOverflow));
+    }
+}
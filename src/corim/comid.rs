@@ -0,0 +1,1461 @@
+// SPDX-License-Identifier: MIT
+
+//! CoMID tags and their triples.
+
+use super::{
+    BYTES, COSE_KEY, MIN_SVN, PKIX_BASE64_CERT, PKIX_BASE64_KEY, SVN, THUMBPRINT, TagId, UEID,
+    typed,
+};
+use crate::{
+    Value,
+    cose::key::CoseKey,
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    list::{Item, List, fields, text, unsigned},
+    result::Result,
+    tag,
+};
+
+/// An identifier that carries its kind as a tag, used for classes, instances and groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaggedId<'a> {
+    /// An object identifier, in tag 111.
+    Oid(&'a [u8]),
+    /// A 16-byte UUID, in tag 37.
+    Uuid(&'a [u8]),
+    /// A UEID, in tag 550.
+    Ueid(&'a [u8]),
+    /// Opaque bytes, in tag 560.
+    Bytes(&'a [u8]),
+}
+
+impl TaggedId<'_> {
+    /// Writes the tagged identifier.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let (tag, bytes) = match *self {
+            Self::Oid(bytes) => (tag::OID, bytes),
+            Self::Uuid(bytes) => (tag::UUID, bytes),
+            Self::Ueid(bytes) => (UEID, bytes),
+            Self::Bytes(bytes) => (BYTES, bytes),
+        };
+        sink.header(major_type::TAG, tag)?;
+        sink.value(&Value::Bytes(bytes))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for TaggedId<'a> {
+    type Error = Error;
+
+    /// Reads a tagged identifier.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TaggedId)` - The identifier.
+    /// * `Err(Error::InvalidType)` - If the value is not a byte string in one of the
+    ///   identifier tags.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Tag(tag::OID, Value::Bytes(bytes)) => Ok(Self::Oid(bytes)),
+            Value::Tag(tag::UUID, Value::Bytes(bytes)) => Ok(Self::Uuid(bytes)),
+            Value::Tag(UEID, Value::Bytes(bytes)) => Ok(Self::Ueid(bytes)),
+            Value::Tag(BYTES, Value::Bytes(bytes)) => Ok(Self::Bytes(bytes)),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The class of an environment: what kind of thing it is, rather than which one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Class<'a> {
+    id: Option<TaggedId<'a>>,
+    vendor: Option<&'a str>,
+    model: Option<&'a str>,
+    layer: Option<u64>,
+    index: Option<u64>,
+}
+
+impl<'a> Class<'a> {
+    /// Creates an empty class. At least one field must be set before encoding.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            id: None,
+            vendor: None,
+            model: None,
+            layer: None,
+            index: None,
+        }
+    }
+
+    /// Returns the class with the given class identifier.
+    #[inline]
+    pub const fn with_id(self, id: TaggedId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    /// Returns the class with the given vendor.
+    #[inline]
+    pub const fn with_vendor(self, vendor: &'a str) -> Self {
+        Self {
+            vendor: Some(vendor),
+            ..self
+        }
+    }
+
+    /// Returns the class with the given model.
+    #[inline]
+    pub const fn with_model(self, model: &'a str) -> Self {
+        Self {
+            model: Some(model),
+            ..self
+        }
+    }
+
+    /// Returns the class with the given layer, such as a DICE layer.
+    #[inline]
+    pub const fn with_layer(self, layer: u64) -> Self {
+        Self {
+            layer: Some(layer),
+            ..self
+        }
+    }
+
+    /// Returns the class with the given index, telling apart identical environments.
+    #[inline]
+    pub const fn with_index(self, index: u64) -> Self {
+        Self {
+            index: Some(index),
+            ..self
+        }
+    }
+
+    /// Returns the class identifier.
+    #[inline]
+    pub const fn id(&self) -> Option<TaggedId<'a>> {
+        self.id
+    }
+
+    /// Returns the vendor.
+    #[inline]
+    pub const fn vendor(&self) -> Option<&'a str> {
+        self.vendor
+    }
+
+    /// Returns the model.
+    #[inline]
+    pub const fn model(&self) -> Option<&'a str> {
+        self.model
+    }
+
+    /// Returns the layer.
+    #[inline]
+    pub const fn layer(&self) -> Option<u64> {
+        self.layer
+    }
+
+    /// Returns the index.
+    #[inline]
+    pub const fn index(&self) -> Option<u64> {
+        self.index
+    }
+
+    /// Writes the class map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let len = self.id.is_some() as u64
+            + self.vendor.is_some() as u64
+            + self.model.is_some() as u64
+            + self.layer.is_some() as u64
+            + self.index.is_some() as u64;
+        if len == 0 {
+            return Err(Error::InvalidValue);
+        }
+        sink.header(major_type::MAP, len)?;
+        if let Some(id) = &self.id {
+            sink.value(&Value::Unsigned(0))?;
+            id.write(sink)?;
+        }
+        let texts = [(1, self.vendor), (2, self.model)];
+        for (key, text) in texts {
+            if let Some(text) = text {
+                sink.value(&Value::Unsigned(key))?;
+                sink.value(&Value::Text(text))?;
+            }
+        }
+        let numbers = [(3, self.layer), (4, self.index)];
+        for (key, number) in numbers {
+            if let Some(number) = number {
+                sink.value(&Value::Unsigned(key))?;
+                sink.value(&Value::Unsigned(number))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Class<'a> {
+    type Error = Error;
+
+    /// Reads a class map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Class)` - The class.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a field has the wrong
+    ///   type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        Ok(Self {
+            id: typed(map.get_int(0))?,
+            vendor: text(map.get_int(1))?,
+            model: text(map.get_int(2))?,
+            layer: unsigned(map.get_int(3))?,
+            index: unsigned(map.get_int(4))?,
+        })
+    }
+}
+
+/// An environment, such as a firmware layer of a device, that triples describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Environment<'a> {
+    class: Option<Class<'a>>,
+    instance: Option<TaggedId<'a>>,
+    group: Option<TaggedId<'a>>,
+}
+
+impl<'a> Environment<'a> {
+    /// Creates an empty environment. At least one field must be set before encoding.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            class: None,
+            instance: None,
+            group: None,
+        }
+    }
+
+    /// Returns the environment with the given class.
+    #[inline]
+    pub const fn with_class(self, class: Class<'a>) -> Self {
+        Self {
+            class: Some(class),
+            ..self
+        }
+    }
+
+    /// Returns the environment with the given instance, such as a UEID.
+    #[inline]
+    pub const fn with_instance(self, instance: TaggedId<'a>) -> Self {
+        Self {
+            instance: Some(instance),
+            ..self
+        }
+    }
+
+    /// Returns the environment with the given group.
+    #[inline]
+    pub const fn with_group(self, group: TaggedId<'a>) -> Self {
+        Self {
+            group: Some(group),
+            ..self
+        }
+    }
+
+    /// Returns the class.
+    #[inline]
+    pub const fn class(&self) -> Option<Class<'a>> {
+        self.class
+    }
+
+    /// Returns the instance.
+    #[inline]
+    pub const fn instance(&self) -> Option<TaggedId<'a>> {
+        self.instance
+    }
+
+    /// Returns the group.
+    #[inline]
+    pub const fn group(&self) -> Option<TaggedId<'a>> {
+        self.group
+    }
+
+    /// Writes the environment map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let len = self.class.is_some() as u64
+            + self.instance.is_some() as u64
+            + self.group.is_some() as u64;
+        if len == 0 {
+            return Err(Error::InvalidValue);
+        }
+        sink.header(major_type::MAP, len)?;
+        if let Some(class) = &self.class {
+            sink.value(&Value::Unsigned(0))?;
+            class.write(sink)?;
+        }
+        let ids = [(1, self.instance), (2, self.group)];
+        for (key, id) in ids {
+            if let Some(id) = id {
+                sink.value(&Value::Unsigned(key))?;
+                id.write(sink)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Environment<'a> {
+    type Error = Error;
+
+    /// Reads an environment map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Environment)` - The environment.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a field has the wrong
+    ///   type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        Ok(Self {
+            class: typed(map.get_int(0))?,
+            instance: typed(map.get_int(1))?,
+            group: typed(map.get_int(2))?,
+        })
+    }
+}
+
+/// A digest, as `[algorithm, value]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digest<'a> {
+    algorithm: i64,
+    value: &'a [u8],
+}
+
+impl<'a> Digest<'a> {
+    /// Creates a digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The Named Information hash algorithm, such as
+    ///   [`SHA_256`](super::SHA_256).
+    /// * `value` - The digest value.
+    #[inline]
+    pub const fn new(algorithm: i64, value: &'a [u8]) -> Self {
+        Self { algorithm, value }
+    }
+
+    /// Returns the hash algorithm.
+    #[inline]
+    pub const fn algorithm(&self) -> i64 {
+        self.algorithm
+    }
+
+    /// Returns the digest value.
+    #[inline]
+    pub const fn value(&self) -> &'a [u8] {
+        self.value
+    }
+}
+
+impl Item for Digest<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        sink.value(&Value::integer(self.algorithm))?;
+        sink.value(&Value::Bytes(self.value))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Digest<'a> {
+    type Error = Error;
+
+    /// Reads a digest.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Digest)` - The digest.
+    /// * `Err(Error::InvalidType)` - If the value is not an `[int, bstr]` array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([algorithm, Value::Bytes(digest)]) => {
+                match algorithm.as_i128().map(i64::try_from) {
+                    Some(Ok(algorithm)) => Ok(Self::new(algorithm, digest)),
+                    _ => Err(Error::InvalidType),
+                }
+            }
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A security version number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Svn {
+    /// The exact security version.
+    Exact(u64),
+    /// The minimum security version that is acceptable.
+    Min(u64),
+}
+
+impl Svn {
+    /// Writes the security version number, untagged if exact and in tag 553 if minimum.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match *self {
+            Self::Exact(svn) => sink.value(&Value::Unsigned(svn)),
+            Self::Min(svn) => {
+                sink.header(major_type::TAG, MIN_SVN)?;
+                sink.value(&Value::Unsigned(svn))
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Svn {
+    type Error = Error;
+
+    /// Reads a security version number, untagged or in tag 552 or 553.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Svn)` - The security version number.
+    /// * `Err(Error::InvalidType)` - If the value is not an unsigned integer.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Unsigned(svn) | Value::Tag(SVN, Value::Unsigned(svn)) => Ok(Self::Exact(*svn)),
+            Value::Tag(MIN_SVN, Value::Unsigned(svn)) => Ok(Self::Min(*svn)),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A version, with the scheme it follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version<'a> {
+    version: &'a str,
+    scheme: Option<i64>,
+}
+
+impl<'a> Version<'a> {
+    /// Creates a version.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version, such as `"1.2.3"`.
+    /// * `scheme` - The CoSWID version scheme, such as 16384 for semantic versioning.
+    #[inline]
+    pub const fn new(version: &'a str, scheme: Option<i64>) -> Self {
+        Self { version, scheme }
+    }
+
+    /// Returns the version.
+    #[inline]
+    pub const fn version(&self) -> &'a str {
+        self.version
+    }
+
+    /// Returns the version scheme.
+    #[inline]
+    pub const fn scheme(&self) -> Option<i64> {
+        self.scheme
+    }
+
+    /// Writes the version map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::MAP, 1 + self.scheme.is_some() as u64)?;
+        sink.value(&Value::Unsigned(0))?;
+        sink.value(&Value::Text(self.version))?;
+        if let Some(scheme) = self.scheme {
+            sink.value(&Value::Unsigned(1))?;
+            sink.value(&Value::integer(scheme))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Version<'a> {
+    type Error = Error;
+
+    /// Reads a version map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Version)` - The version.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the version is missing,
+    ///   or a field has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let version = text(map.get_int(0))?.ok_or(Error::InvalidType)?;
+        let scheme = match map
+            .get_int(1)
+            .map(|scheme| scheme.as_i128().map(i64::try_from))
+        {
+            Some(Some(Ok(scheme))) => Some(scheme),
+            Some(_) => return Err(Error::InvalidType),
+            None => None,
+        };
+        Ok(Self::new(version, scheme))
+    }
+}
+
+/// The values of a measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MeasurementValues<'a> {
+    version: Option<Version<'a>>,
+    svn: Option<Svn>,
+    digests: List<'a, Digest<'a>>,
+    raw_value: Option<&'a [u8]>,
+    serial_number: Option<&'a str>,
+    name: Option<&'a str>,
+}
+
+impl<'a> MeasurementValues<'a> {
+    /// Creates empty measurement values. At least one field must be set before encoding.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            version: None,
+            svn: None,
+            digests: List::Items(&[]),
+            raw_value: None,
+            serial_number: None,
+            name: None,
+        }
+    }
+
+    /// Returns the values with the given version.
+    #[inline]
+    pub const fn with_version(self, version: Version<'a>) -> Self {
+        Self {
+            version: Some(version),
+            ..self
+        }
+    }
+
+    /// Returns the values with the given security version number.
+    #[inline]
+    pub const fn with_svn(self, svn: Svn) -> Self {
+        Self {
+            svn: Some(svn),
+            ..self
+        }
+    }
+
+    /// Returns the values with the given digests, one per hash algorithm.
+    #[inline]
+    pub const fn with_digests(self, digests: &'a [Digest<'a>]) -> Self {
+        Self {
+            digests: List::Items(digests),
+            ..self
+        }
+    }
+
+    /// Returns the values with the given raw value, such as a register's contents.
+    #[inline]
+    pub const fn with_raw_value(self, raw_value: &'a [u8]) -> Self {
+        Self {
+            raw_value: Some(raw_value),
+            ..self
+        }
+    }
+
+    /// Returns the values with the given serial number.
+    #[inline]
+    pub const fn with_serial_number(self, serial_number: &'a str) -> Self {
+        Self {
+            serial_number: Some(serial_number),
+            ..self
+        }
+    }
+
+    /// Returns the values with the given name.
+    #[inline]
+    pub const fn with_name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    /// Returns the version.
+    #[inline]
+    pub const fn version(&self) -> Option<Version<'a>> {
+        self.version
+    }
+
+    /// Returns the security version number.
+    #[inline]
+    pub const fn svn(&self) -> Option<Svn> {
+        self.svn
+    }
+
+    /// Returns the digests.
+    #[inline]
+    pub const fn digests(&self) -> List<'a, Digest<'a>> {
+        self.digests
+    }
+
+    /// Returns the raw value.
+    #[inline]
+    pub const fn raw_value(&self) -> Option<&'a [u8]> {
+        self.raw_value
+    }
+
+    /// Returns the serial number.
+    #[inline]
+    pub const fn serial_number(&self) -> Option<&'a str> {
+        self.serial_number
+    }
+
+    /// Returns the name.
+    #[inline]
+    pub const fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    /// Writes the measurement values map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let len = self.version.is_some() as u64
+            + self.svn.is_some() as u64
+            + !self.digests.is_empty() as u64
+            + self.raw_value.is_some() as u64
+            + self.serial_number.is_some() as u64
+            + self.name.is_some() as u64;
+        if len == 0 {
+            return Err(Error::InvalidValue);
+        }
+        sink.header(major_type::MAP, len)?;
+        if let Some(version) = &self.version {
+            sink.value(&Value::Unsigned(0))?;
+            version.write(sink)?;
+        }
+        if let Some(svn) = &self.svn {
+            sink.value(&Value::Unsigned(1))?;
+            svn.write(sink)?;
+        }
+        if !self.digests.is_empty() {
+            sink.value(&Value::Unsigned(2))?;
+            self.digests.write(sink)?;
+        }
+        if let Some(raw_value) = self.raw_value {
+            sink.value(&Value::Unsigned(4))?;
+            sink.header(major_type::TAG, BYTES)?;
+            sink.value(&Value::Bytes(raw_value))?;
+        }
+        let texts = [(8, self.serial_number), (11, self.name)];
+        for (key, text) in texts {
+            if let Some(text) = text {
+                sink.value(&Value::Unsigned(key))?;
+                sink.value(&Value::Text(text))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MeasurementValues<'a> {
+    type Error = Error;
+
+    /// Reads a measurement values map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MeasurementValues)` - The values.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a field has the wrong
+    ///   type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let raw_value = match map.get_int(4) {
+            Some(Value::Tag(BYTES, Value::Bytes(raw_value))) => Some(*raw_value),
+            Some(_) => return Err(Error::InvalidType),
+            None => None,
+        };
+        Ok(Self {
+            version: typed(map.get_int(0))?,
+            svn: typed(map.get_int(1))?,
+            digests: List::from_field(map.get_int(2))?,
+            raw_value,
+            serial_number: text(map.get_int(8))?,
+            name: text(map.get_int(11))?,
+        })
+    }
+}
+
+/// The key of a measurement, naming the element that was measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasuredElement<'a> {
+    /// An object identifier, in tag 111.
+    Oid(&'a [u8]),
+    /// A 16-byte UUID, in tag 37.
+    Uuid(&'a [u8]),
+    /// A number.
+    Unsigned(u64),
+    /// A name.
+    Text(&'a str),
+}
+
+impl MeasuredElement<'_> {
+    /// Writes the measured element.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match *self {
+            Self::Oid(oid) => TaggedId::Oid(oid).write(sink),
+            Self::Uuid(uuid) => TaggedId::Uuid(uuid).write(sink),
+            Self::Unsigned(number) => sink.value(&Value::Unsigned(number)),
+            Self::Text(text) => sink.value(&Value::Text(text)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MeasuredElement<'a> {
+    type Error = Error;
+
+    /// Reads a measured element.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MeasuredElement)` - The measured element.
+    /// * `Err(Error::InvalidType)` - If the value is not an OID, a UUID, an unsigned
+    ///   integer or a text string.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Unsigned(number) => Ok(Self::Unsigned(*number)),
+            Value::Text(text) => Ok(Self::Text(text)),
+            _ => match TaggedId::try_from(value)? {
+                TaggedId::Oid(oid) => Ok(Self::Oid(oid)),
+                TaggedId::Uuid(uuid) => Ok(Self::Uuid(uuid)),
+                _ => Err(Error::InvalidType),
+            },
+        }
+    }
+}
+
+/// A measurement: the values measured for an element of an environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement<'a> {
+    key: Option<MeasuredElement<'a>>,
+    values: MeasurementValues<'a>,
+}
+
+impl<'a> Measurement<'a> {
+    /// Creates a measurement of the whole environment.
+    #[inline]
+    pub const fn new(values: MeasurementValues<'a>) -> Self {
+        Self { key: None, values }
+    }
+
+    /// Returns the measurement with the given measured element.
+    #[inline]
+    pub const fn with_key(self, key: MeasuredElement<'a>) -> Self {
+        Self {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    /// Returns the measured element.
+    #[inline]
+    pub const fn key(&self) -> Option<MeasuredElement<'a>> {
+        self.key
+    }
+
+    /// Returns the measured values.
+    #[inline]
+    pub const fn values(&self) -> MeasurementValues<'a> {
+        self.values
+    }
+}
+
+impl Item for Measurement<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::MAP, 1 + self.key.is_some() as u64)?;
+        if let Some(key) = &self.key {
+            sink.value(&Value::Unsigned(0))?;
+            key.write(sink)?;
+        }
+        sink.value(&Value::Unsigned(1))?;
+        self.values.write(sink)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Measurement<'a> {
+    type Error = Error;
+
+    /// Reads a measurement map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Measurement)` - The measurement.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the values are missing, or
+    ///   a field has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let values = map.get_int(1).ok_or(Error::InvalidType)?;
+        Ok(Self {
+            key: typed(map.get_int(0))?,
+            values: MeasurementValues::try_from(values)?,
+        })
+    }
+}
+
+/// A cryptographic key or certificate, identifying or attesting an environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CryptoKey<'a> {
+    /// A base64-encoded PKIX public key, in tag 554.
+    PkixBase64Key(&'a str),
+    /// A base64-encoded PKIX certificate, in tag 555.
+    PkixBase64Cert(&'a str),
+    /// The thumbprint of a key, in tag 557.
+    Thumbprint(Digest<'a>),
+    /// A `COSE_Key`, in tag 558.
+    CoseKey(CoseKey<'a>),
+    /// Opaque key bytes, in tag 560.
+    Bytes(&'a [u8]),
+}
+
+impl Item for CryptoKey<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match self {
+            Self::PkixBase64Key(key) => {
+                sink.header(major_type::TAG, PKIX_BASE64_KEY)?;
+                sink.value(&Value::Text(key))
+            }
+            Self::PkixBase64Cert(cert) => {
+                sink.header(major_type::TAG, PKIX_BASE64_CERT)?;
+                sink.value(&Value::Text(cert))
+            }
+            Self::Thumbprint(digest) => {
+                sink.header(major_type::TAG, THUMBPRINT)?;
+                digest.write(sink)
+            }
+            Self::CoseKey(key) => {
+                sink.header(major_type::TAG, COSE_KEY)?;
+                sink.encoded(key.encoded_size(), |buf| key.as_cbor(buf))
+            }
+            Self::Bytes(bytes) => {
+                sink.header(major_type::TAG, BYTES)?;
+                sink.value(&Value::Bytes(bytes))
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CryptoKey<'a> {
+    type Error = Error;
+
+    /// Reads a tagged key or certificate.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CryptoKey)` - The key.
+    /// * `Err(Error::InvalidType)` - If the value is not one of the supported key tags, or
+    ///   its content has the wrong type.
+    /// * `Err(Error::InvalidValue)` - If a `COSE_Key` is not valid.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Tag(PKIX_BASE64_KEY, Value::Text(key)) => Ok(Self::PkixBase64Key(key)),
+            Value::Tag(PKIX_BASE64_CERT, Value::Text(cert)) => Ok(Self::PkixBase64Cert(cert)),
+            Value::Tag(THUMBPRINT, digest) => Ok(Self::Thumbprint(Digest::try_from(*digest)?)),
+            Value::Tag(COSE_KEY, key) => Ok(Self::CoseKey(CoseKey::try_from(*key)?)),
+            Value::Tag(BYTES, Value::Bytes(bytes)) => Ok(Self::Bytes(bytes)),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A reference-values or endorsed-values triple: measurements of an environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeasurementTriple<'a> {
+    environment: Environment<'a>,
+    measurements: List<'a, Measurement<'a>>,
+}
+
+impl<'a> MeasurementTriple<'a> {
+    /// Creates a triple.
+    ///
+    /// # Arguments
+    ///
+    /// * `environment` - The environment that is measured.
+    /// * `measurements` - The measurements, of which there must be at least one.
+    #[inline]
+    pub const fn new(environment: Environment<'a>, measurements: &'a [Measurement<'a>]) -> Self {
+        Self {
+            environment,
+            measurements: List::Items(measurements),
+        }
+    }
+
+    /// Returns the environment.
+    #[inline]
+    pub const fn environment(&self) -> Environment<'a> {
+        self.environment
+    }
+
+    /// Returns the measurements.
+    #[inline]
+    pub const fn measurements(&self) -> List<'a, Measurement<'a>> {
+        self.measurements
+    }
+}
+
+impl Item for MeasurementTriple<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        self.environment.write(sink)?;
+        self.measurements.write(sink)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MeasurementTriple<'a> {
+    type Error = Error;
+
+    /// Reads an `[environment, [+ measurement]]` triple.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MeasurementTriple)` - The triple, whose measurements are converted as they
+    ///   are read.
+    /// * `Err(Error::InvalidType)` - If the value does not have the shape of a triple.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([environment, measurements]) => Ok(Self {
+                environment: Environment::try_from(environment)?,
+                measurements: List::from_field(Some(measurements))?,
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// An identity or attestation key triple: the keys held by an environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyTriple<'a> {
+    environment: Environment<'a>,
+    keys: List<'a, CryptoKey<'a>>,
+}
+
+impl<'a> KeyTriple<'a> {
+    /// Creates a triple.
+    ///
+    /// # Arguments
+    ///
+    /// * `environment` - The environment that holds the keys.
+    /// * `keys` - The keys, of which there must be at least one.
+    #[inline]
+    pub const fn new(environment: Environment<'a>, keys: &'a [CryptoKey<'a>]) -> Self {
+        Self {
+            environment,
+            keys: List::Items(keys),
+        }
+    }
+
+    /// Returns the environment.
+    #[inline]
+    pub const fn environment(&self) -> Environment<'a> {
+        self.environment
+    }
+
+    /// Returns the keys.
+    #[inline]
+    pub const fn keys(&self) -> List<'a, CryptoKey<'a>> {
+        self.keys
+    }
+}
+
+impl Item for KeyTriple<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        self.environment.write(sink)?;
+        self.keys.write(sink)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for KeyTriple<'a> {
+    type Error = Error;
+
+    /// Reads an `[environment, [+ key]]` triple. Conditions, given as a third item, are
+    /// ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(KeyTriple)` - The triple, whose keys are converted as they are read.
+    /// * `Err(Error::InvalidType)` - If the value does not have the shape of a triple.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([environment, keys] | [environment, keys, _]) => Ok(Self {
+                environment: Environment::try_from(environment)?,
+                keys: List::from_field(Some(keys))?,
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The triples of a CoMID.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Triples<'a> {
+    reference: List<'a, MeasurementTriple<'a>>,
+    endorsed: List<'a, MeasurementTriple<'a>>,
+    identity: List<'a, KeyTriple<'a>>,
+    attest_key: List<'a, KeyTriple<'a>>,
+}
+
+impl<'a> Triples<'a> {
+    /// Creates empty triples. At least one kind must be set before encoding.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            reference: List::Items(&[]),
+            endorsed: List::Items(&[]),
+            identity: List::Items(&[]),
+            attest_key: List::Items(&[]),
+        }
+    }
+
+    /// Returns the triples with the given reference-values triples.
+    #[inline]
+    pub const fn with_reference(self, reference: &'a [MeasurementTriple<'a>]) -> Self {
+        Self {
+            reference: List::Items(reference),
+            ..self
+        }
+    }
+
+    /// Returns the triples with the given endorsed-values triples.
+    #[inline]
+    pub const fn with_endorsed(self, endorsed: &'a [MeasurementTriple<'a>]) -> Self {
+        Self {
+            endorsed: List::Items(endorsed),
+            ..self
+        }
+    }
+
+    /// Returns the triples with the given identity key triples.
+    #[inline]
+    pub const fn with_identity(self, identity: &'a [KeyTriple<'a>]) -> Self {
+        Self {
+            identity: List::Items(identity),
+            ..self
+        }
+    }
+
+    /// Returns the triples with the given attestation key triples.
+    #[inline]
+    pub const fn with_attest_key(self, attest_key: &'a [KeyTriple<'a>]) -> Self {
+        Self {
+            attest_key: List::Items(attest_key),
+            ..self
+        }
+    }
+
+    /// Returns the reference-values triples.
+    #[inline]
+    pub const fn reference(&self) -> List<'a, MeasurementTriple<'a>> {
+        self.reference
+    }
+
+    /// Returns the endorsed-values triples.
+    #[inline]
+    pub const fn endorsed(&self) -> List<'a, MeasurementTriple<'a>> {
+        self.endorsed
+    }
+
+    /// Returns the identity key triples.
+    #[inline]
+    pub const fn identity(&self) -> List<'a, KeyTriple<'a>> {
+        self.identity
+    }
+
+    /// Returns the attestation key triples.
+    #[inline]
+    pub const fn attest_key(&self) -> List<'a, KeyTriple<'a>> {
+        self.attest_key
+    }
+
+    /// Writes the triples map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let len = !self.reference.is_empty() as u64
+            + !self.endorsed.is_empty() as u64
+            + !self.identity.is_empty() as u64
+            + !self.attest_key.is_empty() as u64;
+        if len == 0 {
+            return Err(Error::InvalidValue);
+        }
+        sink.header(major_type::MAP, len)?;
+        let measurements = [(0, &self.reference), (1, &self.endorsed)];
+        for (key, triples) in measurements {
+            if !triples.is_empty() {
+                sink.value(&Value::Unsigned(key))?;
+                triples.write(sink)?;
+            }
+        }
+        let keys = [(2, &self.identity), (3, &self.attest_key)];
+        for (key, triples) in keys {
+            if !triples.is_empty() {
+                sink.value(&Value::Unsigned(key))?;
+                triples.write(sink)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Triples<'a> {
+    type Error = Error;
+
+    /// Reads a triples map. Kinds of triples this module does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Triples)` - The triples, which are converted as they are read.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a list of triples is
+    ///   not an array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        Ok(Self {
+            reference: List::from_field(map.get_int(0))?,
+            endorsed: List::from_field(map.get_int(1))?,
+            identity: List::from_field(map.get_int(2))?,
+            attest_key: List::from_field(map.get_int(3))?,
+        })
+    }
+}
+
+/// A CoMID tag, describing the environments of a module and their triples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comid<'a> {
+    language: Option<&'a str>,
+    tag_id: TagId<'a>,
+    tag_version: Option<u64>,
+    triples: Triples<'a>,
+}
+
+impl<'a> Comid<'a> {
+    /// Creates a CoMID tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_id` - The identifier of the tag.
+    /// * `triples` - The triples of the tag.
+    #[inline]
+    pub const fn new(tag_id: TagId<'a>, triples: Triples<'a>) -> Self {
+        Self {
+            language: None,
+            tag_id,
+            tag_version: None,
+            triples,
+        }
+    }
+
+    /// Returns the tag with the given language tag, such as `"en-US"`.
+    #[inline]
+    pub const fn with_language(self, language: &'a str) -> Self {
+        Self {
+            language: Some(language),
+            ..self
+        }
+    }
+
+    /// Returns the tag with the given tag version.
+    #[inline]
+    pub const fn with_tag_version(self, tag_version: u64) -> Self {
+        Self {
+            tag_version: Some(tag_version),
+            ..self
+        }
+    }
+
+    /// Returns the language tag.
+    #[inline]
+    pub const fn language(&self) -> Option<&'a str> {
+        self.language
+    }
+
+    /// Returns the identifier of the tag.
+    #[inline]
+    pub const fn tag_id(&self) -> TagId<'a> {
+        self.tag_id
+    }
+
+    /// Returns the tag version.
+    #[inline]
+    pub const fn tag_version(&self) -> Option<u64> {
+        self.tag_version
+    }
+
+    /// Returns the triples.
+    #[inline]
+    pub const fn triples(&self) -> Triples<'a> {
+        self.triples
+    }
+
+    /// Calculates the number of bytes needed to encode the tag.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The encoded size.
+    /// * `Err(Error::InvalidValue)` - If the triples, or a structure within them, has no
+    ///   fields set.
+    pub fn encoded_size(&self) -> Result<usize> {
+        Sink::checked_size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the CoMID map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::MAP, 2 + self.language.is_some() as u64)?;
+        if let Some(language) = self.language {
+            sink.value(&Value::Unsigned(0))?;
+            sink.value(&Value::Text(language))?;
+        }
+        sink.value(&Value::Unsigned(1))?;
+        sink.header(major_type::MAP, 1 + self.tag_version.is_some() as u64)?;
+        sink.value(&Value::Unsigned(0))?;
+        sink.value(&self.tag_id.to_value())?;
+        if let Some(tag_version) = self.tag_version {
+            sink.value(&Value::Unsigned(1))?;
+            sink.value(&Value::Unsigned(tag_version))?;
+        }
+        sink.value(&Value::Unsigned(4))?;
+        self.triples.write(sink)
+    }
+}
+
+impl<'a> Encode<'a> for Comid<'_> {
+    /// Encodes the CoMID map, untagged, ready to be wrapped in a
+    /// [`ConciseTag`](super::ConciseTag).
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Comid<'a> {
+    type Error = Error;
+
+    /// Reads a CoMID map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Comid)` - The tag.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the tag identity or the
+    ///   triples are missing, or a field has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let identity = fields(map.get_int(1).ok_or(Error::InvalidType)?)?;
+        let tag_id = identity.get_int(0).ok_or(Error::InvalidType)?;
+        let triples = map.get_int(4).ok_or(Error::InvalidType)?;
+        Ok(Self {
+            language: text(map.get_int(0))?,
+            tag_id: TagId::try_from(tag_id)?,
+            tag_version: unsigned(identity.get_int(1))?,
+            triples: Triples::try_from(triples)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Class, Comid, CryptoKey, Digest, Environment, KeyTriple, MeasuredElement, Measurement,
+        MeasurementTriple, MeasurementValues, Svn, TaggedId, Triples, Version,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        corim::{SHA_256, TagId},
+        cose::key::{CoseKey, ED25519},
+        decode::decode,
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+    };
+
+    const ENVIRONMENT: Environment = Environment::new()
+        .with_class(
+            Class::new()
+                .with_id(TaggedId::Oid(&[0x2B, 0x06, 0x01]))
+                .with_vendor("ACME")
+                .with_model("RoadRunner")
+                .with_layer(1),
+        )
+        .with_instance(TaggedId::Ueid(&[0x02, 0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x01]));
+
+    #[test]
+    fn test_reference_values() {
+        let digests = [Digest::new(SHA_256, &[0xAA; 4])];
+        let measurements = [
+            Measurement::new(
+                MeasurementValues::new()
+                    .with_version(Version::new("1.0.0", Some(16384)))
+                    .with_svn(Svn::Min(2))
+                    .with_digests(&digests),
+            )
+            .with_key(MeasuredElement::Text("bootloader")),
+            Measurement::new(
+                MeasurementValues::new()
+                    .with_raw_value(&[0x01, 0x02])
+                    .with_serial_number("SN-1")
+                    .with_name("config"),
+            ),
+        ];
+        let reference = [MeasurementTriple::new(ENVIRONMENT, &measurements)];
+        let comid = Comid::new(
+            TagId::Text("acme-rr-1"),
+            Triples::new().with_reference(&reference),
+        )
+        .with_language("en-US")
+        .with_tag_version(3);
+
+        static EXPECTED: Value = cbor!({
+            0: "en-US",
+            1: {0: "acme-rr-1", 1: 3},
+            4: {0: [[
+                {
+                    0: {0: 111(h "2B0601"), 1: "ACME", 2: "RoadRunner", 3: 1},
+                    1: 550(h "02DEADBEEF0001")
+                },
+                [
+                    {0: "bootloader", 1: {
+                        0: {0: "1.0.0", 1: 16384},
+                        1: 553(2),
+                        2: [[1, h "AAAAAAAA"]]
+                    }},
+                    {1: {4: 560(h "0102"), 8: "SN-1", 11: "config"}}
+                ]
+            ]]}
+        });
+        let mut buf = [0u8; 512];
+        let size = comid.as_cbor(&mut buf).unwrap();
+        assert_encoding(&buf[..size], &EXPECTED);
+        assert_eq!(comid.encoded_size(), Ok(size));
+
+        let mut values = [Value::null(); 24];
+        let mut pairs = [(Value::null(), Value::null()); 24];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let decoded = Comid::try_from(&value).unwrap();
+
+        assert_eq!(decoded.language(), Some("en-US"));
+        assert_eq!(decoded.tag_id(), TagId::Text("acme-rr-1"));
+        assert_eq!(decoded.tag_version(), Some(3));
+        let triples = decoded.triples();
+        assert!(triples.endorsed().is_empty());
+        let triple = triples.reference().get(0).unwrap().unwrap();
+        assert_eq!(triple.environment(), ENVIRONMENT);
+        let decoded_measurements = triple.measurements();
+        assert_eq!(decoded_measurements.len(), 2);
+        let first = decoded_measurements.get(0).unwrap().unwrap();
+        assert_eq!(first.key(), measurements[0].key());
+        assert_eq!(first.values().svn(), Some(Svn::Min(2)));
+        assert_eq!(first.values().version(), measurements[0].values().version());
+        assert_eq!(first.values().digests().get(0), Some(Ok(digests[0])));
+        let second = decoded_measurements.get(1).unwrap().unwrap();
+        assert_eq!(second.values().raw_value(), Some(&[0x01, 0x02][..]));
+        assert_eq!(second.values().name(), Some("config"));
+
+        // Re-encoding the decoded tag gives the same bytes
+        let mut again = [0u8; 512];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+        assert_eq!(decoded.encoded_size(), Ok(size));
+    }
+
+    #[test]
+    fn test_key_triples() {
+        let cose_key = CoseKey::okp(ED25519, &[0x11; 32]);
+        let identity_keys = [
+            CryptoKey::CoseKey(cose_key),
+            CryptoKey::Thumbprint(Digest::new(SHA_256, &[0xBB; 2])),
+        ];
+        let attest_keys = [
+            CryptoKey::PkixBase64Key("MCowBQYDK2VwAyEA"),
+            CryptoKey::Bytes(&[0xCC]),
+        ];
+        let environment = Environment::new().with_group(TaggedId::Uuid(&[0x01; 16]));
+        let identity = [KeyTriple::new(environment, &identity_keys)];
+        let attest_key = [KeyTriple::new(environment, &attest_keys)];
+        let triples = Triples::new()
+            .with_identity(&identity)
+            .with_attest_key(&attest_key);
+        let comid = Comid::new(TagId::Uuid(&[0x02; 16]), triples);
+
+        let mut buf = [0u8; 256];
+        let size = comid.as_cbor(&mut buf).unwrap();
+        assert_eq!(comid.encoded_size(), Ok(size));
+
+        let mut values = [Value::null(); 24];
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let decoded = Comid::try_from(&value).unwrap();
+        assert_eq!(decoded.tag_id(), TagId::Uuid(&[0x02; 16]));
+
+        let triple = decoded.triples().identity().get(0).unwrap().unwrap();
+        assert_eq!(triple.environment(), environment);
+        let keys: [_; 2] = core::array::from_fn(|i| triple.keys().get(i).unwrap().unwrap());
+        assert_eq!(keys, identity_keys);
+
+        let triple = decoded.triples().attest_key().get(0).unwrap().unwrap();
+        let mut keys = triple.keys().iter();
+        assert_eq!(keys.next(), Some(Ok(attest_keys[0])));
+        assert_eq!(keys.next(), Some(Ok(attest_keys[1])));
+        assert_eq!(keys.next(), None);
+
+        let mut short = [0u8; 100];
+        assert_eq!(comid.as_cbor(&mut short), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_empty_structures() {
+        let id = TagId::Text("id");
+        let mut buf = [0u8; 64];
+        let comid = Comid::new(id, Triples::new());
+        assert_eq!(comid.as_cbor(&mut buf), Err(Error::InvalidValue));
+        assert_eq!(comid.encoded_size(), Err(Error::InvalidValue));
+
+        let measurements = [Measurement::new(MeasurementValues::new())];
+        let environments = [
+            Environment::new(),
+            Environment::new().with_class(Class::new()),
+        ];
+        for environment in environments {
+            let reference = [MeasurementTriple::new(environment, &measurements[..0])];
+            let comid = Comid::new(id, Triples::new().with_reference(&reference));
+            assert_eq!(comid.as_cbor(&mut buf), Err(Error::InvalidValue));
+            assert_eq!(comid.encoded_size(), Err(Error::InvalidValue));
+        }
+
+        let environment = Environment::new().with_instance(TaggedId::Uuid(&[0x01; 16]));
+        let reference = [MeasurementTriple::new(environment, &measurements)];
+        let comid = Comid::new(id, Triples::new().with_reference(&reference));
+        assert_eq!(comid.as_cbor(&mut buf), Err(Error::InvalidValue));
+        assert_eq!(comid.encoded_size(), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        static NO_TRIPLES: Value = cbor!({1: {0: "id"}});
+        static NO_TAG_ID: Value = cbor!({1: {1: 0}, 4: {}});
+        static BAD_LANGUAGE: Value = cbor!({0: 1, 1: {0: "id"}, 4: {}});
+        static BAD_TRIPLES: Value = cbor!({1: {0: "id"}, 4: {0: {}}});
+        for value in [&NO_TRIPLES, &NO_TAG_ID, &BAD_LANGUAGE, &BAD_TRIPLES] {
+            assert_eq!(Comid::try_from(value), Err(Error::InvalidType), "{value}");
+        }
+
+        // Errors inside triples surface when the triple is read
+        static BAD_ENVIRONMENT: Value = cbor!({1: {0: "id"}, 4: {0: [[{0: []}, []]]}});
+        let comid = Comid::try_from(&BAD_ENVIRONMENT).unwrap();
+        assert_eq!(
+            comid.triples().reference().get(0),
+            Some(Err(Error::InvalidType))
+        );
+
+        static UNTAGGED_ID: Value = cbor!(h "00");
+        assert_eq!(TaggedId::try_from(&UNTAGGED_ID), Err(Error::InvalidType));
+        static EXACT_SVN: Value = cbor!(552(7));
+        assert_eq!(Svn::try_from(&EXACT_SVN), Ok(Svn::Exact(7)));
+        assert_eq!(Svn::try_from(&Value::unsigned(7)), Ok(Svn::Exact(7)));
+        static BAD_KEY: Value = cbor!(558({1: 99}));
+        assert_eq!(CryptoKey::try_from(&BAD_KEY), Err(Error::InvalidValue));
+        static UEID_ELEMENT: Value = cbor!(550(h "01"));
+        assert_eq!(
+            MeasuredElement::try_from(&UEID_ELEMENT),
+            Err(Error::InvalidType)
+        );
+    }
+}
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: MIT
+
+//! Concise Reference Integrity Manifests (CoRIM) and Concise Module Identifiers (CoMID),
+//! as defined in the IETF RATS [CoRIM draft](https://datatracker.ietf.org/doc/draft-ietf-rats-corim/).
+//!
+//! A CoRIM (tag 501) is a bundle of tags from a supplier, such as a silicon or firmware
+//! vendor, that a verifier uses to appraise attestation evidence. Each CoMID tag in it
+//! (tag 506) carries triples, which tie an environment, such as a firmware layer of a
+//! device, to:
+//!
+//! * reference values, the measurements the environment is expected to have;
+//! * endorsed values, further measurements the supplier vouches for;
+//! * identity keys, which identify the environment;
+//! * attestation keys, which the environment signs evidence with.
+//!
+//! [`Corim`] and [`Comid`] model these structures with typed fields. Lists are
+//! [`List`]s: built structures hold typed items, while decoded structures keep the
+//! decoded values and convert each item when it is read, so neither direction allocates.
+//! Fields this module does not model, such as entities and linked tags, are skipped
+//! when decoding and not written when encoding.
+//!
+//! A signed CoRIM is a [`CoseSign1`](crate::cose::CoseSign1) whose payload is the
+//! encoded tag 501 CoRIM.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Arena, Value,
+//!     corim::{self, Class, Comid, Digest, Environment, Measurement, MeasurementTriple,
+//!             MeasurementValues, TagId, Triples},
+//!     decode::decode,
+//!     encode::Encode,
+//! };
+//!
+//! let digests = [Digest::new(corim::SHA_256, &[0xAB; 32])];
+//! let measurements = [Measurement::new(MeasurementValues::new().with_digests(&digests))];
+//! let environment = Environment::new().with_class(Class::new().with_vendor("ACME"));
+//! let reference = [MeasurementTriple::new(environment, &measurements)];
+//! let comid = Comid::new(TagId::Text("acme-fw-1.0"), Triples::new().with_reference(&reference));
+//!
+//! let mut buf = [0u8; 128];
+//! let size = comid.as_cbor(&mut buf).unwrap();
+//!
+//! let mut values = [Value::null(); 8];
+//! let mut pairs = [(Value::null(), Value::null()); 8];
+//! let mut arena = Arena::new(&mut values, &mut pairs);
+//! let value = decode(&buf[..size], &mut arena).unwrap();
+//! let decoded = Comid::try_from(&value).unwrap();
+//!
+//! let triple = decoded.triples().reference().get(0).unwrap().unwrap();
+//! assert_eq!(triple.environment().class().unwrap().vendor(), Some("ACME"));
+//! let measurement = triple.measurements().get(0).unwrap().unwrap();
+//! assert_eq!(measurement.values().digests().get(0), Some(Ok(digests[0])));
+//! ```
+
+mod comid;
+
+pub use comid::*;
+
+use crate::{
+    Value,
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    list::{Item, List},
+    result::Result,
+    tag,
+};
+
+/// Tag 550: A UEID, identifying an instance.
+pub const UEID: u64 = 550;
+/// Tag 552: An exact security version number.
+pub const SVN: u64 = 552;
+/// Tag 553: A minimum security version number.
+pub const MIN_SVN: u64 = 553;
+/// Tag 554: A public key in base64-encoded PKIX form.
+pub const PKIX_BASE64_KEY: u64 = 554;
+/// Tag 555: A certificate in base64-encoded PKIX form.
+pub const PKIX_BASE64_CERT: u64 = 555;
+/// Tag 557: The thumbprint of a key, as a digest.
+pub const THUMBPRINT: u64 = 557;
+/// Tag 558: A `COSE_Key`.
+pub const COSE_KEY: u64 = 558;
+/// Tag 560: Opaque bytes, such as an instance ID or a raw measurement.
+pub const BYTES: u64 = 560;
+
+/// Hash algorithm 1: SHA-256, from the IANA Named Information registry.
+pub const SHA_256: i64 = 1;
+/// Hash algorithm 7: SHA-384, from the IANA Named Information registry.
+pub const SHA_384: i64 = 7;
+/// Hash algorithm 8: SHA-512, from the IANA Named Information registry.
+pub const SHA_512: i64 = 8;
+
+/// The identifier of a CoRIM or of a CoMID tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagId<'a> {
+    /// A text identifier.
+    Text(&'a str),
+    /// A 16-byte UUID.
+    Uuid(&'a [u8]),
+}
+
+impl<'a> TagId<'a> {
+    /// Returns the identifier as a value.
+    const fn to_value(self) -> Value<'a> {
+        match self {
+            Self::Text(text) => Value::Text(text),
+            Self::Uuid(uuid) => Value::Bytes(uuid),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for TagId<'a> {
+    type Error = Error;
+
+    /// Reads a text or UUID identifier.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TagId)` - The identifier.
+    /// * `Err(Error::InvalidType)` - If the value is neither a text string nor a byte
+    ///   string.
+    /// * `Err(Error::InvalidValue)` - If a UUID is not 16 bytes long.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Text(text) => Ok(Self::Text(text)),
+            Value::Bytes(uuid) if uuid.len() == 16 => Ok(Self::Uuid(uuid)),
+            Value::Bytes(_) => Err(Error::InvalidValue),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A tag in a CoRIM, such as an encoded CoMID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConciseTag<'a> {
+    tag: u64,
+    content: &'a [u8],
+}
+
+impl<'a> ConciseTag<'a> {
+    /// Creates a tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The CBOR tag number that identifies the kind of tag, such as
+    ///   [`tag::COMID`].
+    /// * `content` - The encoded tag.
+    #[inline]
+    pub const fn new(tag: u64, content: &'a [u8]) -> Self {
+        Self { tag, content }
+    }
+
+    /// Creates a CoMID tag from an encoded [`Comid`].
+    #[inline]
+    pub const fn comid(content: &'a [u8]) -> Self {
+        Self::new(tag::COMID, content)
+    }
+
+    /// Returns the CBOR tag number that identifies the kind of tag.
+    #[inline]
+    pub const fn tag(&self) -> u64 {
+        self.tag
+    }
+
+    /// Returns `true` if this is a CoMID tag.
+    #[inline]
+    pub const fn is_comid(&self) -> bool {
+        self.tag == tag::COMID
+    }
+
+    /// Returns the encoded tag, which is decoded separately.
+    #[inline]
+    pub const fn content(&self) -> &'a [u8] {
+        self.content
+    }
+}
+
+impl Item for ConciseTag<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::TAG, self.tag)?;
+        sink.value(&Value::Bytes(self.content))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for ConciseTag<'a> {
+    type Error = Error;
+
+    /// Reads a tag, a byte string wrapped in the tag number of its kind.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ConciseTag)` - The tag.
+    /// * `Err(Error::InvalidType)` - If the value is not a tagged byte string.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Tag(tag, Value::Bytes(content)) => Ok(Self::new(*tag, content)),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A CoRIM, a bundle of tags from one supplier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Corim<'a> {
+    id: TagId<'a>,
+    tags: List<'a, ConciseTag<'a>>,
+}
+
+impl<'a> Corim<'a> {
+    /// Creates a CoRIM.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the CoRIM.
+    /// * `tags` - The tags in the CoRIM, of which there must be at least one.
+    #[inline]
+    pub const fn new(id: TagId<'a>, tags: &'a [ConciseTag<'a>]) -> Self {
+        Self {
+            id,
+            tags: List::Items(tags),
+        }
+    }
+
+    /// Returns the identifier of the CoRIM.
+    #[inline]
+    pub const fn id(&self) -> TagId<'a> {
+        self.id
+    }
+
+    /// Returns the tags in the CoRIM.
+    #[inline]
+    pub const fn tags(&self) -> List<'a, ConciseTag<'a>> {
+        self.tags
+    }
+
+    /// Calculates the number of bytes needed to encode the CoRIM, including tag 501.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The encoded size.
+    /// * `Err(Error::InvalidValue)` - If the CoRIM has no tags.
+    pub fn encoded_size(&self) -> Result<usize> {
+        Sink::checked_size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the tagged CoRIM map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        if self.tags.is_empty() {
+            return Err(Error::InvalidValue);
+        }
+        sink.header(major_type::TAG, tag::CORIM)?;
+        sink.header(major_type::MAP, 2)?;
+        sink.value(&Value::Unsigned(0))?;
+        sink.value(&self.id.to_value())?;
+        sink.value(&Value::Unsigned(1))?;
+        self.tags.write(sink)
+    }
+}
+
+impl<'a> Encode<'a> for Corim<'_> {
+    /// Encodes the CoRIM map, wrapped in tag 501.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Corim<'a> {
+    type Error = Error;
+
+    /// Reads a CoRIM map, with or without tag 501.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Corim)` - The CoRIM, whose tags are converted as they are read.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or the identifier or the
+    ///   tags are missing or have the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = match value {
+            Value::Tag(tag::CORIM, map) => map,
+            _ => value,
+        };
+        if map.as_map().is_none() {
+            return Err(Error::InvalidType);
+        }
+        let id = map.get_int(0).ok_or(Error::InvalidType)?;
+        let tags = map.get_int(1).ok_or(Error::InvalidType)?;
+
+        Ok(Self {
+            id: TagId::try_from(id)?,
+            tags: List::from_field(Some(tags))?,
+        })
+    }
+}
+
+/// Reads an optional field with `T::try_from`.
+fn typed<'a, T: TryFrom<&'a Value<'a>, Error = Error>>(
+    field: Option<&'a Value<'a>>,
+) -> Result<Option<T>> {
+    field.map(T::try_from).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConciseTag, Corim, TagId};
+    use crate::{
+        Arena, Value, cbor,
+        decode::decode,
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+        tag,
+    };
+
+    #[test]
+    fn test_corim_roundtrip() {
+        let tags = [
+            ConciseTag::comid(&[0xA0]),
+            ConciseTag::new(505, &[0xA1, 0x00, 0x00]),
+        ];
+        let corim = Corim::new(TagId::Uuid(&[0x5A; 16]), &tags);
+
+        let mut buf = [0u8; 64];
+        let size = corim.as_cbor(&mut buf).unwrap();
+        assert_eq!(corim.encoded_size(), Ok(size));
+
+        static EXPECTED: Value = cbor!(501({
+            0: h "5A5A5A5A5A5A5A5A5A5A5A5A5A5A5A5A",
+            1: [506(h "A0"), 505(h "A10000")]
+        }));
+        assert_encoding(&buf[..size], &EXPECTED);
+
+        let mut values = [Value::null(); 6];
+        let mut pairs = [(Value::null(), Value::null()); 2];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let decoded = Corim::try_from(&value).unwrap();
+        assert_eq!(decoded.id(), corim.id());
+        let decoded_tags = decoded.tags();
+        assert!(decoded_tags.get(0).unwrap().unwrap().is_comid());
+        assert_eq!(decoded_tags.get(1), Some(Ok(tags[1])));
+
+        // Re-encoding the decoded CoRIM gives the same bytes
+        let mut again = [0u8; 64];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+    }
+
+    #[test]
+    fn test_corim_errors() {
+        static UNTAGGED: Value = cbor!({0: "id", 1: [506(h "A0")], 5: []});
+        let corim = Corim::try_from(&UNTAGGED).unwrap();
+        assert_eq!(corim.id(), TagId::Text("id"));
+        assert_eq!(corim.tags().get(0).unwrap().unwrap().tag(), tag::COMID);
+
+        static NO_TAGS: Value = cbor!(501({0: "id"}));
+        static TAGS_NOT_ARRAY: Value = cbor!({0: "id", 1: 506(h "A0")});
+        static NOT_A_MAP: Value = cbor!(501([0, "id"]));
+        for value in [&NO_TAGS, &TAGS_NOT_ARRAY, &NOT_A_MAP] {
+            assert_eq!(Corim::try_from(value), Err(Error::InvalidType), "{value}");
+        }
+
+        static UNTAGGED_ITEM: Value = cbor!({0: "id", 1: [h "A0"]});
+        let corim = Corim::try_from(&UNTAGGED_ITEM).unwrap();
+        assert_eq!(corim.tags().get(0), Some(Err(Error::InvalidType)));
+
+        let empty = Corim::new(TagId::Text("id"), &[]);
+        assert_eq!(empty.as_cbor(&mut [0u8; 16]), Err(Error::InvalidValue));
+        assert_eq!(empty.encoded_size(), Err(Error::InvalidValue));
+    }
+}
@@ -0,0 +1,459 @@
+// SPDX-License-Identifier: MIT
+
+//! `COSE_Encrypt0` messages (tag 16) and their `Enc_structure`.
+//!
+//! See [RFC 9052 §5.2](https://www.rfc-editor.org/rfc/rfc9052#section-5.2) for the
+//! message layout and [§5.3](https://www.rfc-editor.org/rfc/rfc9052#section-5.3) for the
+//! additional authenticated data.
+
+use crate::{
+    Value,
+    cose::{self, detachable, detached_value, headers, message_items},
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    value::Map,
+};
+
+/// The context string of an `Enc_structure` for a `COSE_Encrypt0` message.
+const ENCRYPT0: &str = "Encrypt0";
+
+/// An authenticated encryption algorithm provided by the caller's crypto backend.
+pub trait Aead {
+    /// Encrypts and authenticates a plaintext.
+    ///
+    /// # Arguments
+    ///
+    /// * `iv` - The initialization vector.
+    /// * `aad` - The encoded `Enc_structure`, to be authenticated but not encrypted.
+    /// * `plaintext` - The data to encrypt.
+    /// * `ciphertext` - The buffer to write the ciphertext into, including any
+    ///   authentication tag the algorithm appends.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the ciphertext.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small for the ciphertext.
+    /// * `Err(Error)` - Any other error, as chosen by the backend.
+    fn seal(&self, iv: &[u8], aad: &[u8], plaintext: &[u8], ciphertext: &mut [u8])
+    -> Result<usize>;
+
+    /// Checks and decrypts a ciphertext.
+    ///
+    /// # Arguments
+    ///
+    /// * `iv` - The initialization vector.
+    /// * `aad` - The encoded `Enc_structure`.
+    /// * `ciphertext` - The ciphertext carried by the message.
+    /// * `plaintext` - The buffer to write the plaintext into.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the plaintext.
+    /// * `Err(Error::InvalidValue)` - If the ciphertext fails authentication.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small for the plaintext.
+    fn open(&self, iv: &[u8], aad: &[u8], ciphertext: &[u8], plaintext: &mut [u8])
+    -> Result<usize>;
+}
+
+/// The `Enc_structure` of a `COSE_Encrypt0` message, whose encoding is the additional
+/// authenticated data passed to the AEAD algorithm.
+///
+/// It is the array `["Encrypt0", protected, external_aad]`.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{cose::EncStructure, encode::Encode};
+///
+/// let aad = EncStructure::new(&[0xA1, 0x01, 0x01], b"");
+/// let mut buf = [0u8; 16];
+/// let size = aad.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..size], b"\x83\x68Encrypt0\x43\xA1\x01\x01\x40");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncStructure<'a> {
+    protected: &'a [u8],
+    external_aad: &'a [u8],
+}
+
+impl<'a> EncStructure<'a> {
+    /// Creates an `Enc_structure` from its fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header of the message.
+    /// * `external_aad` - Additional data supplied by the application, often empty.
+    #[inline]
+    pub const fn new(protected: &'a [u8], external_aad: &'a [u8]) -> Self {
+        Self {
+            protected,
+            external_aad,
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the structure.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the structure as a `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Text(ENCRYPT0),
+            Value::Bytes(self.protected),
+            Value::Bytes(self.external_aad),
+        ];
+        f(&Value::Array(&items))
+    }
+}
+
+impl<'a> Encode<'a> for EncStructure<'_> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+/// A `COSE_Encrypt0` message (tag 16): a ciphertext whose key is implied by context.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{cbor, cose::CoseEncrypt0};
+///
+/// // 16([h'A10101', {5: h'89F52F65A1C580933B5261A7'}, h'5A'])
+/// static MESSAGE: const_cbor::Value =
+///     cbor!(16([h "A10101", {5: h "89F52F65A1C580933B5261A7"}, h "5A"]));
+///
+/// let message = CoseEncrypt0::try_from(&MESSAGE).unwrap();
+/// assert_eq!(message.iv().map(<[u8]>::len), Some(12));
+/// assert_eq!(message.ciphertext(), Some(&[0x5A][..]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoseEncrypt0<'a> {
+    protected: &'a [u8],
+    unprotected: Map<'a>,
+    ciphertext: Option<&'a [u8]>,
+}
+
+impl<'a> CoseEncrypt0<'a> {
+    /// Creates a message from its parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header, as from
+    ///   [`encode_protected`](cose::encode_protected).
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `ciphertext` - The ciphertext, or `None` if it is detached and carried elsewhere.
+    #[inline]
+    pub const fn new(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        ciphertext: Option<&'a [u8]>,
+    ) -> Self {
+        Self {
+            protected,
+            unprotected,
+            ciphertext,
+        }
+    }
+
+    /// Encrypts a plaintext and assembles the message around it.
+    ///
+    /// The `Enc_structure` is encoded at the start of `scratch` and passed to `aead`,
+    /// which writes the ciphertext into the rest of `scratch`. The returned message
+    /// borrows the ciphertext from there. The caller is responsible for carrying `iv` in
+    /// the headers, usually as the [`IV`](cose::IV) parameter of the unprotected header.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header.
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `iv` - The initialization vector.
+    /// * `plaintext` - The data to encrypt.
+    /// * `external_aad` - Additional data that is authenticated but not carried.
+    /// * `aead` - The encryption algorithm.
+    /// * `scratch` - Space for the `Enc_structure` followed by the ciphertext.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoseEncrypt0)` - The encrypted message.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `aead`.
+    pub fn encrypt<A: Aead + ?Sized>(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        iv: &[u8],
+        plaintext: &[u8],
+        external_aad: &[u8],
+        aead: &A,
+        scratch: &'a mut [u8],
+    ) -> Result<Self> {
+        let aad = EncStructure::new(protected, external_aad);
+        let len = aad.as_cbor(scratch)?;
+        let (aad, ciphertext) = scratch.split_at_mut(len);
+        let len = aead.seal(iv, aad, plaintext, ciphertext)?;
+        Ok(Self::new(protected, unprotected, Some(&ciphertext[..len])))
+    }
+
+    /// Checks and decrypts the ciphertext of the message.
+    ///
+    /// The `Enc_structure` is encoded at the start of `scratch`, and the plaintext is
+    /// written after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `iv` - The initialization vector, usually from [`iv`](Self::iv).
+    /// * `external_aad` - The additional data the message was encrypted with.
+    /// * `aead` - The encryption algorithm.
+    /// * `scratch` - Space for the `Enc_structure` followed by the plaintext.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - The plaintext.
+    /// * `Err(Error::InvalidType)` - If the ciphertext is detached; supply it with
+    ///   [`with_ciphertext`](Self::with_ciphertext) first.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `aead`.
+    pub fn decrypt<'s, A: Aead + ?Sized>(
+        &self,
+        iv: &[u8],
+        external_aad: &[u8],
+        aead: &A,
+        scratch: &'s mut [u8],
+    ) -> Result<&'s [u8]> {
+        let Some(ciphertext) = self.ciphertext else {
+            return Err(Error::InvalidType);
+        };
+        let aad = EncStructure::new(self.protected, external_aad);
+        let len = aad.as_cbor(scratch)?;
+        let (aad, plaintext) = scratch.split_at_mut(len);
+        let len = aead.open(iv, aad, ciphertext, plaintext)?;
+        Ok(&plaintext[..len])
+    }
+
+    /// Returns the message with the given ciphertext, as needed to decrypt a message
+    /// whose ciphertext was detached.
+    #[inline]
+    pub const fn with_ciphertext(self, ciphertext: &'a [u8]) -> Self {
+        Self {
+            ciphertext: Some(ciphertext),
+            ..self
+        }
+    }
+
+    /// Returns the [`IV`](cose::IV) parameter of the unprotected header.
+    #[inline]
+    pub const fn iv(&self) -> Option<&'a [u8]> {
+        match Value::Map(self.unprotected).get_int(cose::IV) {
+            Some(iv) => iv.as_bytes(),
+            None => None,
+        }
+    }
+
+    /// Returns the encoded protected header.
+    #[inline]
+    pub const fn protected(&self) -> &'a [u8] {
+        self.protected
+    }
+
+    /// Returns the entries of the unprotected header map.
+    #[inline]
+    pub const fn unprotected(&self) -> Map<'a> {
+        self.unprotected
+    }
+
+    /// Returns the ciphertext, or `None` if it is detached.
+    #[inline]
+    pub const fn ciphertext(&self) -> Option<&'a [u8]> {
+        self.ciphertext
+    }
+
+    /// Calculates the number of bytes needed to encode the message.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the message as a tagged `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Bytes(self.protected),
+            Value::Map(self.unprotected),
+            detached_value(self.ciphertext),
+        ];
+        let array = Value::Array(&items);
+        f(&Value::Tag(cose::COSE_ENCRYPT0, &array))
+    }
+}
+
+impl<'a> Encode<'a> for CoseEncrypt0<'_> {
+    /// Encodes the message wrapped in tag 16.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CoseEncrypt0<'a> {
+    type Error = Error;
+
+    /// Validates the `COSE_Encrypt0` layout, tagged with 16 or untagged, and extracts its
+    /// parts.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let [protected, unprotected, ciphertext] = message_items(value, cose::COSE_ENCRYPT0)?;
+        let (protected, unprotected) = headers(protected, unprotected)?;
+        Ok(Self::new(protected, unprotected, detachable(ciphertext)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aead, CoseEncrypt0, EncStructure};
+    use crate::{Arena, Value, cbor, decode::decode, encode::Encode, error::Error, result::Result};
+
+    /// A keystream cipher with a one-byte checksum tag, standing in for a real AEAD.
+    struct Toy;
+
+    impl Toy {
+        fn keystream(iv: &[u8], aad: &[u8], i: usize) -> u8 {
+            iv[i % iv.len()] ^ aad.len() as u8
+        }
+
+        fn checksum(aad: &[u8], plaintext: &[u8]) -> u8 {
+            aad.iter()
+                .chain(plaintext)
+                .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        }
+    }
+
+    impl Aead for Toy {
+        fn seal(
+            &self,
+            iv: &[u8],
+            aad: &[u8],
+            plaintext: &[u8],
+            ciphertext: &mut [u8],
+        ) -> Result<usize> {
+            let out = ciphertext
+                .get_mut(..plaintext.len() + 1)
+                .ok_or(Error::BufferOverflow)?;
+            for (i, byte) in plaintext.iter().enumerate() {
+                out[i] = byte ^ Self::keystream(iv, aad, i);
+            }
+            out[plaintext.len()] = Self::checksum(aad, plaintext);
+            Ok(out.len())
+        }
+
+        fn open(
+            &self,
+            iv: &[u8],
+            aad: &[u8],
+            ciphertext: &[u8],
+            plaintext: &mut [u8],
+        ) -> Result<usize> {
+            let (tag, body) = ciphertext.split_last().ok_or(Error::InvalidValue)?;
+            let out = plaintext
+                .get_mut(..body.len())
+                .ok_or(Error::BufferOverflow)?;
+            for (i, byte) in body.iter().enumerate() {
+                out[i] = byte ^ Self::keystream(iv, aad, i);
+            }
+            if Self::checksum(aad, out) == *tag {
+                Ok(out.len())
+            } else {
+                Err(Error::InvalidValue)
+            }
+        }
+    }
+
+    /// The protected header `{1: 1}`, selecting A128GCM.
+    const PROTECTED: [u8; 3] = [0xA1, 0x01, 0x01];
+    const IV: [u8; 12] = [
+        0x89, 0xF5, 0x2F, 0x65, 0xA1, 0xC5, 0x80, 0x93, 0x3B, 0x52, 0x61, 0xA7,
+    ];
+
+    #[test]
+    fn test_enc_structure() {
+        // The `AAD_hex` of the `aes-gcm-01` example from the COSE working group
+        let aad = EncStructure::new(&PROTECTED, &[]);
+        let mut buf = [0u8; 16];
+        let size = aad.as_cbor(&mut buf).unwrap();
+        assert_eq!(
+            &buf[..size],
+            &[
+                0x83, 0x68, 0x45, 0x6E, 0x63, 0x72, 0x79, 0x70, 0x74, 0x30, 0x43, 0xA1, 0x01, 0x01,
+                0x40
+            ]
+        );
+        assert_eq!(aad.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let unprotected = [(Value::unsigned(5), Value::bytes(&IV))];
+        let mut scratch = [0u8; 64];
+        let message = CoseEncrypt0::encrypt(
+            &PROTECTED,
+            &unprotected,
+            &IV,
+            b"This is the content.",
+            &[],
+            &Toy,
+            &mut scratch,
+        )
+        .unwrap();
+        assert_eq!(message.ciphertext().map(<[u8]>::len), Some(21));
+        assert_eq!(message.iv(), Some(&IV[..]));
+
+        let mut buf = [0u8; 64];
+        let size = message.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, message.encoded_size());
+        assert_eq!(&buf[..7], &[0xD0, 0x83, 0x43, 0xA1, 0x01, 0x01, 0xA1]);
+
+        let mut values = [Value::null(); 4];
+        let mut pairs = [(Value::null(), Value::null()); 1];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let parsed = CoseEncrypt0::try_from(&value).unwrap();
+        assert_eq!(parsed, message);
+
+        let mut scratch = [0u8; 64];
+        let iv = parsed.iv().unwrap();
+        assert_eq!(
+            parsed.decrypt(iv, &[], &Toy, &mut scratch),
+            Ok(&b"This is the content."[..])
+        );
+        assert_eq!(
+            parsed.decrypt(iv, b"aad", &Toy, &mut scratch),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            parsed.decrypt(iv, &[], &Toy, &mut scratch[..34]),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        static DETACHED: Value = cbor!([h "", {}, null]);
+        let detached = CoseEncrypt0::try_from(&DETACHED).unwrap();
+        assert_eq!(detached.ciphertext(), None);
+        assert_eq!(detached.iv(), None);
+        assert_eq!(
+            detached.decrypt(&IV, &[], &Toy, &mut [0u8; 32]),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            detached.with_ciphertext(&[0x00]).ciphertext(),
+            Some(&[0x00][..])
+        );
+
+        static SIGN1: Value = cbor!(18([h "", {}, null]));
+        static SHORT: Value = cbor!(16([h "", {}]));
+        static TEXT_IV: Value = cbor!(16([h "", {5: "iv"}, null]));
+        assert_eq!(CoseEncrypt0::try_from(&SIGN1), Err(Error::InvalidType));
+        assert_eq!(CoseEncrypt0::try_from(&SHORT), Err(Error::InvalidType));
+        assert_eq!(CoseEncrypt0::try_from(&TEXT_IV).unwrap().iv(), None);
+    }
+}
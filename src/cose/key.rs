@@ -0,0 +1,717 @@
+// SPDX-License-Identifier: MIT
+
+//! `COSE_Key` objects, as defined in
+//! [RFC 9052 §7](https://www.rfc-editor.org/rfc/rfc9052#section-7) and
+//! [RFC 9053](https://www.rfc-editor.org/rfc/rfc9053).
+//!
+//! A `COSE_Key` is a map keyed by integer labels. The common parameters use positive
+//! labels, while the parameters specific to each key type use negative ones, so a P-256
+//! public key looks like `{1: 2, -1: 1, -2: h'..', -3: h'..'}`.
+//!
+//! [`CoseKey`] supports elliptic curve keys in both the `EC2` (x and y coordinates) and
+//! `OKP` (octet key pair) forms, RSA public keys as defined in
+//! [RFC 8230](https://www.rfc-editor.org/rfc/rfc8230), and symmetric keys. It encodes
+//! with the deterministic ordering of RFC 8949 §4.2.1, and [`CoseKey::thumbprint_input`]
+//! produces the bytes that are hashed for a key thumbprint under
+//! [RFC 9679](https://www.rfc-editor.org/rfc/rfc9679).
+
+use crate::{
+    Value,
+    encode::{self, Encode},
+    error::Error,
+    list::required,
+    result::Result,
+};
+
+/// Label 1: The key type.
+pub const KTY: i64 = 1;
+/// Label 2: The key identifier.
+pub const KID: i64 = 2;
+/// Label 3: The algorithm the key is restricted to.
+pub const ALG: i64 = 3;
+/// Label 4: The operations the key is restricted to.
+pub const KEY_OPS: i64 = 4;
+/// Label 5: The base initialization vector.
+pub const BASE_IV: i64 = 5;
+
+/// Label -1: The curve of an `EC2` or `OKP` key.
+pub const CRV: i64 = -1;
+/// Label -2: The x coordinate of an `EC2` key, or the public key of an `OKP` key.
+pub const X: i64 = -2;
+/// Label -3: The y coordinate of an `EC2` key.
+pub const Y: i64 = -3;
+/// Label -4: The private key of an `EC2` or `OKP` key.
+pub const D: i64 = -4;
+/// Label -1: The key value of a symmetric key.
+pub const K: i64 = -1;
+/// Label -1: The modulus of an RSA key.
+pub const N: i64 = -1;
+/// Label -2: The public exponent of an RSA key.
+pub const E: i64 = -2;
+
+/// Key type 1: Octet key pair, used with the Edwards and Montgomery curves.
+pub const KTY_OKP: i64 = 1;
+/// Key type 2: Elliptic curve key with x and y coordinates.
+pub const KTY_EC2: i64 = 2;
+/// Key type 3: RSA key.
+pub const KTY_RSA: i64 = 3;
+/// Key type 4: Symmetric key.
+pub const KTY_SYMMETRIC: i64 = 4;
+
+/// Curve 1: NIST P-256, for `EC2` keys.
+pub const P256: i64 = 1;
+/// Curve 2: NIST P-384, for `EC2` keys.
+pub const P384: i64 = 2;
+/// Curve 3: NIST P-521, for `EC2` keys.
+pub const P521: i64 = 3;
+/// Curve 4: X25519, for `OKP` keys used in ECDH.
+pub const X25519: i64 = 4;
+/// Curve 5: X448, for `OKP` keys used in ECDH.
+pub const X448: i64 = 5;
+/// Curve 6: Ed25519, for `OKP` keys used in EdDSA.
+pub const ED25519: i64 = 6;
+/// Curve 7: Ed448, for `OKP` keys used in EdDSA.
+pub const ED448: i64 = 7;
+
+/// The most entries a [`CoseKey`] encodes: kty, kid, alg, key_ops, base_iv, crv, x, y
+/// and d.
+const MAX_ENTRIES: usize = 9;
+
+/// An integer or text string, the two forms RFC 9052 allows for the `alg` of a key and
+/// for the operations in its `key_ops`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label<'a> {
+    /// A registered integer value, such as [`ES256`](crate::cose::ES256).
+    Int(i64),
+
+    /// A text string value.
+    Text(&'a str),
+}
+
+impl<'a> Label<'a> {
+    /// Returns the label as a `Value`.
+    #[inline]
+    pub const fn to_value(&self) -> Value<'a> {
+        match *self {
+            Label::Int(n) => Value::integer(n),
+            Label::Text(text) => Value::Text(text),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Label<'a> {
+    type Error = Error;
+
+    /// Reads an integer that fits in an `i64`, or a text string.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Text(text) => Ok(Label::Text(text)),
+            _ => match value.as_i128().map(i64::try_from) {
+                Some(Ok(n)) => Ok(Label::Int(n)),
+                _ => Err(Error::InvalidType),
+            },
+        }
+    }
+}
+
+/// The key material of a [`CoseKey`], which depends on its key type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyParameters<'a> {
+    /// An elliptic curve key with x and y coordinates (key type 2).
+    ///
+    /// Compressed points, which carry the sign of y as a boolean, are not supported.
+    Ec2 {
+        /// The curve, such as [`P256`].
+        curve: i64,
+        /// The x coordinate.
+        x: &'a [u8],
+        /// The y coordinate.
+        y: &'a [u8],
+        /// The private key, if present.
+        d: Option<&'a [u8]>,
+    },
+
+    /// An octet key pair (key type 1).
+    Okp {
+        /// The curve, such as [`ED25519`].
+        curve: i64,
+        /// The public key.
+        x: &'a [u8],
+        /// The private key, if present.
+        d: Option<&'a [u8]>,
+    },
+
+    /// An RSA public key (key type 3).
+    ///
+    /// Private keys, which carry the private exponent and primes, are not supported.
+    Rsa {
+        /// The modulus.
+        n: &'a [u8],
+        /// The public exponent.
+        e: &'a [u8],
+    },
+
+    /// A symmetric key (key type 4).
+    Symmetric {
+        /// The key value.
+        k: &'a [u8],
+    },
+}
+
+impl KeyParameters<'_> {
+    /// Returns the key type these parameters belong to.
+    #[inline]
+    pub const fn key_type(&self) -> i64 {
+        match self {
+            KeyParameters::Ec2 { .. } => KTY_EC2,
+            KeyParameters::Okp { .. } => KTY_OKP,
+            KeyParameters::Rsa { .. } => KTY_RSA,
+            KeyParameters::Symmetric { .. } => KTY_SYMMETRIC,
+        }
+    }
+}
+
+/// A `COSE_Key`: key material along with its optional common parameters.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{
+///     Value,
+///     cose::key::{CoseKey, ED25519},
+///     encode::Encode,
+/// };
+///
+/// let public = [0xD7; 32];
+/// let key = CoseKey::okp(ED25519, &public).with_kid(b"11");
+///
+/// let mut buf = [0u8; 64];
+/// let size = key.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..8], &[0xA4, 0x01, 0x01, 0x02, 0x42, 0x31, 0x31, 0x20]);
+///
+/// // Keys are read back from a decoded map
+/// let pairs = [
+///     (Value::unsigned(1), Value::unsigned(1)),
+///     (Value::negative(-1), Value::unsigned(6)),
+///     (Value::negative(-2), Value::bytes(&public)),
+/// ];
+/// let parsed = CoseKey::try_from(&Value::map(&pairs)).unwrap();
+/// assert_eq!(parsed, CoseKey::okp(ED25519, &public));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoseKey<'a> {
+    parameters: KeyParameters<'a>,
+    kid: Option<&'a [u8]>,
+    alg: Option<Label<'a>>,
+    key_ops: Option<&'a [Value<'a>]>,
+    base_iv: Option<&'a [u8]>,
+}
+
+impl<'a> CoseKey<'a> {
+    /// Creates a key from its key material, with no common parameters.
+    #[inline]
+    pub const fn new(parameters: KeyParameters<'a>) -> Self {
+        Self {
+            parameters,
+            kid: None,
+            alg: None,
+            key_ops: None,
+            base_iv: None,
+        }
+    }
+
+    /// Creates an `EC2` public key from its curve and coordinates.
+    #[inline]
+    pub const fn ec2(curve: i64, x: &'a [u8], y: &'a [u8]) -> Self {
+        Self::new(KeyParameters::Ec2 {
+            curve,
+            x,
+            y,
+            d: None,
+        })
+    }
+
+    /// Creates an `OKP` public key from its curve and public key.
+    #[inline]
+    pub const fn okp(curve: i64, x: &'a [u8]) -> Self {
+        Self::new(KeyParameters::Okp { curve, x, d: None })
+    }
+
+    /// Creates an RSA public key from its modulus and public exponent.
+    #[inline]
+    pub const fn rsa(n: &'a [u8], e: &'a [u8]) -> Self {
+        Self::new(KeyParameters::Rsa { n, e })
+    }
+
+    /// Creates a symmetric key from its key value.
+    #[inline]
+    pub const fn symmetric(k: &'a [u8]) -> Self {
+        Self::new(KeyParameters::Symmetric { k })
+    }
+
+    /// Returns the key with the given key identifier.
+    #[inline]
+    pub const fn with_kid(self, kid: &'a [u8]) -> Self {
+        Self {
+            kid: Some(kid),
+            ..self
+        }
+    }
+
+    /// Returns the key restricted to the given algorithm, such as
+    /// [`ES256`](crate::cose::ES256).
+    #[inline]
+    pub const fn with_alg(self, alg: i64) -> Self {
+        self.with_alg_label(Label::Int(alg))
+    }
+
+    /// Returns the key restricted to the given algorithm, which may be a text string.
+    #[inline]
+    pub const fn with_alg_label(self, alg: Label<'a>) -> Self {
+        Self {
+            alg: Some(alg),
+            ..self
+        }
+    }
+
+    /// Returns the key restricted to the given operations.
+    ///
+    /// Each operation is an integer, such as 1 for `sign`, or a text string.
+    #[inline]
+    pub const fn with_key_ops(self, key_ops: &'a [Value<'a>]) -> Self {
+        Self {
+            key_ops: Some(key_ops),
+            ..self
+        }
+    }
+
+    /// Returns the key with the given base initialization vector.
+    #[inline]
+    pub const fn with_base_iv(self, base_iv: &'a [u8]) -> Self {
+        Self {
+            base_iv: Some(base_iv),
+            ..self
+        }
+    }
+
+    /// Returns the key material.
+    #[inline]
+    pub const fn parameters(&self) -> KeyParameters<'a> {
+        self.parameters
+    }
+
+    /// Returns the key type, such as [`KTY_EC2`].
+    #[inline]
+    pub const fn key_type(&self) -> i64 {
+        self.parameters.key_type()
+    }
+
+    /// Returns the key identifier.
+    #[inline]
+    pub const fn kid(&self) -> Option<&'a [u8]> {
+        self.kid
+    }
+
+    /// Returns the algorithm the key is restricted to.
+    #[inline]
+    pub const fn alg(&self) -> Option<Label<'a>> {
+        self.alg
+    }
+
+    /// Returns the operations the key is restricted to.
+    #[inline]
+    pub const fn key_ops(&self) -> Option<&'a [Value<'a>]> {
+        self.key_ops
+    }
+
+    /// Returns the base initialization vector.
+    #[inline]
+    pub const fn base_iv(&self) -> Option<&'a [u8]> {
+        self.base_iv
+    }
+
+    /// Calculates the number of bytes needed to encode the key.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(false, encode::encoded_size)
+    }
+
+    /// Encodes the input to a COSE key thumbprint, as defined in RFC 9679.
+    ///
+    /// Only the required parameters of the key type are included, in deterministic order:
+    /// kty, crv, x and y for `EC2` keys, kty, crv and x for `OKP` keys, kty, n and e for
+    /// RSA keys, and kty and k for symmetric keys. Hashing the result gives the thumbprint.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of bytes written.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::cose::key::{CoseKey, P256};
+    ///
+    /// let key = CoseKey::ec2(P256, &[0x01; 32], &[0x02; 32]).with_kid(b"device");
+    /// let mut buf = [0u8; 80];
+    /// let size = key.thumbprint_input(&mut buf).unwrap();
+    /// // The kid is left out: {1: 2, -1: 1, -2: h'01..', -3: h'02..'}
+    /// assert_eq!(&buf[..7], &[0xA4, 0x01, 0x02, 0x20, 0x01, 0x21, 0x58]);
+    /// assert_eq!(size, 75);
+    /// ```
+    pub fn thumbprint_input(&self, buf: &mut [u8]) -> Result<usize> {
+        self.with_value(true, |value| encode::encode(value, buf))
+    }
+
+    /// Calls `f` with the key as a map `Value`, optionally limited to the parameters
+    /// required for a thumbprint.
+    ///
+    /// Entries are written in the deterministic order of their encoded labels: 1 to 5,
+    /// then -1, -2, -3 and -4.
+    fn with_value<R>(&self, required_only: bool, f: impl FnOnce(&Value) -> R) -> R {
+        let mut pairs = [(Value::null(), Value::null()); MAX_ENTRIES];
+        let mut len = 0;
+        let mut push = |label: i64, value: Value<'a>| {
+            pairs[len] = (Value::integer(label), value);
+            len += 1;
+        };
+
+        push(KTY, Value::integer(self.key_type()));
+        if !required_only {
+            if let Some(kid) = self.kid {
+                push(KID, Value::Bytes(kid));
+            }
+            if let Some(alg) = self.alg {
+                push(ALG, alg.to_value());
+            }
+            if let Some(key_ops) = self.key_ops {
+                push(KEY_OPS, Value::Array(key_ops));
+            }
+            if let Some(base_iv) = self.base_iv {
+                push(BASE_IV, Value::Bytes(base_iv));
+            }
+        }
+
+        let private = match self.parameters {
+            KeyParameters::Ec2 { curve, x, y, d } => {
+                push(CRV, Value::integer(curve));
+                push(X, Value::Bytes(x));
+                push(Y, Value::Bytes(y));
+                d
+            }
+            KeyParameters::Okp { curve, x, d } => {
+                push(CRV, Value::integer(curve));
+                push(X, Value::Bytes(x));
+                d
+            }
+            KeyParameters::Rsa { n, e } => {
+                push(N, Value::Bytes(n));
+                push(E, Value::Bytes(e));
+                None
+            }
+            KeyParameters::Symmetric { k } => {
+                push(K, Value::Bytes(k));
+                None
+            }
+        };
+        if let (Some(d), false) = (private, required_only) {
+            push(D, Value::Bytes(d));
+        }
+
+        f(&Value::Map(&pairs[..len]))
+    }
+}
+
+impl<'a> Encode<'a> for CoseKey<'_> {
+    /// Encodes the key as a map in deterministic order.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(false, |value| encode::encode(value, buf))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CoseKey<'a> {
+    type Error = Error;
+
+    /// Reads a `COSE_Key` map, checking the parameters its key type requires.
+    ///
+    /// Parameters this type does not model, such as the private exponent of an RSA key,
+    /// are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoseKey)` - The key.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a parameter is missing
+    ///   or has the wrong type.
+    /// * `Err(Error::InvalidValue)` - If the key type is unsupported, the curve does not
+    ///   belong to the key type, a coordinate has the wrong length for its curve, or
+    ///   `key_ops` is empty.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        if value.as_map().is_none() {
+            return Err(Error::InvalidType);
+        }
+
+        let key_type = match value.get_int(KTY) {
+            Some(Value::Text(_)) => return Err(Error::InvalidValue),
+            _ => label_int(value, KTY)?,
+        };
+        let parameters = match key_type {
+            Some(KTY_EC2) => {
+                let curve = required(label_int(value, CRV)?)?;
+                let x = required(label_bytes(value, X)?)?;
+                let y = required(label_bytes(value, Y)?)?;
+                let d = label_bytes(value, D)?;
+                check_curve(curve, KTY_EC2, &[x, y, d.unwrap_or(x)])?;
+                KeyParameters::Ec2 { curve, x, y, d }
+            }
+            Some(KTY_OKP) => {
+                let curve = required(label_int(value, CRV)?)?;
+                let x = required(label_bytes(value, X)?)?;
+                let d = label_bytes(value, D)?;
+                check_curve(curve, KTY_OKP, &[x, d.unwrap_or(x)])?;
+                KeyParameters::Okp { curve, x, d }
+            }
+            Some(KTY_RSA) => KeyParameters::Rsa {
+                n: required(label_bytes(value, N)?)?,
+                e: required(label_bytes(value, E)?)?,
+            },
+            Some(KTY_SYMMETRIC) => KeyParameters::Symmetric {
+                k: required(label_bytes(value, K)?)?,
+            },
+            Some(_) => return Err(Error::InvalidValue),
+            None => return Err(Error::InvalidType),
+        };
+
+        let key_ops = match value.get_int(KEY_OPS) {
+            Some(Value::Array([])) => return Err(Error::InvalidValue),
+            Some(Value::Array(ops)) => {
+                for op in *ops {
+                    let _ = Label::try_from(op)?;
+                }
+                Some(*ops)
+            }
+            Some(_) => return Err(Error::InvalidType),
+            None => None,
+        };
+
+        Ok(Self {
+            parameters,
+            kid: label_bytes(value, KID)?,
+            alg: value.get_int(ALG).map(Label::try_from).transpose()?,
+            key_ops,
+            base_iv: label_bytes(value, BASE_IV)?,
+        })
+    }
+}
+
+/// Reads an optional integer parameter.
+fn label_int(map: &Value, label: i64) -> Result<Option<i64>> {
+    match map.get_int(label) {
+        Some(value) => match value.as_i128().map(i64::try_from) {
+            Some(Ok(n)) => Ok(Some(n)),
+            _ => Err(Error::InvalidType),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Reads an optional byte string parameter.
+fn label_bytes<'a>(map: &Value<'a>, label: i64) -> Result<Option<&'a [u8]>> {
+    match map.get_int(label) {
+        Some(value) => value.as_bytes().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Checks that a registered curve belongs to the key type and that every coordinate has
+/// the curve's length.
+///
+/// Curves outside the registry are accepted as they are.
+fn check_curve(curve: i64, key_type: i64, coordinates: &[&[u8]]) -> Result<()> {
+    let (expected_type, len) = match curve {
+        P256 => (KTY_EC2, 32),
+        P384 => (KTY_EC2, 48),
+        P521 => (KTY_EC2, 66),
+        X25519 | ED25519 => (KTY_OKP, 32),
+        X448 => (KTY_OKP, 56),
+        ED448 => (KTY_OKP, 57),
+        _ => return Ok(()),
+    };
+    if expected_type != key_type || coordinates.iter().any(|c| c.len() != len) {
+        return Err(Error::InvalidValue);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoseKey, ED25519, KTY_EC2, KeyParameters, Label, P256, P384};
+    use crate::{Arena, Value, cbor, cose, decode::decode, encode::Encode, error::Error};
+
+    /// The P-256 public key with kid "meriadoc.brandybuck@buckland.example" from the COSE
+    /// working group examples, as used in RFC 9679 §6.
+    const X: [u8; 32] = [
+        0x65, 0xED, 0xA5, 0xA1, 0x25, 0x77, 0xC2, 0xBA, 0xE8, 0x29, 0x43, 0x7F, 0xE3, 0x38, 0x70,
+        0x1A, 0x10, 0xAA, 0xA3, 0x75, 0xE1, 0xBB, 0x5B, 0x5D, 0xE1, 0x08, 0xDE, 0x43, 0x9C, 0x08,
+        0x55, 0x1D,
+    ];
+    const Y: [u8; 32] = [
+        0x1E, 0x52, 0xED, 0x75, 0x70, 0x11, 0x63, 0xF7, 0xF9, 0xE4, 0x0D, 0xDF, 0x9F, 0x34, 0x1B,
+        0x3D, 0xC9, 0xBA, 0x86, 0x0A, 0xF7, 0xE0, 0xCA, 0x7C, 0xA7, 0xE9, 0xEE, 0xCD, 0x00, 0x84,
+        0xD1, 0x9C,
+    ];
+    const KID: &[u8] = b"meriadoc.brandybuck@buckland.example";
+
+    #[test]
+    fn test_encode_ec2() {
+        let key = CoseKey::ec2(P256, &X, &Y)
+            .with_kid(KID)
+            .with_alg(cose::ES256);
+        let mut buf = [0u8; 128];
+        let size = key.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, key.encoded_size());
+
+        // {1: 2, 2: h'6D65..', 3: -7, -1: 1, -2: h'65ED..', -3: h'1E52..'}
+        assert_eq!(&buf[..5], &[0xA6, 0x01, 0x02, 0x02, 0x58]);
+        assert_eq!(&buf[6..6 + KID.len()], KID);
+        let rest = &buf[6 + KID.len()..size];
+        assert_eq!(&rest[..7], &[0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+        assert_eq!(&rest[7..39], &X);
+        assert_eq!(&rest[39..42], &[0x22, 0x58, 0x20]);
+        assert_eq!(&rest[42..], &Y);
+    }
+
+    #[test]
+    fn test_thumbprint_input() {
+        // RFC 9679 §6 gives the input for this key as
+        // {1: 2, -1: 1, -2: h'65ED..', -3: h'1E52..'}
+        let key = CoseKey::ec2(P256, &X, &Y)
+            .with_kid(KID)
+            .with_alg(cose::ES256);
+        let mut buf = [0u8; 80];
+        let size = key.thumbprint_input(&mut buf).unwrap();
+        assert_eq!(size, 75);
+        assert_eq!(&buf[..8], &[0xA4, 0x01, 0x02, 0x20, 0x01, 0x21, 0x58, 0x20]);
+        assert_eq!(&buf[8..40], &X);
+        assert_eq!(&buf[40..43], &[0x22, 0x58, 0x20]);
+        assert_eq!(&buf[43..75], &Y);
+
+        // The private key is never part of the thumbprint
+        let private = CoseKey::new(KeyParameters::Okp {
+            curve: ED25519,
+            x: &X,
+            d: Some(&Y),
+        });
+        let size = private.thumbprint_input(&mut buf).unwrap();
+        assert_eq!(&buf[..6], &[0xA3, 0x01, 0x01, 0x20, 0x06, 0x21]);
+        assert_eq!(size, 40);
+        assert_eq!(private.encoded_size(), 40 + 35);
+
+        let rsa = CoseKey::rsa(&[0xC5; 4], &[0x01, 0x00, 0x01]).with_alg(cose::RS256);
+        let size = rsa.thumbprint_input(&mut buf).unwrap();
+        assert_eq!(
+            &buf[..size],
+            &[
+                0xA3, 0x01, 0x03, 0x20, 0x44, 0xC5, 0xC5, 0xC5, 0xC5, 0x21, 0x43, 0x01, 0x00, 0x01
+            ]
+        );
+
+        let symmetric = CoseKey::symmetric(b"secret").with_kid(b"k");
+        let size = symmetric.thumbprint_input(&mut buf).unwrap();
+        assert_eq!(&buf[..size], b"\xA2\x01\x04\x20\x46secret");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let keys = [
+            CoseKey::ec2(P256, &X, &Y).with_kid(KID),
+            CoseKey::new(KeyParameters::Ec2 {
+                curve: P256,
+                x: &X,
+                y: &Y,
+                d: Some(&X),
+            }),
+            CoseKey::okp(ED25519, &Y).with_alg(cose::EDDSA),
+            CoseKey::rsa(&[0xC5; 256], &[0x01, 0x00, 0x01]).with_alg(cose::RS256),
+            CoseKey::symmetric(&[0x2A; 16]).with_alg(cose::A128GCM),
+            CoseKey::symmetric(&[0x2A; 16])
+                .with_alg_label(Label::Text("vendor-aead"))
+                .with_key_ops(&[Value::Unsigned(3), Value::Text("vendor-op")])
+                .with_base_iv(&[0x5A; 8]),
+        ];
+        for key in keys {
+            let mut buf = [0u8; 320];
+            let size = key.as_cbor(&mut buf).unwrap();
+            let mut values = [Value::null(); 2];
+            let mut pairs = [(Value::null(), Value::null()); 8];
+            let mut arena = Arena::new(&mut values, &mut pairs);
+            let value = decode(&buf[..size], &mut arena).unwrap();
+            assert_eq!(CoseKey::try_from(&value), Ok(key));
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        // Parameters this type does not model are ignored
+        static EXTRA: Value = cbor!({1: 4, -1: h "00", -2: h "01", "use": "enc"});
+        assert_eq!(CoseKey::try_from(&EXTRA), Ok(CoseKey::symmetric(&[0x00])));
+
+        // The algorithm and operations may be text strings
+        static TEXT_ALG: Value = cbor!({1: 4, -1: h "00", 3: "vendor", 4: [1, "op"]});
+        let key = CoseKey::try_from(&TEXT_ALG).unwrap();
+        assert_eq!(key.alg(), Some(Label::Text("vendor")));
+        assert_eq!(
+            key.key_ops(),
+            Some(&[Value::unsigned(1), Value::text("op")][..])
+        );
+
+        static NOT_A_MAP: Value = cbor!([1, 4]);
+        static NO_KTY: Value = cbor!({-1: h "00"});
+        static TEXT_KTY: Value = cbor!({1: "EC2"});
+        static NO_Y: Value = cbor!({1: 2, -1: 99, -2: h "00"});
+        static COMPRESSED: Value = cbor!({1: 2, -1: 99, -2: h "00", -3: true});
+        static NO_K: Value = cbor!({1: 4});
+        static NO_E: Value = cbor!({1: 3, -1: h "00"});
+        static TEXT_KID: Value = cbor!({1: 4, -1: h "00", 2: "kid"});
+        static BYTES_ALG: Value = cbor!({1: 4, -1: h "00", 3: h "00"});
+        static KEY_OPS_MAP: Value = cbor!({1: 4, -1: h "00", 4: {1: 2}});
+        static KEY_OP_BYTES: Value = cbor!({1: 4, -1: h "00", 4: [h "00"]});
+        for value in [
+            &NOT_A_MAP,
+            &NO_KTY,
+            &NO_Y,
+            &COMPRESSED,
+            &NO_K,
+            &NO_E,
+            &TEXT_KID,
+            &BYTES_ALG,
+            &KEY_OPS_MAP,
+            &KEY_OP_BYTES,
+        ] {
+            assert_eq!(CoseKey::try_from(value), Err(Error::InvalidType), "{value}");
+        }
+
+        static UNKNOWN_KTY: Value = cbor!({1: 5, -1: h "00"});
+        static OKP_CURVE: Value = cbor!({1: 2, -1: 6, -2: h "00", -3: h "00"});
+        static NO_KEY_OPS: Value = cbor!({1: 4, -1: h "00", 4: []});
+        assert_eq!(CoseKey::try_from(&UNKNOWN_KTY), Err(Error::InvalidValue));
+        assert_eq!(CoseKey::try_from(&NO_KEY_OPS), Err(Error::InvalidValue));
+
+        // Text key types are allowed, but none are supported
+        assert_eq!(CoseKey::try_from(&TEXT_KTY), Err(Error::InvalidValue));
+        assert_eq!(CoseKey::try_from(&OKP_CURVE), Err(Error::InvalidValue));
+
+        // Coordinates must match the curve's length, but private curves are unchecked
+        let pairs = [
+            (Value::unsigned(1), Value::unsigned(KTY_EC2 as u64)),
+            (Value::negative(-1), Value::unsigned(P384 as u64)),
+            (Value::negative(-2), Value::bytes(&X)),
+            (Value::negative(-3), Value::bytes(&Y)),
+        ];
+        assert_eq!(
+            CoseKey::try_from(&Value::map(&pairs)),
+            Err(Error::InvalidValue)
+        );
+        static PRIVATE_CURVE: Value = cbor!({1: 2, -1: -65537, -2: h "00", -3: h "0000"});
+        assert!(CoseKey::try_from(&PRIVATE_CURVE).is_ok());
+    }
+}
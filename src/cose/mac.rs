@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: MIT
+
+//! `COSE_Mac0` messages (tag 17) and their `MAC_structure`.
+//!
+//! See [RFC 9052 §6.2](https://www.rfc-editor.org/rfc/rfc9052#section-6.2) for the
+//! message layout and [§6.3](https://www.rfc-editor.org/rfc/rfc9052#section-6.3) for the
+//! bytes that are authenticated.
+
+use crate::{
+    Value,
+    cose::{self, detachable, detached_value, headers, message_items},
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    value::Map,
+};
+
+/// The context string of a `MAC_structure` for a `COSE_Mac0` message.
+const MAC0: &str = "MAC0";
+
+/// A message authentication code algorithm provided by the caller's crypto backend.
+pub trait Mac {
+    /// Computes the authentication tag of some data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The encoded `MAC_structure`.
+    /// * `tag` - The buffer to write the tag into.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the tag.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small for the tag.
+    /// * `Err(Error)` - Any other error, as chosen by the backend.
+    fn compute(&self, data: &[u8], tag: &mut [u8]) -> Result<usize>;
+}
+
+/// The `MAC_structure` of a `COSE_Mac0` message, whose encoding is what gets
+/// authenticated.
+///
+/// It is the array `["MAC0", protected, external_aad, payload]`.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{cose::MacStructure, encode::Encode};
+///
+/// let data = MacStructure::new(&[0xA1, 0x01, 0x05], b"", b"hi");
+/// let mut buf = [0u8; 16];
+/// let size = data.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..size], b"\x84\x64MAC0\x43\xA1\x01\x05\x40\x42hi");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacStructure<'a> {
+    protected: &'a [u8],
+    external_aad: &'a [u8],
+    payload: &'a [u8],
+}
+
+impl<'a> MacStructure<'a> {
+    /// Creates a `MAC_structure` from its fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header of the message.
+    /// * `external_aad` - Additional data supplied by the application, often empty.
+    /// * `payload` - The payload, whether or not it is carried in the message.
+    #[inline]
+    pub const fn new(protected: &'a [u8], external_aad: &'a [u8], payload: &'a [u8]) -> Self {
+        Self {
+            protected,
+            external_aad,
+            payload,
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the structure.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the structure as a `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Text(MAC0),
+            Value::Bytes(self.protected),
+            Value::Bytes(self.external_aad),
+            Value::Bytes(self.payload),
+        ];
+        f(&Value::Array(&items))
+    }
+}
+
+impl<'a> Encode<'a> for MacStructure<'_> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+/// A `COSE_Mac0` message (tag 17): a payload authenticated with a key implied by
+/// context.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, cbor, cose::CoseMac0};
+///
+/// static MESSAGE: Value = cbor!(17([h "A10105", {}, h "CAFE", h "A1B2"]));
+///
+/// let message = CoseMac0::try_from(&MESSAGE).unwrap();
+/// assert_eq!(message.payload(), Some(&[0xCA, 0xFE][..]));
+/// assert_eq!(message.tag(), &[0xA1, 0xB2]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoseMac0<'a> {
+    protected: &'a [u8],
+    unprotected: Map<'a>,
+    payload: Option<&'a [u8]>,
+    tag: &'a [u8],
+}
+
+impl<'a> CoseMac0<'a> {
+    /// Creates a message from its parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header, as from
+    ///   [`encode_protected`](cose::encode_protected).
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `payload` - The payload, or `None` if it is detached and carried elsewhere.
+    /// * `tag` - The authentication tag over the message's [`MacStructure`].
+    #[inline]
+    pub const fn new(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        payload: Option<&'a [u8]>,
+        tag: &'a [u8],
+    ) -> Self {
+        Self {
+            protected,
+            unprotected,
+            payload,
+            tag,
+        }
+    }
+
+    /// Authenticates a payload and assembles the message around it.
+    ///
+    /// The `MAC_structure` is encoded at the start of `scratch` and passed to `mac`, which
+    /// writes the tag into the rest of `scratch`. The returned message borrows the tag
+    /// from there.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header.
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `payload` - The payload to authenticate and carry in the message.
+    /// * `external_aad` - Additional data that is authenticated but not carried.
+    /// * `mac` - The MAC algorithm.
+    /// * `scratch` - Space for the `MAC_structure` followed by the tag.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoseMac0)` - The authenticated message.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `mac`.
+    pub fn authenticate<M: Mac + ?Sized>(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        payload: &'a [u8],
+        external_aad: &[u8],
+        mac: &M,
+        scratch: &'a mut [u8],
+    ) -> Result<Self> {
+        let data = MacStructure::new(protected, external_aad, payload);
+        let len = data.as_cbor(scratch)?;
+        let (data, tag) = scratch.split_at_mut(len);
+        let len = mac.compute(data, tag)?;
+        Ok(Self::new(
+            protected,
+            unprotected,
+            Some(payload),
+            &tag[..len],
+        ))
+    }
+
+    /// Checks the authentication tag of the message.
+    ///
+    /// The expected tag is recomputed after the `MAC_structure` in `scratch` and compared
+    /// in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `external_aad` - The additional data the message was authenticated with.
+    /// * `mac` - The MAC algorithm.
+    /// * `scratch` - Space for the `MAC_structure` followed by the expected tag.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the tag is valid.
+    /// * `Err(Error::InvalidValue)` - If the tag does not match.
+    /// * `Err(Error::InvalidType)` - If the payload is detached; supply it with
+    ///   [`with_payload`](Self::with_payload) first.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `mac`.
+    pub fn verify<M: Mac + ?Sized>(
+        &self,
+        external_aad: &[u8],
+        mac: &M,
+        scratch: &mut [u8],
+    ) -> Result<()> {
+        let Some(payload) = self.payload else {
+            return Err(Error::InvalidType);
+        };
+        let data = MacStructure::new(self.protected, external_aad, payload);
+        let len = data.as_cbor(scratch)?;
+        let (data, expected) = scratch.split_at_mut(len);
+        let len = mac.compute(data, expected)?;
+        if constant_time_eq(&expected[..len], self.tag) {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    /// Returns the message with the given payload, as needed to verify a message whose
+    /// payload was detached.
+    #[inline]
+    pub const fn with_payload(self, payload: &'a [u8]) -> Self {
+        Self {
+            payload: Some(payload),
+            ..self
+        }
+    }
+
+    /// Returns the encoded protected header.
+    #[inline]
+    pub const fn protected(&self) -> &'a [u8] {
+        self.protected
+    }
+
+    /// Returns the entries of the unprotected header map.
+    #[inline]
+    pub const fn unprotected(&self) -> Map<'a> {
+        self.unprotected
+    }
+
+    /// Returns the payload, or `None` if it is detached.
+    #[inline]
+    pub const fn payload(&self) -> Option<&'a [u8]> {
+        self.payload
+    }
+
+    /// Returns the authentication tag.
+    #[inline]
+    pub const fn tag(&self) -> &'a [u8] {
+        self.tag
+    }
+
+    /// Calculates the number of bytes needed to encode the message.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the message as a tagged `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Bytes(self.protected),
+            Value::Map(self.unprotected),
+            detached_value(self.payload),
+            Value::Bytes(self.tag),
+        ];
+        let array = Value::Array(&items);
+        f(&Value::Tag(cose::COSE_MAC0, &array))
+    }
+}
+
+impl<'a> Encode<'a> for CoseMac0<'_> {
+    /// Encodes the message wrapped in tag 17.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CoseMac0<'a> {
+    type Error = Error;
+
+    /// Validates the `COSE_Mac0` layout, tagged with 17 or untagged, and extracts its parts.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let [protected, unprotected, payload, tag] = message_items(value, cose::COSE_MAC0)?;
+        let (protected, unprotected) = headers(protected, unprotected)?;
+        let Value::Bytes(tag) = tag else {
+            return Err(Error::InvalidType);
+        };
+        Ok(Self::new(protected, unprotected, detachable(payload)?, tag))
+    }
+}
+
+/// Compares two byte strings without branching on their contents.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoseMac0, Mac, MacStructure, constant_time_eq};
+    use crate::{Arena, Value, cbor, decode::decode, encode::Encode, error::Error, result::Result};
+
+    /// A keyed checksum standing in for a real MAC algorithm.
+    struct Checksum(u8);
+
+    impl Mac for Checksum {
+        fn compute(&self, data: &[u8], tag: &mut [u8]) -> Result<usize> {
+            let sum = data
+                .iter()
+                .fold(self.0, |sum, byte| sum.wrapping_add(*byte));
+            let out = tag.get_mut(..2).ok_or(Error::BufferOverflow)?;
+            out.copy_from_slice(&[sum, !sum]);
+            Ok(2)
+        }
+    }
+
+    /// The protected header `{1: 5}`, selecting HMAC 256/256.
+    const PROTECTED: [u8; 3] = [0xA1, 0x01, 0x05];
+    const CONTENT: &[u8] = b"This is the content.";
+
+    #[test]
+    fn test_mac_structure() {
+        // The `ToMac_hex` of the `HMac-01` example from the COSE working group
+        let data = MacStructure::new(&PROTECTED, &[], CONTENT);
+        let mut buf = [0u8; 64];
+        let size = data.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..6], &[0x84, 0x64, 0x4D, 0x41, 0x43, 0x30]);
+        assert_eq!(&buf[6..12], &[0x43, 0xA1, 0x01, 0x05, 0x40, 0x54]);
+        assert_eq!(&buf[12..size], CONTENT);
+        assert_eq!(data.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_authenticate_and_verify() {
+        let mut scratch = [0u8; 64];
+        let message =
+            CoseMac0::authenticate(&PROTECTED, &[], CONTENT, &[], &Checksum(1), &mut scratch)
+                .unwrap();
+        assert_eq!(message.tag().len(), 2);
+
+        let mut buf = [0u8; 64];
+        let size = message.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, message.encoded_size());
+        assert_eq!(&buf[..7], &[0xD1, 0x84, 0x43, 0xA1, 0x01, 0x05, 0xA0]);
+
+        let mut values = [Value::null(); 5];
+        let mut arena = Arena::new(&mut values, &mut []);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let parsed = CoseMac0::try_from(&value).unwrap();
+        assert_eq!(parsed, message);
+
+        let mut scratch = [0u8; 64];
+        assert_eq!(parsed.verify(&[], &Checksum(1), &mut scratch), Ok(()));
+        assert_eq!(
+            parsed.verify(&[], &Checksum(2), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            parsed.verify(b"aad", &Checksum(1), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        static DETACHED: Value = cbor!([h "", {}, null, h "00"]);
+        let detached = CoseMac0::try_from(&DETACHED).unwrap();
+        assert_eq!(detached.payload(), None);
+        assert_eq!(
+            detached.verify(&[], &Checksum(0), &mut [0u8; 32]),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(detached.with_payload(CONTENT).payload(), Some(CONTENT));
+
+        static ENCRYPT0: Value = cbor!(16([h "", {}, null, h "00"]));
+        static TEXT_TAG: Value = cbor!(17([h "", {}, null, "00"]));
+        assert_eq!(CoseMac0::try_from(&ENCRYPT0), Err(Error::InvalidType));
+        assert_eq!(CoseMac0::try_from(&TEXT_TAG), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(&[1, 2], &[1, 2]));
+        assert!(!constant_time_eq(&[1, 2], &[1, 3]));
+        assert!(!constant_time_eq(&[1, 2], &[1]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+}
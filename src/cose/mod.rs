@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: MIT
+
+//! CBOR Object Signing and Encryption (COSE) message framing, as defined in
+//! [RFC 9052](https://www.rfc-editor.org/rfc/rfc9052).
+//!
+//! This module builds and parses the CBOR structures of COSE messages, including the
+//! exact bytes that are signed, encrypted or authenticated, but performs no cryptography
+//! itself. The caller's crypto backend plugs in through the [`Signer`] and [`Verifier`]
+//! traits for [`CoseSign1`], [`Aead`] for [`CoseEncrypt0`] and [`Mac`] for [`CoseMac0`].
+//!
+//! Protected headers are carried as the encoded bytes of a header map, since signatures
+//! cover those exact bytes. [`encode_protected`] produces them from a map `Value`, and
+//! [`decode_protected`] reads them back.
+//!
+//! Keys are represented by [`key::CoseKey`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Value, cbor,
+//!     cose::{self, CoseSign1},
+//!     encode::Encode,
+//!     error::Error,
+//! };
+//!
+//! /// A stand-in for a real signature algorithm.
+//! struct Checksum;
+//!
+//! impl cose::Signer for Checksum {
+//!     fn sign(&self, to_be_signed: &[u8], signature: &mut [u8]) -> Result<usize, Error> {
+//!         let sum = to_be_signed.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+//!         *signature.first_mut().ok_or(Error::BufferOverflow)? = sum;
+//!         Ok(1)
+//!     }
+//! }
+//!
+//! let mut protected = [0u8; 8];
+//! let len = cose::encode_protected(&cbor!({1: -7}), &mut protected).unwrap();
+//! let unprotected = [(Value::unsigned(4), Value::bytes(b"11"))];
+//!
+//! let mut scratch = [0u8; 64];
+//! let message = CoseSign1::sign(
+//!     &protected[..len],
+//!     &unprotected,
+//!     b"This is the content.",
+//!     b"",
+//!     &Checksum,
+//!     &mut scratch,
+//! )
+//! .unwrap();
+//!
+//! let mut buf = [0u8; 64];
+//! let size = message.as_cbor(&mut buf).unwrap();
+//! assert_eq!(&buf[..4], &[0xD2, 0x84, 0x43, 0xA1]);
+//! assert_eq!(size, message.encoded_size());
+//! ```
+
+mod encrypt;
+pub mod key;
+mod mac;
+mod sign;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use encrypt::*;
+pub use mac::*;
+pub use sign::*;
+
+use crate::{Arena, Value, decode::decode, encode, error::Error, result::Result, value::Map};
+
+/// Tag 16: A `COSE_Encrypt0` message, encrypted with a key implied by context.
+pub const COSE_ENCRYPT0: u64 = 16;
+/// Tag 17: A `COSE_Mac0` message, authenticated with a key implied by context.
+pub const COSE_MAC0: u64 = 17;
+/// Tag 18: A `COSE_Sign1` message, signed by a single signer.
+pub const COSE_SIGN1: u64 = 18;
+
+/// Header label 1: The algorithm used for the security processing.
+pub const ALG: i64 = 1;
+/// Header label 2: The header parameters a recipient must understand.
+pub const CRIT: i64 = 2;
+/// Header label 3: The content type of the payload.
+pub const CONTENT_TYPE: i64 = 3;
+/// Header label 4: The identifier of the key used.
+pub const KID: i64 = 4;
+/// Header label 5: The full initialization vector.
+pub const IV: i64 = 5;
+/// Header label 6: The part of the initialization vector that is not implied.
+pub const PARTIAL_IV: i64 = 6;
+
+/// Algorithm 1: AES-GCM with a 128-bit key and 128-bit tag.
+pub const A128GCM: i64 = 1;
+/// Algorithm 2: AES-GCM with a 192-bit key and 128-bit tag.
+pub const A192GCM: i64 = 2;
+/// Algorithm 3: AES-GCM with a 256-bit key and 128-bit tag.
+pub const A256GCM: i64 = 3;
+/// Algorithm 5: HMAC with SHA-256, untruncated.
+pub const HMAC_256_256: i64 = 5;
+/// Algorithm 6: HMAC with SHA-384, untruncated.
+pub const HMAC_384_384: i64 = 6;
+/// Algorithm 7: HMAC with SHA-512, untruncated.
+pub const HMAC_512_512: i64 = 7;
+/// Algorithm 24: ChaCha20/Poly1305 with a 256-bit key and 128-bit tag.
+pub const CHACHA20_POLY1305: i64 = 24;
+/// Algorithm -7: ECDSA with SHA-256.
+pub const ES256: i64 = -7;
+/// Algorithm -8: EdDSA.
+pub const EDDSA: i64 = -8;
+/// Algorithm -16: SHA-256, as a hash algorithm.
+pub const SHA_256: i64 = -16;
+/// Algorithm -35: ECDSA with SHA-384.
+pub const ES384: i64 = -35;
+/// Algorithm -36: ECDSA with SHA-512.
+pub const ES512: i64 = -36;
+/// Algorithm -37: RSASSA-PSS with SHA-256.
+pub const PS256: i64 = -37;
+/// Algorithm -43: SHA-384, as a hash algorithm.
+pub const SHA_384: i64 = -43;
+/// Algorithm -44: SHA-512, as a hash algorithm.
+pub const SHA_512: i64 = -44;
+/// Algorithm -257: RSASSA-PKCS1-v1_5 with SHA-256.
+pub const RS256: i64 = -257;
+
+/// Encodes a header map as the contents of a protected header bucket.
+///
+/// An empty map is encoded as no bytes at all, as RFC 9052 section 3 requires.
+///
+/// # Arguments
+///
+/// * `header` - The header map.
+/// * `buf` - The buffer to write the encoded map into.
+///
+/// # Returns
+///
+/// * `Ok(usize)` - The number of bytes written.
+/// * `Err(Error::InvalidType)` - If `header` is not a map.
+/// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+pub fn encode_protected(header: &Value, buf: &mut [u8]) -> Result<usize> {
+    match header {
+        Value::Map([]) => Ok(0),
+        Value::Map(_) => encode::encode(header, buf),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Decodes the contents of a protected header bucket into a header map.
+///
+/// Both an empty bucket and an encoded empty map decode to an empty map.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The header map.
+/// * `Err(Error::InvalidType)` - If the bucket holds something other than a map.
+/// * `Err(Error)` - If the bucket is malformed, as for [`decode`].
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, Value, cose};
+///
+/// let mut pairs = [(Value::null(), Value::null()); 1];
+/// let mut arena = Arena::new(&mut [], &mut pairs);
+/// let header = cose::decode_protected(&[0xA1, 0x01, 0x26], &mut arena).unwrap();
+/// assert_eq!(header.get_int(cose::ALG), Some(&Value::negative(cose::ES256)));
+/// ```
+pub fn decode_protected<'a>(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Value<'a>> {
+    if bytes.is_empty() {
+        return Ok(Value::Map(&[]));
+    }
+    match decode(bytes, arena)? {
+        header @ Value::Map(_) => Ok(header),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Returns the items of a COSE message array, which may be wrapped in its tag.
+///
+/// RFC 9052 leaves the tag to the application, so untagged messages are accepted too.
+fn message_items<'a, const N: usize>(value: &Value<'a>, tag: u64) -> Result<&'a [Value<'a>; N]> {
+    let items = match value {
+        Value::Tag(found, Value::Array(items)) if *found == tag => items,
+        Value::Array(items) => items,
+        _ => return Err(Error::InvalidType),
+    };
+    <&[Value; N]>::try_from(*items).map_err(|_| Error::InvalidType)
+}
+
+/// Splits the protected and unprotected header buckets that begin every COSE message.
+fn headers<'a>(protected: &Value<'a>, unprotected: &Value<'a>) -> Result<(&'a [u8], Map<'a>)> {
+    match (protected, unprotected) {
+        (Value::Bytes(protected), Value::Map(unprotected)) => Ok((protected, unprotected)),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Reads a payload or ciphertext field, which is `nil` when detached.
+fn detachable<'a>(value: &Value<'a>) -> Result<Option<&'a [u8]>> {
+    match value {
+        Value::Bytes(bytes) => Ok(Some(bytes)),
+        _ if value.is_null() => Ok(None),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Returns the value of a payload or ciphertext field, using `nil` when detached.
+const fn detached_value(bytes: Option<&[u8]>) -> Value<'_> {
+    match bytes {
+        Some(bytes) => Value::Bytes(bytes),
+        None => Value::null(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ALG, ES256, decode_protected, encode_protected, message_items};
+    use crate::{Arena, Value, cbor, error::Error};
+
+    #[test]
+    fn test_protected_headers() {
+        let mut buf = [0u8; 8];
+        assert_eq!(encode_protected(&cbor!({}), &mut buf), Ok(0));
+        assert_eq!(encode_protected(&cbor!({1: -7}), &mut buf), Ok(3));
+        assert_eq!(&buf[..3], &[0xA1, 0x01, 0x26]);
+        assert_eq!(
+            encode_protected(&cbor!([1]), &mut buf),
+            Err(Error::InvalidType)
+        );
+
+        let mut pairs = [(Value::null(), Value::null()); 1];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let header = decode_protected(&buf[..3], &mut arena).unwrap();
+        assert_eq!(header.get_int(ALG), Some(&Value::negative(ES256)));
+
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(decode_protected(&[], &mut arena), Ok(Value::Map(&[])));
+        assert_eq!(decode_protected(&[0xA0], &mut arena), Ok(Value::Map(&[])));
+        assert_eq!(
+            decode_protected(&[0x80], &mut arena),
+            Err(Error::InvalidType)
+        );
+    }
+
+    #[test]
+    fn test_message_items() {
+        static TAGGED: Value = cbor!(18([1, 2]));
+        static UNTAGGED: Value = cbor!([1, 2]);
+        assert!(message_items::<2>(&TAGGED, 18).is_ok());
+        assert!(message_items::<2>(&UNTAGGED, 18).is_ok());
+        assert_eq!(
+            message_items::<2>(&TAGGED, 16).err(),
+            Some(Error::InvalidType)
+        );
+        assert_eq!(
+            message_items::<3>(&UNTAGGED, 18).err(),
+            Some(Error::InvalidType)
+        );
+    }
+}
@@ -0,0 +1,473 @@
+// SPDX-License-Identifier: MIT
+
+//! `COSE_Sign1` messages (tag 18) and their `Sig_structure`.
+//!
+//! See [RFC 9052 §4.2](https://www.rfc-editor.org/rfc/rfc9052#section-4.2) for the
+//! message layout and [§4.4](https://www.rfc-editor.org/rfc/rfc9052#section-4.4) for the
+//! bytes that are signed.
+
+use crate::{
+    Value,
+    cose::{self, detachable, detached_value, headers, message_items},
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    value::Map,
+};
+
+/// The context string of a `Sig_structure` for a `COSE_Sign1` message.
+const SIGNATURE1: &str = "Signature1";
+
+/// A signature algorithm provided by the caller's crypto backend.
+pub trait Signer {
+    /// Signs the to-be-signed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_be_signed` - The encoded `Sig_structure`.
+    /// * `signature` - The buffer to write the signature into.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the signature.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small for the signature.
+    /// * `Err(Error)` - Any other error, as chosen by the backend.
+    fn sign(&self, to_be_signed: &[u8], signature: &mut [u8]) -> Result<usize>;
+}
+
+/// A signature check provided by the caller's crypto backend.
+pub trait Verifier {
+    /// Checks a signature over the to-be-signed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_be_signed` - The encoded `Sig_structure`.
+    /// * `signature` - The signature carried by the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the signature is valid.
+    /// * `Err(Error::InvalidValue)` - If the signature does not verify.
+    fn verify(&self, to_be_signed: &[u8], signature: &[u8]) -> Result<()>;
+}
+
+/// The `Sig_structure` of a `COSE_Sign1` message, whose encoding is what gets signed.
+///
+/// It is the array `["Signature1", body_protected, external_aad, payload]`, where every
+/// field after the context is a byte string. Since the fields are fixed, the encoding is
+/// deterministic.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{cose::SigStructure, encode::Encode};
+///
+/// let tbs = SigStructure::new(&[0xA1, 0x01, 0x26], b"", b"payload");
+/// let mut buf = [0u8; 32];
+/// let size = tbs.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..2], &[0x84, 0x6A]);
+/// assert_eq!(size, tbs.encoded_size());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigStructure<'a> {
+    body_protected: &'a [u8],
+    external_aad: &'a [u8],
+    payload: &'a [u8],
+}
+
+impl<'a> SigStructure<'a> {
+    /// Creates a `Sig_structure` from its fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `body_protected` - The encoded protected header of the message.
+    /// * `external_aad` - Additional data supplied by the application, often empty.
+    /// * `payload` - The payload, whether or not it is carried in the message.
+    #[inline]
+    pub const fn new(body_protected: &'a [u8], external_aad: &'a [u8], payload: &'a [u8]) -> Self {
+        Self {
+            body_protected,
+            external_aad,
+            payload,
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the structure.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the structure as a `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Text(SIGNATURE1),
+            Value::Bytes(self.body_protected),
+            Value::Bytes(self.external_aad),
+            Value::Bytes(self.payload),
+        ];
+        f(&Value::Array(&items))
+    }
+}
+
+impl<'a> Encode<'a> for SigStructure<'_> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+/// A `COSE_Sign1` message (tag 18): a payload signed by a single signer.
+///
+/// The message borrows all of its parts, so it can be built over caller-owned buffers or
+/// parsed from a decoded `Value` without copying.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, Value, cose::CoseSign1, decode::decode};
+///
+/// // 18([h'A10126', {}, h'CAFE', h'5167'])
+/// let bytes = [
+///     0xD2, 0x84, 0x43, 0xA1, 0x01, 0x26, 0xA0, 0x42, 0xCA, 0xFE, 0x42, 0x51, 0x67,
+/// ];
+/// let mut values = [Value::null(); 5];
+/// let mut arena = Arena::new(&mut values, &mut []);
+/// let value = decode(&bytes, &mut arena).unwrap();
+///
+/// let message = CoseSign1::try_from(&value).unwrap();
+/// assert_eq!(message.protected(), &[0xA1, 0x01, 0x26]);
+/// assert_eq!(message.payload(), Some(&[0xCA, 0xFE][..]));
+/// assert_eq!(message.signature(), &[0x51, 0x67]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoseSign1<'a> {
+    protected: &'a [u8],
+    unprotected: Map<'a>,
+    payload: Option<&'a [u8]>,
+    signature: &'a [u8],
+}
+
+impl<'a> CoseSign1<'a> {
+    /// Creates a message from its parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header, as from
+    ///   [`encode_protected`](cose::encode_protected).
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `payload` - The payload, or `None` if it is detached and carried elsewhere.
+    /// * `signature` - The signature over the message's [`SigStructure`].
+    #[inline]
+    pub const fn new(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        payload: Option<&'a [u8]>,
+        signature: &'a [u8],
+    ) -> Self {
+        Self {
+            protected,
+            unprotected,
+            payload,
+            signature,
+        }
+    }
+
+    /// Signs a payload and assembles the message around it.
+    ///
+    /// The `Sig_structure` is encoded at the start of `scratch` and passed to `signer`,
+    /// which writes the signature into the rest of `scratch`. The returned message borrows
+    /// the signature from there.
+    ///
+    /// # Arguments
+    ///
+    /// * `protected` - The encoded protected header.
+    /// * `unprotected` - The entries of the unprotected header map.
+    /// * `payload` - The payload to sign and carry in the message.
+    /// * `external_aad` - Additional data that is signed but not carried, often empty.
+    /// * `signer` - The signature algorithm.
+    /// * `scratch` - Space for the `Sig_structure` followed by the signature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoseSign1)` - The signed message.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `signer`.
+    pub fn sign<S: Signer + ?Sized>(
+        protected: &'a [u8],
+        unprotected: Map<'a>,
+        payload: &'a [u8],
+        external_aad: &[u8],
+        signer: &S,
+        scratch: &'a mut [u8],
+    ) -> Result<Self> {
+        let tbs = SigStructure::new(protected, external_aad, payload);
+        let len = tbs.as_cbor(scratch)?;
+        let (tbs, signature) = scratch.split_at_mut(len);
+        let len = signer.sign(tbs, signature)?;
+        Ok(Self::new(
+            protected,
+            unprotected,
+            Some(payload),
+            &signature[..len],
+        ))
+    }
+
+    /// Checks the signature of the message.
+    ///
+    /// # Arguments
+    ///
+    /// * `external_aad` - The additional data the message was signed with, often empty.
+    /// * `verifier` - The signature check.
+    /// * `scratch` - Space for the encoded `Sig_structure`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the signature is valid.
+    /// * `Err(Error::InvalidType)` - If the payload is detached; supply it with
+    ///   [`with_payload`](Self::with_payload) first.
+    /// * `Err(Error::BufferOverflow)` - If `scratch` is too small.
+    /// * `Err(Error)` - Any error returned by `verifier`.
+    pub fn verify<V: Verifier + ?Sized>(
+        &self,
+        external_aad: &[u8],
+        verifier: &V,
+        scratch: &mut [u8],
+    ) -> Result<()> {
+        let Some(payload) = self.payload else {
+            return Err(Error::InvalidType);
+        };
+        let tbs = SigStructure::new(self.protected, external_aad, payload);
+        let len = tbs.as_cbor(scratch)?;
+        verifier.verify(&scratch[..len], self.signature)
+    }
+
+    /// Returns the message with the given payload, as needed to verify a message whose
+    /// payload was detached.
+    #[inline]
+    pub const fn with_payload(self, payload: &'a [u8]) -> Self {
+        Self {
+            payload: Some(payload),
+            ..self
+        }
+    }
+
+    /// Returns the encoded protected header.
+    #[inline]
+    pub const fn protected(&self) -> &'a [u8] {
+        self.protected
+    }
+
+    /// Returns the entries of the unprotected header map.
+    #[inline]
+    pub const fn unprotected(&self) -> Map<'a> {
+        self.unprotected
+    }
+
+    /// Returns the payload, or `None` if it is detached.
+    #[inline]
+    pub const fn payload(&self) -> Option<&'a [u8]> {
+        self.payload
+    }
+
+    /// Returns the signature.
+    #[inline]
+    pub const fn signature(&self) -> &'a [u8] {
+        self.signature
+    }
+
+    /// Calculates the number of bytes needed to encode the message.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calculates the number of bytes needed to encode the message without tag 18.
+    #[inline]
+    pub fn untagged_size(&self) -> usize {
+        self.with_untagged_value(encode::encoded_size)
+    }
+
+    /// Encodes the message without tag 18, for contexts that imply the message type,
+    /// such as the entries of a DICE chain.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of bytes written.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+    pub fn encode_untagged(&self, buf: &mut [u8]) -> Result<usize> {
+        self.with_untagged_value(|value| encode::encode(value, buf))
+    }
+
+    /// Calls `f` with the message as a tagged `Value`.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        self.with_untagged_value(|array| f(&Value::Tag(cose::COSE_SIGN1, array)))
+    }
+
+    /// Calls `f` with the message as an untagged `Value`.
+    fn with_untagged_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let items = [
+            Value::Bytes(self.protected),
+            Value::Map(self.unprotected),
+            detached_value(self.payload),
+            Value::Bytes(self.signature),
+        ];
+        f(&Value::Array(&items))
+    }
+}
+
+impl<'a> Encode<'a> for CoseSign1<'_> {
+    /// Encodes the message wrapped in tag 18.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CoseSign1<'a> {
+    type Error = Error;
+
+    /// Validates the `COSE_Sign1` layout, tagged with 18 or untagged, and extracts its parts.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let [protected, unprotected, payload, signature] = message_items(value, cose::COSE_SIGN1)?;
+        let (protected, unprotected) = headers(protected, unprotected)?;
+        let Value::Bytes(signature) = signature else {
+            return Err(Error::InvalidType);
+        };
+        Ok(Self::new(
+            protected,
+            unprotected,
+            detachable(payload)?,
+            signature,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoseSign1, SigStructure};
+    use crate::{
+        Arena, Value, cbor,
+        cose::{self, decode_protected, test_support::Checksum},
+        decode::decode,
+        encode::Encode,
+        error::Error,
+    };
+
+    const PROTECTED: [u8; 3] = [0xA1, 0x01, 0x26];
+    const CONTENT: &[u8] = b"This is the content.";
+
+    /// The `ToBeSign` bytes of the `sign1-pass-01` example from the COSE working group,
+    /// which RFC 9052 Appendix C.2.1 is based on.
+    const TO_BE_SIGNED: [u8; 38] = [
+        0x84, 0x6A, 0x53, 0x69, 0x67, 0x6E, 0x61, 0x74, 0x75, 0x72, 0x65, 0x31, 0x43, 0xA1, 0x01,
+        0x26, 0x40, 0x54, 0x54, 0x68, 0x69, 0x73, 0x20, 0x69, 0x73, 0x20, 0x74, 0x68, 0x65, 0x20,
+        0x63, 0x6F, 0x6E, 0x74, 0x65, 0x6E, 0x74, 0x2E,
+    ];
+
+    #[test]
+    fn test_sig_structure() {
+        let tbs = SigStructure::new(&PROTECTED, &[], CONTENT);
+        let mut buf = [0u8; 64];
+        let size = tbs.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &TO_BE_SIGNED);
+        assert_eq!(tbs.encoded_size(), TO_BE_SIGNED.len());
+
+        let mut short = [0u8; 37];
+        assert_eq!(tbs.as_cbor(&mut short), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let unprotected = [(Value::unsigned(4), Value::bytes(b"11"))];
+        let mut scratch = [0u8; 64];
+        let message = CoseSign1::sign(
+            &PROTECTED,
+            &unprotected,
+            CONTENT,
+            &[],
+            &Checksum(7),
+            &mut scratch,
+        )
+        .unwrap();
+        assert_eq!(message.signature(), &Checksum(7).digest(&TO_BE_SIGNED));
+
+        // The message layout from RFC 9052 Appendix C.2.1, with a shorter signature
+        let mut buf = [0u8; 64];
+        let size = message.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, message.encoded_size());
+        assert_eq!(
+            &buf[..9],
+            &[0xD2, 0x84, 0x43, 0xA1, 0x01, 0x26, 0xA1, 0x04, 0x42]
+        );
+        assert_eq!(&buf[11..13], &[0x54, 0x54]);
+        assert_eq!(&buf[32..size], &[0x42, message.signature()[0], 38]);
+
+        let mut untagged = [0u8; 64];
+        let untagged_size = message.encode_untagged(&mut untagged).unwrap();
+        assert_eq!(untagged_size, message.untagged_size());
+        assert_eq!(&untagged[..untagged_size], &buf[1..size]);
+
+        let mut scratch = [0u8; 64];
+        assert_eq!(message.verify(&[], &Checksum(7), &mut scratch), Ok(()));
+        assert_eq!(
+            message.verify(&[], &Checksum(8), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            message.verify(b"aad", &Checksum(7), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+
+        // The signature must fit after the Sig_structure
+        let mut scratch = [0u8; 39];
+        assert_eq!(
+            CoseSign1::sign(&PROTECTED, &[], CONTENT, &[], &Checksum(7), &mut scratch),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let unprotected = [(Value::unsigned(4), Value::bytes(b"11"))];
+        let signature = [0x5A; 64];
+        let message = CoseSign1::new(&PROTECTED, &unprotected, Some(CONTENT), &signature);
+        let mut buf = [0u8; 128];
+        let size = message.as_cbor(&mut buf).unwrap();
+
+        let mut values = [Value::null(); 5];
+        let mut pairs = [(Value::null(), Value::null()); 2];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let parsed = CoseSign1::try_from(&value).unwrap();
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.unprotected()[0].1, Value::bytes(b"11"));
+
+        let mut pairs = [(Value::null(), Value::null()); 1];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let header = decode_protected(parsed.protected(), &mut arena).unwrap();
+        assert_eq!(
+            header.get_int(cose::ALG),
+            Some(&Value::negative(cose::ES256))
+        );
+
+        // Untagged messages and detached payloads
+        static DETACHED: Value = cbor!([h "", {}, null, h "00"]);
+        let detached = CoseSign1::try_from(&DETACHED).unwrap();
+        assert_eq!(detached.payload(), None);
+        assert_eq!(detached.protected(), &[]);
+        let mut scratch = [0u8; 64];
+        assert_eq!(
+            detached.verify(&[], &Checksum(0), &mut scratch),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(detached.with_payload(CONTENT).payload(), Some(CONTENT));
+
+        // Malformed messages
+        static WRONG_TAG: Value = cbor!(17([h "", {}, null, h "00"]));
+        static SHORT: Value = cbor!(18([h "", {}, null]));
+        static MAP_PROTECTED: Value = cbor!(18([{}, {}, null, h "00"]));
+        static TEXT_PAYLOAD: Value = cbor!(18([h "", {}, "text", h "00"]));
+        for value in [&WRONG_TAG, &SHORT, &MAP_PROTECTED, &TEXT_PAYLOAD] {
+            assert_eq!(CoseSign1::try_from(value), Err(Error::InvalidType));
+        }
+    }
+}
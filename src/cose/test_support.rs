@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+
+//! Test fixtures shared by the COSE tests and the formats built on COSE.
+
+use super::{Signer, Verifier};
+use crate::{error::Error, result::Result};
+
+/// A keyed checksum standing in for a real signature algorithm.
+///
+/// The signature is the key plus the sum of the signed bytes, followed by their length.
+pub(crate) struct Checksum(pub(crate) u8);
+
+impl Checksum {
+    /// Returns the two-byte signature of `data`.
+    pub(crate) fn digest(&self, data: &[u8]) -> [u8; 2] {
+        let sum = data
+            .iter()
+            .fold(self.0, |sum, byte| sum.wrapping_add(*byte));
+        [sum, data.len() as u8]
+    }
+}
+
+impl Signer for Checksum {
+    fn sign(&self, to_be_signed: &[u8], signature: &mut [u8]) -> Result<usize> {
+        let digest = self.digest(to_be_signed);
+        signature
+            .get_mut(..2)
+            .ok_or(Error::BufferOverflow)?
+            .copy_from_slice(&digest);
+        Ok(2)
+    }
+}
+
+impl Verifier for Checksum {
+    fn verify(&self, to_be_signed: &[u8], signature: &[u8]) -> Result<()> {
+        if signature == self.digest(to_be_signed) {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+}
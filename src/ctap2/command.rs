@@ -0,0 +1,1334 @@
+// SPDX-License-Identifier: MIT
+
+//! CTAP2 command parameters.
+//!
+//! The parameter maps of the commands are keyed by small integers, while the structures
+//! they contain, such as the relying party or the user, are maps keyed by text strings.
+
+use super::{MAX_DEPTH, PUBLIC_KEY, key, map_header, optional, write_list, write_map};
+use crate::{
+    Arena, Value,
+    cose::key::CoseKey,
+    encode::{Encode, Sink},
+    error::Error,
+    list::{Item, List, boolean, bytes, fields, map, required, text, unsigned},
+    result::Result,
+    value::Map,
+};
+
+/// Sub-command 0x01: `getPINRetries`.
+pub const GET_PIN_RETRIES: u64 = 0x01;
+/// Sub-command 0x02: `getKeyAgreement`.
+pub const GET_KEY_AGREEMENT: u64 = 0x02;
+/// Sub-command 0x03: `setPIN`.
+pub const SET_PIN: u64 = 0x03;
+/// Sub-command 0x04: `changePIN`.
+pub const CHANGE_PIN: u64 = 0x04;
+/// Sub-command 0x05: `getPinToken`.
+pub const GET_PIN_TOKEN: u64 = 0x05;
+/// Sub-command 0x06: `getPinUvAuthTokenUsingUvWithPermissions`.
+pub const GET_PIN_UV_AUTH_TOKEN_USING_UV_WITH_PERMISSIONS: u64 = 0x06;
+/// Sub-command 0x07: `getUVRetries`.
+pub const GET_UV_RETRIES: u64 = 0x07;
+/// Sub-command 0x09: `getPinUvAuthTokenUsingPinWithPermissions`.
+pub const GET_PIN_UV_AUTH_TOKEN_USING_PIN_WITH_PERMISSIONS: u64 = 0x09;
+
+/// Permission 0x01: The token may be used for `authenticatorMakeCredential`.
+pub const PERMISSION_MAKE_CREDENTIAL: u64 = 0x01;
+/// Permission 0x02: The token may be used for `authenticatorGetAssertion`.
+pub const PERMISSION_GET_ASSERTION: u64 = 0x02;
+/// Permission 0x04: The token may be used for credential management.
+pub const PERMISSION_CREDENTIAL_MANAGEMENT: u64 = 0x04;
+/// Permission 0x08: The token may be used for bio enrollment.
+pub const PERMISSION_BIO_ENROLLMENT: u64 = 0x08;
+/// Permission 0x10: The token may be used to write large blobs.
+pub const PERMISSION_LARGE_BLOB_WRITE: u64 = 0x10;
+/// Permission 0x20: The token may be used for authenticator configuration.
+pub const PERMISSION_AUTHENTICATOR_CONFIG: u64 = 0x20;
+
+/// The relying party a credential is created for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelyingParty<'a> {
+    id: &'a str,
+    name: Option<&'a str>,
+}
+
+impl<'a> RelyingParty<'a> {
+    /// Creates a relying party with the given ID, such as a domain name.
+    #[inline]
+    pub const fn new(id: &'a str) -> Self {
+        Self { id, name: None }
+    }
+
+    /// Returns the relying party with the given human-readable name.
+    #[inline]
+    pub const fn with_name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    /// Returns the relying party ID.
+    #[inline]
+    pub const fn id(&self) -> &'a str {
+        self.id
+    }
+
+    /// Returns the human-readable name.
+    #[inline]
+    pub const fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+}
+
+impl Item for RelyingParty<'_> {
+    /// Writes the map `{"id": .., "name": ..}`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(sink, &[true, self.name.is_some()])?;
+        sink.value(&Value::Text("id"))?;
+        sink.value(&Value::Text(self.id))?;
+        if let Some(name) = self.name {
+            sink.value(&Value::Text("name"))?;
+            sink.value(&Value::Text(name))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for RelyingParty<'a> {
+    type Error = Error;
+
+    /// Reads a relying party map. Other members, such as the deprecated `icon`, are
+    /// ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RelyingParty)` - The relying party.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, `id` is missing, or a
+    ///   member has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            id: required(text(value.get_str("id"))?)?,
+            name: text(value.get_str("name"))?,
+        })
+    }
+}
+
+/// The user account a credential is created for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct User<'a> {
+    id: &'a [u8],
+    name: Option<&'a str>,
+    display_name: Option<&'a str>,
+}
+
+impl<'a> User<'a> {
+    /// Creates a user with the given user handle, of at most 64 bytes.
+    #[inline]
+    pub const fn new(id: &'a [u8]) -> Self {
+        Self {
+            id,
+            name: None,
+            display_name: None,
+        }
+    }
+
+    /// Returns the user with the given account name.
+    #[inline]
+    pub const fn with_name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    /// Returns the user with the given display name.
+    #[inline]
+    pub const fn with_display_name(self, display_name: &'a str) -> Self {
+        Self {
+            display_name: Some(display_name),
+            ..self
+        }
+    }
+
+    /// Returns the user handle.
+    #[inline]
+    pub const fn id(&self) -> &'a [u8] {
+        self.id
+    }
+
+    /// Returns the account name.
+    #[inline]
+    pub const fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    /// Returns the display name.
+    #[inline]
+    pub const fn display_name(&self) -> Option<&'a str> {
+        self.display_name
+    }
+}
+
+impl Item for User<'_> {
+    /// Writes the map `{"id": .., "name": .., "displayName": ..}`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[true, self.name.is_some(), self.display_name.is_some()],
+        )?;
+        sink.value(&Value::Text("id"))?;
+        sink.value(&Value::Bytes(self.id))?;
+        if let Some(name) = self.name {
+            sink.value(&Value::Text("name"))?;
+            sink.value(&Value::Text(name))?;
+        }
+        if let Some(display_name) = self.display_name {
+            sink.value(&Value::Text("displayName"))?;
+            sink.value(&Value::Text(display_name))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for User<'a> {
+    type Error = Error;
+
+    /// Reads a user map. Other members are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(User)` - The user.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, `id` is missing, or a
+    ///   member has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            id: required(bytes(value.get_str("id"))?)?,
+            name: text(value.get_str("name"))?,
+            display_name: text(value.get_str("displayName"))?,
+        })
+    }
+}
+
+/// A credential type and algorithm that the relying party accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CredentialParameters<'a> {
+    credential_type: &'a str,
+    alg: i64,
+}
+
+impl<'a> CredentialParameters<'a> {
+    /// Creates parameters for a public key credential with the given COSE algorithm.
+    #[inline]
+    pub const fn new(alg: i64) -> Self {
+        Self {
+            credential_type: PUBLIC_KEY,
+            alg,
+        }
+    }
+
+    /// Returns the credential type.
+    #[inline]
+    pub const fn credential_type(&self) -> &'a str {
+        self.credential_type
+    }
+
+    /// Returns the COSE algorithm.
+    #[inline]
+    pub const fn alg(&self) -> i64 {
+        self.alg
+    }
+
+    /// Returns `true` for public key credentials. Authenticators skip parameters of
+    /// other types.
+    #[inline]
+    pub fn is_public_key(&self) -> bool {
+        self.credential_type == PUBLIC_KEY
+    }
+}
+
+impl Item for CredentialParameters<'_> {
+    /// Writes the map `{"alg": .., "type": ..}`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(sink, &[true, true])?;
+        sink.value(&Value::Text("alg"))?;
+        sink.value(&Value::integer(self.alg))?;
+        sink.value(&Value::Text("type"))?;
+        sink.value(&Value::Text(self.credential_type))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CredentialParameters<'a> {
+    type Error = Error;
+
+    /// Reads a credential parameters map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CredentialParameters)` - The parameters.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or `alg` or `type` is
+    ///   missing or has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+        let alg = match value.get_str("alg").and_then(Value::as_i128) {
+            Some(alg) => i64::try_from(alg).map_err(|_| Error::InvalidType)?,
+            None => return Err(Error::InvalidType),
+        };
+
+        Ok(Self {
+            credential_type: required(text(value.get_str("type"))?)?,
+            alg,
+        })
+    }
+}
+
+/// A reference to a credential, as listed in an exclude or allow list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CredentialDescriptor<'a> {
+    credential_type: &'a str,
+    id: &'a [u8],
+    transports: List<'a, &'a str>,
+}
+
+impl<'a> CredentialDescriptor<'a> {
+    /// Creates a descriptor for the public key credential with the given ID.
+    #[inline]
+    pub const fn new(id: &'a [u8]) -> Self {
+        Self {
+            credential_type: PUBLIC_KEY,
+            id,
+            transports: List::Items(&[]),
+        }
+    }
+
+    /// Returns the descriptor with the given transports, such as `"usb"` or `"nfc"`.
+    #[inline]
+    pub const fn with_transports(self, transports: &'a [&'a str]) -> Self {
+        Self {
+            transports: List::Items(transports),
+            ..self
+        }
+    }
+
+    /// Returns the credential type.
+    #[inline]
+    pub const fn credential_type(&self) -> &'a str {
+        self.credential_type
+    }
+
+    /// Returns the credential ID.
+    #[inline]
+    pub const fn id(&self) -> &'a [u8] {
+        self.id
+    }
+
+    /// Returns the transports the credential may be reached over.
+    #[inline]
+    pub const fn transports(&self) -> List<'a, &'a str> {
+        self.transports
+    }
+}
+
+impl Item for CredentialDescriptor<'_> {
+    /// Writes the map `{"id": .., "type": .., "transports": [..]}`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(sink, &[true, true, !self.transports.is_empty()])?;
+        sink.value(&Value::Text("id"))?;
+        sink.value(&Value::Bytes(self.id))?;
+        sink.value(&Value::Text("type"))?;
+        sink.value(&Value::Text(self.credential_type))?;
+        if !self.transports.is_empty() {
+            sink.value(&Value::Text("transports"))?;
+            write_list(sink, &self.transports)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CredentialDescriptor<'a> {
+    type Error = Error;
+
+    /// Reads a credential descriptor map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CredentialDescriptor)` - The descriptor.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, `id` or `type` is missing,
+    ///   or a member has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            credential_type: required(text(value.get_str("type"))?)?,
+            id: required(bytes(value.get_str("id"))?)?,
+            transports: List::from_field(value.get_str("transports"))?,
+        })
+    }
+}
+
+/// The options of `authenticatorMakeCredential` and `authenticatorGetAssertion`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Options {
+    resident_key: Option<bool>,
+    user_presence: Option<bool>,
+    user_verification: Option<bool>,
+}
+
+impl Options {
+    /// Creates options that leave every option at the authenticator's default.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            resident_key: None,
+            user_presence: None,
+            user_verification: None,
+        }
+    }
+
+    /// Returns the options with the `rk` option, which asks for a discoverable
+    /// credential.
+    #[inline]
+    pub const fn with_resident_key(self, resident_key: bool) -> Self {
+        Self {
+            resident_key: Some(resident_key),
+            ..self
+        }
+    }
+
+    /// Returns the options with the `up` option, which asks for user presence.
+    #[inline]
+    pub const fn with_user_presence(self, user_presence: bool) -> Self {
+        Self {
+            user_presence: Some(user_presence),
+            ..self
+        }
+    }
+
+    /// Returns the options with the `uv` option, which asks for user verification.
+    #[inline]
+    pub const fn with_user_verification(self, user_verification: bool) -> Self {
+        Self {
+            user_verification: Some(user_verification),
+            ..self
+        }
+    }
+
+    /// Returns the `rk` option.
+    #[inline]
+    pub const fn resident_key(&self) -> Option<bool> {
+        self.resident_key
+    }
+
+    /// Returns the `up` option.
+    #[inline]
+    pub const fn user_presence(&self) -> Option<bool> {
+        self.user_presence
+    }
+
+    /// Returns the `uv` option.
+    #[inline]
+    pub const fn user_verification(&self) -> Option<bool> {
+        self.user_verification
+    }
+}
+
+impl Item for Options {
+    /// Writes the map `{"rk": .., "up": .., "uv": ..}` of the options that are set.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let options = [
+            ("rk", self.resident_key),
+            ("up", self.user_presence),
+            ("uv", self.user_verification),
+        ];
+        map_header(sink, &options.map(|(_, option)| option.is_some()))?;
+        for (name, option) in options {
+            if let Some(option) = option {
+                sink.value(&Value::Text(name))?;
+                sink.value(&Value::bool(option))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&Value<'_>> for Options {
+    type Error = Error;
+
+    /// Reads an options map. Options the authenticator does not know are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Options)` - The options.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or an option is not a
+    ///   boolean.
+    fn try_from(value: &Value) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            resident_key: boolean(value.get_str("rk"))?,
+            user_presence: boolean(value.get_str("up"))?,
+            user_verification: boolean(value.get_str("uv"))?,
+        })
+    }
+}
+
+/// The parameters of `authenticatorMakeCredential`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MakeCredential<'a> {
+    client_data_hash: &'a [u8],
+    rp: RelyingParty<'a>,
+    user: User<'a>,
+    pub_key_cred_params: List<'a, CredentialParameters<'a>>,
+    exclude_list: List<'a, CredentialDescriptor<'a>>,
+    extensions: Option<Map<'a>>,
+    options: Option<Options>,
+    pin_uv_auth_param: Option<&'a [u8]>,
+    pin_uv_auth_protocol: Option<u64>,
+    enterprise_attestation: Option<u64>,
+}
+
+impl<'a> MakeCredential<'a> {
+    /// Creates the parameters of `authenticatorMakeCredential`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_data_hash` - The hash of the client data (key 0x01).
+    /// * `rp` - The relying party (key 0x02).
+    /// * `user` - The user account (key 0x03).
+    /// * `pub_key_cred_params` - The credential types and algorithms the relying party
+    ///   accepts, most preferred first (key 0x04).
+    #[inline]
+    pub const fn new(
+        client_data_hash: &'a [u8],
+        rp: RelyingParty<'a>,
+        user: User<'a>,
+        pub_key_cred_params: &'a [CredentialParameters<'a>],
+    ) -> Self {
+        Self {
+            client_data_hash,
+            rp,
+            user,
+            pub_key_cred_params: List::Items(pub_key_cred_params),
+            exclude_list: List::Items(&[]),
+            extensions: None,
+            options: None,
+            pin_uv_auth_param: None,
+            pin_uv_auth_protocol: None,
+            enterprise_attestation: None,
+        }
+    }
+
+    /// Returns the parameters with the given credentials that must not be created
+    /// again (key 0x05).
+    #[inline]
+    pub const fn with_exclude_list(self, exclude_list: &'a [CredentialDescriptor<'a>]) -> Self {
+        Self {
+            exclude_list: List::Items(exclude_list),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given map of extension inputs (key 0x06).
+    #[inline]
+    pub const fn with_extensions(self, extensions: Map<'a>) -> Self {
+        Self {
+            extensions: Some(extensions),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given options (key 0x07).
+    #[inline]
+    pub const fn with_options(self, options: Options) -> Self {
+        Self {
+            options: Some(options),
+            ..self
+        }
+    }
+
+    /// Returns the parameters authenticated with a PIN/UV auth token.
+    ///
+    /// # Arguments
+    ///
+    /// * `param` - The MAC of the client data hash under the token (key 0x08).
+    /// * `protocol` - The PIN/UV auth protocol version (key 0x09).
+    #[inline]
+    pub const fn with_pin_uv_auth(self, param: &'a [u8], protocol: u64) -> Self {
+        Self {
+            pin_uv_auth_param: Some(param),
+            pin_uv_auth_protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Returns the parameters requesting the given kind of enterprise attestation
+    /// (key 0x0A).
+    #[inline]
+    pub const fn with_enterprise_attestation(self, enterprise_attestation: u64) -> Self {
+        Self {
+            enterprise_attestation: Some(enterprise_attestation),
+            ..self
+        }
+    }
+
+    /// Returns the hash of the client data.
+    #[inline]
+    pub const fn client_data_hash(&self) -> &'a [u8] {
+        self.client_data_hash
+    }
+
+    /// Returns the relying party.
+    #[inline]
+    pub const fn rp(&self) -> RelyingParty<'a> {
+        self.rp
+    }
+
+    /// Returns the user account.
+    #[inline]
+    pub const fn user(&self) -> User<'a> {
+        self.user
+    }
+
+    /// Returns the credential types and algorithms the relying party accepts.
+    #[inline]
+    pub const fn pub_key_cred_params(&self) -> List<'a, CredentialParameters<'a>> {
+        self.pub_key_cred_params
+    }
+
+    /// Returns the credentials that must not be created again.
+    #[inline]
+    pub const fn exclude_list(&self) -> List<'a, CredentialDescriptor<'a>> {
+        self.exclude_list
+    }
+
+    /// Returns the entries of the map of extension inputs.
+    #[inline]
+    pub const fn extensions(&self) -> Option<Map<'a>> {
+        self.extensions
+    }
+
+    /// Returns the options.
+    #[inline]
+    pub const fn options(&self) -> Option<Options> {
+        self.options
+    }
+
+    /// Returns the MAC of the client data hash under a PIN/UV auth token.
+    #[inline]
+    pub const fn pin_uv_auth_param(&self) -> Option<&'a [u8]> {
+        self.pin_uv_auth_param
+    }
+
+    /// Returns the PIN/UV auth protocol version.
+    #[inline]
+    pub const fn pin_uv_auth_protocol(&self) -> Option<u64> {
+        self.pin_uv_auth_protocol
+    }
+
+    /// Returns the kind of enterprise attestation requested.
+    #[inline]
+    pub const fn enterprise_attestation(&self) -> Option<u64> {
+        self.enterprise_attestation
+    }
+
+    /// Decodes the parameter map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MakeCredential)` - The parameters, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid parameters, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the parameter map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the parameter map.
+    pub(super) fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                true,
+                true,
+                true,
+                true,
+                !self.exclude_list.is_empty(),
+                self.extensions.is_some(),
+                self.options.is_some(),
+                self.pin_uv_auth_param.is_some(),
+                self.pin_uv_auth_protocol.is_some(),
+                self.enterprise_attestation.is_some(),
+            ],
+        )?;
+        key(sink, 0x01)?;
+        sink.value(&Value::Bytes(self.client_data_hash))?;
+        key(sink, 0x02)?;
+        self.rp.write(sink)?;
+        key(sink, 0x03)?;
+        self.user.write(sink)?;
+        key(sink, 0x04)?;
+        write_list(sink, &self.pub_key_cred_params)?;
+        if !self.exclude_list.is_empty() {
+            key(sink, 0x05)?;
+            write_list(sink, &self.exclude_list)?;
+        }
+        if let Some(extensions) = self.extensions {
+            key(sink, 0x06)?;
+            write_map(sink, extensions, MAX_DEPTH - 1)?;
+        }
+        if let Some(options) = &self.options {
+            key(sink, 0x07)?;
+            options.write(sink)?;
+        }
+        optional(sink, 0x08, self.pin_uv_auth_param.map(Value::Bytes))?;
+        optional(sink, 0x09, self.pin_uv_auth_protocol.map(Value::Unsigned))?;
+        optional(sink, 0x0A, self.enterprise_attestation.map(Value::Unsigned))
+    }
+}
+
+impl<'a> Encode<'a> for MakeCredential<'_> {
+    /// Encodes the parameter map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MakeCredential<'a> {
+    type Error = Error;
+
+    /// Reads the parameter map. Parameters this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MakeCredential)` - The parameters.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, a required parameter is
+    ///   missing, or a parameter has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            client_data_hash: required(bytes(value.get_int(0x01))?)?,
+            rp: RelyingParty::try_from(required(value.get_int(0x02))?)?,
+            user: User::try_from(required(value.get_int(0x03))?)?,
+            pub_key_cred_params: List::from_field(Some(required(value.get_int(0x04))?))?,
+            exclude_list: List::from_field(value.get_int(0x05))?,
+            extensions: map(value.get_int(0x06))?,
+            options: value.get_int(0x07).map(Options::try_from).transpose()?,
+            pin_uv_auth_param: bytes(value.get_int(0x08))?,
+            pin_uv_auth_protocol: unsigned(value.get_int(0x09))?,
+            enterprise_attestation: unsigned(value.get_int(0x0A))?,
+        })
+    }
+}
+
+/// The parameters of `authenticatorGetAssertion`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GetAssertion<'a> {
+    rp_id: &'a str,
+    client_data_hash: &'a [u8],
+    allow_list: List<'a, CredentialDescriptor<'a>>,
+    extensions: Option<Map<'a>>,
+    options: Option<Options>,
+    pin_uv_auth_param: Option<&'a [u8]>,
+    pin_uv_auth_protocol: Option<u64>,
+}
+
+impl<'a> GetAssertion<'a> {
+    /// Creates the parameters of `authenticatorGetAssertion`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rp_id` - The relying party ID (key 0x01).
+    /// * `client_data_hash` - The hash of the client data (key 0x02).
+    #[inline]
+    pub const fn new(rp_id: &'a str, client_data_hash: &'a [u8]) -> Self {
+        Self {
+            rp_id,
+            client_data_hash,
+            allow_list: List::Items(&[]),
+            extensions: None,
+            options: None,
+            pin_uv_auth_param: None,
+            pin_uv_auth_protocol: None,
+        }
+    }
+
+    /// Returns the parameters with the given credentials that may sign (key 0x03).
+    /// Without an allow list, the authenticator uses its discoverable credentials.
+    #[inline]
+    pub const fn with_allow_list(self, allow_list: &'a [CredentialDescriptor<'a>]) -> Self {
+        Self {
+            allow_list: List::Items(allow_list),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given map of extension inputs (key 0x04).
+    #[inline]
+    pub const fn with_extensions(self, extensions: Map<'a>) -> Self {
+        Self {
+            extensions: Some(extensions),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given options (key 0x05).
+    #[inline]
+    pub const fn with_options(self, options: Options) -> Self {
+        Self {
+            options: Some(options),
+            ..self
+        }
+    }
+
+    /// Returns the parameters authenticated with a PIN/UV auth token.
+    ///
+    /// # Arguments
+    ///
+    /// * `param` - The MAC of the client data hash under the token (key 0x06).
+    /// * `protocol` - The PIN/UV auth protocol version (key 0x07).
+    #[inline]
+    pub const fn with_pin_uv_auth(self, param: &'a [u8], protocol: u64) -> Self {
+        Self {
+            pin_uv_auth_param: Some(param),
+            pin_uv_auth_protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Returns the relying party ID.
+    #[inline]
+    pub const fn rp_id(&self) -> &'a str {
+        self.rp_id
+    }
+
+    /// Returns the hash of the client data.
+    #[inline]
+    pub const fn client_data_hash(&self) -> &'a [u8] {
+        self.client_data_hash
+    }
+
+    /// Returns the credentials that may sign.
+    #[inline]
+    pub const fn allow_list(&self) -> List<'a, CredentialDescriptor<'a>> {
+        self.allow_list
+    }
+
+    /// Returns the entries of the map of extension inputs.
+    #[inline]
+    pub const fn extensions(&self) -> Option<Map<'a>> {
+        self.extensions
+    }
+
+    /// Returns the options.
+    #[inline]
+    pub const fn options(&self) -> Option<Options> {
+        self.options
+    }
+
+    /// Returns the MAC of the client data hash under a PIN/UV auth token.
+    #[inline]
+    pub const fn pin_uv_auth_param(&self) -> Option<&'a [u8]> {
+        self.pin_uv_auth_param
+    }
+
+    /// Returns the PIN/UV auth protocol version.
+    #[inline]
+    pub const fn pin_uv_auth_protocol(&self) -> Option<u64> {
+        self.pin_uv_auth_protocol
+    }
+
+    /// Decodes the parameter map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetAssertion)` - The parameters, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid parameters, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the parameter map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the parameter map.
+    pub(super) fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                true,
+                true,
+                !self.allow_list.is_empty(),
+                self.extensions.is_some(),
+                self.options.is_some(),
+                self.pin_uv_auth_param.is_some(),
+                self.pin_uv_auth_protocol.is_some(),
+            ],
+        )?;
+        key(sink, 0x01)?;
+        sink.value(&Value::Text(self.rp_id))?;
+        key(sink, 0x02)?;
+        sink.value(&Value::Bytes(self.client_data_hash))?;
+        if !self.allow_list.is_empty() {
+            key(sink, 0x03)?;
+            write_list(sink, &self.allow_list)?;
+        }
+        if let Some(extensions) = self.extensions {
+            key(sink, 0x04)?;
+            write_map(sink, extensions, MAX_DEPTH - 1)?;
+        }
+        if let Some(options) = &self.options {
+            key(sink, 0x05)?;
+            options.write(sink)?;
+        }
+        optional(sink, 0x06, self.pin_uv_auth_param.map(Value::Bytes))?;
+        optional(sink, 0x07, self.pin_uv_auth_protocol.map(Value::Unsigned))
+    }
+}
+
+impl<'a> Encode<'a> for GetAssertion<'_> {
+    /// Encodes the parameter map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for GetAssertion<'a> {
+    type Error = Error;
+
+    /// Reads the parameter map. Parameters this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetAssertion)` - The parameters.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, a required parameter is
+    ///   missing, or a parameter has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            rp_id: required(text(value.get_int(0x01))?)?,
+            client_data_hash: required(bytes(value.get_int(0x02))?)?,
+            allow_list: List::from_field(value.get_int(0x03))?,
+            extensions: map(value.get_int(0x04))?,
+            options: value.get_int(0x05).map(Options::try_from).transpose()?,
+            pin_uv_auth_param: bytes(value.get_int(0x06))?,
+            pin_uv_auth_protocol: unsigned(value.get_int(0x07))?,
+        })
+    }
+}
+
+/// The parameters of `authenticatorClientPIN`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientPin<'a> {
+    pin_uv_auth_protocol: Option<u64>,
+    sub_command: u64,
+    key_agreement: Option<CoseKey<'a>>,
+    pin_uv_auth_param: Option<&'a [u8]>,
+    new_pin_enc: Option<&'a [u8]>,
+    pin_hash_enc: Option<&'a [u8]>,
+    permissions: Option<u64>,
+    rp_id: Option<&'a str>,
+}
+
+impl<'a> ClientPin<'a> {
+    /// Creates the parameters of `authenticatorClientPIN` for the given sub-command,
+    /// such as [`GET_KEY_AGREEMENT`] (key 0x02).
+    #[inline]
+    pub const fn new(sub_command: u64) -> Self {
+        Self {
+            pin_uv_auth_protocol: None,
+            sub_command,
+            key_agreement: None,
+            pin_uv_auth_param: None,
+            new_pin_enc: None,
+            pin_hash_enc: None,
+            permissions: None,
+            rp_id: None,
+        }
+    }
+
+    /// Returns the parameters with the given PIN/UV auth protocol version (key 0x01).
+    #[inline]
+    pub const fn with_pin_uv_auth_protocol(self, protocol: u64) -> Self {
+        Self {
+            pin_uv_auth_protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the platform's key agreement key (key 0x03).
+    #[inline]
+    pub const fn with_key_agreement(self, key_agreement: CoseKey<'a>) -> Self {
+        Self {
+            key_agreement: Some(key_agreement),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given MAC under the shared secret (key 0x04).
+    #[inline]
+    pub const fn with_pin_uv_auth_param(self, param: &'a [u8]) -> Self {
+        Self {
+            pin_uv_auth_param: Some(param),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given encrypted new PIN (key 0x05).
+    #[inline]
+    pub const fn with_new_pin_enc(self, new_pin_enc: &'a [u8]) -> Self {
+        Self {
+            new_pin_enc: Some(new_pin_enc),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given encrypted hash of the current PIN
+    /// (key 0x06).
+    #[inline]
+    pub const fn with_pin_hash_enc(self, pin_hash_enc: &'a [u8]) -> Self {
+        Self {
+            pin_hash_enc: Some(pin_hash_enc),
+            ..self
+        }
+    }
+
+    /// Returns the parameters requesting the given permissions, such as
+    /// [`PERMISSION_GET_ASSERTION`], for a token (key 0x09).
+    #[inline]
+    pub const fn with_permissions(self, permissions: u64) -> Self {
+        Self {
+            permissions: Some(permissions),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the relying party ID a token is limited to
+    /// (key 0x0A).
+    #[inline]
+    pub const fn with_rp_id(self, rp_id: &'a str) -> Self {
+        Self {
+            rp_id: Some(rp_id),
+            ..self
+        }
+    }
+
+    /// Returns the PIN/UV auth protocol version.
+    #[inline]
+    pub const fn pin_uv_auth_protocol(&self) -> Option<u64> {
+        self.pin_uv_auth_protocol
+    }
+
+    /// Returns the sub-command.
+    #[inline]
+    pub const fn sub_command(&self) -> u64 {
+        self.sub_command
+    }
+
+    /// Returns the platform's key agreement key.
+    #[inline]
+    pub const fn key_agreement(&self) -> Option<CoseKey<'a>> {
+        self.key_agreement
+    }
+
+    /// Returns the MAC under the shared secret.
+    #[inline]
+    pub const fn pin_uv_auth_param(&self) -> Option<&'a [u8]> {
+        self.pin_uv_auth_param
+    }
+
+    /// Returns the encrypted new PIN.
+    #[inline]
+    pub const fn new_pin_enc(&self) -> Option<&'a [u8]> {
+        self.new_pin_enc
+    }
+
+    /// Returns the encrypted hash of the current PIN.
+    #[inline]
+    pub const fn pin_hash_enc(&self) -> Option<&'a [u8]> {
+        self.pin_hash_enc
+    }
+
+    /// Returns the permissions requested for a token.
+    #[inline]
+    pub const fn permissions(&self) -> Option<u64> {
+        self.permissions
+    }
+
+    /// Returns the relying party ID a token is limited to.
+    #[inline]
+    pub const fn rp_id(&self) -> Option<&'a str> {
+        self.rp_id
+    }
+
+    /// Decodes the parameter map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ClientPin)` - The parameters, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid parameters, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the parameter map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the parameter map.
+    pub(super) fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                self.pin_uv_auth_protocol.is_some(),
+                true,
+                self.key_agreement.is_some(),
+                self.pin_uv_auth_param.is_some(),
+                self.new_pin_enc.is_some(),
+                self.pin_hash_enc.is_some(),
+                self.permissions.is_some(),
+                self.rp_id.is_some(),
+            ],
+        )?;
+        optional(sink, 0x01, self.pin_uv_auth_protocol.map(Value::Unsigned))?;
+        optional(sink, 0x02, Some(Value::Unsigned(self.sub_command)))?;
+        if let Some(key_agreement) = &self.key_agreement {
+            key(sink, 0x03)?;
+            sink.encoded(key_agreement.encoded_size(), |buf| {
+                key_agreement.as_cbor(buf)
+            })?;
+        }
+        optional(sink, 0x04, self.pin_uv_auth_param.map(Value::Bytes))?;
+        optional(sink, 0x05, self.new_pin_enc.map(Value::Bytes))?;
+        optional(sink, 0x06, self.pin_hash_enc.map(Value::Bytes))?;
+        optional(sink, 0x09, self.permissions.map(Value::Unsigned))?;
+        optional(sink, 0x0A, self.rp_id.map(Value::Text))
+    }
+}
+
+impl<'a> Encode<'a> for ClientPin<'_> {
+    /// Encodes the parameter map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for ClientPin<'a> {
+    type Error = Error;
+
+    /// Reads the parameter map. Parameters this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ClientPin)` - The parameters.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the sub-command is
+    ///   missing, or a parameter has the wrong type.
+    /// * `Err(Error::InvalidValue)` - If the key agreement key is not a supported
+    ///   `COSE_Key`.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            pin_uv_auth_protocol: unsigned(value.get_int(0x01))?,
+            sub_command: required(unsigned(value.get_int(0x02))?)?,
+            key_agreement: value.get_int(0x03).map(CoseKey::try_from).transpose()?,
+            pin_uv_auth_param: bytes(value.get_int(0x04))?,
+            new_pin_enc: bytes(value.get_int(0x05))?,
+            pin_hash_enc: bytes(value.get_int(0x06))?,
+            permissions: unsigned(value.get_int(0x09))?,
+            rp_id: text(value.get_int(0x0A))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientPin, CredentialDescriptor, CredentialParameters,
+        GET_PIN_UV_AUTH_TOKEN_USING_PIN_WITH_PERMISSIONS, GetAssertion, MakeCredential, Options,
+        PERMISSION_GET_ASSERTION, RelyingParty, User,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        cose::key::{CoseKey, P256},
+        encode::{Encode, encode},
+        error::Error,
+    };
+
+    #[test]
+    fn test_make_credential() {
+        let params = [CredentialParameters::new(-7), CredentialParameters::new(-8)];
+        let transports = ["usb", "nfc"];
+        let exclude = [CredentialDescriptor::new(&[0xEE; 4]).with_transports(&transports)];
+        static EXTENSIONS: [(Value, Value); 2] = [
+            (Value::text("credProtect"), Value::unsigned(2)),
+            (Value::text("hmac-secret"), Value::bool(true)),
+        ];
+        let command = MakeCredential::new(
+            &[0xCD; 32],
+            RelyingParty::new("example.com").with_name("Example"),
+            User::new(&[1, 2])
+                .with_name("alice")
+                .with_display_name("Alice"),
+            &params,
+        )
+        .with_exclude_list(&exclude)
+        .with_extensions(&EXTENSIONS)
+        .with_options(
+            Options::new()
+                .with_resident_key(true)
+                .with_user_verification(false),
+        )
+        .with_pin_uv_auth(&[0xAA; 16], 2)
+        .with_enterprise_attestation(1);
+
+        let mut buf = [0u8; 256];
+        let size = command.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, command.encoded_size());
+        // Ten parameters, keyed in order
+        assert_eq!(&buf[..4], &[0xAA, 0x01, 0x58, 0x20]);
+
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 32];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = MakeCredential::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded.client_data_hash(), &[0xCD; 32]);
+        assert_eq!(decoded.rp(), command.rp());
+        assert_eq!(decoded.user(), command.user());
+        assert_eq!(decoded.pub_key_cred_params().get(1), Some(Ok(params[1])));
+        let excluded = decoded.exclude_list().get(0).unwrap().unwrap();
+        assert_eq!(excluded.id(), &[0xEE; 4]);
+        assert!(excluded.transports().iter().eq([Ok("usb"), Ok("nfc")]));
+        assert_eq!(decoded.extensions(), Some(&EXTENSIONS[..]));
+        assert_eq!(decoded.options(), command.options());
+        assert_eq!(decoded.options().unwrap().user_presence(), None);
+        assert_eq!(decoded.pin_uv_auth_param(), Some(&[0xAA; 16][..]));
+        assert_eq!(decoded.pin_uv_auth_protocol(), Some(2));
+        assert_eq!(decoded.enterprise_attestation(), Some(1));
+
+        // Decoded parameters encode back to the same bytes
+        let mut again = [0u8; 256];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+    }
+
+    #[test]
+    fn test_make_credential_errors() {
+        let params = [CredentialParameters::new(-7)];
+        let command =
+            MakeCredential::new(&[0; 32], RelyingParty::new("a"), User::new(&[1]), &params);
+        let mut buf = [0u8; 128];
+
+        // Extension maps must be canonical and fit in the remaining depth
+        static UNSORTED: [(Value, Value); 2] = [
+            (Value::text("bb"), Value::unsigned(1)),
+            (Value::text("a"), Value::unsigned(1)),
+        ];
+        static DEEP: [(Value, Value); 1] = [(Value::text("a"), cbor!([[[1]]]))];
+        assert_eq!(
+            command.with_extensions(&UNSORTED).as_cbor(&mut buf),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            command.with_extensions(&DEEP).as_cbor(&mut buf),
+            Err(Error::DepthExceeded)
+        );
+
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut values = [Value::null(); 2];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        // The user is missing
+        static MISSING: Value = cbor!({1: h "00", 2: {"id": "a"}, 4: []});
+        let size = encode(&MISSING, &mut buf).unwrap();
+        assert_eq!(
+            MakeCredential::decode(&buf[..size], &mut arena),
+            Err(Error::InvalidType)
+        );
+        // The relying party map has its keys out of order
+        static UNSORTED_RP: Value =
+            cbor!({1: h "00", 2: {"name": "b", "id": "a"}, 3: {"id": h "01"}, 4: []});
+        let mut unsorted = [0u8; 32];
+        let size = encode(&UNSORTED_RP, &mut unsorted).unwrap();
+        assert_eq!(
+            MakeCredential::decode(&unsorted[..size], &mut arena),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_get_assertion() {
+        let allow = [
+            CredentialDescriptor::new(&[1; 16]),
+            CredentialDescriptor::new(&[2; 16]),
+        ];
+        let command = GetAssertion::new("example.com", &[0xCD; 32])
+            .with_allow_list(&allow)
+            .with_options(Options::new().with_user_presence(false))
+            .with_pin_uv_auth(&[0xAA; 32], 1);
+        let mut buf = [0u8; 192];
+        let size = command.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, command.encoded_size());
+        assert_eq!(&buf[..3], &[0xA6, 0x01, 0x6B]);
+
+        let mut values = [Value::null(); 2];
+        let mut pairs = [(Value::null(), Value::null()); 12];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = GetAssertion::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded.rp_id(), "example.com");
+        assert_eq!(decoded.client_data_hash(), &[0xCD; 32]);
+        assert!(decoded.allow_list().iter().eq(allow.map(Ok)));
+        assert_eq!(decoded.extensions(), None);
+        assert_eq!(decoded.options().unwrap().user_presence(), Some(false));
+        assert_eq!(decoded.pin_uv_auth_param(), Some(&[0xAA; 32][..]));
+        assert_eq!(decoded.pin_uv_auth_protocol(), Some(1));
+    }
+
+    #[test]
+    fn test_client_pin() {
+        let key = CoseKey::ec2(P256, &[0x11; 32], &[0x22; 32]).with_alg(-25);
+        let command = ClientPin::new(GET_PIN_UV_AUTH_TOKEN_USING_PIN_WITH_PERMISSIONS)
+            .with_pin_uv_auth_protocol(2)
+            .with_key_agreement(key)
+            .with_pin_hash_enc(&[0x33; 32])
+            .with_permissions(PERMISSION_GET_ASSERTION)
+            .with_rp_id("example.com");
+        let mut buf = [0u8; 192];
+        let size = command.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, command.encoded_size());
+
+        let mut pairs = [(Value::null(), Value::null()); 12];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = ClientPin::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, command);
+        assert_eq!(decoded.sub_command(), 9);
+        assert_eq!(decoded.key_agreement(), Some(key));
+        assert_eq!(decoded.pin_uv_auth_param(), None);
+        assert_eq!(decoded.new_pin_enc(), None);
+        assert_eq!(decoded.pin_hash_enc(), Some(&[0x33; 32][..]));
+        assert_eq!(decoded.permissions(), Some(PERMISSION_GET_ASSERTION));
+        assert_eq!(decoded.rp_id(), Some("example.com"));
+
+        // A sub-command is required
+        let mut missing = [0u8; 4];
+        let size = encode(&cbor!({1: 2}), &mut missing).unwrap();
+        assert_eq!(
+            ClientPin::decode(&missing[..size], &mut arena),
+            Err(Error::InvalidType)
+        );
+    }
+}
@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: MIT
+
+//! CTAP2 command and response messages, as defined by the FIDO
+//! [Client to Authenticator Protocol](https://fidoalliance.org/specs/fido-v2.1-ps-20210615/fido-client-to-authenticator-protocol-v2.1-ps-20210615.html).
+//!
+//! A CTAP2 request is a command byte followed by a CBOR map of the command parameters,
+//! and a response is a status byte followed, on success, by a CBOR map of the results.
+//! Both maps are keyed by small integers. [`Request`] reads and writes the requests for
+//! `authenticatorMakeCredential`, `authenticatorGetAssertion`, `authenticatorGetInfo`
+//! and `authenticatorClientPIN`, and the response types, such as
+//! [`MakeCredentialResponse`], read and write the maps of their results.
+//!
+//! CTAP2 restricts the CBOR it exchanges. Encoders must use the CTAP2 canonical form:
+//! integers, lengths and floats in their shortest form, definite lengths only, no tags,
+//! and map keys sorted by major type, then by length, then byte by byte. Messages must
+//! not nest arrays and maps more than four levels deep. The types in this module always
+//! write this form, and [`decode`], which they decode with, rejects anything else, so an
+//! authenticator can hand a request to [`Request::decode`] as it arrives.
+//!
+//! This module is stricter than the specification in one respect: it rejects floats
+//! altogether, even in their shortest form. No CTAP2 command or response uses them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Arena, Value,
+//!     ctap2::{self, CredentialParameters, MakeCredential, RelyingParty, Request, User},
+//!     encode::Encode,
+//! };
+//!
+//! let params = [CredentialParameters::new(-7)];
+//! let command = MakeCredential::new(
+//!     &[0xCD; 32],
+//!     RelyingParty::new("example.com"),
+//!     User::new(&[0x01, 0x02]).with_name("alice"),
+//!     &params,
+//! );
+//! let request = Request::MakeCredential(command);
+//! let mut buf = [0u8; 128];
+//! let size = request.as_cbor(&mut buf).unwrap();
+//! assert_eq!(buf[0], ctap2::MAKE_CREDENTIAL);
+//!
+//! let mut values = [Value::null(); 4];
+//! let mut pairs = [(Value::null(), Value::null()); 12];
+//! let mut arena = Arena::new(&mut values, &mut pairs);
+//! let Request::MakeCredential(command) = Request::decode(&buf[..size], &mut arena).unwrap()
+//! else {
+//!     panic!("expected authenticatorMakeCredential");
+//! };
+//! assert_eq!(command.rp().id(), "example.com");
+//! assert_eq!(command.user().name(), Some("alice"));
+//! assert_eq!(command.pub_key_cred_params().get(0), Some(Ok(params[0])));
+//! ```
+
+mod command;
+mod response;
+
+pub use command::*;
+pub use response::*;
+
+use core::cmp::Ordering;
+
+use crate::{
+    Arena, Value,
+    decode::{decode_with_depth, nested},
+    encode::{self, Encode, Sink, major_type},
+    error::Error,
+    list::{FromValue, Item, List},
+    result::Result,
+    value::Map,
+};
+
+/// Command 0x01: `authenticatorMakeCredential`, which creates a credential.
+pub const MAKE_CREDENTIAL: u8 = 0x01;
+/// Command 0x02: `authenticatorGetAssertion`, which signs with a credential.
+pub const GET_ASSERTION: u8 = 0x02;
+/// Command 0x04: `authenticatorGetInfo`, which reports the authenticator's capabilities.
+pub const GET_INFO: u8 = 0x04;
+/// Command 0x06: `authenticatorClientPIN`, which manages the PIN and PIN tokens.
+pub const CLIENT_PIN: u8 = 0x06;
+
+/// Status 0x00: `CTAP2_OK`, the command succeeded.
+pub const STATUS_OK: u8 = 0x00;
+
+/// The deepest nesting of arrays and maps that CTAP2 messages may use.
+pub const MAX_DEPTH: usize = 4;
+
+/// The credential type of public key credentials, the only type CTAP2 defines.
+pub const PUBLIC_KEY: &str = "public-key";
+
+/// Decodes a CBOR data item, enforcing the strictness rules of CTAP2.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The decoded value.
+/// * `Err(Error::DepthExceeded)` - If arrays and maps nest more than [`MAX_DEPTH`]
+///   levels deep.
+/// * `Err(Error::InvalidValue)` - If an integer or length is not in its shortest form, or
+///   the keys of a map are not in canonical order or repeat.
+/// * `Err(Error::InvalidType)` - If a map key is not an integer or string, or the value
+///   contains a tag or a float, as [`check`] describes.
+/// * `Err(Error)` - If the bytes are not valid CBOR, or the arena is too small.
+pub fn decode<'a>(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Value<'a>> {
+    let value = decode_with_depth(bytes, arena, MAX_DEPTH)?;
+    check(&value)?;
+    // Every header is re-encoded in its shortest form, so the value only encodes to as
+    // many bytes as it was decoded from if none of them was longer.
+    if encode::encoded_size(&value) != bytes.len() {
+        return Err(Error::InvalidValue);
+    }
+    Ok(value)
+}
+
+/// Checks that a value can be encoded in the CTAP2 canonical form, as required before
+/// writing values that were not built by this module, such as extension maps.
+///
+/// Floats are rejected even in their shortest form, which is stricter than CTAP2
+/// requires: they are always encoded in 8 bytes, which is only canonical for floats
+/// that have no shorter form, and no CTAP2 message uses them.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the value is canonical.
+/// * `Err(Error::DepthExceeded)` - If arrays and maps nest more than [`MAX_DEPTH`]
+///   levels deep.
+/// * `Err(Error::InvalidValue)` - If the keys of a map are not in canonical order or
+///   repeat.
+/// * `Err(Error::InvalidType)` - If a map key is not an integer or string, or the value
+///   contains a tag or a float.
+#[inline]
+pub fn check(value: &Value) -> Result<()> {
+    check_nested(value, MAX_DEPTH)
+}
+
+/// Checks a value that may nest at most `depth` levels, for values inside a message.
+fn check_nested(value: &Value, depth: usize) -> Result<()> {
+    match value {
+        Value::Array(items) => {
+            let depth = nested(depth)?;
+            items.iter().try_for_each(|item| check_nested(item, depth))
+        }
+        Value::Map(pairs) => {
+            let depth = nested(depth)?;
+            for pair in pairs.windows(2) {
+                if compare_keys(&pair[0].0, &pair[1].0)? != Ordering::Less {
+                    return Err(Error::InvalidValue);
+                }
+            }
+            pairs.iter().try_for_each(|(key, value)| {
+                let _ = key_order(key)?;
+                check_nested(value, depth)
+            })
+        }
+        Value::Tag(..) | Value::Float(_) => Err(Error::InvalidType),
+        _ => Ok(()),
+    }
+}
+
+/// Compares two map keys in CTAP2 canonical order.
+fn compare_keys(a: &Value, b: &Value) -> Result<Ordering> {
+    Ok(key_order(a)?.cmp(&key_order(b)?))
+}
+
+/// Returns the major type, length or magnitude, and bytes of a key, which order keys as
+/// CTAP2 requires. A shorter integer has a smaller magnitude, so integers of the same
+/// major type are ordered by their magnitude.
+fn key_order<'v>(key: &'v Value) -> Result<(u8, u64, &'v [u8])> {
+    match key {
+        Value::Unsigned(n) => Ok((major_type::UNSIGNED, *n, &[])),
+        Value::Negative(n) => Ok((major_type::NEGATIVE, *n, &[])),
+        Value::Bytes(bytes) => Ok((major_type::BYTES, bytes.len() as u64, bytes)),
+        Value::Text(text) => Ok((major_type::TEXT, text.len() as u64, text.as_bytes())),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// A CTAP2 request: a command and its parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Request<'a> {
+    /// `authenticatorMakeCredential`.
+    MakeCredential(MakeCredential<'a>),
+    /// `authenticatorGetAssertion`.
+    GetAssertion(GetAssertion<'a>),
+    /// `authenticatorGetInfo`, which has no parameters.
+    GetInfo,
+    /// `authenticatorClientPIN`.
+    ClientPin(ClientPin<'a>),
+}
+
+impl<'a> Request<'a> {
+    /// Returns the command byte, such as [`MAKE_CREDENTIAL`].
+    #[inline]
+    pub const fn command(&self) -> u8 {
+        match self {
+            Self::MakeCredential(_) => MAKE_CREDENTIAL,
+            Self::GetAssertion(_) => GET_ASSERTION,
+            Self::GetInfo => GET_INFO,
+            Self::ClientPin(_) => CLIENT_PIN,
+        }
+    }
+
+    /// Decodes a request message: the command byte followed by the parameter map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Request)` - The request, borrowing from `message`.
+    /// * `Err(Error::UnexpectedEnd)` - If the message is empty, or the parameters of a
+    ///   command that requires them are missing.
+    /// * `Err(Error::InvalidValue)` - If the command is not one of the commands above,
+    ///   `authenticatorGetInfo` has parameters, or the parameters are not canonical.
+    /// * `Err(Error)` - If the parameters are not valid for the command, as described
+    ///   by [`decode`] and the `TryFrom` implementation of each command, or the arena
+    ///   is too small.
+    pub fn decode(message: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        let Some((&command, parameters)) = message.split_first() else {
+            return Err(Error::UnexpectedEnd);
+        };
+        match command {
+            GET_INFO if parameters.is_empty() => Ok(Self::GetInfo),
+            GET_INFO => Err(Error::InvalidValue),
+            MAKE_CREDENTIAL => MakeCredential::decode(parameters, arena).map(Self::MakeCredential),
+            GET_ASSERTION => GetAssertion::decode(parameters, arena).map(Self::GetAssertion),
+            CLIENT_PIN => ClientPin::decode(parameters, arena).map(Self::ClientPin),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the request message.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the command byte and the parameter map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.encoded(1, |buf| match buf.first_mut() {
+            Some(byte) => {
+                *byte = self.command();
+                Ok(1)
+            }
+            None => Err(Error::BufferOverflow),
+        })?;
+        match self {
+            Self::MakeCredential(command) => command.write(sink),
+            Self::GetAssertion(command) => command.write(sink),
+            Self::GetInfo => Ok(()),
+            Self::ClientPin(command) => command.write(sink),
+        }
+    }
+}
+
+impl<'a> Encode<'a> for Request<'_> {
+    /// Encodes the request message: the command byte followed by the parameter map in
+    /// CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+/// Splits a response message into its status byte and the encoded result map, which is
+/// empty for errors and for commands without results.
+///
+/// # Returns
+///
+/// * `Ok((u8, &[u8]))` - The status, such as [`STATUS_OK`], and the result map.
+/// * `Err(Error::UnexpectedEnd)` - If the message is empty.
+#[inline]
+pub const fn split_response(message: &[u8]) -> Result<(u8, &[u8])> {
+    match message.split_first() {
+        Some((&status, results)) => Ok((status, results)),
+        None => Err(Error::UnexpectedEnd),
+    }
+}
+
+/// Writes the header of a map with as many entries as `entries` has present.
+fn map_header(sink: &mut Sink, entries: &[bool]) -> Result<()> {
+    let len = entries.iter().filter(|present| **present).count();
+    sink.header(major_type::MAP, len as u64)
+}
+
+/// Writes an integer map key.
+fn key(sink: &mut Sink, key: i64) -> Result<()> {
+    sink.value(&Value::integer(key))
+}
+
+/// Writes an optional entry of a map keyed by integers.
+fn optional(sink: &mut Sink, key: i64, value: Option<Value>) -> Result<()> {
+    match value {
+        Some(value) => {
+            sink.value(&Value::integer(key))?;
+            sink.value(&value)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Writes a list as an array, converting decoded items so that they are written in
+/// canonical form as well.
+fn write_list<'a, T>(sink: &mut Sink, list: &List<'a, T>) -> Result<()>
+where
+    T: Item + Copy + FromValue<'a>,
+{
+    sink.header(major_type::ARRAY, list.len() as u64)?;
+    list.iter().try_for_each(|item| item?.write(sink))
+}
+
+/// Writes a map that was not built by this module, such as an extension map, after
+/// checking that it is canonical and nests at most `depth` levels.
+fn write_map(sink: &mut Sink, map: Map, depth: usize) -> Result<()> {
+    let value = Value::Map(map);
+    check_nested(&value, depth)?;
+    sink.value(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CLIENT_PIN, ClientPin, GET_INFO, MAX_DEPTH, Request, STATUS_OK, check, decode,
+        split_response,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        encode::{Encode, encode},
+        error::Error,
+    };
+
+    #[test]
+    fn test_decode_canonical() {
+        static VALUE: Value = cbor!({1: h "00", 2: {"id": "a", "name": "b"}, -1: [1, [2]]});
+        let mut buf = [0u8; 32];
+        let size = encode(&VALUE, &mut buf).unwrap();
+
+        let mut values = [Value::null(); 4];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        assert_eq!(decode(&buf[..size], &mut arena), Ok(VALUE));
+    }
+
+    #[test]
+    fn test_decode_rejects_non_canonical() {
+        let cases: [(&[u8], Error); 9] = [
+            // 10 encoded in one extra byte
+            (&[0xA1, 0x01, 0x18, 0x0A], Error::InvalidValue),
+            // A map length encoded in one extra byte
+            (&[0xB8, 0x01, 0x01, 0x00], Error::InvalidValue),
+            // Keys out of order, and a repeated key
+            (&[0xA2, 0x02, 0x00, 0x01, 0x00], Error::InvalidValue),
+            (&[0xA2, 0x01, 0x00, 0x01, 0x00], Error::InvalidValue),
+            // A negative key before a positive one, and a longer text key before a shorter one
+            (&[0xA2, 0x20, 0x00, 0x01, 0x00], Error::InvalidValue),
+            (
+                &[0xA2, 0x62, b'a', b'a', 0x00, 0x61, b'b', 0x00],
+                Error::InvalidValue,
+            ),
+            // A float, an array key, and a tag
+            (&[0xF9, 0x3C, 0x00], Error::InvalidType),
+            (&[0xA1, 0x80, 0x00], Error::InvalidType),
+            (&[0xA1, 0x01, 0xC1, 0x00], Error::InvalidType),
+        ];
+        for (bytes, error) in cases {
+            let mut values = [Value::null(); 1];
+            let mut pairs = [(Value::null(), Value::null()); 2];
+            let mut arena = Arena::new(&mut values, &mut pairs);
+            assert_eq!(decode(bytes, &mut arena), Err(error), "{bytes:02X?}");
+        }
+        // Indefinite lengths are rejected by the decoder itself
+        let mut pairs = [(Value::null(), Value::null()); 2];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        assert!(decode(&[0xBF, 0xFF], &mut arena).is_err());
+    }
+
+    #[test]
+    fn test_depth() {
+        // Four levels are allowed, five are not
+        let four = [0x81, 0x81, 0x81, 0x80];
+        let five = [0x81, 0x81, 0x81, 0x81, 0x80];
+        let mut values = [Value::null(); 8];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert!(decode(&four, &mut arena).is_ok());
+        assert_eq!(decode(&five, &mut arena), Err(Error::DepthExceeded));
+
+        static DEEP: Value = cbor!([[[[[]]]]]);
+        assert_eq!(check(&DEEP), Err(Error::DepthExceeded));
+        assert_eq!(MAX_DEPTH, 4);
+    }
+
+    #[test]
+    fn test_request_framing() {
+        let mut buf = [0u8; 8];
+        assert_eq!(Request::GetInfo.as_cbor(&mut buf), Ok(1));
+        assert_eq!(buf[0], GET_INFO);
+        assert_eq!(Request::GetInfo.encoded_size(), 1);
+        assert_eq!(
+            Request::GetInfo.as_cbor(&mut []),
+            Err(Error::BufferOverflow)
+        );
+
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(
+            Request::decode(&[GET_INFO], &mut arena),
+            Ok(Request::GetInfo)
+        );
+        assert_eq!(
+            Request::decode(&[GET_INFO, 0xA0], &mut arena),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            Request::decode(&[0x03, 0xA0], &mut arena),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(Request::decode(&[], &mut arena), Err(Error::UnexpectedEnd));
+        assert_eq!(
+            Request::decode(&[CLIENT_PIN], &mut arena),
+            Err(Error::UnexpectedEnd)
+        );
+
+        let request = Request::ClientPin(ClientPin::new(1).with_pin_uv_auth_protocol(2));
+        let size = request.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[CLIENT_PIN, 0xA2, 0x01, 0x02, 0x02, 0x01]);
+        assert_eq!(request.encoded_size(), size);
+
+        let mut pairs = [(Value::null(), Value::null()); 2];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        assert_eq!(Request::decode(&buf[..size], &mut arena), Ok(request));
+    }
+
+    #[test]
+    fn test_split_response() {
+        assert_eq!(
+            split_response(&[STATUS_OK, 0xA0]),
+            Ok((STATUS_OK, &[0xA0][..]))
+        );
+        assert_eq!(split_response(&[0x31]), Ok((0x31, &[][..])));
+        assert_eq!(split_response(&[]), Err(Error::UnexpectedEnd));
+    }
+}
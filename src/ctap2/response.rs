@@ -0,0 +1,844 @@
+// SPDX-License-Identifier: MIT
+
+//! CTAP2 response maps.
+//!
+//! A successful response is [`STATUS_OK`](super::STATUS_OK) followed by the map of the
+//! command's results, which the types in this module read and write.
+
+use super::{
+    CredentialDescriptor, MAX_DEPTH, User, key, map_header, optional, write_list, write_map,
+};
+use crate::{
+    Arena, Value,
+    cose::key::CoseKey,
+    encode::{Encode, Sink},
+    error::Error,
+    list::{Item, List, boolean, bytes, fields, map, required, text, unsigned},
+    result::Result,
+    value::Map,
+    webauthn::AttestationObject,
+};
+
+/// Version `FIDO_2_0`, for authenticators implementing CTAP 2.0.
+pub const FIDO_2_0: &str = "FIDO_2_0";
+/// Version `FIDO_2_1`, for authenticators implementing CTAP 2.1.
+pub const FIDO_2_1: &str = "FIDO_2_1";
+/// Version `U2F_V2`, for authenticators that also implement CTAP1/U2F.
+pub const U2F_V2: &str = "U2F_V2";
+
+/// The results of `authenticatorMakeCredential`: the attestation of the new credential.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MakeCredentialResponse<'a> {
+    fmt: &'a str,
+    auth_data: &'a [u8],
+    att_stmt: Map<'a>,
+}
+
+impl<'a> MakeCredentialResponse<'a> {
+    /// Creates the results of `authenticatorMakeCredential`.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmt` - The attestation statement format, such as `"packed"` (key 0x01).
+    /// * `auth_data` - The encoded authenticator data (key 0x02).
+    /// * `att_stmt` - The entries of the attestation statement map (key 0x03).
+    #[inline]
+    pub const fn new(fmt: &'a str, auth_data: &'a [u8], att_stmt: Map<'a>) -> Self {
+        Self {
+            fmt,
+            auth_data,
+            att_stmt,
+        }
+    }
+
+    /// Returns the attestation statement format.
+    #[inline]
+    pub const fn fmt(&self) -> &'a str {
+        self.fmt
+    }
+
+    /// Returns the encoded authenticator data.
+    #[inline]
+    pub const fn auth_data(&self) -> &'a [u8] {
+        self.auth_data
+    }
+
+    /// Returns the entries of the attestation statement map.
+    #[inline]
+    pub const fn att_stmt(&self) -> Map<'a> {
+        self.att_stmt
+    }
+
+    /// Returns the attestation object that the platform passes on to the relying party,
+    /// which holds the same members keyed by name.
+    #[inline]
+    pub const fn attestation_object(&self) -> AttestationObject<'a> {
+        AttestationObject::new(self.fmt, self.att_stmt, self.auth_data)
+    }
+
+    /// Decodes the response map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MakeCredentialResponse)` - The results, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid results, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the response map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the response map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(sink, &[true, true, true])?;
+        key(sink, 0x01)?;
+        sink.value(&Value::Text(self.fmt))?;
+        key(sink, 0x02)?;
+        sink.value(&Value::Bytes(self.auth_data))?;
+        key(sink, 0x03)?;
+        write_map(sink, self.att_stmt, MAX_DEPTH - 1)
+    }
+}
+
+impl<'a> From<AttestationObject<'a>> for MakeCredentialResponse<'a> {
+    /// Creates the results that carry an attestation object.
+    #[inline]
+    fn from(object: AttestationObject<'a>) -> Self {
+        Self::new(object.fmt(), object.auth_data(), object.att_stmt())
+    }
+}
+
+impl<'a> Encode<'a> for MakeCredentialResponse<'_> {
+    /// Encodes the response map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MakeCredentialResponse<'a> {
+    type Error = Error;
+
+    /// Reads the response map. Results this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MakeCredentialResponse)` - The results.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a member is missing
+    ///   or has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            fmt: required(text(value.get_int(0x01))?)?,
+            auth_data: required(bytes(value.get_int(0x02))?)?,
+            att_stmt: required(map(value.get_int(0x03))?)?,
+        })
+    }
+}
+
+/// The results of `authenticatorGetAssertion`: a signature by one of the credentials.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GetAssertionResponse<'a> {
+    credential: Option<CredentialDescriptor<'a>>,
+    auth_data: &'a [u8],
+    signature: &'a [u8],
+    user: Option<User<'a>>,
+    number_of_credentials: Option<u64>,
+}
+
+impl<'a> GetAssertionResponse<'a> {
+    /// Creates the results of `authenticatorGetAssertion`.
+    ///
+    /// # Arguments
+    ///
+    /// * `auth_data` - The encoded authenticator data (key 0x02).
+    /// * `signature` - The signature over the authenticator data and the client data
+    ///   hash (key 0x03).
+    #[inline]
+    pub const fn new(auth_data: &'a [u8], signature: &'a [u8]) -> Self {
+        Self {
+            credential: None,
+            auth_data,
+            signature,
+            user: None,
+            number_of_credentials: None,
+        }
+    }
+
+    /// Returns the results naming the credential that signed (key 0x01), which may only
+    /// be left out when the allow list held exactly one credential.
+    #[inline]
+    pub const fn with_credential(self, credential: CredentialDescriptor<'a>) -> Self {
+        Self {
+            credential: Some(credential),
+            ..self
+        }
+    }
+
+    /// Returns the results with the user account of a discoverable credential
+    /// (key 0x04).
+    #[inline]
+    pub const fn with_user(self, user: User<'a>) -> Self {
+        Self {
+            user: Some(user),
+            ..self
+        }
+    }
+
+    /// Returns the results with the number of discoverable credentials found for the
+    /// relying party (key 0x05).
+    #[inline]
+    pub const fn with_number_of_credentials(self, number_of_credentials: u64) -> Self {
+        Self {
+            number_of_credentials: Some(number_of_credentials),
+            ..self
+        }
+    }
+
+    /// Returns the credential that signed.
+    #[inline]
+    pub const fn credential(&self) -> Option<CredentialDescriptor<'a>> {
+        self.credential
+    }
+
+    /// Returns the encoded authenticator data.
+    #[inline]
+    pub const fn auth_data(&self) -> &'a [u8] {
+        self.auth_data
+    }
+
+    /// Returns the signature.
+    #[inline]
+    pub const fn signature(&self) -> &'a [u8] {
+        self.signature
+    }
+
+    /// Returns the user account of a discoverable credential.
+    #[inline]
+    pub const fn user(&self) -> Option<User<'a>> {
+        self.user
+    }
+
+    /// Returns the number of discoverable credentials found for the relying party.
+    #[inline]
+    pub const fn number_of_credentials(&self) -> Option<u64> {
+        self.number_of_credentials
+    }
+
+    /// Decodes the response map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetAssertionResponse)` - The results, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid results, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the response map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the response map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                self.credential.is_some(),
+                true,
+                true,
+                self.user.is_some(),
+                self.number_of_credentials.is_some(),
+            ],
+        )?;
+        if let Some(credential) = &self.credential {
+            key(sink, 0x01)?;
+            credential.write(sink)?;
+        }
+        key(sink, 0x02)?;
+        sink.value(&Value::Bytes(self.auth_data))?;
+        key(sink, 0x03)?;
+        sink.value(&Value::Bytes(self.signature))?;
+        if let Some(user) = &self.user {
+            key(sink, 0x04)?;
+            user.write(sink)?;
+        }
+        optional(sink, 0x05, self.number_of_credentials.map(Value::Unsigned))
+    }
+}
+
+impl<'a> Encode<'a> for GetAssertionResponse<'_> {
+    /// Encodes the response map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for GetAssertionResponse<'a> {
+    type Error = Error;
+
+    /// Reads the response map. Results this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetAssertionResponse)` - The results.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the authenticator data or
+    ///   the signature is missing, or a member has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            credential: value
+                .get_int(0x01)
+                .map(CredentialDescriptor::try_from)
+                .transpose()?,
+            auth_data: required(bytes(value.get_int(0x02))?)?,
+            signature: required(bytes(value.get_int(0x03))?)?,
+            user: value.get_int(0x04).map(User::try_from).transpose()?,
+            number_of_credentials: unsigned(value.get_int(0x05))?,
+        })
+    }
+}
+
+/// The results of `authenticatorGetInfo`: the versions, extensions and options the
+/// authenticator supports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GetInfoResponse<'a> {
+    versions: List<'a, &'a str>,
+    extensions: List<'a, &'a str>,
+    aaguid: &'a [u8; 16],
+    options: Option<Map<'a>>,
+    max_msg_size: Option<u64>,
+    pin_uv_auth_protocols: List<'a, u64>,
+}
+
+impl<'a> GetInfoResponse<'a> {
+    /// Creates the results of `authenticatorGetInfo`.
+    ///
+    /// # Arguments
+    ///
+    /// * `versions` - The supported versions, such as [`FIDO_2_1`] (key 0x01).
+    /// * `aaguid` - The AAGUID, identifying the authenticator model (key 0x03).
+    #[inline]
+    pub const fn new(versions: &'a [&'a str], aaguid: &'a [u8; 16]) -> Self {
+        Self {
+            versions: List::Items(versions),
+            extensions: List::Items(&[]),
+            aaguid,
+            options: None,
+            max_msg_size: None,
+            pin_uv_auth_protocols: List::Items(&[]),
+        }
+    }
+
+    /// Returns the results with the given supported extensions (key 0x02).
+    #[inline]
+    pub const fn with_extensions(self, extensions: &'a [&'a str]) -> Self {
+        Self {
+            extensions: List::Items(extensions),
+            ..self
+        }
+    }
+
+    /// Returns the results with the given map of option names to booleans, such as
+    /// `{"rk": true, "up": true}` (key 0x04).
+    #[inline]
+    pub const fn with_options(self, options: Map<'a>) -> Self {
+        Self {
+            options: Some(options),
+            ..self
+        }
+    }
+
+    /// Returns the results with the longest message the authenticator accepts
+    /// (key 0x05).
+    #[inline]
+    pub const fn with_max_msg_size(self, max_msg_size: u64) -> Self {
+        Self {
+            max_msg_size: Some(max_msg_size),
+            ..self
+        }
+    }
+
+    /// Returns the results with the supported PIN/UV auth protocol versions, most
+    /// preferred first (key 0x06).
+    #[inline]
+    pub const fn with_pin_uv_auth_protocols(self, protocols: &'a [u64]) -> Self {
+        Self {
+            pin_uv_auth_protocols: List::Items(protocols),
+            ..self
+        }
+    }
+
+    /// Returns the supported versions.
+    #[inline]
+    pub const fn versions(&self) -> List<'a, &'a str> {
+        self.versions
+    }
+
+    /// Returns the supported extensions.
+    #[inline]
+    pub const fn extensions(&self) -> List<'a, &'a str> {
+        self.extensions
+    }
+
+    /// Returns the AAGUID.
+    #[inline]
+    pub const fn aaguid(&self) -> &'a [u8; 16] {
+        self.aaguid
+    }
+
+    /// Returns the entries of the options map.
+    #[inline]
+    pub const fn options(&self) -> Option<Map<'a>> {
+        self.options
+    }
+
+    /// Returns the option with the given name, such as `"clientPin"`, or `None` if the
+    /// authenticator does not report it.
+    pub fn option(&self, name: &str) -> Option<bool> {
+        Value::Map(self.options?).get_str(name)?.as_bool()
+    }
+
+    /// Returns the longest message the authenticator accepts.
+    #[inline]
+    pub const fn max_msg_size(&self) -> Option<u64> {
+        self.max_msg_size
+    }
+
+    /// Returns the supported PIN/UV auth protocol versions.
+    #[inline]
+    pub const fn pin_uv_auth_protocols(&self) -> List<'a, u64> {
+        self.pin_uv_auth_protocols
+    }
+
+    /// Decodes the response map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetInfoResponse)` - The results, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid results, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the response map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the response map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                true,
+                !self.extensions.is_empty(),
+                true,
+                self.options.is_some(),
+                self.max_msg_size.is_some(),
+                !self.pin_uv_auth_protocols.is_empty(),
+            ],
+        )?;
+        key(sink, 0x01)?;
+        write_list(sink, &self.versions)?;
+        if !self.extensions.is_empty() {
+            key(sink, 0x02)?;
+            write_list(sink, &self.extensions)?;
+        }
+        key(sink, 0x03)?;
+        sink.value(&Value::Bytes(self.aaguid))?;
+        if let Some(options) = self.options {
+            key(sink, 0x04)?;
+            write_map(sink, options, MAX_DEPTH - 1)?;
+        }
+        optional(sink, 0x05, self.max_msg_size.map(Value::Unsigned))?;
+        if !self.pin_uv_auth_protocols.is_empty() {
+            key(sink, 0x06)?;
+            write_list(sink, &self.pin_uv_auth_protocols)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for GetInfoResponse<'_> {
+    /// Encodes the response map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for GetInfoResponse<'a> {
+    type Error = Error;
+
+    /// Reads the response map. Results this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GetInfoResponse)` - The results.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the versions or the AAGUID
+    ///   are missing, a member has the wrong type, or an option is not a boolean.
+    /// * `Err(Error::InvalidValue)` - If the AAGUID is not 16 bytes long.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+        let aaguid = required(bytes(value.get_int(0x03))?)?;
+        let options = map(value.get_int(0x04))?;
+        if let Some(options) = options {
+            for (_, option) in options {
+                let _ = required(boolean(Some(option))?)?;
+            }
+        }
+
+        Ok(Self {
+            versions: List::from_field(Some(required(value.get_int(0x01))?))?,
+            extensions: List::from_field(value.get_int(0x02))?,
+            aaguid: aaguid.try_into().map_err(|_| Error::InvalidValue)?,
+            options,
+            max_msg_size: unsigned(value.get_int(0x05))?,
+            pin_uv_auth_protocols: List::from_field(value.get_int(0x06))?,
+        })
+    }
+}
+
+/// The results of `authenticatorClientPIN`, which depend on the sub-command.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClientPinResponse<'a> {
+    key_agreement: Option<CoseKey<'a>>,
+    pin_uv_auth_token: Option<&'a [u8]>,
+    pin_retries: Option<u64>,
+    power_cycle_state: Option<bool>,
+    uv_retries: Option<u64>,
+}
+
+impl<'a> ClientPinResponse<'a> {
+    /// Creates empty results, as returned by `setPIN` and `changePIN`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            key_agreement: None,
+            pin_uv_auth_token: None,
+            pin_retries: None,
+            power_cycle_state: None,
+            uv_retries: None,
+        }
+    }
+
+    /// Returns the results with the authenticator's key agreement key (key 0x01).
+    #[inline]
+    pub const fn with_key_agreement(self, key_agreement: CoseKey<'a>) -> Self {
+        Self {
+            key_agreement: Some(key_agreement),
+            ..self
+        }
+    }
+
+    /// Returns the results with the given encrypted PIN/UV auth token (key 0x02).
+    #[inline]
+    pub const fn with_pin_uv_auth_token(self, token: &'a [u8]) -> Self {
+        Self {
+            pin_uv_auth_token: Some(token),
+            ..self
+        }
+    }
+
+    /// Returns the results with the number of PIN attempts left (key 0x03), and whether
+    /// the authenticator must be power cycled before the next attempt (key 0x04).
+    #[inline]
+    pub const fn with_pin_retries(self, retries: u64, power_cycle_state: Option<bool>) -> Self {
+        Self {
+            pin_retries: Some(retries),
+            power_cycle_state,
+            ..self
+        }
+    }
+
+    /// Returns the results with the number of built-in user verification attempts left
+    /// (key 0x05).
+    #[inline]
+    pub const fn with_uv_retries(self, retries: u64) -> Self {
+        Self {
+            uv_retries: Some(retries),
+            ..self
+        }
+    }
+
+    /// Returns the authenticator's key agreement key.
+    #[inline]
+    pub const fn key_agreement(&self) -> Option<CoseKey<'a>> {
+        self.key_agreement
+    }
+
+    /// Returns the encrypted PIN/UV auth token.
+    #[inline]
+    pub const fn pin_uv_auth_token(&self) -> Option<&'a [u8]> {
+        self.pin_uv_auth_token
+    }
+
+    /// Returns the number of PIN attempts left.
+    #[inline]
+    pub const fn pin_retries(&self) -> Option<u64> {
+        self.pin_retries
+    }
+
+    /// Returns whether the authenticator must be power cycled before the next PIN
+    /// attempt.
+    #[inline]
+    pub const fn power_cycle_state(&self) -> Option<bool> {
+        self.power_cycle_state
+    }
+
+    /// Returns the number of built-in user verification attempts left.
+    #[inline]
+    pub const fn uv_retries(&self) -> Option<u64> {
+        self.uv_retries
+    }
+
+    /// Decodes the response map, enforcing the strictness rules of CTAP2.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ClientPinResponse)` - The results, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not canonical CTAP2 CBOR, as described by
+    ///   [`decode`](super::decode), are not valid results, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&super::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the response map.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the response map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        map_header(
+            sink,
+            &[
+                self.key_agreement.is_some(),
+                self.pin_uv_auth_token.is_some(),
+                self.pin_retries.is_some(),
+                self.power_cycle_state.is_some(),
+                self.uv_retries.is_some(),
+            ],
+        )?;
+        if let Some(key_agreement) = &self.key_agreement {
+            key(sink, 0x01)?;
+            sink.encoded(key_agreement.encoded_size(), |buf| {
+                key_agreement.as_cbor(buf)
+            })?;
+        }
+        optional(sink, 0x02, self.pin_uv_auth_token.map(Value::Bytes))?;
+        optional(sink, 0x03, self.pin_retries.map(Value::Unsigned))?;
+        optional(sink, 0x04, self.power_cycle_state.map(Value::bool))?;
+        optional(sink, 0x05, self.uv_retries.map(Value::Unsigned))
+    }
+}
+
+impl<'a> Encode<'a> for ClientPinResponse<'_> {
+    /// Encodes the response map in CTAP2 canonical form.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for ClientPinResponse<'a> {
+    type Error = Error;
+
+    /// Reads the response map. Results this type does not model are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ClientPinResponse)` - The results.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a member has the wrong
+    ///   type.
+    /// * `Err(Error::InvalidValue)` - If the key agreement key is not a supported
+    ///   `COSE_Key`.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let value = fields(value)?;
+
+        Ok(Self {
+            key_agreement: value.get_int(0x01).map(CoseKey::try_from).transpose()?,
+            pin_uv_auth_token: bytes(value.get_int(0x02))?,
+            pin_retries: unsigned(value.get_int(0x03))?,
+            power_cycle_state: boolean(value.get_int(0x04))?,
+            uv_retries: unsigned(value.get_int(0x05))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientPinResponse, FIDO_2_0, FIDO_2_1, GetAssertionResponse, GetInfoResponse,
+        MakeCredentialResponse,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        cose::key::{CoseKey, P256},
+        ctap2::{CredentialDescriptor, User},
+        encode::{Encode, encode},
+        error::Error,
+        webauthn::{AttestationObject, FMT_PACKED, PackedStatement, Statement},
+    };
+
+    #[test]
+    fn test_make_credential_response() {
+        static STATEMENT: Value = cbor!({"alg": -7, "sig": h "3045"});
+        let Value::Map(statement) = STATEMENT else {
+            unreachable!()
+        };
+        let response = MakeCredentialResponse::new(FMT_PACKED, &[0xAD; 37], statement);
+        let mut buf = [0u8; 96];
+        let size = response.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, response.encoded_size());
+        assert_eq!(&buf[..3], &[0xA3, 0x01, 0x66]);
+
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = MakeCredentialResponse::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, response);
+
+        let object = decoded.attestation_object();
+        assert_eq!(object.auth_data(), &[0xAD; 37]);
+        let Ok(Statement::Packed(packed)) = object.statement() else {
+            panic!("expected a packed statement");
+        };
+        assert_eq!(packed, PackedStatement::try_from(&STATEMENT).unwrap());
+        assert_eq!(MakeCredentialResponse::from(object), response);
+
+        // An attestation statement that is not canonical is not written
+        static UNSORTED: Value = cbor!({"sig": h "3045", "alg": -7});
+        let Value::Map(unsorted) = UNSORTED else {
+            unreachable!()
+        };
+        let object = AttestationObject::new(FMT_PACKED, unsorted, &[0xAD; 37]);
+        assert_eq!(
+            MakeCredentialResponse::from(object).as_cbor(&mut buf),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_get_assertion_response() {
+        let credential = CredentialDescriptor::new(&[0xC1; 16]);
+        let user = User::new(&[7]).with_name("alice");
+        let response = GetAssertionResponse::new(&[0xAD; 37], &[0x30, 0x44])
+            .with_credential(credential)
+            .with_user(user)
+            .with_number_of_credentials(2);
+        let mut buf = [0u8; 128];
+        let size = response.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, response.encoded_size());
+
+        let mut pairs = [(Value::null(), Value::null()); 12];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = GetAssertionResponse::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, response);
+        assert_eq!(decoded.credential(), Some(credential));
+        assert_eq!(decoded.signature(), &[0x30, 0x44]);
+        assert_eq!(decoded.user(), Some(user));
+        assert_eq!(decoded.number_of_credentials(), Some(2));
+
+        // Without the optional members, only the authenticator data and signature remain
+        let minimal = GetAssertionResponse::new(&[0xAD; 37], &[0x30, 0x44]);
+        let mut minimal_buf = [0u8; 64];
+        let size = minimal.as_cbor(&mut minimal_buf).unwrap();
+        assert_eq!(&minimal_buf[..3], &[0xA2, 0x02, 0x58]);
+        let decoded = GetAssertionResponse::decode(&minimal_buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, minimal);
+    }
+
+    #[test]
+    fn test_get_info_response() {
+        let versions = [FIDO_2_0, FIDO_2_1];
+        let extensions = ["credProtect", "hmac-secret"];
+        static OPTIONS: Value = cbor!({"rk": true, "up": true, "clientPin": false});
+        let Value::Map(options) = OPTIONS else {
+            unreachable!()
+        };
+        let response = GetInfoResponse::new(&versions, &[0xA7; 16])
+            .with_extensions(&extensions)
+            .with_options(options)
+            .with_max_msg_size(1200)
+            .with_pin_uv_auth_protocols(&[2, 1]);
+        let mut buf = [0u8; 128];
+        let size = response.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, response.encoded_size());
+
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 12];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = GetInfoResponse::decode(&buf[..size], &mut arena).unwrap();
+        assert!(decoded.versions().iter().eq(versions.map(Ok)));
+        assert!(decoded.extensions().iter().eq(extensions.map(Ok)));
+        assert_eq!(decoded.aaguid(), &[0xA7; 16]);
+        assert_eq!(decoded.option("rk"), Some(true));
+        assert_eq!(decoded.option("clientPin"), Some(false));
+        assert_eq!(decoded.option("uv"), None);
+        assert_eq!(decoded.max_msg_size(), Some(1200));
+        assert!(decoded.pin_uv_auth_protocols().iter().eq([Ok(2), Ok(1)]));
+
+        let mut again = [0u8; 128];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+    }
+
+    #[test]
+    fn test_get_info_response_errors() {
+        let mut short = [0u8; 32];
+        let short_len = encode(&cbor!({1: ["FIDO_2_1"], 3: h "00"}), &mut short).unwrap();
+        let mut not_bool = [0u8; 48];
+        let not_bool_len = encode(
+            &cbor!({1: ["FIDO_2_1"], 3: h "000102030405060708090A0B0C0D0E0F", 4: {"rk": 1}}),
+            &mut not_bool,
+        )
+        .unwrap();
+
+        let mut values = [Value::null(); 4];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        assert_eq!(
+            GetInfoResponse::decode(&short[..short_len], &mut arena),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            GetInfoResponse::decode(&not_bool[..not_bool_len], &mut arena),
+            Err(Error::InvalidType)
+        );
+    }
+
+    #[test]
+    fn test_client_pin_response() {
+        let key = CoseKey::ec2(P256, &[0x44; 32], &[0x55; 32]).with_alg(-25);
+        let responses = [
+            ClientPinResponse::new().with_key_agreement(key),
+            ClientPinResponse::new().with_pin_uv_auth_token(&[0x66; 48]),
+            ClientPinResponse::new().with_pin_retries(8, Some(false)),
+            ClientPinResponse::new().with_uv_retries(3),
+            ClientPinResponse::new(),
+        ];
+        for response in responses {
+            let mut buf = [0u8; 96];
+            let size = response.as_cbor(&mut buf).unwrap();
+            assert_eq!(size, response.encoded_size());
+
+            let mut pairs = [(Value::null(), Value::null()); 8];
+            let mut arena = Arena::new(&mut [], &mut pairs);
+            assert_eq!(
+                ClientPinResponse::decode(&buf[..size], &mut arena),
+                Ok(response)
+            );
+        }
+        assert_eq!(responses[2].pin_retries(), Some(8));
+        assert_eq!(responses[2].power_cycle_state(), Some(false));
+        assert_eq!(responses[3].uv_retries(), Some(3));
+        assert_eq!(responses[4], ClientPinResponse::default());
+    }
+}
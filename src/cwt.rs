@@ -0,0 +1,659 @@
+// SPDX-License-Identifier: MIT
+
+//! CBOR Web Token (CWT) claims sets, as defined in
+//! [RFC 8392](https://www.rfc-editor.org/rfc/rfc8392).
+//!
+//! A CWT is a claims set, a map keyed by integer or text claim names, carried as the
+//! payload of a COSE message such as a [`CoseSign1`](crate::cose::CoseSign1). The message
+//! may be wrapped in tag 61 to mark it as a CWT.
+//!
+//! [`CwtClaims`] models the registered claims with typed fields and carries any other
+//! claims as map entries alongside them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{Arena, Value, cwt::CwtClaims, decode::decode, encode::Encode};
+//!
+//! let custom = [(Value::text("scope"), Value::text("read"))];
+//! let claims = CwtClaims::new()
+//!     .with_issuer("coap://as.example.com")
+//!     .with_expiration(1444064944)
+//!     .with_custom(&custom);
+//!
+//! let mut buf = [0u8; 64];
+//! let size = claims.as_cbor(&mut buf).unwrap();
+//!
+//! let mut pairs = [(Value::null(), Value::null()); 3];
+//! let mut arena = Arena::new(&mut [], &mut pairs);
+//! let value = decode(&buf[..size], &mut arena).unwrap();
+//! let decoded = CwtClaims::try_from(&value).unwrap();
+//! assert_eq!(decoded.issuer(), Some("coap://as.example.com"));
+//! assert_eq!(decoded.claim(&Value::text("scope")), Some(&Value::text("read")));
+//! assert!(decoded.validate_time(1444064943, 0).is_ok());
+//! assert!(decoded.validate_time(1444064944, 0).is_err());
+//! ```
+
+use crate::{
+    Value,
+    encode::{self, Cursor, Encode, encode_header, encode_value, major_type},
+    error::Error,
+    result::Result,
+    tag,
+    value::Map,
+};
+
+/// Claim 1: The principal that issued the token.
+pub const ISS: i64 = 1;
+/// Claim 2: The principal that is the subject of the token.
+pub const SUB: i64 = 2;
+/// Claim 3: The recipient the token is intended for.
+pub const AUD: i64 = 3;
+/// Claim 4: The time on or after which the token must not be accepted.
+pub const EXP: i64 = 4;
+/// Claim 5: The time before which the token must not be accepted.
+pub const NBF: i64 = 5;
+/// Claim 6: The time at which the token was issued.
+pub const IAT: i64 = 6;
+/// Claim 7: The unique identifier of the token.
+pub const CTI: i64 = 7;
+/// Claim 8: The key the presenter holds, as defined in RFC 8747.
+pub const CNF: i64 = 8;
+
+/// A CWT claims set.
+///
+/// Times are NumericDate values: seconds since 1970-01-01T00:00Z, ignoring leap seconds.
+/// They are held as whole seconds, so a float time read from a claims map loses its
+/// fractional part, truncated toward zero: `1444064944.75` reads as `1444064944`.
+///
+/// Decoding keeps the whole claims map as the custom claims, so every claim, registered
+/// or not, can be found with [`claim`](Self::claim). Entries of the custom claims that
+/// use a registered label are never encoded, since those claims are written from their
+/// typed fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CwtClaims<'a> {
+    iss: Option<&'a str>,
+    sub: Option<&'a str>,
+    aud: Option<&'a str>,
+    exp: Option<i64>,
+    nbf: Option<i64>,
+    iat: Option<i64>,
+    cti: Option<&'a [u8]>,
+    cnf: Option<Value<'a>>,
+    custom: Map<'a>,
+}
+
+impl Default for CwtClaims<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> CwtClaims<'a> {
+    /// Creates an empty claims set.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            iss: None,
+            sub: None,
+            aud: None,
+            exp: None,
+            nbf: None,
+            iat: None,
+            cti: None,
+            cnf: None,
+            custom: &[],
+        }
+    }
+
+    /// Returns the claims with the given issuer (`iss`).
+    #[inline]
+    pub const fn with_issuer(self, iss: &'a str) -> Self {
+        Self {
+            iss: Some(iss),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given subject (`sub`).
+    #[inline]
+    pub const fn with_subject(self, sub: &'a str) -> Self {
+        Self {
+            sub: Some(sub),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given audience (`aud`).
+    #[inline]
+    pub const fn with_audience(self, aud: &'a str) -> Self {
+        Self {
+            aud: Some(aud),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given expiration time (`exp`).
+    #[inline]
+    pub const fn with_expiration(self, exp: i64) -> Self {
+        Self {
+            exp: Some(exp),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given not-before time (`nbf`).
+    #[inline]
+    pub const fn with_not_before(self, nbf: i64) -> Self {
+        Self {
+            nbf: Some(nbf),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given issue time (`iat`).
+    #[inline]
+    pub const fn with_issued_at(self, iat: i64) -> Self {
+        Self {
+            iat: Some(iat),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given token identifier (`cti`).
+    #[inline]
+    pub const fn with_cti(self, cti: &'a [u8]) -> Self {
+        Self {
+            cti: Some(cti),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given confirmation (`cnf`), a map such as
+    /// `{1: COSE_Key}`.
+    #[inline]
+    pub const fn with_confirmation(self, cnf: Value<'a>) -> Self {
+        Self {
+            cnf: Some(cnf),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given custom claims, which are encoded after the
+    /// registered ones in their given order.
+    #[inline]
+    pub const fn with_custom(self, custom: Map<'a>) -> Self {
+        Self { custom, ..self }
+    }
+
+    /// Returns the issuer (`iss`).
+    #[inline]
+    pub const fn issuer(&self) -> Option<&'a str> {
+        self.iss
+    }
+
+    /// Returns the subject (`sub`).
+    #[inline]
+    pub const fn subject(&self) -> Option<&'a str> {
+        self.sub
+    }
+
+    /// Returns the audience (`aud`).
+    #[inline]
+    pub const fn audience(&self) -> Option<&'a str> {
+        self.aud
+    }
+
+    /// Returns the expiration time (`exp`).
+    #[inline]
+    pub const fn expiration(&self) -> Option<i64> {
+        self.exp
+    }
+
+    /// Returns the not-before time (`nbf`).
+    #[inline]
+    pub const fn not_before(&self) -> Option<i64> {
+        self.nbf
+    }
+
+    /// Returns the issue time (`iat`).
+    #[inline]
+    pub const fn issued_at(&self) -> Option<i64> {
+        self.iat
+    }
+
+    /// Returns the token identifier (`cti`).
+    #[inline]
+    pub const fn cti(&self) -> Option<&'a [u8]> {
+        self.cti
+    }
+
+    /// Returns the confirmation (`cnf`).
+    #[inline]
+    pub const fn confirmation(&self) -> Option<Value<'a>> {
+        self.cnf
+    }
+
+    /// Returns the custom claims.
+    #[inline]
+    pub const fn custom(&self) -> Map<'a> {
+        self.custom
+    }
+
+    /// Looks up a claim among the custom claims.
+    ///
+    /// For decoded claims this searches the whole claims map.
+    pub fn claim(&self, key: &Value) -> Option<&'a Value<'a>> {
+        Value::Map(self.custom).get(key)
+    }
+
+    /// Returns `true` if the token has expired at `now`.
+    ///
+    /// A token without an expiration time never expires.
+    #[inline]
+    pub const fn is_expired(&self, now: i64) -> bool {
+        match self.exp {
+            Some(exp) => now >= exp,
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the token is not yet valid at `now`.
+    #[inline]
+    pub const fn is_not_yet_valid(&self, now: i64) -> bool {
+        match self.nbf {
+            Some(nbf) => now < nbf,
+            None => false,
+        }
+    }
+
+    /// Checks the expiration and not-before times against the current time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, in seconds since the epoch.
+    /// * `leeway` - The clock skew to allow, in seconds, in both directions.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the token is valid at `now`.
+    /// * `Err(Error::InvalidValue)` - If the token has expired or is not yet valid.
+    pub const fn validate_time(&self, now: i64, leeway: u32) -> Result<()> {
+        let leeway = leeway as i64;
+        if self.is_expired(now.saturating_sub(leeway))
+            || self.is_not_yet_valid(now.saturating_add(leeway))
+        {
+            Err(Error::InvalidValue)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the claims.
+    pub fn encoded_size(&self) -> usize {
+        let (_, _, extra) = encode_header(major_type::MAP, self.len() as u64);
+        let mut size = 1 + extra;
+        let _ = self.for_each(|key, value| {
+            size += encode::encoded_size(key) + encode::encoded_size(value);
+            Ok(())
+        });
+        size
+    }
+
+    /// Returns the number of entries in the encoded claims map.
+    fn len(&self) -> usize {
+        let mut len = 0;
+        let _ = self.for_each(|_, _| {
+            len += 1;
+            Ok(())
+        });
+        len
+    }
+
+    /// Calls `f` with each entry of the encoded claims map, in order.
+    fn for_each(&self, mut f: impl FnMut(&Value, &Value) -> Result<()>) -> Result<()> {
+        self.for_each_registered(&mut f)?;
+        for (key, value) in self.custom {
+            if !is_registered(key) {
+                f(key, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with each registered claim that is set, in label order.
+    pub(crate) fn for_each_registered(
+        &self,
+        mut f: impl FnMut(&Value, &Value) -> Result<()>,
+    ) -> Result<()> {
+        let strings = [(ISS, self.iss), (SUB, self.sub), (AUD, self.aud)];
+        for (label, text) in strings {
+            if let Some(text) = text {
+                f(&Value::integer(label), &Value::Text(text))?;
+            }
+        }
+        let times = [(EXP, self.exp), (NBF, self.nbf), (IAT, self.iat)];
+        for (label, time) in times {
+            if let Some(time) = time {
+                f(&Value::integer(label), &Value::integer(time))?;
+            }
+        }
+        if let Some(cti) = self.cti {
+            f(&Value::integer(CTI), &Value::Bytes(cti))?;
+        }
+        if let Some(cnf) = &self.cnf {
+            f(&Value::integer(CNF), cnf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for CwtClaims<'_> {
+    /// Encodes the claims map, with the registered claims first in label order.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        let mut cursor = Cursor::new(buf);
+        encode::write_header_with_extras(&mut cursor, major_type::MAP, self.len() as u64)?;
+        self.for_each(|key, value| {
+            encode_value(key, &mut cursor)?;
+            encode_value(value, &mut cursor)
+        })?;
+        Ok(cursor.pos)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for CwtClaims<'a> {
+    type Error = Error;
+
+    /// Reads a claims map, checking the types of the registered claims.
+    ///
+    /// A tag 1 around a time is accepted. Float times are truncated toward zero to whole
+    /// seconds; floats outside the range of `i64` are rejected.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CwtClaims)` - The claims.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a registered claim has
+    ///   the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let Some(custom) = value.as_map() else {
+            return Err(Error::InvalidType);
+        };
+        let text = |label| match value.get_int(label) {
+            Some(claim) => claim.as_str().map(Some).ok_or(Error::InvalidType),
+            None => Ok(None),
+        };
+        let time = |label| match value.get_int(label) {
+            Some(claim) => numeric_date(claim).map(Some),
+            None => Ok(None),
+        };
+        let cti = match value.get_int(CTI) {
+            Some(claim) => Some(claim.as_bytes().ok_or(Error::InvalidType)?),
+            None => None,
+        };
+        let cnf = match value.get_int(CNF) {
+            Some(claim @ Value::Map(_)) => Some(*claim),
+            Some(_) => return Err(Error::InvalidType),
+            None => None,
+        };
+
+        Ok(Self {
+            iss: text(ISS)?,
+            sub: text(SUB)?,
+            aud: text(AUD)?,
+            exp: time(EXP)?,
+            nbf: time(NBF)?,
+            iat: time(IAT)?,
+            cti,
+            cnf,
+            custom,
+        })
+    }
+}
+
+/// Returns `true` if a map key is the label of a registered claim.
+pub(crate) fn is_registered(key: &Value) -> bool {
+    matches!(key.as_i128(), Some(label) if (ISS as i128..=CNF as i128).contains(&label))
+}
+
+/// Reads a NumericDate, which may be an integer or a float, optionally with tag 1.
+///
+/// Floats are truncated toward zero. `i64::MAX as f64` rounds up to 2^63, so the upper
+/// bound is exclusive.
+fn numeric_date(value: &Value) -> Result<i64> {
+    match value {
+        Value::Tag(tag::EPOCH_DATE_TIME, item) => numeric_date(item),
+        Value::Float(seconds) if *seconds >= i64::MIN as f64 && *seconds < i64::MAX as f64 => {
+            Ok(*seconds as i64)
+        }
+        _ => match value.as_i128().map(i64::try_from) {
+            Some(Ok(seconds)) => Ok(seconds),
+            _ => Err(Error::InvalidType),
+        },
+    }
+}
+
+/// Writes the tag 61 header that marks a COSE message as a CWT.
+///
+/// The COSE message is encoded directly after it.
+///
+/// # Returns
+///
+/// * `Ok(usize)` - The number of bytes written.
+/// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::cwt::write_tag;
+///
+/// let mut buf = [0u8; 2];
+/// assert_eq!(write_tag(&mut buf), Ok(2));
+/// assert_eq!(buf, [0xD8, 0x3D]);
+/// ```
+pub fn write_tag(buf: &mut [u8]) -> Result<usize> {
+    let mut cursor = Cursor::new(buf);
+    encode::write_header_with_extras(&mut cursor, major_type::TAG, tag::CWT)?;
+    Ok(cursor.pos)
+}
+
+/// Returns the COSE message inside a tag 61, or the value itself if it has no such tag.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, cbor, cwt::untag};
+///
+/// static TOKEN: Value = cbor!(61(18([h "", {}, h "A0", h ""])));
+/// assert_eq!(untag(&TOKEN).tag_number(), Some(18));
+/// assert_eq!(untag(untag(&TOKEN)).tag_number(), Some(18));
+/// ```
+pub const fn untag<'v, 'a>(value: &'v Value<'a>) -> &'v Value<'a> {
+    match value {
+        Value::Tag(tag::CWT, message) => message,
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CwtClaims, untag, write_tag};
+    use crate::{
+        Arena, Value, cbor,
+        cose::{CoseSign1, Signer},
+        decode::decode,
+        encode::Encode,
+        error::Error,
+        result::Result,
+    };
+
+    /// The claims of the example CWT in RFC 8392 Appendix A.1.
+    const EXAMPLE: CwtClaims = CwtClaims::new()
+        .with_issuer("coap://as.example.com")
+        .with_subject("erikw")
+        .with_audience("coap://light.example.com")
+        .with_expiration(1444064944)
+        .with_not_before(1443944944)
+        .with_issued_at(1443944944)
+        .with_cti(&[0x0B, 0x71]);
+
+    /// The encoding of [`EXAMPLE`] from RFC 8392 Appendix A.1.
+    const EXAMPLE_BYTES: &[u8] = b"\xA7\
+        \x01\x75coap://as.example.com\
+        \x02\x65erikw\
+        \x03\x78\x18coap://light.example.com\
+        \x04\x1A\x56\x12\xAE\xB0\
+        \x05\x1A\x56\x10\xD9\xF0\
+        \x06\x1A\x56\x10\xD9\xF0\
+        \x07\x42\x0B\x71";
+
+    #[test]
+    fn test_encode_example() {
+        let mut buf = [0u8; 128];
+        let size = EXAMPLE.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..size], EXAMPLE_BYTES);
+        assert_eq!(EXAMPLE.encoded_size(), EXAMPLE_BYTES.len());
+
+        let mut short = [0u8; 79];
+        assert_eq!(EXAMPLE.as_cbor(&mut short), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_decode_example() {
+        let mut pairs = [(Value::null(), Value::null()); 7];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let value = decode(EXAMPLE_BYTES, &mut arena).unwrap();
+        let claims = CwtClaims::try_from(&value).unwrap();
+
+        assert_eq!(claims.subject(), Some("erikw"));
+        assert_eq!(claims.not_before(), Some(1443944944));
+        assert_eq!(
+            claims.claim(&Value::unsigned(2)),
+            Some(&Value::text("erikw"))
+        );
+        assert_eq!(claims.with_custom(&[]), EXAMPLE);
+
+        // Registered claims in the custom map are not written twice
+        let mut buf = [0u8; 128];
+        let size = claims.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..size], EXAMPLE_BYTES);
+    }
+
+    #[test]
+    fn test_custom_claims() {
+        static CNF: Value = cbor!({1: {1: 4, -1: h "00"}});
+        let custom = [
+            (Value::integer(-65537), Value::text("private")),
+            (Value::unsigned(4), Value::unsigned(0)),
+            (Value::text("scope"), Value::text("read")),
+        ];
+        let claims = CwtClaims::new().with_confirmation(CNF).with_custom(&custom);
+
+        let mut buf = [0u8; 64];
+        let size = claims.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, claims.encoded_size());
+        assert_eq!(&buf[..3], &[0xA3, 0x08, 0xA1]);
+
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let decoded = CwtClaims::try_from(&value).unwrap();
+        assert_eq!(decoded.confirmation(), Some(CNF));
+        assert_eq!(decoded.expiration(), None);
+        assert_eq!(
+            decoded.claim(&Value::integer(-65537)),
+            Some(&Value::text("private"))
+        );
+    }
+
+    #[test]
+    fn test_decode_types() {
+        // Float times are truncated toward zero
+        static FLOAT_TIMES: Value = cbor!({4: 1444064944.75, 5: -1.5, 6: 1(1443944944)});
+        let claims = CwtClaims::try_from(&FLOAT_TIMES).unwrap();
+        assert_eq!(claims.expiration(), Some(1444064944));
+        assert_eq!(claims.not_before(), Some(-1));
+        assert_eq!(claims.issued_at(), Some(1443944944));
+
+        static NOT_A_MAP: Value = cbor!([1]);
+        static INTEGER_ISSUER: Value = cbor!({1: 1});
+        static TEXT_TIME: Value = cbor!({4: "tomorrow"});
+        static TEXT_CTI: Value = cbor!({7: "id"});
+        static ARRAY_CNF: Value = cbor!({8: []});
+        static HUGE_TIME: Value = cbor!({5: 18446744073709551615});
+        static HUGE_FLOAT_TIME: Value = cbor!({5: (Value::float(1e300))});
+        for value in [
+            &NOT_A_MAP,
+            &INTEGER_ISSUER,
+            &TEXT_TIME,
+            &TEXT_CTI,
+            &ARRAY_CNF,
+            &HUGE_TIME,
+            &HUGE_FLOAT_TIME,
+        ] {
+            assert_eq!(
+                CwtClaims::try_from(value),
+                Err(Error::InvalidType),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_time() {
+        assert!(!EXAMPLE.is_expired(1444064943));
+        assert!(EXAMPLE.is_expired(1444064944));
+        assert!(EXAMPLE.is_not_yet_valid(1443944943));
+        assert!(!EXAMPLE.is_not_yet_valid(1443944944));
+
+        assert_eq!(EXAMPLE.validate_time(1444000000, 0), Ok(()));
+        assert_eq!(
+            EXAMPLE.validate_time(1444064944, 0),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(EXAMPLE.validate_time(1444064944, 1), Ok(()));
+        assert_eq!(
+            EXAMPLE.validate_time(1443944940, 0),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(EXAMPLE.validate_time(1443944940, 5), Ok(()));
+
+        // Without times, a token is always valid
+        assert_eq!(CwtClaims::new().validate_time(i64::MIN, u32::MAX), Ok(()));
+    }
+
+    /// A signer that produces an empty signature.
+    struct Unsigned;
+
+    impl Signer for Unsigned {
+        fn sign(&self, _: &[u8], _: &mut [u8]) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_tagged_token() {
+        let mut payload = [0u8; 128];
+        let len = EXAMPLE.as_cbor(&mut payload).unwrap();
+        let mut scratch = [0u8; 160];
+        let message =
+            CoseSign1::sign(&[], &[], &payload[..len], &[], &Unsigned, &mut scratch).unwrap();
+
+        let mut buf = [0u8; 160];
+        let tag_len = write_tag(&mut buf).unwrap();
+        let size = tag_len + message.as_cbor(&mut buf[tag_len..]).unwrap();
+        assert_eq!(&buf[..3], &[0xD8, 0x3D, 0xD2]);
+
+        let mut values = [Value::null(); 6];
+        let mut arena = Arena::new(&mut values, &mut []);
+        let token = decode(&buf[..size], &mut arena).unwrap();
+        let message = CoseSign1::try_from(untag(&token)).unwrap();
+
+        let mut pairs = [(Value::null(), Value::null()); 7];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let value = decode(message.payload().unwrap(), &mut arena).unwrap();
+        assert_eq!(
+            CwtClaims::try_from(&value).unwrap().with_custom(&[]),
+            EXAMPLE
+        );
+    }
+}
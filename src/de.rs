@@ -0,0 +1,564 @@
+// SPDX-License-Identifier: MIT
+
+//! Serde deserialization from a byte slice.
+//!
+//! This module provides a `no_std` [`Deserializer`] that reads CBOR directly from the
+//! input, without building an intermediate [`Value`](crate::Value) tree. Byte and text
+//! strings are borrowed from the input, so `&str` and `&[u8]` fields are decoded without
+//! copying. It is available with the `serde` feature.
+//!
+//! The input is self-describing, so `deserialize_any` is supported, and the data model
+//! mapping matches the [`ser`](crate::ser) module:
+//!
+//! * Null and undefined decode as `None` or `()`; any other item decodes as `Some`.
+//! * Bignums (tags 2 and 3) decode as `u128` or `i128` when they fit. Other tags are
+//!   transparent, so the tagged item is decoded in their place.
+//! * An enum variant is either its name as text, for a unit variant, or a single-entry map
+//!   from the name to the content.
+//!
+//! Nesting is limited to [`MAX_DEPTH`] levels by default, which can be lowered with
+//! [`Deserializer::with_max_depth`].
+//!
+//! Like [`decode`], the deserializer only reads definite-length items.
+//! Indefinite-length strings, arrays and maps are rejected with [`Error::InvalidType`],
+//! even where the target type would accept them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::de::from_slice;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Device<'a> {
+//!     name: &'a str,
+//!     id: u8,
+//! }
+//!
+//! let bytes = [
+//!     0xA2, // Map with 2 pairs
+//!     0x64, 0x6E, 0x61, 0x6D, 0x65, 0x64, 0x65, 0x74, 0x68, 0x30, // "name": "eth0"
+//!     0x62, 0x69, 0x64, 0x07, // "id": 7
+//! ];
+//!
+//! let device: Device = from_slice(&bytes).unwrap();
+//! assert_eq!(device, Device { name: "eth0", id: 7 });
+//! ```
+
+use serde::de::{self, Deserialize, DeserializeSeed, Visitor};
+
+use crate::{
+    decode::{self, Header, MAX_DEPTH, Reader},
+    error::Error,
+    result::Result,
+    tag::{self, Bignum},
+};
+
+/// Deserializes a value from a byte buffer holding exactly one data item.
+///
+/// Indefinite-length strings, arrays and maps are not supported.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded data item.
+///
+/// # Returns
+///
+/// * `Ok(T)` - The decoded value, which may borrow from `bytes`.
+/// * `Err(Error::UnexpectedEnd)` - If the input ends partway through the data item.
+/// * `Err(Error::DepthExceeded)` - If items are nested more than [`MAX_DEPTH`] deep.
+/// * `Err(Error::InvalidType)` - If the input does not match the shape of `T`, or uses
+///   indefinite-length encoding.
+/// * `Err(Error::InvalidValue)` - If the input is malformed, is followed by trailing
+///   bytes, or is rejected by the `Deserialize` implementation.
+#[inline]
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T> {
+    let mut deserializer = Deserializer::new(bytes);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A serde deserializer that reads CBOR from a byte slice.
+///
+/// Several values can be read back to back from a CBOR sequence. Only definite-length
+/// items are read; indefinite-length strings, arrays and maps fail with
+/// [`Error::InvalidType`], including through `deserialize_any`.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::de::Deserializer;
+/// use serde::Deserialize;
+///
+/// let bytes = [0x01, 0x62, 0x68, 0x69];
+/// let mut deserializer = Deserializer::new(&bytes);
+/// assert_eq!(u8::deserialize(&mut deserializer), Ok(1));
+/// assert_eq!(<&str>::deserialize(&mut deserializer), Ok("hi"));
+/// assert_eq!(deserializer.end(), Ok(()));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Deserializer<'de> {
+    reader: Reader<'de>,
+    /// The number of further nesting levels allowed.
+    depth: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer that reads from the start of the provided buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded input.
+    #[inline]
+    pub const fn new(bytes: &'de [u8]) -> Self {
+        Self {
+            reader: Reader::new(bytes),
+            depth: MAX_DEPTH,
+        }
+    }
+
+    /// Limits nesting of arrays, maps and tags to `depth` levels.
+    #[inline]
+    pub const fn with_max_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Returns the number of bytes read so far.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.reader.pos
+    }
+
+    /// Checks that the whole input has been read.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If no bytes remain.
+    /// * `Err(Error::InvalidValue)` - If trailing bytes follow the last data item.
+    #[inline]
+    pub const fn end(&self) -> Result<()> {
+        decode::finish(&self.reader)
+    }
+
+    /// Runs `f` one nesting level deeper.
+    fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        let depth = self.depth;
+        self.depth = decode::nested(depth)?;
+        let result = f(self);
+        self.depth = depth;
+        result
+    }
+
+    /// Consumes a null or undefined value if one is next.
+    #[inline]
+    fn take_null(&mut self) -> bool {
+        // 0xF6 is null and 0xF7 is undefined
+        match self.reader.data.get(self.reader.pos) {
+            Some(0xF6 | 0xF7) => {
+                self.reader.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Decodes the item following a tag.
+    fn tagged<V: Visitor<'de>>(&mut self, number: u64, visitor: V) -> Result<V::Value> {
+        let negative = match number {
+            tag::POSITIVE_BIGNUM => false,
+            tag::NEGATIVE_BIGNUM => true,
+            _ => return de::Deserializer::deserialize_any(self, visitor),
+        };
+
+        let bignum = match decode::read_header(&mut self.reader)? {
+            Header::Bytes(magnitude) => Bignum::new(negative, magnitude),
+            _ => return Err(Error::InvalidType),
+        };
+
+        match (bignum.to_u128(), bignum.to_i128()) {
+            (Some(n), _) => visitor.visit_u128(n),
+            (None, Some(n)) => visitor.visit_i128(n),
+            (None, None) => Err(Error::InvalidValue),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match decode::read_header(&mut self.reader)? {
+            Header::Unsigned(n) => visitor.visit_u64(n),
+            Header::Negative(n) => match i64::try_from(n) {
+                Ok(n) => visitor.visit_i64(-1 - n),
+                Err(_) => visitor.visit_i128(-1 - n as i128),
+            },
+            Header::Bytes(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Header::Text(text) => visitor.visit_borrowed_str(text),
+            Header::Array(len) => self.nested(|de| {
+                let mut access = Access { de, remaining: len };
+                let value = visitor.visit_seq(&mut access)?;
+                access.finish(value)
+            }),
+            Header::Map(len) => self.nested(|de| {
+                let mut access = Access { de, remaining: len };
+                let value = visitor.visit_map(&mut access)?;
+                access.finish(value)
+            }),
+            Header::Tag(number) => self.nested(|de| de.tagged(number, visitor)),
+            Header::Simple(20) => visitor.visit_bool(false),
+            Header::Simple(21) => visitor.visit_bool(true),
+            Header::Simple(22 | 23) => visitor.visit_unit(),
+            Header::Simple(_) => Err(Error::InvalidType),
+            Header::Float(f) => visitor.visit_f64(f),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.take_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        // A text string is a unit variant; a single-entry map holds any other variant
+        let start = self.reader.pos;
+        match decode::read_header(&mut self.reader)? {
+            Header::Text(_) => {
+                self.reader.pos = start;
+                visitor.visit_enum(Unit { de: self })
+            }
+            Header::Map(1) => self.nested(|de| visitor.visit_enum(Variant { de })),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+/// Reads the items of an array or the entries of a map.
+struct Access<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    /// The number of items or entries not yet read.
+    remaining: usize,
+}
+
+impl Access<'_, '_> {
+    /// Checks that the visitor read every item before returning its value.
+    #[inline]
+    fn finish<T>(self, value: T) -> Result<T> {
+        if self.remaining == 0 {
+            Ok(value)
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Access<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Access<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Reads a unit variant written as its name.
+struct Unit<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Unit<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Unit<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value> {
+        Err(Error::InvalidType)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value> {
+        Err(Error::InvalidType)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value> {
+        Err(Error::InvalidType)
+    }
+}
+
+/// Reads a variant written as a single-entry map from its name to its content.
+struct Variant<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        <() as Deserialize>::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Deserializer, from_slice};
+    use crate::{decode::MAX_DEPTH, error::Error};
+    use serde::de::{Deserialize, IgnoredAny};
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(from_slice::<bool>(&[0xF5]), Ok(true));
+        assert_eq!(from_slice::<u8>(&[0x18, 0x2A]), Ok(42));
+        assert_eq!(from_slice::<u16>(&[0x19, 0x01, 0xF4]), Ok(500));
+        assert_eq!(from_slice::<i32>(&[0x39, 0x01, 0xF3]), Ok(-500));
+        assert_eq!(
+            from_slice::<i64>(&[0x3B, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            Ok(i64::MIN)
+        );
+        assert_eq!(from_slice::<f32>(&[0xFA, 0x3F, 0xC0, 0x00, 0x00]), Ok(1.5));
+        assert_eq!(from_slice::<f64>(&[0xF9, 0x3E, 0x00]), Ok(1.5));
+        assert_eq!(from_slice::<char>(&[0x62, 0xC3, 0xBC]), Ok('\u{00fc}'));
+        assert_eq!(from_slice::<()>(&[0xF6]), Ok(()));
+
+        // Out of range for the target type
+        assert_eq!(
+            from_slice::<u8>(&[0x19, 0x01, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(from_slice::<u8>(&[0x20]), Err(Error::InvalidValue));
+        assert_eq!(from_slice::<bool>(&[0x01]), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_wide_integers() {
+        assert_eq!(
+            from_slice::<i128>(&[0x3B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            Ok(-(1i128 << 64))
+        );
+        assert_eq!(
+            from_slice::<u128>(&[0xC2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Ok(1u128 << 64)
+        );
+        assert_eq!(
+            from_slice::<i128>(&[0xC3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Ok(-(1i128 << 64) - 1)
+        );
+        assert_eq!(
+            from_slice::<u128>(&[
+                0xC2, 0x50, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0xFF, 0xFF, 0xFF
+            ]),
+            Ok(u128::MAX)
+        );
+
+        // Too large for any integer type
+        let mut bytes = [0xFFu8; 19];
+        bytes[..2].copy_from_slice(&[0xC2, 0x51]);
+        assert_eq!(from_slice::<u128>(&bytes), Err(Error::InvalidValue));
+
+        // Bignum tags must wrap a byte string
+        assert_eq!(from_slice::<u128>(&[0xC2, 0x01]), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_borrowed_strings() {
+        let bytes = [0x64, 0x49, 0x45, 0x54, 0x46];
+        let text: &str = from_slice(&bytes).unwrap();
+        assert_eq!(text, "IETF");
+        assert_eq!(text.as_ptr(), bytes[1..].as_ptr());
+
+        let bytes = [0x43, 0x01, 0x02, 0x03];
+        let blob: &[u8] = from_slice(&bytes).unwrap();
+        assert_eq!(blob, &[1, 2, 3]);
+        assert_eq!(blob.as_ptr(), bytes[1..].as_ptr());
+
+        assert_eq!(from_slice::<&str>(&[0x01]), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_options_and_tags() {
+        assert_eq!(from_slice::<Option<u8>>(&[0xF6]), Ok(None));
+        assert_eq!(from_slice::<Option<u8>>(&[0xF7]), Ok(None));
+        assert_eq!(from_slice::<Option<u8>>(&[0x07]), Ok(Some(7)));
+
+        // Tags other than bignums are transparent
+        assert_eq!(from_slice::<u8>(&[0xC1, 0x07]), Ok(7));
+    }
+
+    #[test]
+    fn test_sequences() {
+        assert_eq!(
+            from_slice::<[u8; 3]>(&[0x83, 0x01, 0x02, 0x03]),
+            Ok([1, 2, 3])
+        );
+        assert_eq!(
+            from_slice::<(u8, &str)>(&[0x82, 0x01, 0x61, 0x61]),
+            Ok((1, "a"))
+        );
+
+        // Item counts must match exactly
+        assert_eq!(
+            from_slice::<[u8; 2]>(&[0x83, 0x01, 0x02, 0x03]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            from_slice::<[u8; 3]>(&[0x82, 0x01, 0x02]),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_ignored_any() {
+        let bytes = [0xA1, 0x61, 0x61, 0x82, 0xC1, 0x01, 0xF6];
+        assert_eq!(from_slice::<IgnoredAny>(&bytes), Ok(IgnoredAny));
+    }
+
+    #[test]
+    fn test_sequence_of_items() {
+        let bytes = [0x01, 0x02, 0x03];
+        let mut deserializer = Deserializer::new(&bytes);
+        assert_eq!(u8::deserialize(&mut deserializer), Ok(1));
+        assert_eq!(deserializer.position(), 1);
+        assert_eq!(deserializer.end(), Err(Error::InvalidValue));
+
+        assert_eq!(from_slice::<u8>(&bytes), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let bytes = [0x81, 0x81, 0x81, 0x00];
+        assert_eq!(from_slice::<[[[u8; 1]; 1]; 1]>(&bytes), Ok([[[0]]]));
+
+        let mut deserializer = Deserializer::new(&bytes).with_max_depth(2);
+        assert_eq!(
+            <[[[u8; 1]; 1]; 1]>::deserialize(&mut deserializer),
+            Err(Error::DepthExceeded)
+        );
+
+        let nested = [0x81u8; MAX_DEPTH + 2];
+        assert_eq!(from_slice::<IgnoredAny>(&nested), Err(Error::DepthExceeded));
+    }
+
+    #[test]
+    fn test_indefinite_length() {
+        assert_eq!(
+            from_slice::<IgnoredAny>(&[0x9F, 0x01, 0xFF]),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            from_slice::<&[u8]>(&[0x5F, 0x41, 0x01, 0xFF]),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            from_slice::<IgnoredAny>(&[0xBF, 0x61, 0x61, 0x01, 0xFF]),
+            Err(Error::InvalidType)
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(from_slice::<u8>(&[]), Err(Error::UnexpectedEnd));
+        assert_eq!(from_slice::<&str>(&[0x63, 0x61]), Err(Error::UnexpectedEnd));
+        assert_eq!(
+            from_slice::<[u8; 2]>(&[0x82, 0x01]),
+            Err(Error::UnexpectedEnd)
+        );
+    }
+}
@@ -0,0 +1,612 @@
+// SPDX-License-Identifier: MIT
+
+//! CBOR decoding functionality.
+//!
+//! This module provides functions for decoding CBOR bytes back into a [`Value`] tree.
+//! Strings borrow directly from the input, while array items, map entries and tagged
+//! items are placed in a caller-supplied [`Arena`].
+//!
+//! Only definite-length items are supported; indefinite-length strings, arrays and maps
+//! are rejected with `Error::InvalidType`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{Arena, Value, decode::decode};
+//!
+//! // An array holding the integer 42 and the text "hi"
+//! let bytes = [0x82, 0x18, 0x2A, 0x62, 0x68, 0x69];
+//!
+//! // Provide storage for the array items
+//! let mut values = [Value::null(); 2];
+//! let mut arena = Arena::new(&mut values, &mut []);
+//!
+//! let value = decode(&bytes, &mut arena).unwrap();
+//! assert_eq!(value, Value::array(&[Value::unsigned(42), Value::text("hi")]));
+//! ```
+
+use crate::{
+    Arena, Value,
+    encode::major_type::{self, MajorType},
+    error::Error,
+    result::Result,
+    tag,
+};
+
+#[cfg(feature = "alloc")]
+use crate::OwnedValue;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+mod reader;
+
+pub(crate) use reader::Reader;
+
+/// The additional information value marking an indefinite length or a break.
+const INDEFINITE: u8 = 31;
+
+/// The deepest nesting of arrays, maps and tags that the decoders accept.
+///
+/// Decoding recurses once per level, so this bounds stack usage on hostile input.
+pub const MAX_DEPTH: usize = 128;
+
+/// A single data item header, along with any payload that follows it inline.
+///
+/// Arrays, maps and tags carry only their length or tag number; the caller reads the
+/// nested items that follow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Header<'a> {
+    Unsigned(u64),
+    Negative(u64),
+    Bytes(&'a [u8]),
+    Text(&'a str),
+    Array(usize),
+    Map(usize),
+    Tag(u64),
+    Simple(u8),
+    Float(f64),
+}
+
+/// Decodes a single CBOR data item from a byte buffer.
+///
+/// This is the main decoding function that converts CBOR bytes into a `Value`. The whole
+/// buffer must hold exactly one data item. Byte and text strings borrow from `bytes`;
+/// every array item and tagged item takes one value slot from `arena`, and every map
+/// entry takes one pair slot.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded data item.
+/// * `arena` - Storage for the nested items of the decoded tree.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The decoded value.
+/// * `Err(Error::UnexpectedEnd)` - If the input ends partway through the data item.
+/// * `Err(Error::BufferOverflow)` - If the arena is too small to hold the nested items.
+/// * `Err(Error::DepthExceeded)` - If items are nested more than [`MAX_DEPTH`] deep.
+/// * `Err(Error::InvalidType)` - If the input uses an unsupported encoding, such as an
+///   indefinite length.
+/// * `Err(Error::InvalidValue)` - If the input is malformed, or is followed by trailing
+///   bytes.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, Value, decode::decode};
+///
+/// let mut arena = Arena::new(&mut [], &mut []);
+/// let value = decode(&[0x18, 0x2A], &mut arena).unwrap();
+/// assert_eq!(value, Value::unsigned(42));
+/// ```
+#[inline]
+pub fn decode<'a>(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Value<'a>> {
+    decode_with_depth(bytes, arena, MAX_DEPTH)
+}
+
+/// Decodes a single CBOR data item like [`decode`], allowing at most `depth` levels of
+/// nested arrays, maps and tags, for protocols with a stricter limit than [`MAX_DEPTH`].
+#[inline]
+pub(crate) fn decode_with_depth<'a>(
+    bytes: &'a [u8],
+    arena: &mut Arena<'a>,
+    depth: usize,
+) -> Result<Value<'a>> {
+    let mut reader = Reader::new(bytes);
+    let value = decode_value(&mut reader, arena, depth)?;
+    finish(&reader)?;
+    Ok(value)
+}
+
+/// Decodes a single CBOR data item into an [`OwnedValue`].
+///
+/// This behaves like [`decode`], except that strings are copied and nested items are
+/// allocated on the heap, so the result does not borrow from `bytes`.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded data item.
+///
+/// # Returns
+///
+/// * `Ok(OwnedValue)` - The decoded value.
+/// * `Err(Error)` - As for [`decode`], except that no arena can overflow.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{OwnedValue, decode::decode_owned};
+///
+/// let value = decode_owned(&[0x63, 0x61, 0x62, 0x63]).unwrap();
+/// assert_eq!(value, OwnedValue::Text("abc".into()));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_owned(bytes: &[u8]) -> Result<OwnedValue> {
+    let mut reader = Reader::new(bytes);
+    let value = decode_owned_value(&mut reader, MAX_DEPTH)?;
+    finish(&reader)?;
+    Ok(value)
+}
+
+/// Checks that the reader consumed the whole input.
+#[inline]
+pub(crate) const fn finish(reader: &Reader) -> Result<()> {
+    if reader.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidValue)
+    }
+}
+
+/// Returns the depth remaining below a nested item, if another level is allowed.
+#[inline]
+pub(crate) const fn nested(depth: usize) -> Result<usize> {
+    match depth.checked_sub(1) {
+        Some(depth) => Ok(depth),
+        None => Err(Error::DepthExceeded),
+    }
+}
+
+/// Internal function that decodes a CBOR value using a reader.
+///
+/// # Arguments
+///
+/// * `reader` - A mutable reference to the reader positioned at the data item.
+/// * `arena` - Storage for the nested items of the decoded tree.
+/// * `depth` - The number of further nesting levels allowed.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The decoded value.
+/// * `Err(Error)` - If the input is malformed or the arena is exhausted.
+fn decode_value<'a>(
+    reader: &mut Reader<'a>,
+    arena: &mut Arena<'a>,
+    depth: usize,
+) -> Result<Value<'a>> {
+    let value = match read_header(reader)? {
+        Header::Unsigned(n) => Value::Unsigned(n),
+        Header::Negative(n) => Value::Negative(n),
+        Header::Bytes(bytes) => Value::Bytes(bytes),
+        Header::Text(text) => Value::Text(text),
+        Header::Array(len) => {
+            let depth = nested(depth)?;
+            let items = arena.alloc_values(len)?;
+            for item in items.iter_mut() {
+                *item = decode_value(reader, arena, depth)?;
+            }
+            Value::Array(items)
+        }
+        Header::Map(len) => {
+            let depth = nested(depth)?;
+            let pairs = arena.alloc_pairs(len)?;
+            for (key, value) in pairs.iter_mut() {
+                *key = decode_value(reader, arena, depth)?;
+                *value = decode_value(reader, arena, depth)?;
+            }
+            Value::Map(pairs)
+        }
+        Header::Tag(tag) => {
+            let item = decode_value(reader, arena, nested(depth)?)?;
+            tag::check_content(tag, &item)?;
+            Value::Tag(tag, arena.alloc(item)?)
+        }
+        Header::Simple(s) => Value::Simple(s),
+        Header::Float(f) => Value::Float(f),
+    };
+    Ok(value)
+}
+
+/// Internal function that decodes an owned CBOR value using a reader.
+#[cfg(feature = "alloc")]
+fn decode_owned_value(reader: &mut Reader, depth: usize) -> Result<OwnedValue> {
+    let value = match read_header(reader)? {
+        Header::Unsigned(n) => OwnedValue::Unsigned(n),
+        Header::Negative(n) => OwnedValue::Negative(n),
+        Header::Bytes(bytes) => OwnedValue::Bytes(bytes.into()),
+        Header::Text(text) => OwnedValue::Text(text.into()),
+        Header::Array(len) => {
+            let depth = nested(depth)?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(decode_owned_value(reader, depth)?);
+            }
+            OwnedValue::Array(items)
+        }
+        Header::Map(len) => {
+            let depth = nested(depth)?;
+            let mut pairs = Vec::with_capacity(len);
+            for _ in 0..len {
+                let key = decode_owned_value(reader, depth)?;
+                pairs.push((key, decode_owned_value(reader, depth)?));
+            }
+            OwnedValue::Map(pairs)
+        }
+        Header::Tag(tag) => {
+            let item = decode_owned_value(reader, nested(depth)?)?;
+            tag::check_content(tag, &item.leaf())?;
+            OwnedValue::Tag(tag, Box::new(item))
+        }
+        Header::Simple(s) => OwnedValue::Simple(s),
+        Header::Float(f) => OwnedValue::Float(f),
+    };
+    Ok(value)
+}
+
+/// Reads the header of the next data item, along with any string payload.
+///
+/// The length of an array or map is checked against the remaining input, so callers can
+/// size storage from it without trusting the encoded count.
+///
+/// # Returns
+///
+/// * `Ok(Header)` - The decoded header.
+/// * `Err(Error)` - If the header is truncated, malformed or uses an indefinite length.
+pub(crate) fn read_header<'a>(reader: &mut Reader<'a>) -> Result<Header<'a>> {
+    let initial = reader.read_byte()?;
+    let major: MajorType = initial >> 5;
+    let info = initial & 0x1F;
+
+    if major == major_type::SIMPLE {
+        return read_simple(reader, info);
+    }
+
+    if info == INDEFINITE {
+        return match major {
+            major_type::BYTES | major_type::TEXT | major_type::ARRAY | major_type::MAP => {
+                Err(Error::InvalidType)
+            }
+            _ => Err(Error::InvalidValue),
+        };
+    }
+
+    let argument = read_argument(reader, info)?;
+    let header = match major {
+        major_type::UNSIGNED => Header::Unsigned(argument),
+        major_type::NEGATIVE => Header::Negative(argument),
+        major_type::BYTES => Header::Bytes(reader.read_bytes(argument)?),
+        major_type::TEXT => match core::str::from_utf8(reader.read_bytes(argument)?) {
+            Ok(text) => Header::Text(text),
+            Err(_) => return Err(Error::InvalidValue),
+        },
+        major_type::ARRAY => Header::Array(checked_len(reader, argument, 1)?),
+        major_type::MAP => Header::Map(checked_len(reader, argument, 2)?),
+        _ => Header::Tag(argument),
+    };
+    Ok(header)
+}
+
+/// Skips over the next data item without decoding it.
+///
+/// Strings are still checked, so a skipped item is as well-formed as a decoded one.
+pub(crate) fn skip(reader: &mut Reader, depth: usize) -> Result<()> {
+    let header = read_header(reader)?;
+    skip_contents(reader, header, depth)
+}
+
+/// Skips over the nested items that follow an array, map or tag header.
+pub(crate) fn skip_contents(reader: &mut Reader, header: Header, depth: usize) -> Result<()> {
+    match header {
+        Header::Array(len) => {
+            let depth = nested(depth)?;
+            for _ in 0..len {
+                skip(reader, depth)?;
+            }
+        }
+        Header::Map(len) => {
+            let depth = nested(depth)?;
+            for _ in 0..len {
+                skip(reader, depth)?;
+                skip(reader, depth)?;
+            }
+        }
+        Header::Tag(_) => skip(reader, nested(depth)?)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Reads the argument that follows an initial byte with the given additional information.
+#[inline]
+pub(crate) fn read_argument(reader: &mut Reader, info: u8) -> Result<u64> {
+    match info {
+        0..=23 => Ok(info as u64),
+        24 => Ok(reader.read_byte()? as u64),
+        25 => Ok(u16::from_be_bytes(reader.read_be()?) as u64),
+        26 => Ok(u32::from_be_bytes(reader.read_be()?) as u64),
+        27 => Ok(u64::from_be_bytes(reader.read_be()?)),
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+/// Reads a major type 7 item: a simple value or a floating-point number.
+#[inline]
+pub(crate) fn read_simple<'a>(reader: &mut Reader<'a>, info: u8) -> Result<Header<'a>> {
+    match info {
+        0..=23 => Ok(Header::Simple(info)),
+        // Simple values below 32 must use the one-byte form
+        24 => match reader.read_byte()? {
+            s if s < 32 => Err(Error::InvalidValue),
+            s => Ok(Header::Simple(s)),
+        },
+        25 => Ok(Header::Float(f16_to_f64(u16::from_be_bytes(
+            reader.read_be()?,
+        )))),
+        26 => Ok(Header::Float(
+            f32::from_bits(u32::from_be_bytes(reader.read_be()?)) as f64,
+        )),
+        27 => Ok(Header::Float(f64::from_bits(u64::from_be_bytes(
+            reader.read_be()?,
+        )))),
+        // A break outside of an indefinite-length item
+        INDEFINITE => Err(Error::InvalidType),
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+/// Checks that `count` items of at least `width` bytes each can fit in the remaining input.
+#[inline]
+fn checked_len(reader: &Reader, count: u64, width: u64) -> Result<usize> {
+    let remaining = (reader.data.len() - reader.pos) as u64;
+    match count.checked_mul(width) {
+        Some(needed) if needed <= remaining => Ok(count as usize),
+        _ => Err(Error::UnexpectedEnd),
+    }
+}
+
+/// Widens an IEEE 754 half-precision float to double precision.
+#[inline]
+pub(crate) const fn f16_to_f64(bits: u16) -> f64 {
+    let sign = ((bits as u64) & 0x8000) << 48;
+    let exponent = (bits >> 10) & 0x1F;
+    let mantissa = (bits & 0x3FF) as u64;
+
+    match exponent {
+        // Subnormal numbers are mantissa * 2^-24
+        0 => {
+            let magnitude = mantissa as f64 * f64::from_bits((1023 - 24) << 52);
+            f64::from_bits(magnitude.to_bits() | sign)
+        }
+        // Infinity and NaN, keeping any NaN payload
+        0x1F => f64::from_bits(sign | (0x7FF << 52) | (mantissa << 42)),
+        // Rebias the exponent from 15 to 1023
+        _ => f64::from_bits(sign | ((exponent as u64 + 1008) << 52) | (mantissa << 42)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Arena, Value,
+        decode::{MAX_DEPTH, decode},
+        encode::{encode, encoded_size},
+        error::Error,
+    };
+
+    /// Decodes `bytes` with a small arena and checks the result against `expected`.
+    fn check(bytes: &[u8], expected: Value) {
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        assert_eq!(decode(bytes, &mut arena), Ok(expected));
+    }
+
+    /// Decodes `bytes` with a small arena and returns the error.
+    fn check_err(bytes: &[u8]) -> Error {
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        decode(bytes, &mut arena).unwrap_err()
+    }
+
+    #[test]
+    fn test_integers() {
+        check(&[0x00], Value::unsigned(0));
+        check(&[0x17], Value::unsigned(23));
+        check(&[0x18, 0x18], Value::unsigned(24));
+        check(&[0x19, 0x03, 0xE8], Value::unsigned(1000));
+        check(&[0x1A, 0x00, 0x0F, 0x42, 0x40], Value::unsigned(1_000_000));
+        check(
+            &[0x1B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            Value::unsigned(u64::MAX),
+        );
+        check(&[0x20], Value::negative(-1));
+        check(&[0x38, 0x63], Value::negative(-100));
+    }
+
+    #[test]
+    fn test_strings() {
+        check(&[0x40], Value::bytes(&[]));
+        check(&[0x43, 0x01, 0x02, 0x03], Value::bytes(&[1, 2, 3]));
+        check(&[0x60], Value::text(""));
+        check(&[0x64, 0x49, 0x45, 0x54, 0x46], Value::text("IETF"));
+        check(&[0x62, 0xC3, 0xBC], Value::text("\u{00fc}"));
+
+        // Invalid UTF-8
+        assert_eq!(check_err(&[0x62, 0xC3, 0x28]), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_simple() {
+        check(&[0xF4], Value::bool(false));
+        check(&[0xF5], Value::bool(true));
+        check(&[0xF6], Value::null());
+        check(&[0xF7], Value::Simple(23));
+        check(&[0xF0], Value::Simple(16));
+        check(&[0xF8, 0xFF], Value::Simple(255));
+
+        // Two-byte encoding of a value below 32
+        assert_eq!(check_err(&[0xF8, 0x18]), Error::InvalidValue);
+        // Reserved additional information
+        assert_eq!(check_err(&[0xFC]), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_floats() {
+        check(&[0xF9, 0x00, 0x00], Value::float(0.0));
+        check(&[0xF9, 0x3C, 0x00], Value::float(1.0));
+        check(&[0xF9, 0x3E, 0x00], Value::float(1.5));
+        check(&[0xF9, 0x7B, 0xFF], Value::float(65504.0));
+        check(&[0xF9, 0x00, 0x01], Value::float(5.960464477539063e-8));
+        check(&[0xF9, 0xC4, 0x00], Value::float(-4.0));
+        check(&[0xF9, 0x7C, 0x00], Value::float(f64::INFINITY));
+        check(&[0xF9, 0xFC, 0x00], Value::float(f64::NEG_INFINITY));
+        check(&[0xFA, 0x47, 0xC3, 0x50, 0x00], Value::float(100000.0));
+        check(
+            &[0xFB, 0x3F, 0xF1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9A],
+            Value::float(1.1),
+        );
+
+        let mut arena = Arena::new(&mut [], &mut []);
+        match decode(&[0xF9, 0x7E, 0x00], &mut arena) {
+            Ok(Value::Float(f)) => assert!(f.is_nan()),
+            other => panic!("Expected NaN, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested() {
+        let inner = [Value::unsigned(2), Value::unsigned(3)];
+        let items = [Value::unsigned(1), Value::array(&inner)];
+        check(&[0x82, 0x01, 0x82, 0x02, 0x03], Value::array(&items));
+
+        let pairs = [
+            (Value::unsigned(1), Value::unsigned(2)),
+            (Value::unsigned(3), Value::unsigned(4)),
+        ];
+        check(&[0xA2, 0x01, 0x02, 0x03, 0x04], Value::map(&pairs));
+
+        let date = Value::text("2013-03-21T20:04:00Z");
+        let mut bytes = [0u8; 22];
+        let size = encode(&Value::tag(0, &date), &mut bytes).unwrap();
+        check(&bytes[..size], Value::tag(0, &date));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let colors = [Value::text("blue"), Value::text("green")];
+        let blob = Value::bytes(&[0xDE, 0xAD]);
+        let items = [
+            (Value::text("name"), Value::text("River")),
+            (Value::text("age"), Value::negative(-7)),
+            (Value::text("colors"), Value::array(&colors)),
+            (Value::text("blob"), Value::tag(24, &blob)),
+            (Value::text("ratio"), Value::float(0.25)),
+        ];
+        let original = Value::map(&items);
+
+        let mut buf = [0u8; 64];
+        let size = encode(&original, &mut buf).unwrap();
+        assert_eq!(size, encoded_size(&original));
+
+        check(&buf[..size], original);
+    }
+
+    #[test]
+    fn test_arena_exhausted() {
+        let mut values = [Value::null(); 1];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert_eq!(
+            decode(&[0x82, 0x01, 0x02], &mut arena),
+            Err(Error::BufferOverflow)
+        );
+
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(
+            decode(&[0xA1, 0x01, 0x02], &mut arena),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(check_err(&[]), Error::UnexpectedEnd);
+        assert_eq!(check_err(&[0x19, 0x03]), Error::UnexpectedEnd);
+        assert_eq!(check_err(&[0x64, 0x49, 0x45]), Error::UnexpectedEnd);
+        assert_eq!(check_err(&[0x82, 0x01]), Error::UnexpectedEnd);
+        assert_eq!(check_err(&[0xC0]), Error::UnexpectedEnd);
+
+        // A huge count is rejected before any storage is claimed
+        assert_eq!(
+            check_err(&[0x9B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            Error::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // Tags nested exactly MAX_DEPTH deep around a single integer
+        let mut bytes = [0xC1u8; MAX_DEPTH + 2];
+        bytes[MAX_DEPTH] = 0x00;
+
+        let mut values = [Value::null(); MAX_DEPTH];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert!(decode(&bytes[..MAX_DEPTH + 1], &mut arena).is_ok());
+
+        // One more level is rejected
+        bytes[MAX_DEPTH] = 0xC1;
+        bytes[MAX_DEPTH + 1] = 0x00;
+        let mut values = [Value::null(); MAX_DEPTH + 1];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert_eq!(decode(&bytes, &mut arena), Err(Error::DepthExceeded));
+    }
+
+    #[test]
+    fn test_tag_content() {
+        // 100(-10676) and 1004("1940-10-09")
+        check(
+            &[0xD8, 0x64, 0x39, 0x29, 0xB3],
+            Value::tag(100, &Value::negative(-10676)),
+        );
+        check(
+            &[
+                0xD9, 0x03, 0xEC, 0x6A, 0x31, 0x39, 0x34, 0x30, 0x2D, 0x31, 0x30, 0x2D, 0x30, 0x39,
+            ],
+            Value::tag(1004, &Value::text("1940-10-09")),
+        );
+
+        // 1004("garbage") and 100(h'00')
+        assert_eq!(
+            check_err(&[
+                0xD9, 0x03, 0xEC, 0x67, 0x67, 0x61, 0x72, 0x62, 0x61, 0x67, 0x65
+            ]),
+            Error::InvalidValue
+        );
+        assert_eq!(check_err(&[0xD8, 0x64, 0x41, 0x00]), Error::InvalidType);
+    }
+
+    #[test]
+    fn test_malformed() {
+        // Trailing bytes after the data item
+        assert_eq!(check_err(&[0x01, 0x02]), Error::InvalidValue);
+        // Reserved additional information
+        assert_eq!(check_err(&[0x1C]), Error::InvalidValue);
+        // Indefinite-length integer
+        assert_eq!(check_err(&[0x1F]), Error::InvalidValue);
+        // Indefinite-length strings and containers are not supported
+        assert_eq!(check_err(&[0x5F, 0x41, 0x01, 0xFF]), Error::InvalidType);
+        assert_eq!(check_err(&[0x9F, 0x01, 0xFF]), Error::InvalidType);
+        // Lone break
+        assert_eq!(check_err(&[0xFF]), Error::InvalidType);
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+
+//! Reader implementation for consuming bytes from a buffer.
+//!
+//! This module provides a `Reader` type that walks an encoded byte slice, splitting off
+//! item headers and payloads while tracking the position and detecting truncated input.
+
+use crate::{error::Error, result::Result};
+
+/// A reader for consuming bytes from a buffer with position tracking.
+///
+/// This struct maintains a reference to an encoded byte slice and tracks the current
+/// position within that slice. Payloads are returned as sub-slices of the input, so
+/// decoded strings borrow from the original buffer.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Reader<'a> {
+    /// The underlying byte buffer being read.
+    pub(crate) data: &'a [u8],
+
+    /// The current position in the buffer.
+    pub(crate) pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new reader positioned at the start of the provided buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The byte slice to read from.
+    #[inline]
+    pub(crate) const fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    /// Returns `true` if every byte of the buffer has been consumed.
+    #[inline]
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Reads a single byte at the current position and advances the reader.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u8)` if a byte was available.
+    /// * `Err(Error::UnexpectedEnd)` if the buffer is exhausted.
+    #[inline]
+    pub(crate) const fn read_byte(&mut self) -> Result<u8> {
+        if self.pos < self.data.len() {
+            let byte = self.data[self.pos];
+            self.pos += 1;
+            Ok(byte)
+        } else {
+            Err(Error::UnexpectedEnd)
+        }
+    }
+
+    /// Reads `len` bytes at the current position and advances the reader.
+    ///
+    /// Nothing is consumed if fewer than `len` bytes remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of bytes to read.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - The bytes, borrowed from the underlying buffer.
+    /// * `Err(Error::UnexpectedEnd)` if the remaining input is too short.
+    #[inline]
+    pub(crate) fn read_bytes(&mut self, len: u64) -> Result<&'a [u8]> {
+        let remaining = self.data.len() - self.pos;
+        if len > remaining as u64 {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        let start = self.pos;
+        self.pos += len as usize;
+        Ok(&self.data[start..self.pos])
+    }
+
+    /// Reads a big-endian unsigned integer of `N` bytes.
+    #[inline]
+    pub(crate) fn read_be<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.read_bytes(N as u64)?);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reader;
+    use crate::error::Error;
+
+    #[test]
+    fn test_read_bytes() {
+        let data = [0x01, 0x02, 0x03];
+        let mut reader = Reader::new(&data);
+
+        assert_eq!(reader.read_byte(), Ok(0x01));
+        assert_eq!(reader.read_bytes(3), Err(Error::UnexpectedEnd));
+        assert_eq!(reader.pos, 1);
+        assert_eq!(reader.read_bytes(2), Ok(&data[1..]));
+        assert!(reader.is_empty());
+        assert_eq!(reader.read_byte(), Err(Error::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_read_oversized_length() {
+        // Lengths that do not fit in usize must not wrap around
+        let data = [0u8; 4];
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_bytes(u64::MAX), Err(Error::UnexpectedEnd));
+        assert_eq!(reader.read_be::<4>(), Ok([0u8; 4]));
+    }
+}
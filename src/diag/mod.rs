@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+
+//! CBOR diagnostic notation.
+//!
+//! [RFC 8949 section 8](https://www.rfc-editor.org/rfc/rfc8949#section-8) defines a
+//! human-readable text form for CBOR data items, extended by the Extended Diagnostic
+//! Notation (EDN) of [RFC 8610 appendix G](https://www.rfc-editor.org/rfc/rfc8610#appendix-G).
+//! A [`Value`](crate::Value) prints in this notation through its `Display`
+//! implementation, and [`write_encoded`] prints encoded bytes along with encoding
+//! indicators that show how each item was serialized. Both write into any
+//! `core::fmt::Write` without allocating.
+//!
+//! In the other direction, [`parse`] reads diagnostic notation into a `Value`, storing
+//! nested items in an [`Arena`](crate::Arena) as the decoder does.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{Value, diag::write_encoded};
+//! use core::fmt::Write;
+//!
+//! let items = [Value::unsigned(1), Value::bytes(&[0x01, 0x02])];
+//! let pairs = [(Value::text("a"), Value::array(&items))];
+//!
+//! let mut text = String::new();
+//! write!(text, "{}", Value::map(&pairs)).unwrap();
+//! assert_eq!(text, r#"{"a": [1, h'0102']}"#);
+//!
+//! // The encoded form shows that 1.5 was written as a half-precision float
+//! let mut text = String::new();
+//! write_encoded(&[0xA1, 0x61, 0x66, 0xF9, 0x3E, 0x00], &mut text).unwrap();
+//! assert_eq!(text, r#"{"f": 1.5_1}"#);
+//! ```
+
+mod parse;
+mod print;
+
+pub use parse::*;
+pub use print::*;
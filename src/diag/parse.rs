@@ -0,0 +1,809 @@
+// SPDX-License-Identifier: MIT
+
+//! Parsing diagnostic notation into a `Value`.
+
+use crate::{
+    Arena, Value,
+    decode::{self, MAX_DEPTH},
+    error::Error,
+    result::Result,
+};
+
+/// Parses a single data item written in diagnostic notation.
+///
+/// Text strings without escapes and single-quoted byte strings without escapes borrow
+/// from `text`. Every other string, such as a hex or base64 literal, is decoded into
+/// `bytes`. Array items, map entries and tagged items are stored in `arena` as for
+/// [`decode`](crate::decode::decode).
+///
+/// The accepted syntax is that of RFC 8949 section 8 and RFC 8610 appendix G:
+///
+/// * Integers in decimal, or with a `0x`, `0o` or `0b` prefix, and floats including
+///   `Infinity`, `-Infinity` and `NaN`.
+/// * Text strings in double quotes with JSON escapes, and byte strings as `'text'`,
+///   `h'hex'`, `b64'base64'`, `b32'base32'` or `h32'base32hex'`.
+/// * Arrays, maps, tags such as `1(1700000000)`, `false`, `true`, `null`, `undefined`
+///   and `simple(n)`.
+/// * Indefinite-length forms such as `[_ 1, 2]`, `{_ "a": 1}` or `(_ h'01', h'02')`,
+///   which produce the equivalent definite-length value.
+/// * Encoding indicators such as `1_0` or `1.5_1`, which are accepted and ignored.
+/// * Comments between slashes, `/ like this /`, or from `#` to the end of the line.
+///
+/// # Arguments
+///
+/// * `text` - The diagnostic notation of exactly one data item.
+/// * `bytes` - Storage for decoded string contents.
+/// * `arena` - Storage for the nested items of the parsed tree.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The parsed value.
+/// * `Err(Error::BufferOverflow)` - If `bytes` or `arena` is too small.
+/// * `Err(Error::UnexpectedEnd)` - If the text ends inside an item.
+/// * `Err(Error::DepthExceeded)` - If items nest more deeply than
+///   [`MAX_DEPTH`](crate::decode::MAX_DEPTH).
+/// * `Err(Error::InvalidValue)` - If the text is malformed or has trailing content.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, Value, diag::parse};
+///
+/// let mut bytes = [0u8; 8];
+/// let mut values = [Value::null(); 4];
+/// let mut pairs = [(Value::null(), Value::null()); 2];
+/// let mut arena = Arena::new(&mut values, &mut pairs);
+///
+/// let value = parse(
+///     r#"{"a": [1, h'0102', 1(1700000000)], / half / "f": 1.5_1}"#,
+///     &mut bytes,
+///     &mut arena,
+/// )
+/// .unwrap();
+/// assert_eq!(value.to_string(), r#"{"a": [1, h'0102', 1(1700000000)], "f": 1.5}"#);
+/// ```
+pub fn parse<'a>(text: &'a str, bytes: &'a mut [u8], arena: &mut Arena<'a>) -> Result<Value<'a>> {
+    let mut parser = Parser {
+        lexer: Lexer { text, pos: 0 },
+        scratch: bytes,
+        len: 0,
+        arena,
+    };
+    let value = parser.item(MAX_DEPTH)?;
+    if parser.lexer.is_end()? {
+        Ok(value)
+    } else {
+        Err(Error::InvalidValue)
+    }
+}
+
+/// A single token of diagnostic notation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// One of `[]{}(),:`.
+    Punct(u8),
+    /// A number, keyword or encoding indicator.
+    Word(&'a str),
+    /// The contents of a double-quoted string.
+    Text { body: &'a str, escaped: bool },
+    /// The prefix and contents of a single-quoted string, such as `h'0102'`.
+    Bytes {
+        prefix: &'a str,
+        body: &'a str,
+        escaped: bool,
+    },
+}
+
+/// Splits diagnostic notation into tokens, skipping whitespace and comments.
+#[derive(Debug, Clone, Copy)]
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Returns the byte at the current position, if any.
+    #[inline]
+    fn byte(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    /// Advances past whitespace and comments.
+    fn skip_space(&mut self) -> Result<()> {
+        while let Some(byte) = self.byte() {
+            match byte {
+                b'/' => match self.text[self.pos + 1..].find('/') {
+                    Some(end) => self.pos += end + 2,
+                    None => return Err(Error::UnexpectedEnd),
+                },
+                b'#' => match self.text[self.pos..].find('\n') {
+                    Some(end) => self.pos += end + 1,
+                    None => self.pos = self.text.len(),
+                },
+                byte if byte.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if only whitespace and comments remain.
+    fn is_end(&mut self) -> Result<bool> {
+        self.skip_space()?;
+        Ok(self.pos == self.text.len())
+    }
+
+    /// Returns the next token without consuming it.
+    #[inline]
+    fn peek(&self) -> Result<Token<'a>> {
+        let mut lexer = *self;
+        lexer.next()
+    }
+
+    /// Consumes and returns the next token.
+    fn next(&mut self) -> Result<Token<'a>> {
+        self.skip_space()?;
+        let byte = self.byte().ok_or(Error::UnexpectedEnd)?;
+        match byte {
+            b'[' | b']' | b'{' | b'}' | b'(' | b')' | b',' | b':' => {
+                self.pos += 1;
+                Ok(Token::Punct(byte))
+            }
+            b'"' => {
+                let (body, escaped) = self.quoted(b'"')?;
+                Ok(Token::Text { body, escaped })
+            }
+            b'\'' => {
+                let (body, escaped) = self.quoted(b'\'')?;
+                Ok(Token::Bytes {
+                    prefix: "",
+                    body,
+                    escaped,
+                })
+            }
+            byte if is_word(byte) => {
+                let start = self.pos;
+                while self.byte().is_some_and(is_word) {
+                    self.pos += 1;
+                }
+                let word = &self.text[start..self.pos];
+                if self.byte() == Some(b'\'') {
+                    let (body, escaped) = self.quoted(b'\'')?;
+                    Ok(Token::Bytes {
+                        prefix: word,
+                        body,
+                        escaped,
+                    })
+                } else {
+                    Ok(Token::Word(word))
+                }
+            }
+            _ => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Consumes a quoted string starting at the current position, along with any
+    /// encoding indicator that follows it.
+    ///
+    /// Returns the contents between the quotes, and whether they contain escapes.
+    fn quoted(&mut self, quote: u8) -> Result<(&'a str, bool)> {
+        let start = self.pos + 1;
+        let mut end = start;
+        let mut escaped = false;
+        loop {
+            match self.text.as_bytes().get(end) {
+                Some(b'\\') => {
+                    escaped = true;
+                    end += 2;
+                }
+                Some(&byte) if byte == quote => break,
+                Some(_) => end += 1,
+                None => return Err(Error::UnexpectedEnd),
+            }
+        }
+
+        self.pos = end + 1;
+        if self.byte() == Some(b'_') {
+            self.pos += 1;
+            if matches!(self.byte(), Some(b'0'..=b'3')) {
+                self.pos += 1;
+            }
+        }
+        Ok((&self.text[start..end], escaped))
+    }
+
+    /// Counts the items of the array or map whose opening bracket was just consumed.
+    ///
+    /// This only scans ahead, so a later parse still reports any syntax errors.
+    fn count(mut self) -> Result<usize> {
+        let mut depth = 0usize;
+        let mut count = 0;
+        loop {
+            match self.next()? {
+                Token::Punct(b']' | b'}' | b')') if depth == 0 => return Ok(count),
+                Token::Punct(b']' | b'}' | b')') => depth -= 1,
+                Token::Punct(b',') if depth == 0 => count += 1,
+                token => {
+                    if depth == 0 && count == 0 {
+                        count = 1;
+                    }
+                    if matches!(token, Token::Punct(b'[' | b'{' | b'(')) {
+                        depth += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` for bytes that make up numbers, keywords and encoding indicators.
+#[inline]
+const fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'+' | b'-' | b'_')
+}
+
+/// Builds a `Value` tree from tokens.
+struct Parser<'a, 'b> {
+    lexer: Lexer<'a>,
+    /// Unused storage for string contents.
+    scratch: &'a mut [u8],
+    /// The length of the string being written at the start of `scratch`.
+    len: usize,
+    arena: &'b mut Arena<'a>,
+}
+
+impl<'a> Parser<'a, '_> {
+    /// Parses one data item.
+    fn item(&mut self, depth: usize) -> Result<Value<'a>> {
+        match self.lexer.next()? {
+            Token::Punct(b'[') => {
+                let depth = decode::nested(depth)?;
+                self.indicator()?;
+                let items = self.arena.alloc_values(self.lexer.count()?)?;
+                for (i, item) in items.iter_mut().enumerate() {
+                    if i > 0 {
+                        self.expect(b',')?;
+                    }
+                    *item = self.item(depth)?;
+                }
+                self.expect(b']')?;
+                Ok(Value::Array(items))
+            }
+            Token::Punct(b'{') => {
+                let depth = decode::nested(depth)?;
+                self.indicator()?;
+                let pairs = self.arena.alloc_pairs(self.lexer.count()?)?;
+                for (i, (key, value)) in pairs.iter_mut().enumerate() {
+                    if i > 0 {
+                        self.expect(b',')?;
+                    }
+                    *key = self.item(depth)?;
+                    self.expect(b':')?;
+                    *value = self.item(depth)?;
+                }
+                self.expect(b'}')?;
+                Ok(Value::Map(pairs))
+            }
+            Token::Punct(b'(') => self.chunks(),
+            Token::Punct(_) => Err(Error::InvalidValue),
+            Token::Word(word) => self.word(word, depth),
+            Token::Text {
+                body,
+                escaped: false,
+            } => Ok(Value::Text(body)),
+            Token::Bytes {
+                prefix: "",
+                body,
+                escaped: false,
+            } => Ok(Value::Bytes(body.as_bytes())),
+            token => {
+                self.write_string(token)?;
+                match token {
+                    Token::Text { .. } => self.take_text().map(Value::Text),
+                    _ => Ok(Value::Bytes(self.take())),
+                }
+            }
+        }
+    }
+
+    /// Parses a number, keyword, tag or `simple(n)`.
+    fn word(&mut self, word: &'a str, depth: usize) -> Result<Value<'a>> {
+        if self.lexer.peek() == Ok(Token::Punct(b'(')) {
+            let _ = self.lexer.next()?;
+            let value = if word == "simple" {
+                let value = match self.lexer.next()? {
+                    Token::Word(number) => number.parse::<u8>().ok(),
+                    _ => None,
+                };
+                match value {
+                    Some(value) if !(24..32).contains(&value) => Value::Simple(value),
+                    _ => return Err(Error::InvalidValue),
+                }
+            } else {
+                let tag = match number(strip_indicator(word)?)? {
+                    Value::Unsigned(tag) => tag,
+                    _ => return Err(Error::InvalidValue),
+                };
+                let item = self.item(decode::nested(depth)?)?;
+                Value::Tag(tag, self.arena.alloc(item)?)
+            };
+            self.expect(b')')?;
+            return Ok(value);
+        }
+
+        match word {
+            "false" => Ok(Value::Simple(20)),
+            "true" => Ok(Value::Simple(21)),
+            "null" => Ok(Value::Simple(22)),
+            "undefined" => Ok(Value::Simple(23)),
+            word => number(strip_indicator(word)?),
+        }
+    }
+
+    /// Parses the chunks of an indefinite-length string after its opening parenthesis,
+    /// joining them into a single string.
+    fn chunks(&mut self) -> Result<Value<'a>> {
+        if self.lexer.next()? != Token::Word("_") {
+            return Err(Error::InvalidValue);
+        }
+
+        let first = self.lexer.next()?;
+        let is_text = matches!(first, Token::Text { .. });
+        let mut chunk = first;
+        loop {
+            if matches!(chunk, Token::Text { .. }) != is_text {
+                return Err(Error::InvalidValue);
+            }
+            self.write_string(chunk)?;
+            match self.lexer.next()? {
+                Token::Punct(b',') => chunk = self.lexer.next()?,
+                Token::Punct(b')') => break,
+                _ => return Err(Error::InvalidValue),
+            }
+        }
+
+        if is_text {
+            self.take_text().map(Value::Text)
+        } else {
+            Ok(Value::Bytes(self.take()))
+        }
+    }
+
+    /// Consumes an optional encoding indicator after an opening bracket.
+    fn indicator(&mut self) -> Result<()> {
+        if let Ok(Token::Word(word)) = self.lexer.peek() {
+            if matches!(word, "_" | "_0" | "_1" | "_2" | "_3") {
+                let _ = self.lexer.next()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Consumes the given punctuation.
+    fn expect(&mut self, punct: u8) -> Result<()> {
+        if self.lexer.next()? == Token::Punct(punct) {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    /// Appends a byte to the string being written.
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<()> {
+        let slot = self
+            .scratch
+            .get_mut(self.len)
+            .ok_or(Error::BufferOverflow)?;
+        *slot = byte;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Splits the string written so far off the scratch storage.
+    fn take(&mut self) -> &'a [u8] {
+        let (head, tail) = core::mem::take(&mut self.scratch).split_at_mut(self.len);
+        self.scratch = tail;
+        self.len = 0;
+        head
+    }
+
+    /// Splits the text written so far off the scratch storage.
+    fn take_text(&mut self) -> Result<&'a str> {
+        core::str::from_utf8(self.take()).map_err(|_| Error::InvalidValue)
+    }
+
+    /// Appends the decoded contents of a string token to the string being written.
+    fn write_string(&mut self, token: Token<'a>) -> Result<()> {
+        match token {
+            Token::Text { body, .. } => self.write_escaped(body),
+            Token::Bytes {
+                prefix: "", body, ..
+            } => self.write_escaped(body),
+            Token::Bytes {
+                prefix: "h", body, ..
+            } => self.write_radix(body, 4, |byte| (byte as char).to_digit(16)),
+            Token::Bytes {
+                prefix: "b64",
+                body,
+                ..
+            } => self.write_radix(body, 6, base64_digit),
+            Token::Bytes {
+                prefix: "b32",
+                body,
+                ..
+            } => self.write_radix(body, 5, |byte| match byte.to_ascii_uppercase() {
+                byte @ b'A'..=b'Z' => Some(u32::from(byte - b'A')),
+                byte @ b'2'..=b'7' => Some(u32::from(byte - b'2') + 26),
+                _ => None,
+            }),
+            Token::Bytes {
+                prefix: "h32",
+                body,
+                ..
+            } => self.write_radix(body, 5, |byte| (byte as char).to_digit(32)),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Appends the contents of a quoted string, resolving JSON escapes.
+    fn write_escaped(&mut self, body: &str) -> Result<()> {
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            let c = if c == '\\' {
+                match chars.next() {
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let unit = hex4(&mut chars)?;
+                        let code = if (0xD800..0xDC00).contains(&unit) {
+                            if chars.next() != Some('\\') || chars.next() != Some('u') {
+                                return Err(Error::InvalidValue);
+                            }
+                            let low = hex4(&mut chars)?;
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(Error::InvalidValue);
+                            }
+                            0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                        } else {
+                            unit
+                        };
+                        char::from_u32(code).ok_or(Error::InvalidValue)?
+                    }
+                    Some(c @ ('"' | '\'' | '\\' | '/')) => c,
+                    _ => return Err(Error::InvalidValue),
+                }
+            } else {
+                c
+            };
+
+            let mut buf = [0u8; 4];
+            for &byte in c.encode_utf8(&mut buf).as_bytes() {
+                self.push(byte)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the bytes of a hex, base32 or base64 literal.
+    ///
+    /// Whitespace is ignored, as are comments in hex literals. Trailing `=` padding is
+    /// optional, but any unused low-order bits must be zero.
+    fn write_radix(&mut self, body: &str, bits: u32, digit: fn(u8) -> Option<u32>) -> Result<()> {
+        let mut acc = 0u32;
+        let mut held = 0u32;
+        let mut padding = false;
+        let mut comment = false;
+        for byte in body.bytes() {
+            match byte {
+                _ if comment => comment = byte != b'/',
+                b'/' if bits == 4 => comment = true,
+                byte if byte.is_ascii_whitespace() => {}
+                b'=' if bits != 4 => padding = true,
+                _ if padding => return Err(Error::InvalidValue),
+                byte => {
+                    acc = (acc << bits) | digit(byte).ok_or(Error::InvalidValue)?;
+                    held += bits;
+                    if held >= 8 {
+                        held -= 8;
+                        self.push((acc >> held) as u8)?;
+                        acc &= (1 << held) - 1;
+                    }
+                }
+            }
+        }
+
+        if comment || held >= bits || acc != 0 {
+            Err(Error::InvalidValue)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns the value of a base64 or base64url digit.
+const fn base64_digit(byte: u8) -> Option<u32> {
+    let value = match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' | b'-' => 62,
+        b'/' | b'_' => 63,
+        _ => return None,
+    };
+    Some(value as u32)
+}
+
+/// Reads the four hex digits of a `\u` escape.
+fn hex4(chars: &mut core::str::Chars) -> Result<u32> {
+    let mut value = 0;
+    for _ in 0..4 {
+        let digit = chars.next().and_then(|c| c.to_digit(16));
+        value = (value << 4) | digit.ok_or(Error::InvalidValue)?;
+    }
+    Ok(value)
+}
+
+/// Removes an encoding indicator such as `_0` from the end of a number.
+fn strip_indicator(word: &str) -> Result<&str> {
+    match word.split_once('_') {
+        Some((number, "0" | "1" | "2" | "3")) if !number.is_empty() => Ok(number),
+        Some(_) => Err(Error::InvalidValue),
+        None => Ok(word),
+    }
+}
+
+/// Parses an integer or float.
+fn number(word: &str) -> Result<Value<'_>> {
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word),
+    };
+
+    match digits {
+        "Infinity" if negative => return Ok(Value::Float(f64::NEG_INFINITY)),
+        "Infinity" => return Ok(Value::Float(f64::INFINITY)),
+        "NaN" if !negative => return Ok(Value::Float(f64::NAN)),
+        _ => {}
+    }
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(Error::InvalidValue);
+    }
+
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ if digits.contains(['.', 'e', 'E']) => {
+            return word
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|_| Error::InvalidValue);
+        }
+        _ => (10, digits),
+    };
+    if !digits.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(Error::InvalidValue);
+    }
+
+    let magnitude = u128::from_str_radix(digits, radix).map_err(|_| Error::InvalidValue)?;
+    match (negative, magnitude) {
+        (_, 0) => Ok(Value::Unsigned(0)),
+        (true, magnitude) => u64::try_from(magnitude - 1)
+            .map(Value::Negative)
+            .map_err(|_| Error::InvalidValue),
+        (false, magnitude) => u64::try_from(magnitude)
+            .map(Value::Unsigned)
+            .map_err(|_| Error::InvalidValue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{Arena, Value, error::Error};
+
+    /// Parses `text` with plenty of storage, returning the value's diagnostic notation.
+    fn check(text: &str, expected: &str) {
+        let mut bytes = [0u8; 64];
+        let mut values = [Value::null(); 16];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+
+        let value = parse(text, &mut bytes, &mut arena).unwrap();
+        let mut out = [0u8; 128];
+        let mut cursor = Cursor(&mut out, 0);
+        core::fmt::Write::write_fmt(&mut cursor, format_args!("{}", value)).unwrap();
+        assert_eq!(cursor.as_str(), expected, "parsing {}", text);
+    }
+
+    fn error(text: &str) -> Error {
+        let mut bytes = [0u8; 16];
+        let mut values = [Value::null(); 4];
+        let mut pairs = [(Value::null(), Value::null()); 2];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        parse(text, &mut bytes, &mut arena).unwrap_err()
+    }
+
+    /// A fixed buffer for formatting without allocation.
+    struct Cursor<'a>(&'a mut [u8], usize);
+
+    impl Cursor<'_> {
+        fn as_str(&self) -> &str {
+            core::str::from_utf8(&self.0[..self.1]).unwrap()
+        }
+    }
+
+    impl core::fmt::Write for Cursor<'_> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let end = self.1 + s.len();
+            self.0
+                .get_mut(self.1..end)
+                .ok_or(core::fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.1 = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        check("0", "0");
+        check("18446744073709551615", "18446744073709551615");
+        check("-18446744073709551616", "-18446744073709551616");
+        check("-0", "0");
+        check("0x1F", "31");
+        check("-0b101", "-5");
+        check("0o17_1", "15");
+        check("1.5", "1.5");
+        check("-4.1e-3_3", "-0.0041");
+        check("1e3", "1000.0");
+        check("Infinity", "Infinity");
+        check("-Infinity_1", "-Infinity");
+        check("NaN", "NaN");
+
+        assert_eq!(error("18446744073709551616"), Error::InvalidValue);
+        assert_eq!(error("-18446744073709551617"), Error::InvalidValue);
+        assert_eq!(error("+1"), Error::InvalidValue);
+        assert_eq!(error("inf"), Error::InvalidValue);
+        assert_eq!(error("1_4"), Error::InvalidValue);
+        assert_eq!(error("0x"), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_parse_strings() {
+        check(r#""""#, r#""""#);
+        check(r#""hello""#, r#""hello""#);
+        check(r#""a\"\\\/\nü𝄞""#, "\"a\\\"\\\\/\\n\u{fc}\u{1d11e}\"");
+        check("'abc'", "h'616263'");
+        check(r"'it\'s'", "h'69742773'");
+        check("h''", "h''");
+        check("h'01 02 / comment / ff'", "h'0102ff'");
+        check("b64'AQID'", "h'010203'");
+        check("b64'AQI='", "h'0102'");
+        check("b64'-_8'", "h'fbff'");
+        check("b32'MZXW6==='", "h'666f6f'");
+        check("h32'CPNMU'", "h'666f6f'");
+        check("\"a\"_0", "\"a\"");
+
+        assert_eq!(error("h'0'"), Error::InvalidValue);
+        assert_eq!(error("h'0g'"), Error::InvalidValue);
+        assert_eq!(error("b64'AQJ'"), Error::InvalidValue);
+        assert_eq!(error("x'00'"), Error::InvalidValue);
+        assert_eq!(error(r#""\q""#), Error::InvalidValue);
+        assert_eq!(error(r#""\ud834""#), Error::InvalidValue);
+        assert_eq!(error(r#""abc"#), Error::UnexpectedEnd);
+    }
+
+    #[test]
+    fn test_parse_simple_and_tags() {
+        check(
+            "[false, true, null, undefined]",
+            "[false, true, null, undefined]",
+        );
+        check("simple(16)", "simple(16)");
+        check("simple(255)", "simple(255)");
+        check("0(\"2013-03-21T20:04:00Z\")", "0(\"2013-03-21T20:04:00Z\")");
+        check("24_0(h'6449455446')", "24(h'6449455446')");
+        check("1(2(3))", "1(2(3))");
+
+        assert_eq!(error("simple(24)"), Error::InvalidValue);
+        assert_eq!(error("simple(256)"), Error::InvalidValue);
+        assert_eq!(error("-1(0)"), Error::InvalidValue);
+        assert_eq!(error("1(2, 3)"), Error::InvalidValue);
+        assert_eq!(error("nil"), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        check("[]", "[]");
+        check("{}", "{}");
+        check("[1, [2, 3], [4, 5]]", "[1, [2, 3], [4, 5]]");
+        check(
+            r#"{"a": 1, "b": [2, 3], 4: {"c": h'ff'}}"#,
+            r#"{"a": 1, "b": [2, 3], 4: {"c": h'ff'}}"#,
+        );
+        check("[_0 1, 2]", "[1, 2]");
+
+        assert_eq!(error("[1, 2"), Error::UnexpectedEnd);
+        assert_eq!(error("[1 2]"), Error::InvalidValue);
+        assert_eq!(error("[1, ]"), Error::InvalidValue);
+        assert_eq!(error("{1, 2}"), Error::InvalidValue);
+        assert_eq!(error("[1}"), Error::InvalidValue);
+        assert_eq!(error("1 2"), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_parse_indefinite() {
+        check("(_ h'0102', h'030405')", "h'0102030405'");
+        check(r#"(_ "strea", "ming")"#, r#""streaming""#);
+        check("''_", "h''");
+        check(r#"""_"#, r#""""#);
+        check("[_ ]", "[]");
+        check("[_ 1, [2, 3], [_ 4, 5]]", "[1, [2, 3], [4, 5]]");
+        check(
+            r#"{_ "Fun": true, "Amt": -2}"#,
+            r#"{"Fun": true, "Amt": -2}"#,
+        );
+        check(r#"["a", {_ "b": "c"}]"#, r#"["a", {"b": "c"}]"#);
+
+        assert_eq!(error("(_ )"), Error::InvalidValue);
+        assert_eq!(error("(_ h'01', \"a\")"), Error::InvalidValue);
+        assert_eq!(error("(h'01')"), Error::InvalidValue);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        check(
+            "/ leading / [1, # to end of line\n 2 / inline /] # trailing",
+            "[1, 2]",
+        );
+        assert_eq!(error("[1 / unterminated"), Error::UnexpectedEnd);
+        assert_eq!(error("# nothing"), Error::UnexpectedEnd);
+    }
+
+    #[test]
+    fn test_parse_storage() {
+        // Escaped and encoded strings need byte storage, plain ones borrow from the text
+        let mut values = [Value::null(); 2];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert_eq!(
+            parse(r#"["abc", 'def']"#, &mut [], &mut arena),
+            Ok(Value::array(&[Value::text("abc"), Value::bytes(b"def")]))
+        );
+
+        let mut bytes = [0u8; 2];
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(
+            parse("h'010203'", &mut bytes, &mut arena),
+            Err(Error::BufferOverflow)
+        );
+
+        let mut values = [Value::null(); 2];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert_eq!(
+            parse("[1, 2, 3]", &mut [], &mut arena),
+            Err(Error::BufferOverflow)
+        );
+
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(
+            parse("{1: 2}", &mut [], &mut arena),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_parse_depth_limit() {
+        // Balanced brackets one level deeper than the limit
+        let mut text = [b']'; 2 * (crate::decode::MAX_DEPTH + 1)];
+        text[..crate::decode::MAX_DEPTH + 1].fill(b'[');
+        let text = core::str::from_utf8(&text).unwrap();
+        let mut values = [Value::null(); crate::decode::MAX_DEPTH + 1];
+        let mut arena = Arena::new(&mut values, &mut []);
+        assert_eq!(parse(text, &mut [], &mut arena), Err(Error::DepthExceeded));
+    }
+}
@@ -0,0 +1,531 @@
+// SPDX-License-Identifier: MIT
+
+//! Diagnostic notation printing for values and encoded bytes.
+
+use core::fmt::{self, Write};
+
+use crate::{
+    Value,
+    decode::{self, Header, MAX_DEPTH, Reader},
+    encode::{
+        self,
+        major_type::{self, MajorType},
+    },
+    error::Error,
+    result::Result,
+};
+
+/// The additional information value marking an indefinite length or a break.
+const INDEFINITE: u8 = 31;
+/// The "break" stop code that ends an indefinite-length item.
+const BREAK: u8 = 0xFF;
+
+impl fmt::Display for Value<'_> {
+    /// Formats the value in diagnostic notation.
+    ///
+    /// No encoding indicators are printed, since a `Value` is always encoded in its
+    /// preferred serialization, apart from floats which are encoded in double precision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let time = Value::unsigned(1700000000);
+    /// let items = [Value::negative(-2), Value::tag(1, &time), Value::float(1.5)];
+    /// assert_eq!(Value::array(&items).to_string(), "[-2, 1(1700000000), 1.5]");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unsigned(n) => write!(f, "{}", n),
+            Value::Negative(n) => write!(f, "{}", -1 - *n as i128),
+            Value::Bytes(bytes) => write_bytes(f, bytes),
+            Value::Text(text) => write_text(f, text),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Value::Map(pairs) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                f.write_char('}')
+            }
+            Value::Tag(tag, item) => write!(f, "{}({})", tag, item),
+            Value::Simple(s) => write_simple(f, *s),
+            Value::Float(x) => write_float(f, *x),
+        }
+    }
+}
+
+/// Writes encoded CBOR bytes in diagnostic notation, with encoding indicators.
+///
+/// Unlike the `Display` implementation of [`Value`], this shows how each item was
+/// serialized:
+///
+/// * Floats always carry their width: `_1` for half, `_2` for single and `_3` for double
+///   precision.
+/// * Integers, lengths and tags carry `_0` to `_3` when their argument uses a longer
+///   encoding than necessary.
+/// * Indefinite-length items are marked with `_`, such as `[_ 1, 2]` or
+///   `(_ h'01', h'02')` for a chunked byte string.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded data item.
+/// * `out` - The writer to print into.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the whole input was printed.
+/// * `Err(Error::BufferOverflow)` - If the writer failed, such as by running out of space.
+/// * `Err(Error)` - If the input is not a single well-formed data item, as for
+///   [`decode`](crate::decode::decode).
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::diag::write_encoded;
+///
+/// let mut text = String::new();
+/// write_encoded(&[0x9F, 0x18, 0x01, 0xFA, 0x3F, 0xC0, 0x00, 0x00, 0xFF], &mut text).unwrap();
+/// assert_eq!(text, "[_ 1_0, 1.5_2]");
+/// ```
+pub fn write_encoded<W: Write>(bytes: &[u8], out: &mut W) -> Result<()> {
+    let mut reader = Reader::new(bytes);
+    write_item(&mut reader, out, MAX_DEPTH)?;
+    decode::finish(&reader)
+}
+
+/// Maps a writer failure onto the error reported by [`write_encoded`].
+#[inline]
+fn out_of_space(_: fmt::Error) -> Error {
+    Error::BufferOverflow
+}
+
+/// Writes one encoded data item.
+fn write_item<W: Write>(reader: &mut Reader, out: &mut W, depth: usize) -> Result<()> {
+    let initial = reader.read_byte()?;
+    let major: MajorType = initial >> 5;
+    let info = initial & 0x1F;
+
+    if major == major_type::SIMPLE {
+        return match decode::read_simple(reader, info)? {
+            Header::Float(x) => {
+                write_float(out, x).map_err(out_of_space)?;
+                write!(out, "_{}", info - 25 + 1).map_err(out_of_space)
+            }
+            Header::Simple(s) => write_simple(out, s).map_err(out_of_space),
+            _ => Err(Error::InvalidValue),
+        };
+    }
+
+    if info == INDEFINITE {
+        return write_indefinite(reader, out, major, depth);
+    }
+
+    let argument = decode::read_argument(reader, info)?;
+    let indicator = Indicator {
+        info,
+        major,
+        argument,
+    };
+
+    match major {
+        major_type::UNSIGNED => write!(out, "{}{}", argument, indicator),
+        major_type::NEGATIVE => write!(out, "{}{}", -1 - argument as i128, indicator),
+        major_type::BYTES => {
+            let bytes = reader.read_bytes(argument)?;
+            write_bytes(out, bytes).and_then(|_| write!(out, "{}", indicator))
+        }
+        major_type::TEXT => {
+            let text = match core::str::from_utf8(reader.read_bytes(argument)?) {
+                Ok(text) => text,
+                Err(_) => return Err(Error::InvalidValue),
+            };
+            write_text(out, text).and_then(|_| write!(out, "{}", indicator))
+        }
+        major_type::ARRAY => {
+            let depth = decode::nested(depth)?;
+            write!(out, "[{}", indicator.prefix()).map_err(out_of_space)?;
+            for i in 0..argument {
+                if i > 0 {
+                    out.write_str(", ").map_err(out_of_space)?;
+                }
+                write_item(reader, out, depth)?;
+            }
+            out.write_char(']')
+        }
+        major_type::MAP => {
+            let depth = decode::nested(depth)?;
+            write!(out, "{{{}", indicator.prefix()).map_err(out_of_space)?;
+            for i in 0..argument {
+                if i > 0 {
+                    out.write_str(", ").map_err(out_of_space)?;
+                }
+                write_item(reader, out, depth)?;
+                out.write_str(": ").map_err(out_of_space)?;
+                write_item(reader, out, depth)?;
+            }
+            out.write_char('}')
+        }
+        _ => {
+            let depth = decode::nested(depth)?;
+            write!(out, "{}{}(", argument, indicator).map_err(out_of_space)?;
+            write_item(reader, out, depth)?;
+            out.write_char(')')
+        }
+    }
+    .map_err(out_of_space)
+}
+
+/// Writes an indefinite-length string, array or map, up to and including its break.
+fn write_indefinite<W: Write>(
+    reader: &mut Reader,
+    out: &mut W,
+    major: MajorType,
+    depth: usize,
+) -> Result<()> {
+    let depth = decode::nested(depth)?;
+    let (open, close) = match major {
+        major_type::BYTES | major_type::TEXT => ("(_ ", ')'),
+        major_type::ARRAY => ("[_ ", ']'),
+        major_type::MAP => ("{_ ", '}'),
+        _ => return Err(Error::InvalidValue),
+    };
+
+    // An indefinite-length string without chunks has its own notation
+    if matches!(major, major_type::BYTES | major_type::TEXT) && at_break(reader) {
+        reader.pos += 1;
+        let empty = if major == major_type::BYTES {
+            "''_"
+        } else {
+            "\"\"_"
+        };
+        return out.write_str(empty).map_err(out_of_space);
+    }
+
+    out.write_str(open).map_err(out_of_space)?;
+    let mut first = true;
+    while !at_break(reader) {
+        if !first {
+            out.write_str(", ").map_err(out_of_space)?;
+        }
+        first = false;
+
+        match major {
+            major_type::BYTES | major_type::TEXT => {
+                // Chunks must be definite-length strings of the same major type
+                match reader.data.get(reader.pos) {
+                    Some(&byte) if byte >> 5 == major && byte & 0x1F != INDEFINITE => {}
+                    Some(_) => return Err(Error::InvalidValue),
+                    None => return Err(Error::UnexpectedEnd),
+                }
+                write_item(reader, out, depth)?;
+            }
+            major_type::MAP => {
+                write_item(reader, out, depth)?;
+                out.write_str(": ").map_err(out_of_space)?;
+                write_item(reader, out, depth)?;
+            }
+            _ => write_item(reader, out, depth)?,
+        }
+    }
+    reader.pos += 1;
+    out.write_char(close).map_err(out_of_space)
+}
+
+/// Returns `true` if the next byte is a break, or reports the end of the input.
+#[inline]
+fn at_break(reader: &Reader) -> bool {
+    reader.data.get(reader.pos) == Some(&BREAK)
+}
+
+/// The encoding indicator for an integer, length or tag argument.
+///
+/// This prints as nothing when the argument uses its preferred encoding.
+struct Indicator {
+    info: u8,
+    major: MajorType,
+    argument: u64,
+}
+
+impl Indicator {
+    /// Returns the indicator followed by a space, for use after an opening bracket.
+    fn prefix(&self) -> Prefix<'_> {
+        Prefix(self)
+    }
+
+    /// Returns the indicator width `0` to `3`, if the encoding is not the preferred one.
+    fn width(&self) -> Option<u8> {
+        let (preferred, _, _) = encode::encode_header(self.major, self.argument);
+        if self.info == preferred & 0x1F {
+            None
+        } else {
+            Some(self.info - 24)
+        }
+    }
+}
+
+impl fmt::Display for Indicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.width() {
+            Some(width) => write!(f, "_{}", width),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An [`Indicator`] printed before the items of an array or map.
+struct Prefix<'i>(&'i Indicator);
+
+impl fmt::Display for Prefix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.width() {
+            Some(width) if self.0.argument > 0 => write!(f, "_{} ", width),
+            Some(width) => write!(f, "_{}", width),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes a byte string as a hex literal.
+fn write_bytes<W: Write>(out: &mut W, bytes: &[u8]) -> fmt::Result {
+    out.write_str("h'")?;
+    for byte in bytes {
+        write!(out, "{:02x}", byte)?;
+    }
+    out.write_char('\'')
+}
+
+/// Writes a text string as a quoted literal, escaping as in JSON.
+fn write_text<W: Write>(out: &mut W, text: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if (c as u32) < 0x20 || c == '\u{7f}' => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Writes a simple value by name, or as `simple(n)`.
+fn write_simple<W: Write>(out: &mut W, value: u8) -> fmt::Result {
+    match value {
+        20 => out.write_str("false"),
+        21 => out.write_str("true"),
+        22 => out.write_str("null"),
+        23 => out.write_str("undefined"),
+        n => write!(out, "simple({})", n),
+    }
+}
+
+/// Writes a float, always including a decimal point or exponent.
+fn write_float<W: Write>(out: &mut W, value: f64) -> fmt::Result {
+    if value.is_nan() {
+        out.write_str("NaN")
+    } else if value.is_infinite() {
+        out.write_str(if value < 0.0 { "-Infinity" } else { "Infinity" })
+    } else {
+        // Debug formatting keeps a trailing ".0" on whole numbers
+        write!(out, "{:?}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_encoded;
+    use crate::{Value, error::Error};
+    use core::fmt::{self, Write};
+
+    /// A fixed-capacity string for formatting without allocation.
+    struct Text<const N: usize> {
+        buf: [u8; N],
+        len: usize,
+    }
+
+    impl<const N: usize> Text<N> {
+        fn new() -> Self {
+            Self {
+                buf: [0; N],
+                len: 0,
+            }
+        }
+
+        fn as_str(&self) -> &str {
+            core::str::from_utf8(&self.buf[..self.len]).unwrap()
+        }
+    }
+
+    impl<const N: usize> Write for Text<N> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            if end > N {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    fn display(value: &Value) -> Text<128> {
+        let mut text = Text::new();
+        write!(text, "{}", value).unwrap();
+        text
+    }
+
+    fn encoded(bytes: &[u8]) -> Text<128> {
+        let mut text = Text::new();
+        write_encoded(bytes, &mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn test_display_scalars() {
+        assert_eq!(display(&Value::unsigned(0)).as_str(), "0");
+        assert_eq!(
+            display(&Value::Negative(u64::MAX)).as_str(),
+            "-18446744073709551616"
+        );
+        assert_eq!(display(&Value::bytes(&[])).as_str(), "h''");
+        assert_eq!(display(&Value::bytes(&[0xAB, 0x01])).as_str(), "h'ab01'");
+        assert_eq!(
+            display(&Value::text("a\"\\\n\u{1}ü")).as_str(),
+            r#""a\"\\\n\u0001ü""#
+        );
+        assert_eq!(display(&Value::bool(true)).as_str(), "true");
+        assert_eq!(display(&Value::null()).as_str(), "null");
+        assert_eq!(display(&Value::Simple(23)).as_str(), "undefined");
+        assert_eq!(display(&Value::Simple(255)).as_str(), "simple(255)");
+        assert_eq!(display(&Value::float(1.0)).as_str(), "1.0");
+        assert_eq!(display(&Value::float(-0.0)).as_str(), "-0.0");
+        assert_eq!(display(&Value::float(f64::NAN)).as_str(), "NaN");
+        assert_eq!(
+            display(&Value::float(f64::NEG_INFINITY)).as_str(),
+            "-Infinity"
+        );
+    }
+
+    #[test]
+    fn test_display_nested() {
+        let time = Value::unsigned(1700000000);
+        let items = [
+            Value::unsigned(1),
+            Value::bytes(&[0x01, 0x02]),
+            Value::tag(1, &time),
+        ];
+        let pairs = [
+            (Value::text("a"), Value::array(&items)),
+            (Value::text("f"), Value::float(1.5)),
+            (Value::unsigned(1), Value::map(&[])),
+        ];
+        assert_eq!(
+            display(&Value::map(&pairs)).as_str(),
+            r#"{"a": [1, h'0102', 1(1700000000)], "f": 1.5, 1: {}}"#
+        );
+    }
+
+    #[test]
+    fn test_encoded_indicators() {
+        // Preferred encodings carry no indicator
+        assert_eq!(encoded(&[0x18, 0x18]).as_str(), "24");
+        assert_eq!(encoded(&[0x82, 0x01, 0x20]).as_str(), "[1, -1]");
+
+        // Longer encodings than necessary
+        assert_eq!(encoded(&[0x18, 0x01]).as_str(), "1_0");
+        assert_eq!(encoded(&[0x39, 0x00, 0x00]).as_str(), "-1_1");
+        assert_eq!(encoded(&[0x5A, 0, 0, 0, 1, 0xFF]).as_str(), "h'ff'_2");
+        assert_eq!(encoded(&[0x78, 0x01, 0x61]).as_str(), "\"a\"_0");
+        assert_eq!(encoded(&[0x98, 0x01, 0x01]).as_str(), "[_0 1]");
+        assert_eq!(encoded(&[0x98, 0x00]).as_str(), "[_0]");
+        assert_eq!(encoded(&[0xB8, 0x01, 0x01, 0x02]).as_str(), "{_0 1: 2}");
+        assert_eq!(
+            encoded(&[0xDB, 0, 0, 0, 0, 0, 0, 0, 1, 0x00]).as_str(),
+            "1_3(0)"
+        );
+
+        // Floats always carry their width
+        assert_eq!(encoded(&[0xF9, 0x3E, 0x00]).as_str(), "1.5_1");
+        assert_eq!(
+            encoded(&[0xFA, 0x7F, 0x80, 0x00, 0x00]).as_str(),
+            "Infinity_2"
+        );
+        assert_eq!(
+            encoded(&[0xFB, 0x3F, 0xF1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9A]).as_str(),
+            "1.1_3"
+        );
+    }
+
+    #[test]
+    fn test_encoded_indefinite() {
+        assert_eq!(
+            encoded(&[0x5F, 0x42, 0x01, 0x02, 0x43, 0x03, 0x04, 0x05, 0xFF]).as_str(),
+            "(_ h'0102', h'030405')"
+        );
+        assert_eq!(
+            encoded(&[0x7F, 0x62, 0x73, 0x74, 0x61, 0x72, 0xFF]).as_str(),
+            "(_ \"st\", \"r\")"
+        );
+        assert_eq!(encoded(&[0x5F, 0xFF]).as_str(), "''_");
+        assert_eq!(encoded(&[0x7F, 0xFF]).as_str(), "\"\"_");
+        assert_eq!(encoded(&[0x9F, 0xFF]).as_str(), "[_ ]");
+        assert_eq!(
+            encoded(&[0x9F, 0x01, 0x82, 0x02, 0x03, 0x9F, 0x04, 0x05, 0xFF, 0xFF]).as_str(),
+            "[_ 1, [2, 3], [_ 4, 5]]"
+        );
+        assert_eq!(
+            encoded(&[0xBF, 0x61, 0x61, 0x01, 0xFF]).as_str(),
+            "{_ \"a\": 1}"
+        );
+    }
+
+    #[test]
+    fn test_encoded_errors() {
+        let mut text = Text::<64>::new();
+        assert_eq!(
+            write_encoded(&[0x82, 0x01], &mut text),
+            Err(Error::UnexpectedEnd)
+        );
+        // Chunks of a different major type
+        assert_eq!(
+            write_encoded(&[0x5F, 0x61, 0x61, 0xFF], &mut text),
+            Err(Error::InvalidValue)
+        );
+        // Missing break
+        assert_eq!(
+            write_encoded(&[0x9F, 0x01], &mut text),
+            Err(Error::UnexpectedEnd)
+        );
+        // Trailing bytes
+        assert_eq!(
+            write_encoded(&[0x01, 0x02], &mut text),
+            Err(Error::InvalidValue)
+        );
+        // Lone break
+        assert_eq!(write_encoded(&[0xFF], &mut text), Err(Error::InvalidType));
+
+        // The writer runs out of space
+        let mut small = Text::<4>::new();
+        assert_eq!(
+            write_encoded(&[0x63, 0x61, 0x62, 0x63], &mut small),
+            Err(Error::BufferOverflow)
+        );
+    }
+}
@@ -0,0 +1,1067 @@
+// SPDX-License-Identifier: MIT
+
+//! DICE certificates, as defined by the
+//! [Open Profile for DICE](https://pigweed.googlesource.com/open-dice/+/HEAD/docs/specification.md),
+//! and the certificate chains built from them.
+//!
+//! Each layer of a DICE boot chain measures the next layer and certifies its key. A CBOR
+//! DICE certificate is a [`CoseSign1`] whose payload is a CWT claims set: the issuer and
+//! subject claims of RFC 8392, plus private claims for the code hash, the configuration,
+//! the authority hash, the boot mode and the subject's public key. [`DicePayload`] models
+//! this payload on top of a [`CwtClaims`].
+//!
+//! A DICE chain, also known as a boot certificate chain (BCC), is an array of the root
+//! public key followed by one untagged `COSE_Sign1` certificate per layer, each signed
+//! by the key certified in the previous one. [`DiceChain`] reads and writes this layout,
+//! and [`DiceChain::verify`] walks it from the root to the leaf.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Arena, Value,
+//!     cose::key::{CoseKey, ED25519},
+//!     cwt::CwtClaims,
+//!     decode::decode,
+//!     dice::{DiceMode, DicePayload},
+//!     encode::Encode,
+//! };
+//!
+//! let mut key = [0u8; 64];
+//! let key_len = CoseKey::okp(ED25519, &[0x5A; 32]).as_cbor(&mut key).unwrap();
+//!
+//! let payload = DicePayload::new()
+//!     .with_cwt(CwtClaims::new().with_issuer("rom").with_subject("bootloader"))
+//!     .with_code_hash(&[0xC0; 32])
+//!     .with_mode(DiceMode::Normal)
+//!     .with_subject_public_key(&key[..key_len])
+//!     .with_key_usage(&[0x20]);
+//!
+//! let mut buf = [0u8; 256];
+//! let size = payload.as_cbor(&mut buf).unwrap();
+//!
+//! let mut pairs = [(Value::null(), Value::null()); 16];
+//! let mut arena = Arena::new(&mut [], &mut pairs);
+//! let decoded = DicePayload::decode(&buf[..size], &mut arena).unwrap();
+//! assert_eq!(decoded.mode(), Some(DiceMode::Normal));
+//! assert!(decoded.can_sign_certs());
+//! assert_eq!(decoded.subject_key(&mut arena).unwrap(), CoseKey::okp(ED25519, &[0x5A; 32]));
+//! ```
+
+use crate::{
+    Arena, Value,
+    cose::{CoseSign1, Verifier, key::CoseKey},
+    cwt::{self, CwtClaims},
+    decode::decode,
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    list::{self, Item, List, fields, required},
+    result::Result,
+};
+
+/// Claim -4670545: The hash of the code of the layer.
+pub const CODE_HASH: i64 = -4670545;
+/// Claim -4670546: An identifier of the code of the layer.
+pub const CODE: i64 = -4670546;
+/// Claim -4670547: The hash of the configuration of the layer.
+pub const CONFIG_HASH: i64 = -4670547;
+/// Claim -4670548: The encoded configuration descriptor of the layer.
+pub const CONFIG_DESCRIPTOR: i64 = -4670548;
+/// Claim -4670549: The hash of the authority that signed the code of the layer.
+pub const AUTHORITY_HASH: i64 = -4670549;
+/// Claim -4670550: An identifier of the authority that signed the code of the layer.
+pub const AUTHORITY: i64 = -4670550;
+/// Claim -4670551: The boot mode, as a one-byte byte string.
+pub const MODE: i64 = -4670551;
+/// Claim -4670552: The encoded `COSE_Key` of the subject.
+pub const SUBJECT_PUBLIC_KEY: i64 = -4670552;
+/// Claim -4670553: The uses of the subject's key, as a little-endian bit field.
+pub const KEY_USAGE: i64 = -4670553;
+/// Claim -4670554: The name of the profile the certificate follows.
+pub const PROFILE_NAME: i64 = -4670554;
+
+/// Configuration descriptor label -70002: The name of the component.
+pub const COMPONENT_NAME: i64 = -70002;
+/// Configuration descriptor label -70003: The version of the component.
+pub const COMPONENT_VERSION: i64 = -70003;
+/// Configuration descriptor label -70004: Present if the layer's secrets change on a
+/// factory reset.
+pub const RESETTABLE: i64 = -70004;
+/// Configuration descriptor label -70005: The security version of the component.
+pub const SECURITY_VERSION: i64 = -70005;
+
+/// Key usage bit that allows the subject's key to sign certificates.
+pub const KEY_USAGE_CERT_SIGN: u8 = 0x20;
+
+/// The boot mode of a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceMode {
+    /// The mode is not configured.
+    NotConfigured = 0,
+    /// The device booted normally.
+    Normal = 1,
+    /// The device booted with debug enabled.
+    Debug = 2,
+    /// The device booted for recovery or maintenance.
+    Recovery = 3,
+}
+
+impl<'a> TryFrom<&Value<'a>> for DiceMode {
+    type Error = Error;
+
+    /// Reads a mode, given as a one-byte byte string or as an integer.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DiceMode)` - The mode.
+    /// * `Err(Error::InvalidType)` - If the value is neither a one-byte byte string nor an
+    ///   unsigned integer.
+    /// * `Err(Error::InvalidValue)` - If the value is not a known mode.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let mode = match value {
+            Value::Bytes([mode]) => *mode as u64,
+            Value::Unsigned(mode) => *mode,
+            _ => return Err(Error::InvalidType),
+        };
+        match mode {
+            0 => Ok(Self::NotConfigured),
+            1 => Ok(Self::Normal),
+            2 => Ok(Self::Debug),
+            3 => Ok(Self::Recovery),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+/// The configuration descriptor of a layer, as carried encoded in a [`DicePayload`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConfigDescriptor<'a> {
+    component_name: Option<&'a str>,
+    component_version: Option<Value<'a>>,
+    resettable: bool,
+    security_version: Option<u64>,
+}
+
+impl<'a> ConfigDescriptor<'a> {
+    /// Creates an empty configuration descriptor.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            component_name: None,
+            component_version: None,
+            resettable: false,
+            security_version: None,
+        }
+    }
+
+    /// Returns the descriptor with the given component name.
+    #[inline]
+    pub const fn with_component_name(self, component_name: &'a str) -> Self {
+        Self {
+            component_name: Some(component_name),
+            ..self
+        }
+    }
+
+    /// Returns the descriptor with the given component version, an integer or a text
+    /// string.
+    #[inline]
+    pub const fn with_component_version(self, component_version: Value<'a>) -> Self {
+        Self {
+            component_version: Some(component_version),
+            ..self
+        }
+    }
+
+    /// Returns the descriptor marked as resettable.
+    #[inline]
+    pub const fn with_resettable(self) -> Self {
+        Self {
+            resettable: true,
+            ..self
+        }
+    }
+
+    /// Returns the descriptor with the given security version.
+    #[inline]
+    pub const fn with_security_version(self, security_version: u64) -> Self {
+        Self {
+            security_version: Some(security_version),
+            ..self
+        }
+    }
+
+    /// Returns the component name.
+    #[inline]
+    pub const fn component_name(&self) -> Option<&'a str> {
+        self.component_name
+    }
+
+    /// Returns the component version.
+    #[inline]
+    pub const fn component_version(&self) -> Option<Value<'a>> {
+        self.component_version
+    }
+
+    /// Returns `true` if the layer's secrets change on a factory reset.
+    #[inline]
+    pub const fn resettable(&self) -> bool {
+        self.resettable
+    }
+
+    /// Returns the security version.
+    #[inline]
+    pub const fn security_version(&self) -> Option<u64> {
+        self.security_version
+    }
+
+    /// Decodes a configuration descriptor, as found in
+    /// [`DicePayload::config_descriptor`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ConfigDescriptor)` - The descriptor.
+    /// * `Err(Error)` - If the bytes are not a valid descriptor, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the descriptor.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the descriptor map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let mut len = 0;
+        self.for_each(|_, _| {
+            len += 1;
+            Ok(())
+        })?;
+        sink.header(major_type::MAP, len)?;
+        self.for_each(|key, value| {
+            sink.value(key)?;
+            sink.value(value)
+        })
+    }
+
+    /// Calls `f` with each entry of the descriptor map, in order.
+    fn for_each(&self, mut f: impl FnMut(&Value, &Value) -> Result<()>) -> Result<()> {
+        if let Some(name) = self.component_name {
+            f(&Value::integer(COMPONENT_NAME), &Value::Text(name))?;
+        }
+        if let Some(version) = &self.component_version {
+            f(&Value::integer(COMPONENT_VERSION), version)?;
+        }
+        if self.resettable {
+            f(&Value::integer(RESETTABLE), &Value::null())?;
+        }
+        if let Some(version) = self.security_version {
+            f(&Value::integer(SECURITY_VERSION), &Value::Unsigned(version))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for ConfigDescriptor<'_> {
+    /// Encodes the descriptor map.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for ConfigDescriptor<'a> {
+    type Error = Error;
+
+    /// Reads a configuration descriptor map. Other entries are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ConfigDescriptor)` - The descriptor.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or an entry has the wrong
+    ///   type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let component_version = match map.get_int(COMPONENT_VERSION) {
+            Some(version @ (Value::Unsigned(_) | Value::Negative(_) | Value::Text(_))) => {
+                Some(*version)
+            }
+            Some(_) => return Err(Error::InvalidType),
+            None => None,
+        };
+
+        Ok(Self {
+            component_name: list::text(map.get_int(COMPONENT_NAME))?,
+            component_version,
+            resettable: map.get_int(RESETTABLE).is_some(),
+            security_version: list::unsigned(map.get_int(SECURITY_VERSION))?,
+        })
+    }
+}
+
+/// The payload of a DICE certificate: the claims one layer makes about the next.
+///
+/// The issuer and subject come from the [`CwtClaims`] given with
+/// [`with_cwt`](Self::with_cwt); Open DICE sets them to hex-encoded identifiers derived
+/// from the issuer's and subject's public keys. The payload map starts with the
+/// registered CWT claims, followed by the DICE claims in the order of their labels and
+/// finally the custom claims of the CWT claims.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DicePayload<'a> {
+    cwt: CwtClaims<'a>,
+    code_hash: Option<&'a [u8]>,
+    code: Option<&'a [u8]>,
+    config_hash: Option<&'a [u8]>,
+    config_descriptor: Option<&'a [u8]>,
+    authority_hash: Option<&'a [u8]>,
+    authority: Option<&'a [u8]>,
+    mode: Option<DiceMode>,
+    subject_public_key: Option<&'a [u8]>,
+    key_usage: Option<&'a [u8]>,
+    profile_name: Option<&'a str>,
+}
+
+impl Default for DicePayload<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DicePayload<'a> {
+    /// Creates an empty payload.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            cwt: CwtClaims::new(),
+            code_hash: None,
+            code: None,
+            config_hash: None,
+            config_descriptor: None,
+            authority_hash: None,
+            authority: None,
+            mode: None,
+            subject_public_key: None,
+            key_usage: None,
+            profile_name: None,
+        }
+    }
+
+    /// Returns the payload with the given CWT claims, which carry the issuer and subject.
+    #[inline]
+    pub const fn with_cwt(self, cwt: CwtClaims<'a>) -> Self {
+        Self { cwt, ..self }
+    }
+
+    /// Returns the payload with the given code hash.
+    #[inline]
+    pub const fn with_code_hash(self, code_hash: &'a [u8]) -> Self {
+        Self {
+            code_hash: Some(code_hash),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given code identifier.
+    #[inline]
+    pub const fn with_code(self, code: &'a [u8]) -> Self {
+        Self {
+            code: Some(code),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given configuration hash.
+    #[inline]
+    pub const fn with_config_hash(self, config_hash: &'a [u8]) -> Self {
+        Self {
+            config_hash: Some(config_hash),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given encoded configuration descriptor, such as an
+    /// encoded [`ConfigDescriptor`].
+    #[inline]
+    pub const fn with_config_descriptor(self, config_descriptor: &'a [u8]) -> Self {
+        Self {
+            config_descriptor: Some(config_descriptor),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given authority hash.
+    #[inline]
+    pub const fn with_authority_hash(self, authority_hash: &'a [u8]) -> Self {
+        Self {
+            authority_hash: Some(authority_hash),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given authority identifier.
+    #[inline]
+    pub const fn with_authority(self, authority: &'a [u8]) -> Self {
+        Self {
+            authority: Some(authority),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given boot mode.
+    #[inline]
+    pub const fn with_mode(self, mode: DiceMode) -> Self {
+        Self {
+            mode: Some(mode),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given encoded `COSE_Key` of the subject.
+    #[inline]
+    pub const fn with_subject_public_key(self, subject_public_key: &'a [u8]) -> Self {
+        Self {
+            subject_public_key: Some(subject_public_key),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given key usage bits, such as
+    /// `&[KEY_USAGE_CERT_SIGN]`.
+    #[inline]
+    pub const fn with_key_usage(self, key_usage: &'a [u8]) -> Self {
+        Self {
+            key_usage: Some(key_usage),
+            ..self
+        }
+    }
+
+    /// Returns the payload with the given profile name.
+    #[inline]
+    pub const fn with_profile_name(self, profile_name: &'a str) -> Self {
+        Self {
+            profile_name: Some(profile_name),
+            ..self
+        }
+    }
+
+    /// Returns the CWT claims.
+    #[inline]
+    pub const fn cwt(&self) -> &CwtClaims<'a> {
+        &self.cwt
+    }
+
+    /// Returns the issuer, from the CWT claims.
+    #[inline]
+    pub const fn issuer(&self) -> Option<&'a str> {
+        self.cwt.issuer()
+    }
+
+    /// Returns the subject, from the CWT claims.
+    #[inline]
+    pub const fn subject(&self) -> Option<&'a str> {
+        self.cwt.subject()
+    }
+
+    /// Returns the code hash.
+    #[inline]
+    pub const fn code_hash(&self) -> Option<&'a [u8]> {
+        self.code_hash
+    }
+
+    /// Returns the code identifier.
+    #[inline]
+    pub const fn code(&self) -> Option<&'a [u8]> {
+        self.code
+    }
+
+    /// Returns the configuration hash.
+    #[inline]
+    pub const fn config_hash(&self) -> Option<&'a [u8]> {
+        self.config_hash
+    }
+
+    /// Returns the encoded configuration descriptor, which
+    /// [`ConfigDescriptor::decode`] reads.
+    #[inline]
+    pub const fn config_descriptor(&self) -> Option<&'a [u8]> {
+        self.config_descriptor
+    }
+
+    /// Returns the authority hash.
+    #[inline]
+    pub const fn authority_hash(&self) -> Option<&'a [u8]> {
+        self.authority_hash
+    }
+
+    /// Returns the authority identifier.
+    #[inline]
+    pub const fn authority(&self) -> Option<&'a [u8]> {
+        self.authority
+    }
+
+    /// Returns the boot mode.
+    #[inline]
+    pub const fn mode(&self) -> Option<DiceMode> {
+        self.mode
+    }
+
+    /// Returns the encoded `COSE_Key` of the subject, which
+    /// [`subject_key`](Self::subject_key) reads.
+    #[inline]
+    pub const fn subject_public_key(&self) -> Option<&'a [u8]> {
+        self.subject_public_key
+    }
+
+    /// Returns the key usage bits.
+    #[inline]
+    pub const fn key_usage(&self) -> Option<&'a [u8]> {
+        self.key_usage
+    }
+
+    /// Returns the profile name.
+    #[inline]
+    pub const fn profile_name(&self) -> Option<&'a str> {
+        self.profile_name
+    }
+
+    /// Returns `true` if the key usage allows the subject's key to sign certificates.
+    #[inline]
+    pub const fn can_sign_certs(&self) -> bool {
+        match self.key_usage {
+            Some([usage, ..]) => *usage & KEY_USAGE_CERT_SIGN != 0,
+            _ => false,
+        }
+    }
+
+    /// Decodes the subject's public key.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoseKey)` - The key.
+    /// * `Err(Error::InvalidType)` - If the payload has no subject public key.
+    /// * `Err(Error)` - If the key is not a valid `COSE_Key`, or the arena is too small.
+    pub fn subject_key(&self, arena: &mut Arena<'a>) -> Result<CoseKey<'a>> {
+        let key = required(self.subject_public_key)?;
+        CoseKey::try_from(&decode(key, arena)?)
+    }
+
+    /// Decodes a payload, as found in a DICE certificate.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DicePayload)` - The payload.
+    /// * `Err(Error)` - If the bytes are not a valid payload, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the payload.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the payload map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let mut len = 0;
+        self.for_each(|_, _| {
+            len += 1;
+            Ok(())
+        })?;
+        sink.header(major_type::MAP, len)?;
+        self.for_each(|key, value| {
+            sink.value(key)?;
+            sink.value(value)
+        })
+    }
+
+    /// Calls `f` with each entry of the payload map, in order.
+    fn for_each(&self, mut f: impl FnMut(&Value, &Value) -> Result<()>) -> Result<()> {
+        self.cwt.for_each_registered(&mut f)?;
+        let hashes = [
+            (CODE_HASH, self.code_hash),
+            (CODE, self.code),
+            (CONFIG_HASH, self.config_hash),
+            (CONFIG_DESCRIPTOR, self.config_descriptor),
+            (AUTHORITY_HASH, self.authority_hash),
+            (AUTHORITY, self.authority),
+        ];
+        for (label, bytes) in hashes {
+            if let Some(bytes) = bytes {
+                f(&Value::integer(label), &Value::Bytes(bytes))?;
+            }
+        }
+        if let Some(mode) = self.mode {
+            f(&Value::integer(MODE), &Value::Bytes(&[mode as u8]))?;
+        }
+        if let Some(key) = self.subject_public_key {
+            f(&Value::integer(SUBJECT_PUBLIC_KEY), &Value::Bytes(key))?;
+        }
+        if let Some(usage) = self.key_usage {
+            f(&Value::integer(KEY_USAGE), &Value::Bytes(usage))?;
+        }
+        if let Some(name) = self.profile_name {
+            f(&Value::integer(PROFILE_NAME), &Value::Text(name))?;
+        }
+        for (key, value) in self.cwt.custom() {
+            if !cwt::is_registered(key) && !is_dice_claim(key) {
+                f(key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for DicePayload<'_> {
+    /// Encodes the payload map.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for DicePayload<'a> {
+    type Error = Error;
+
+    /// Reads a payload map, checking the types of the CWT and DICE claims.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DicePayload)` - The payload.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a claim has the wrong
+    ///   type.
+    /// * `Err(Error::InvalidValue)` - If the mode is not a known mode.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let cwt = CwtClaims::try_from(value)?;
+        let bytes = |label| list::bytes(value.get_int(label));
+        let mode = match value.get_int(MODE) {
+            Some(mode) => Some(DiceMode::try_from(mode)?),
+            None => None,
+        };
+
+        Ok(Self {
+            cwt,
+            code_hash: bytes(CODE_HASH)?,
+            code: bytes(CODE)?,
+            config_hash: bytes(CONFIG_HASH)?,
+            config_descriptor: bytes(CONFIG_DESCRIPTOR)?,
+            authority_hash: bytes(AUTHORITY_HASH)?,
+            authority: bytes(AUTHORITY)?,
+            mode,
+            subject_public_key: bytes(SUBJECT_PUBLIC_KEY)?,
+            key_usage: bytes(KEY_USAGE)?,
+            profile_name: list::text(value.get_int(PROFILE_NAME))?,
+        })
+    }
+}
+
+/// A DICE chain: the root public key followed by one certificate per layer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiceChain<'a> {
+    root: CoseKey<'a>,
+    certificates: List<'a, CoseSign1<'a>>,
+}
+
+impl<'a> DiceChain<'a> {
+    /// Creates a chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root public key, which signs the first certificate.
+    /// * `certificates` - The certificates, from the first layer to the leaf, of which
+    ///   there must be at least one.
+    #[inline]
+    pub const fn new(root: CoseKey<'a>, certificates: &'a [CoseSign1<'a>]) -> Self {
+        Self {
+            root,
+            certificates: List::Items(certificates),
+        }
+    }
+
+    /// Returns the root public key.
+    #[inline]
+    pub const fn root(&self) -> CoseKey<'a> {
+        self.root
+    }
+
+    /// Returns the certificates, from the first layer to the leaf.
+    #[inline]
+    pub const fn certificates(&self) -> List<'a, CoseSign1<'a>> {
+        self.certificates
+    }
+
+    /// Walks the chain from the root to the leaf, checking each certificate.
+    ///
+    /// Each certificate must be signed by the key certified in the previous one, or by
+    /// the root key for the first one. Each issuer must match the previous subject, and
+    /// each certified key that signs a further certificate must be allowed to sign
+    /// certificates.
+    ///
+    /// # Arguments
+    ///
+    /// * `arena` - Storage for the decoded payloads and keys of all layers.
+    /// * `scratch` - Space for the encoded `Sig_structure` of each certificate.
+    /// * `verifier` - Returns the signature check for a public key.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DicePayload)` - The payload of the leaf certificate.
+    /// * `Err(Error::InvalidType)` - If the chain has no certificates, or a certificate
+    ///   or payload does not have the expected shape.
+    /// * `Err(Error::InvalidValue)` - If an issuer does not match the previous subject,
+    ///   or a key that may not sign certificates signed one.
+    /// * `Err(Error)` - Any error returned by `verifier` or by a signature check.
+    pub fn verify<V: Verifier>(
+        &self,
+        arena: &mut Arena<'a>,
+        scratch: &mut [u8],
+        mut verifier: impl FnMut(&CoseKey<'a>) -> Result<V>,
+    ) -> Result<DicePayload<'a>> {
+        let mut key = self.root;
+        let mut previous: Option<DicePayload<'a>> = None;
+        for certificate in self.certificates.iter() {
+            let certificate = certificate?;
+            certificate.verify(&[], &verifier(&key)?, scratch)?;
+            let payload = required(certificate.payload())?;
+            let payload = DicePayload::decode(payload, arena)?;
+            if let Some(previous) = previous {
+                if !previous.can_sign_certs() || previous.subject() != payload.issuer() {
+                    return Err(Error::InvalidValue);
+                }
+            }
+            key = payload.subject_key(arena)?;
+            previous = Some(payload);
+        }
+        required(previous)
+    }
+
+    /// Calculates the number of bytes needed to encode the chain.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the chain array.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 1 + self.certificates.len() as u64)?;
+        sink.encoded(self.root.encoded_size(), |buf| self.root.as_cbor(buf))?;
+        self.certificates.write_items(sink)
+    }
+}
+
+impl<'a> Encode<'a> for DiceChain<'_> {
+    /// Encodes the chain array, with untagged certificates.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for DiceChain<'a> {
+    type Error = Error;
+
+    /// Reads a chain array.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DiceChain)` - The chain, whose certificates are converted as they are read.
+    /// * `Err(Error::InvalidType)` - If the value is not an array of a key and at least
+    ///   one certificate.
+    /// * `Err(Error::InvalidValue)` - If the root key is not a valid `COSE_Key`.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([root, certificates @ ..]) if !certificates.is_empty() => Ok(Self {
+                root: CoseKey::try_from(root)?,
+                certificates: List::Values(certificates),
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+impl Item for CoseSign1<'_> {
+    /// Writes the certificate untagged, as the chain layout requires.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.encoded(self.untagged_size(), |buf| self.encode_untagged(buf))
+    }
+}
+
+/// Returns `true` if a map key is the label of a DICE claim.
+fn is_dice_claim(key: &Value) -> bool {
+    let labels = PROFILE_NAME as i128..=CODE_HASH as i128;
+    matches!(key.as_i128(), Some(label) if labels.contains(&label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigDescriptor, DiceChain, DiceMode, DicePayload};
+    use crate::{
+        Arena, Value, cbor,
+        cose::{
+            CoseSign1,
+            key::{CoseKey, ED25519, KeyParameters},
+            test_support::Checksum,
+        },
+        cwt::CwtClaims,
+        decode::decode,
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+        result::Result,
+    };
+
+    /// Returns a checksum keyed by the first byte of an `OKP` public key.
+    fn checksum_for(key: &CoseKey) -> Result<Checksum> {
+        match key.parameters() {
+            KeyParameters::Okp { x: [first, ..], .. } => Ok(Checksum(*first)),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    const PROTECTED: [u8; 3] = [0xA1, 0x01, 0x27];
+
+    #[test]
+    fn test_payload() {
+        let descriptor = ConfigDescriptor::new()
+            .with_component_name("bootloader")
+            .with_component_version(Value::unsigned(3))
+            .with_resettable()
+            .with_security_version(12);
+        let mut config = [0u8; 64];
+        let config_len = descriptor.as_cbor(&mut config).unwrap();
+        assert_eq!(config_len, descriptor.encoded_size());
+
+        let custom = [
+            (Value::integer(-4670551), Value::bytes(&[2])),
+            (Value::integer(-1000), Value::text("extra")),
+        ];
+        let payload = DicePayload::new()
+            .with_cwt(
+                CwtClaims::new()
+                    .with_issuer("AB")
+                    .with_subject("CD")
+                    .with_custom(&custom),
+            )
+            .with_code_hash(&[0x11; 4])
+            .with_config_descriptor(&config[..config_len])
+            .with_authority_hash(&[0x22; 4])
+            .with_mode(DiceMode::Debug)
+            .with_subject_public_key(&[0xA0])
+            .with_key_usage(&[0x20])
+            .with_profile_name("android.16");
+
+        static EXPECTED: Value = cbor!({
+            1: "AB",
+            2: "CD",
+            -4670545: h "11111111",
+            -4670548: h "A43A000111716A626F6F746C6F616465723A00011172033A00011173F63A000111740C",
+            -4670549: h "22222222",
+            -4670551: h "02",
+            -4670552: h "A0",
+            -4670553: h "20",
+            -4670554: "android.16",
+            -1000: "extra"
+        });
+        let mut buf = [0u8; 256];
+        let size = payload.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, payload.encoded_size());
+        assert_encoding(&buf[..size], &EXPECTED);
+
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = DicePayload::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded.issuer(), Some("AB"));
+        assert_eq!(decoded.subject(), Some("CD"));
+        assert_eq!(decoded.code_hash(), Some(&[0x11; 4][..]));
+        assert_eq!(decoded.code(), None);
+        assert_eq!(decoded.mode(), Some(DiceMode::Debug));
+        assert_eq!(decoded.profile_name(), Some("android.16"));
+        assert!(decoded.can_sign_certs());
+        assert_eq!(decoded.subject_key(&mut arena), Err(Error::InvalidType));
+
+        let config = decoded.config_descriptor().unwrap();
+        let descriptor = ConfigDescriptor::decode(config, &mut arena).unwrap();
+        assert_eq!(descriptor.component_name(), Some("bootloader"));
+        assert_eq!(descriptor.component_version(), Some(Value::unsigned(3)));
+        assert!(descriptor.resettable());
+        assert_eq!(descriptor.security_version(), Some(12));
+    }
+
+    #[test]
+    fn test_payload_errors() {
+        static INTEGER_MODE: Value = cbor!({-4670551: 1});
+        let payload = DicePayload::try_from(&INTEGER_MODE).unwrap();
+        assert_eq!(payload.mode(), Some(DiceMode::Normal));
+        assert!(!payload.can_sign_certs());
+
+        static BAD_MODE: Value = cbor!({-4670551: h "07"});
+        assert_eq!(DicePayload::try_from(&BAD_MODE), Err(Error::InvalidValue));
+
+        static TEXT_HASH: Value = cbor!({-4670545: "hash"});
+        static LONG_MODE: Value = cbor!({-4670551: h "0101"});
+        static INTEGER_SUBJECT: Value = cbor!({2: 7});
+        static BAD_NAME: Value = cbor!({-70002: 1});
+        for value in [&TEXT_HASH, &LONG_MODE, &INTEGER_SUBJECT] {
+            assert_eq!(
+                DicePayload::try_from(value),
+                Err(Error::InvalidType),
+                "{value}"
+            );
+        }
+        assert_eq!(
+            ConfigDescriptor::try_from(&BAD_NAME),
+            Err(Error::InvalidType)
+        );
+    }
+
+    /// Encodes an OKP public key whose key bytes are all `byte`.
+    fn encode_key<'b>(byte: &'b [u8; 32], buf: &'b mut [u8]) -> &'b [u8] {
+        let size = CoseKey::okp(ED25519, byte).as_cbor(buf).unwrap();
+        &buf[..size]
+    }
+
+    #[test]
+    fn test_chain() {
+        let (key1, key2) = ([0x01; 32], [0x02; 32]);
+        let mut key1_buf = [0u8; 64];
+        let mut key2_buf = [0u8; 64];
+        let layer1_key = encode_key(&key1, &mut key1_buf);
+        let layer2_key = encode_key(&key2, &mut key2_buf);
+
+        let layer1 = DicePayload::new()
+            .with_cwt(CwtClaims::new().with_issuer("root").with_subject("layer1"))
+            .with_mode(DiceMode::Normal)
+            .with_subject_public_key(layer1_key)
+            .with_key_usage(&[0x20]);
+        let layer2 = DicePayload::new()
+            .with_cwt(
+                CwtClaims::new()
+                    .with_issuer("layer1")
+                    .with_subject("layer2"),
+            )
+            .with_mode(DiceMode::Normal)
+            .with_subject_public_key(layer2_key)
+            .with_key_usage(&[0x20]);
+
+        let mut payload1 = [0u8; 128];
+        let mut payload2 = [0u8; 128];
+        let len1 = layer1.as_cbor(&mut payload1).unwrap();
+        let len2 = layer2.as_cbor(&mut payload2).unwrap();
+        let mut scratch1 = [0u8; 192];
+        let mut scratch2 = [0u8; 192];
+        let root = CoseKey::okp(ED25519, &[0x07; 32]);
+        let certificates = [
+            CoseSign1::sign(
+                &PROTECTED,
+                &[],
+                &payload1[..len1],
+                &[],
+                &Checksum(0x07),
+                &mut scratch1,
+            )
+            .unwrap(),
+            CoseSign1::sign(
+                &PROTECTED,
+                &[],
+                &payload2[..len2],
+                &[],
+                &Checksum(0x01),
+                &mut scratch2,
+            )
+            .unwrap(),
+        ];
+        let chain = DiceChain::new(root, &certificates);
+
+        let mut buf = [0u8; 512];
+        let size = chain.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, chain.encoded_size());
+        // An array of the root key and two untagged certificates
+        assert_eq!(&buf[..3], &[0x83, 0xA3, 0x01]);
+
+        let mut values = [Value::null(); 12];
+        let mut pairs = [(Value::null(), Value::null()); 40];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let value = decode(&buf[..size], &mut arena).unwrap();
+        let decoded = DiceChain::try_from(&value).unwrap();
+        assert_eq!(decoded.root(), root);
+        assert_eq!(decoded.certificates().len(), 2);
+        assert_eq!(decoded.certificates().get(1), Some(Ok(certificates[1])));
+
+        let mut scratch = [0u8; 192];
+        let leaf = decoded
+            .verify(&mut arena, &mut scratch, checksum_for)
+            .unwrap();
+        assert_eq!(leaf.subject(), Some("layer2"));
+        assert_eq!(
+            leaf.subject_key(&mut arena),
+            Ok(CoseKey::okp(ED25519, &key2))
+        );
+
+        // Re-encoding the decoded chain gives the same bytes
+        let mut again = [0u8; 512];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+    }
+
+    #[test]
+    fn test_chain_errors() {
+        let mut key_buf = [0u8; 64];
+        let key = encode_key(&[0x01; 32], &mut key_buf);
+        let first = DicePayload::new()
+            .with_cwt(CwtClaims::new().with_issuer("root").with_subject("layer1"))
+            .with_subject_public_key(key);
+        let second = first.with_cwt(CwtClaims::new().with_issuer("other").with_subject("leaf"));
+
+        let mut payload1 = [0u8; 128];
+        let mut payload2 = [0u8; 128];
+        let len1 = first.as_cbor(&mut payload1).unwrap();
+        let len2 = second.as_cbor(&mut payload2).unwrap();
+        let mut scratch1 = [0u8; 192];
+        let mut scratch2 = [0u8; 192];
+        let root = CoseKey::okp(ED25519, &[0x07; 32]);
+        let signed1 = CoseSign1::sign(
+            &PROTECTED,
+            &[],
+            &payload1[..len1],
+            &[],
+            &Checksum(0x07),
+            &mut scratch1,
+        )
+        .unwrap();
+        let signed2 = CoseSign1::sign(
+            &PROTECTED,
+            &[],
+            &payload2[..len2],
+            &[],
+            &Checksum(0x01),
+            &mut scratch2,
+        )
+        .unwrap();
+
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let mut scratch = [0u8; 192];
+
+        // The first layer may not sign certificates, and the issuer does not match
+        let certificates = [signed1, signed2];
+        let chain = DiceChain::new(root, &certificates);
+        assert_eq!(
+            chain.verify(&mut arena, &mut scratch, checksum_for),
+            Err(Error::InvalidValue)
+        );
+
+        // A certificate signed by the wrong key
+        let certificates = [signed2];
+        let chain = DiceChain::new(root, &certificates);
+        let wrong_key = |_: &CoseKey| Ok(Checksum(0x42));
+        assert_eq!(
+            chain.verify(&mut arena, &mut scratch, wrong_key),
+            Err(Error::InvalidValue)
+        );
+
+        // A chain needs the root key and at least one certificate
+        static ROOT_ONLY: Value = cbor!([{1: 1, -1: 6, -2: h "07"}]);
+        static NOT_A_KEY: Value = cbor!([1, [h "", {}, h "", h ""]]);
+        assert_eq!(DiceChain::try_from(&ROOT_ONLY), Err(Error::InvalidType));
+        assert_eq!(DiceChain::try_from(&NOT_A_KEY), Err(Error::InvalidType));
+        assert_eq!(
+            DiceChain::new(root, &[]).verify(&mut arena, &mut scratch, checksum_for),
+            Err(Error::InvalidType)
+        );
+    }
+}
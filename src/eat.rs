@@ -0,0 +1,850 @@
+// SPDX-License-Identifier: MIT
+
+//! Entity Attestation Token (EAT) claims, as defined in
+//! [RFC 9711](https://www.rfc-editor.org/rfc/rfc9711).
+//!
+//! An EAT is a [CWT](crate::cwt) whose claims describe the state of a device: a nonce
+//! for freshness, the device's identity, its debug state, measurements of its software
+//! and the claims of its submodules. [`EatClaims`] adds typed fields for these claims
+//! on top of a [`CwtClaims`], and encodes both in one claims map without allocating.
+//!
+//! Submodules are encoded as nested maps, so a token can describe a whole system in
+//! one pass. A submodule is given either as its own claims, as a nested token, or as
+//! a detached digest of claims that are conveyed separately.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     cose,
+//!     eat::{DebugStatus, DetachedDigest, EatClaims, Submodule},
+//!     encode::Encode,
+//! };
+//!
+//! let radio = EatClaims::new().with_dbgstat(DebugStatus::DisabledPermanently);
+//! let firmware = DetachedDigest::new(cose::SHA_256, &[0xAB; 32]);
+//! let submods = [
+//!     ("radio", Submodule::Claims(&radio)),
+//!     ("firmware", Submodule::Digest(firmware)),
+//! ];
+//! let claims = EatClaims::new()
+//!     .with_nonce(&[b"8 bytes!"])
+//!     .with_dbgstat(DebugStatus::DisabledSinceBoot)
+//!     .with_submods(&submods);
+//!
+//! let mut buf = [0u8; 128];
+//! let size = claims.as_cbor(&mut buf).unwrap();
+//! assert_eq!(size, claims.encoded_size());
+//! ```
+
+use crate::{
+    Value,
+    cwt::{self, CwtClaims},
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    result::Result,
+};
+
+/// Claim 10: A nonce proving the freshness of the token.
+pub const NONCE: i64 = 10;
+/// Claim 256: The universal entity ID of the device.
+pub const UEID: i64 = 256;
+/// Claim 257: Semi-permanent UEIDs, keyed by name.
+pub const SUEIDS: i64 = 257;
+/// Claim 258: The manufacturer of the hardware.
+pub const OEMID: i64 = 258;
+/// Claim 259: The model of the hardware.
+pub const HWMODEL: i64 = 259;
+/// Claim 260: The version of the hardware.
+pub const HWVERSION: i64 = 260;
+/// Claim 261: The number of seconds since the device booted.
+pub const UPTIME: i64 = 261;
+/// Claim 262: Whether the device booted with the manufacturer's software.
+pub const OEMBOOT: i64 = 262;
+/// Claim 263: The debug status of the device.
+pub const DBGSTAT: i64 = 263;
+/// Claim 264: The location of the device.
+pub const LOCATION: i64 = 264;
+/// Claim 265: The profile the token conforms to.
+pub const EAT_PROFILE: i64 = 265;
+/// Claim 266: The claims of the submodules, keyed by name.
+pub const SUBMODS: i64 = 266;
+/// Claim 267: The number of times the device has booted.
+pub const BOOTCOUNT: i64 = 267;
+/// Claim 268: A random value that is the same for the whole boot cycle.
+pub const BOOTSEED: i64 = 268;
+/// Claim 269: Endorsements and certifications of the device.
+pub const DLOAS: i64 = 269;
+/// Claim 270: The name of the software running on the device.
+pub const SWNAME: i64 = 270;
+/// Claim 271: The version of the software running on the device.
+pub const SWVERSION: i64 = 271;
+/// Claim 272: Software manifests, such as SUIT manifests or CoSWID tags.
+pub const MANIFESTS: i64 = 272;
+/// Claim 273: Measurements of the software, such as CoSWID evidence.
+pub const MEASUREMENTS: i64 = 273;
+/// Claim 274: The results of comparing measurements against reference values.
+pub const MEASRES: i64 = 274;
+/// Claim 275: What the token is intended to be used for.
+pub const INTUSE: i64 = 275;
+
+/// UEID type 0x01: A random number of 16, 24 or 32 bytes.
+pub const UEID_RAND: u8 = 0x01;
+/// UEID type 0x02: An IEEE EUI-48 or EUI-64 MAC address.
+pub const UEID_IEEE_EUI: u8 = 0x02;
+/// UEID type 0x03: An IMEI, encoded as a 14-digit integer.
+pub const UEID_IMEI: u8 = 0x03;
+
+/// The debug status of a device, as carried by the `dbgstat` claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugStatus {
+    /// Debug is enabled.
+    Enabled = 0,
+    /// Debug is disabled, but may be enabled again.
+    Disabled = 1,
+    /// Debug has been disabled since boot, and cannot be enabled until the next boot.
+    DisabledSinceBoot = 2,
+    /// Debug is disabled permanently.
+    DisabledPermanently = 3,
+    /// Debug is disabled permanently, including for the manufacturer.
+    DisabledFullyAndPermanently = 4,
+}
+
+impl<'a> TryFrom<&Value<'a>> for DebugStatus {
+    type Error = Error;
+
+    /// Reads a debug status.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DebugStatus)` - The debug status.
+    /// * `Err(Error::InvalidType)` - If the value is not an integer.
+    /// * `Err(Error::InvalidValue)` - If the value is not a known debug status.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_i128() {
+            Some(0) => Ok(Self::Enabled),
+            Some(1) => Ok(Self::Disabled),
+            Some(2) => Ok(Self::DisabledSinceBoot),
+            Some(3) => Ok(Self::DisabledPermanently),
+            Some(4) => Ok(Self::DisabledFullyAndPermanently),
+            Some(_) => Err(Error::InvalidValue),
+            None => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The manufacturer of the hardware, as carried by the `oemid` claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OemId {
+    /// A random manufacturer ID.
+    Random([u8; 16]),
+    /// An IEEE OUI, MA-L.
+    Ieee([u8; 3]),
+    /// An IANA Private Enterprise Number.
+    Pen(u64),
+}
+
+impl<'a> TryFrom<&Value<'a>> for OemId {
+    type Error = Error;
+
+    /// Reads a manufacturer ID.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OemId)` - The manufacturer ID.
+    /// * `Err(Error::InvalidType)` - If the value is neither a byte string nor an unsigned
+    ///   integer.
+    /// * `Err(Error::InvalidValue)` - If a byte string is neither 3 nor 16 bytes long.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Unsigned(pen) => Ok(Self::Pen(*pen)),
+            Value::Bytes(id) => match (<[u8; 16]>::try_from(*id), <[u8; 3]>::try_from(*id)) {
+                (Ok(random), _) => Ok(Self::Random(random)),
+                (_, Ok(oui)) => Ok(Self::Ieee(oui)),
+                _ => Err(Error::InvalidValue),
+            },
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A measurement of software, as an entry of the `measurements` claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement<'a> {
+    content_type: u16,
+    content: &'a [u8],
+}
+
+impl<'a> Measurement<'a> {
+    /// Creates a measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `content_type` - The CoAP content format of the measurement, such as 258 for a
+    ///   CoSWID.
+    /// * `content` - The encoded measurement.
+    #[inline]
+    pub const fn new(content_type: u16, content: &'a [u8]) -> Self {
+        Self {
+            content_type,
+            content,
+        }
+    }
+
+    /// Returns the CoAP content format of the measurement.
+    #[inline]
+    pub const fn content_type(&self) -> u16 {
+        self.content_type
+    }
+
+    /// Returns the encoded measurement.
+    #[inline]
+    pub const fn content(&self) -> &'a [u8] {
+        self.content
+    }
+
+    /// Writes the measurement as `[content-type, content]`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        sink.value(&Value::Unsigned(self.content_type as u64))?;
+        sink.value(&Value::Bytes(self.content))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Measurement<'a> {
+    type Error = Error;
+
+    /// Reads a measurement.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Measurement)` - The measurement.
+    /// * `Err(Error::InvalidType)` - If the value is not a `[uint, bstr]` array, or the
+    ///   content format does not fit in 16 bits.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([Value::Unsigned(content_type), Value::Bytes(content)]) => Ok(Self::new(
+                u16::try_from(*content_type).map_err(|_| Error::InvalidType)?,
+                content,
+            )),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A digest of submodule claims that are conveyed separately from the token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetachedDigest<'a> {
+    algorithm: i64,
+    digest: &'a [u8],
+}
+
+impl<'a> DetachedDigest<'a> {
+    /// Creates a detached digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The COSE hash algorithm, such as [`SHA_256`](crate::cose::SHA_256).
+    /// * `digest` - The digest of the encoded submodule claims.
+    #[inline]
+    pub const fn new(algorithm: i64, digest: &'a [u8]) -> Self {
+        Self { algorithm, digest }
+    }
+
+    /// Returns the COSE hash algorithm.
+    #[inline]
+    pub const fn algorithm(&self) -> i64 {
+        self.algorithm
+    }
+
+    /// Returns the digest.
+    #[inline]
+    pub const fn digest(&self) -> &'a [u8] {
+        self.digest
+    }
+
+    /// Writes the digest as `[algorithm, digest]`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        sink.value(&Value::integer(self.algorithm))?;
+        sink.value(&Value::Bytes(self.digest))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for DetachedDigest<'a> {
+    type Error = Error;
+
+    /// Reads a detached digest.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DetachedDigest)` - The digest.
+    /// * `Err(Error::InvalidType)` - If the value is not an `[int, bstr]` array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([algorithm, Value::Bytes(digest)]) => {
+                match algorithm.as_i128().map(i64::try_from) {
+                    Some(Ok(algorithm)) => Ok(Self::new(algorithm, digest)),
+                    _ => Err(Error::InvalidType),
+                }
+            }
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The claims of a submodule, as an entry of the `submods` claim.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Submodule<'a> {
+    /// The claims of the submodule, encoded as a nested map.
+    Claims(&'a EatClaims<'a>),
+    /// An encoded, tagged token from the submodule, such as a tag 61 CWT.
+    Token(&'a [u8]),
+    /// A digest of the submodule's claims, which are conveyed separately.
+    Digest(DetachedDigest<'a>),
+}
+
+impl Submodule<'_> {
+    /// Writes the submodule.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match self {
+            Self::Claims(claims) => claims.write(sink),
+            Self::Token(token) => sink.value(&Value::Bytes(token)),
+            Self::Digest(digest) => digest.write(sink),
+        }
+    }
+}
+
+/// An EAT claims set.
+///
+/// The CWT claims, such as the issuer and the issue time, come from the [`CwtClaims`]
+/// given with [`with_cwt`](Self::with_cwt). The claims map starts with the registered
+/// CWT claims, followed by the EAT claims in label order and finally the custom claims
+/// of the CWT claims. Custom claims that use the label of a claim with a typed field
+/// here are not encoded, so other EAT claims, such as `hwversion`, can be given as
+/// custom claims.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EatClaims<'a> {
+    cwt: CwtClaims<'a>,
+    nonce: &'a [&'a [u8]],
+    ueid: Option<&'a [u8]>,
+    oemid: Option<OemId>,
+    hwmodel: Option<&'a [u8]>,
+    uptime: Option<u64>,
+    dbgstat: Option<DebugStatus>,
+    profile: Option<&'a str>,
+    submods: &'a [(&'a str, Submodule<'a>)],
+    bootcount: Option<u64>,
+    measurements: &'a [Measurement<'a>],
+}
+
+impl Default for EatClaims<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> EatClaims<'a> {
+    /// Creates an empty claims set.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            cwt: CwtClaims::new(),
+            nonce: &[],
+            ueid: None,
+            oemid: None,
+            hwmodel: None,
+            uptime: None,
+            dbgstat: None,
+            profile: None,
+            submods: &[],
+            bootcount: None,
+            measurements: &[],
+        }
+    }
+
+    /// Returns the claims with the given CWT claims.
+    #[inline]
+    pub const fn with_cwt(self, cwt: CwtClaims<'a>) -> Self {
+        Self { cwt, ..self }
+    }
+
+    /// Returns the claims with the given nonces (`nonce`), each 8 to 64 bytes long.
+    ///
+    /// A single nonce is encoded as a byte string, several as an array.
+    #[inline]
+    pub const fn with_nonce(self, nonce: &'a [&'a [u8]]) -> Self {
+        Self { nonce, ..self }
+    }
+
+    /// Returns the claims with the given universal entity ID (`ueid`), 7 to 33 bytes
+    /// long and starting with its type, such as [`UEID_RAND`].
+    #[inline]
+    pub const fn with_ueid(self, ueid: &'a [u8]) -> Self {
+        Self {
+            ueid: Some(ueid),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given manufacturer (`oemid`).
+    #[inline]
+    pub const fn with_oemid(self, oemid: OemId) -> Self {
+        Self {
+            oemid: Some(oemid),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given hardware model (`hwmodel`).
+    #[inline]
+    pub const fn with_hwmodel(self, hwmodel: &'a [u8]) -> Self {
+        Self {
+            hwmodel: Some(hwmodel),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given uptime in seconds (`uptime`).
+    #[inline]
+    pub const fn with_uptime(self, uptime: u64) -> Self {
+        Self {
+            uptime: Some(uptime),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given debug status (`dbgstat`).
+    #[inline]
+    pub const fn with_dbgstat(self, dbgstat: DebugStatus) -> Self {
+        Self {
+            dbgstat: Some(dbgstat),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given profile URI (`eat_profile`).
+    #[inline]
+    pub const fn with_profile(self, profile: &'a str) -> Self {
+        Self {
+            profile: Some(profile),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given submodules (`submods`), keyed by name.
+    #[inline]
+    pub const fn with_submods(self, submods: &'a [(&'a str, Submodule<'a>)]) -> Self {
+        Self { submods, ..self }
+    }
+
+    /// Returns the claims with the given boot count (`bootcount`).
+    #[inline]
+    pub const fn with_bootcount(self, bootcount: u64) -> Self {
+        Self {
+            bootcount: Some(bootcount),
+            ..self
+        }
+    }
+
+    /// Returns the claims with the given measurements (`measurements`).
+    #[inline]
+    pub const fn with_measurements(self, measurements: &'a [Measurement<'a>]) -> Self {
+        Self {
+            measurements,
+            ..self
+        }
+    }
+
+    /// Returns the CWT claims.
+    #[inline]
+    pub const fn cwt(&self) -> &CwtClaims<'a> {
+        &self.cwt
+    }
+
+    /// Returns the nonces.
+    #[inline]
+    pub const fn nonce(&self) -> &'a [&'a [u8]] {
+        self.nonce
+    }
+
+    /// Returns the universal entity ID.
+    #[inline]
+    pub const fn ueid(&self) -> Option<&'a [u8]> {
+        self.ueid
+    }
+
+    /// Returns the manufacturer.
+    #[inline]
+    pub const fn oemid(&self) -> Option<OemId> {
+        self.oemid
+    }
+
+    /// Returns the hardware model.
+    #[inline]
+    pub const fn hwmodel(&self) -> Option<&'a [u8]> {
+        self.hwmodel
+    }
+
+    /// Returns the uptime in seconds.
+    #[inline]
+    pub const fn uptime(&self) -> Option<u64> {
+        self.uptime
+    }
+
+    /// Returns the debug status.
+    #[inline]
+    pub const fn dbgstat(&self) -> Option<DebugStatus> {
+        self.dbgstat
+    }
+
+    /// Returns the profile URI.
+    #[inline]
+    pub const fn profile(&self) -> Option<&'a str> {
+        self.profile
+    }
+
+    /// Returns the submodules.
+    #[inline]
+    pub const fn submods(&self) -> &'a [(&'a str, Submodule<'a>)] {
+        self.submods
+    }
+
+    /// Returns the boot count.
+    #[inline]
+    pub const fn bootcount(&self) -> Option<u64> {
+        self.bootcount
+    }
+
+    /// Returns the measurements.
+    #[inline]
+    pub const fn measurements(&self) -> &'a [Measurement<'a>] {
+        self.measurements
+    }
+
+    /// Calculates the number of bytes needed to encode the claims, including the claims
+    /// of nested submodules.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the claims map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let mut len = 0;
+        self.for_each(|_, _| {
+            len += 1;
+            Ok(())
+        })?;
+        sink.header(major_type::MAP, len)?;
+        self.for_each(|key, claim| {
+            sink.value(key)?;
+            claim.write(sink)
+        })
+    }
+
+    /// Calls `f` with each entry of the claims map, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidValue)` - If a nonce or the UEID has an invalid length.
+    fn for_each(&self, mut f: impl FnMut(&Value, Claim) -> Result<()>) -> Result<()> {
+        self.cwt
+            .for_each_registered(|key, value| f(key, Claim::Value(value)))?;
+        if !self.nonce.is_empty() {
+            for nonce in self.nonce {
+                if !(8..=64).contains(&nonce.len()) {
+                    return Err(Error::InvalidValue);
+                }
+            }
+            f(&Value::integer(NONCE), Claim::Nonce(self.nonce))?;
+        }
+        if let Some(ueid) = self.ueid {
+            if !(7..=33).contains(&ueid.len()) {
+                return Err(Error::InvalidValue);
+            }
+            f(&Value::integer(UEID), Claim::Value(&Value::Bytes(ueid)))?;
+        }
+        if let Some(oemid) = &self.oemid {
+            let value = match oemid {
+                OemId::Random(id) => Value::Bytes(id),
+                OemId::Ieee(oui) => Value::Bytes(oui),
+                OemId::Pen(pen) => Value::Unsigned(*pen),
+            };
+            f(&Value::integer(OEMID), Claim::Value(&value))?;
+        }
+        if let Some(hwmodel) = self.hwmodel {
+            f(
+                &Value::integer(HWMODEL),
+                Claim::Value(&Value::Bytes(hwmodel)),
+            )?;
+        }
+        if let Some(uptime) = self.uptime {
+            f(
+                &Value::integer(UPTIME),
+                Claim::Value(&Value::Unsigned(uptime)),
+            )?;
+        }
+        if let Some(dbgstat) = self.dbgstat {
+            let value = Value::Unsigned(dbgstat as u64);
+            f(&Value::integer(DBGSTAT), Claim::Value(&value))?;
+        }
+        if let Some(profile) = self.profile {
+            f(
+                &Value::integer(EAT_PROFILE),
+                Claim::Value(&Value::Text(profile)),
+            )?;
+        }
+        if !self.submods.is_empty() {
+            f(&Value::integer(SUBMODS), Claim::Submods(self.submods))?;
+        }
+        if let Some(bootcount) = self.bootcount {
+            let value = Value::Unsigned(bootcount);
+            f(&Value::integer(BOOTCOUNT), Claim::Value(&value))?;
+        }
+        if !self.measurements.is_empty() {
+            let claim = Claim::Measurements(self.measurements);
+            f(&Value::integer(MEASUREMENTS), claim)?;
+        }
+        for (key, value) in self.cwt.custom() {
+            if !cwt::is_registered(key) && !has_field(key) {
+                f(key, Claim::Value(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for EatClaims<'_> {
+    /// Encodes the claims map, including the claims of nested submodules.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidValue)` - If a nonce or the UEID has an invalid length.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+/// The value of an entry of the claims map, before encoding.
+enum Claim<'v> {
+    /// A claim that is a single value.
+    Value(&'v Value<'v>),
+    /// The `nonce` claim.
+    Nonce(&'v [&'v [u8]]),
+    /// The `submods` claim.
+    Submods(&'v [(&'v str, Submodule<'v>)]),
+    /// The `measurements` claim.
+    Measurements(&'v [Measurement<'v>]),
+}
+
+impl Claim<'_> {
+    /// Writes the claim value.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match self {
+            Self::Value(value) => sink.value(value),
+            Self::Nonce([nonce]) => sink.value(&Value::Bytes(nonce)),
+            Self::Nonce(nonces) => {
+                sink.header(major_type::ARRAY, nonces.len() as u64)?;
+                for nonce in *nonces {
+                    sink.value(&Value::Bytes(nonce))?;
+                }
+                Ok(())
+            }
+            Self::Submods(submods) => {
+                sink.header(major_type::MAP, submods.len() as u64)?;
+                for (name, submodule) in *submods {
+                    sink.value(&Value::Text(name))?;
+                    submodule.write(sink)?;
+                }
+                Ok(())
+            }
+            Self::Measurements(measurements) => {
+                sink.header(major_type::ARRAY, measurements.len() as u64)?;
+                for measurement in *measurements {
+                    measurement.write(sink)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns `true` if a map key is the label of a claim with a typed field.
+fn has_field(key: &Value) -> bool {
+    const LABELS: [i64; 10] = [
+        NONCE,
+        UEID,
+        OEMID,
+        HWMODEL,
+        UPTIME,
+        DBGSTAT,
+        EAT_PROFILE,
+        SUBMODS,
+        BOOTCOUNT,
+        MEASUREMENTS,
+    ];
+    matches!(key.as_i128(), Some(label) if LABELS.iter().any(|&known| known as i128 == label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DebugStatus, DetachedDigest, EatClaims, Measurement, OemId, Submodule};
+    use crate::{
+        Value, cbor,
+        cose::SHA_256,
+        cwt::CwtClaims,
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+    };
+
+    #[test]
+    fn test_device_claims() {
+        let custom = [
+            (Value::integer(260), Value::text("1.2")),
+            (Value::integer(263), Value::unsigned(0)),
+            (Value::integer(2), Value::text("ignored")),
+        ];
+        let cwt = CwtClaims::new()
+            .with_issuer("root-of-trust")
+            .with_issued_at(1700000000)
+            .with_custom(&custom);
+        let measurements = [Measurement::new(258, &[0xA1, 0x00, 0x01])];
+        let claims = EatClaims::new()
+            .with_cwt(cwt)
+            .with_nonce(&[&[0x4E; 8]])
+            .with_ueid(&[0x01, 1, 2, 3, 4, 5, 6])
+            .with_oemid(OemId::Ieee([0x00, 0x1B, 0x63]))
+            .with_hwmodel(b"board")
+            .with_uptime(42)
+            .with_dbgstat(DebugStatus::DisabledSinceBoot)
+            .with_profile("urn:example:profile")
+            .with_bootcount(7)
+            .with_measurements(&measurements);
+
+        static EXPECTED: Value = cbor!({
+            1: "root-of-trust",
+            6: 1700000000,
+            10: h "4E4E4E4E4E4E4E4E",
+            256: h "01010203040506",
+            258: h "001B63",
+            259: h "626F617264",
+            261: 42,
+            263: 2,
+            265: "urn:example:profile",
+            267: 7,
+            273: [[258, h "A10001"]],
+            260: "1.2"
+        });
+        let mut buf = [0u8; 256];
+        let size = claims.as_cbor(&mut buf).unwrap();
+        assert_encoding(&buf[..size], &EXPECTED);
+        assert_eq!(claims.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_submods() {
+        let nested = EatClaims::new().with_dbgstat(DebugStatus::Enabled);
+        let inner_submods = [("sensor", Submodule::Claims(&nested))];
+        let radio = EatClaims::new()
+            .with_oemid(OemId::Pen(32473))
+            .with_submods(&inner_submods);
+        let submods = [
+            ("radio", Submodule::Claims(&radio)),
+            ("tee", Submodule::Token(&[0xD8, 0x3D, 0xA0])),
+            (
+                "fw",
+                Submodule::Digest(DetachedDigest::new(SHA_256, &[0xAB; 4])),
+            ),
+        ];
+        let claims = EatClaims::new()
+            .with_nonce(&[&[0x01; 8], &[0x02; 8]])
+            .with_submods(&submods);
+
+        static EXPECTED: Value = cbor!({
+            10: [h "0101010101010101", h "0202020202020202"],
+            266: {
+                "radio": {258: 32473, 266: {"sensor": {263: 0}}},
+                "tee": h "D83DA0",
+                "fw": [-16, h "ABABABAB"]
+            }
+        });
+        let mut buf = [0u8; 256];
+        let size = claims.as_cbor(&mut buf).unwrap();
+        assert_encoding(&buf[..size], &EXPECTED);
+        assert_eq!(claims.encoded_size(), size);
+
+        let mut short = [0u8; 40];
+        assert_eq!(claims.as_cbor(&mut short), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        let mut buf = [0u8; 128];
+        let short_nonce = EatClaims::new().with_nonce(&[&[0; 7]]);
+        assert_eq!(short_nonce.as_cbor(&mut buf), Err(Error::InvalidValue));
+        let long_nonce = EatClaims::new().with_nonce(&[&[0; 8], &[0; 65]]);
+        assert_eq!(long_nonce.as_cbor(&mut buf), Err(Error::InvalidValue));
+        let short_ueid = EatClaims::new().with_ueid(&[0x01; 6]);
+        assert_eq!(short_ueid.as_cbor(&mut buf), Err(Error::InvalidValue));
+
+        // Invalid submodules are caught too
+        let submods = [("bad", Submodule::Claims(&short_ueid))];
+        let outer = EatClaims::new().with_submods(&submods);
+        assert_eq!(outer.as_cbor(&mut buf), Err(Error::InvalidValue));
+
+        assert_eq!(EatClaims::new().as_cbor(&mut buf), Ok(1));
+        assert_eq!(buf[0], 0xA0);
+    }
+
+    #[test]
+    fn test_decode_items() {
+        assert_eq!(
+            DebugStatus::try_from(&Value::unsigned(3)),
+            Ok(DebugStatus::DisabledPermanently)
+        );
+        assert_eq!(
+            DebugStatus::try_from(&Value::unsigned(5)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            DebugStatus::try_from(&Value::text("on")),
+            Err(Error::InvalidType)
+        );
+
+        assert_eq!(OemId::try_from(&Value::unsigned(7)), Ok(OemId::Pen(7)));
+        assert_eq!(
+            OemId::try_from(&Value::bytes(&[1, 2, 3])),
+            Ok(OemId::Ieee([1, 2, 3]))
+        );
+        assert_eq!(
+            OemId::try_from(&Value::bytes(&[9; 16])),
+            Ok(OemId::Random([9; 16]))
+        );
+        assert_eq!(
+            OemId::try_from(&Value::bytes(&[1, 2])),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            OemId::try_from(&Value::negative(-1)),
+            Err(Error::InvalidType)
+        );
+
+        static MEASUREMENT: Value = cbor!([258, h "A0"]);
+        assert_eq!(
+            Measurement::try_from(&MEASUREMENT),
+            Ok(Measurement::new(258, &[0xA0]))
+        );
+        static BIG_FORMAT: Value = cbor!([65536, h "A0"]);
+        assert_eq!(Measurement::try_from(&BIG_FORMAT), Err(Error::InvalidType));
+
+        static DIGEST: Value = cbor!([-16, h "ABCD"]);
+        let digest = DetachedDigest::try_from(&DIGEST).unwrap();
+        assert_eq!(digest.algorithm(), SHA_256);
+        assert_eq!(digest.digest(), &[0xAB, 0xCD]);
+        static TEXT_DIGEST: Value = cbor!([-16, "ABCD"]);
+        assert_eq!(
+            DetachedDigest::try_from(&TEXT_DIGEST),
+            Err(Error::InvalidType)
+        );
+    }
+}
@@ -25,12 +25,16 @@
 //! assert_eq!(buf[1], 42);
 //! ```
 
-use crate::{Value, encode::major_type::MajorType, result::Result};
+use crate::{Value, encode::major_type::MajorType, result::Result, tag};
 
 mod cursor;
 pub mod major_type;
+mod sink;
+#[cfg(test)]
+pub(crate) mod test_support;
 
-use cursor::Cursor;
+pub(crate) use cursor::Cursor;
+pub(crate) use sink::Sink;
 
 /// Trait for types that can be encoded as CBOR.
 ///
@@ -68,7 +72,7 @@ pub trait Encode<'a> {
 /// * An array of up to 8 additional bytes for the value (if needed).
 /// * The number of additional bytes used (0-8).
 #[inline]
-const fn encode_header(major: u8, value: u64) -> (u8, [u8; 8], usize) {
+pub(crate) const fn encode_header(major: u8, value: u64) -> (u8, [u8; 8], usize) {
     let major_shift = major << 5;
 
     if value <= 23 {
@@ -204,6 +208,8 @@ pub const fn encoded_size(value: &Value) -> usize {
 ///
 /// * `Ok(usize)` - The number of bytes written to the buffer.
 /// * `Err(Error::BufferOverflow)` - If the buffer is too small to hold the encoded data.
+/// * `Err(Error::InvalidType)` or `Err(Error::InvalidValue)` - If a tag 100 or 1004 does
+///   not hold a valid date.
 ///
 /// # Examples
 ///
@@ -218,25 +224,40 @@ pub const fn encoded_size(value: &Value) -> usize {
 /// assert_eq!(buf[1], 42);   // the value
 /// ```
 #[inline]
-pub fn encode(value: &Value, buf: &mut [u8]) -> Result<usize> {
+pub const fn encode(value: &Value, buf: &mut [u8]) -> Result<usize> {
     let mut cursor = Cursor::new(buf);
-    encode_value(value, &mut cursor)?;
+    const_try!(encode_value(value, &mut cursor));
     Ok(cursor.pos)
 }
 
 #[inline]
-fn write_header_with_extras(cursor: &mut Cursor, major_type: MajorType, value: u64) -> Result<()> {
+pub(crate) const fn write_header_with_extras(
+    cursor: &mut Cursor,
+    major_type: MajorType,
+    value: u64,
+) -> Result<()> {
     let (header, extra, len) = encode_header(major_type, value);
-    cursor.write_byte(header)?;
+    const_try!(cursor.write_byte(header));
     let mut i = 0;
     while i < len {
-        cursor.write_byte(extra[i])?;
+        const_try!(cursor.write_byte(extra[i]));
         i += 1;
     }
 
     Ok(())
 }
 
+/// Writes every byte of `bytes` to the cursor.
+#[inline]
+pub(crate) const fn write_all(cursor: &mut Cursor, bytes: &[u8]) -> Result<()> {
+    let mut i = 0;
+    while i < bytes.len() {
+        const_try!(cursor.write_byte(bytes[i]));
+        i += 1;
+    }
+    Ok(())
+}
+
 /// Internal function that encodes a CBOR value using a cursor.
 ///
 /// This function performs the actual encoding by writing bytes to the cursor based on
@@ -253,51 +274,64 @@ fn write_header_with_extras(cursor: &mut Cursor, major_type: MajorType, value: u
 /// * `Ok(())` - If the value was successfully encoded.
 /// * `Err(Error::BufferOverflow)` - If the cursor's buffer is too small.
 #[inline]
-fn encode_value(value: &Value, cursor: &mut Cursor) -> Result<()> {
+pub(crate) const fn encode_value(value: &Value, cursor: &mut Cursor) -> Result<()> {
     match value {
-        Value::Unsigned(n) => write_header_with_extras(cursor, major_type::UNSIGNED, *n)?,
-        Value::Negative(n) => write_header_with_extras(cursor, major_type::NEGATIVE, *n)?,
+        Value::Unsigned(n) => write_header_with_extras(cursor, major_type::UNSIGNED, *n),
+        Value::Negative(n) => write_header_with_extras(cursor, major_type::NEGATIVE, *n),
         Value::Bytes(bytes) => {
-            write_header_with_extras(cursor, major_type::BYTES, bytes.len() as u64)?;
-            for &byte in *bytes {
-                cursor.write_byte(byte)?;
-            }
+            const_try!(write_header_with_extras(
+                cursor,
+                major_type::BYTES,
+                bytes.len() as u64
+            ));
+            write_all(cursor, bytes)
         }
         Value::Text(text) => {
-            write_header_with_extras(cursor, major_type::TEXT, text.len() as u64)?;
-            for &byte in text.as_bytes() {
-                cursor.write_byte(byte)?;
-            }
+            const_try!(write_header_with_extras(
+                cursor,
+                major_type::TEXT,
+                text.len() as u64
+            ));
+            write_all(cursor, text.as_bytes())
         }
         Value::Array(items) => {
-            write_header_with_extras(cursor, major_type::ARRAY, items.len() as u64)?;
-            for value in *items {
-                encode_value(value, cursor)?;
+            const_try!(write_header_with_extras(
+                cursor,
+                major_type::ARRAY,
+                items.len() as u64
+            ));
+            let mut i = 0;
+            while i < items.len() {
+                const_try!(encode_value(&items[i], cursor));
+                i += 1;
             }
+            Ok(())
         }
         Value::Map(pairs) => {
-            write_header_with_extras(cursor, major_type::MAP, pairs.len() as u64)?;
-            for (key, value) in *pairs {
-                encode_value(key, cursor)?;
-                encode_value(value, cursor)?;
+            const_try!(write_header_with_extras(
+                cursor,
+                major_type::MAP,
+                pairs.len() as u64
+            ));
+            let mut i = 0;
+            while i < pairs.len() {
+                const_try!(encode_value(&pairs[i].0, cursor));
+                const_try!(encode_value(&pairs[i].1, cursor));
+                i += 1;
             }
+            Ok(())
         }
         Value::Tag(tag, item) => {
-            write_header_with_extras(cursor, major_type::TAG, *tag)?;
-            encode_value(item, cursor)?;
-        }
-        Value::Simple(s) => {
-            write_header_with_extras(cursor, major_type::SIMPLE, *s as u64)?;
+            const_try!(tag::check_content(*tag, item));
+            const_try!(write_header_with_extras(cursor, major_type::TAG, *tag));
+            encode_value(item, cursor)
         }
+        Value::Simple(s) => write_header_with_extras(cursor, major_type::SIMPLE, *s as u64),
         Value::Float(f) => {
-            cursor.write_byte(major_type::SIMPLE << 5 | 27)?;
-            let bytes = f.to_bits().to_be_bytes();
-            for byte in bytes {
-                cursor.write_byte(byte)?;
-            }
+            const_try!(cursor.write_byte(major_type::SIMPLE << 5 | 27));
+            write_all(cursor, &f.to_bits().to_be_bytes())
         }
     }
-    Ok(())
 }
 
 #[cfg(test)]
@@ -632,15 +666,15 @@ mod tests {
     // Test for float value encoding
     #[test]
     fn test_encode_float() {
-        let value = Value::float(3.14159);
+        let value = Value::float(1.1);
 
         let mut buf = [0u8; 16];
         let size = encode(&value, &mut buf).unwrap();
 
         assert_eq!(size, 9);
         assert_eq!(buf[0], 0xFB); // 0xE0 | 27 (IEEE 754 double-precision float)
-        // The next 8 bytes are the IEEE 754 encoding of 3.14159
-        let expected = 3.14159f64.to_bits().to_be_bytes();
+        // The next 8 bytes are the IEEE 754 encoding of 1.1
+        let expected = 1.1f64.to_bits().to_be_bytes();
         assert_eq!(&buf[1..9], &expected);
     }
 
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT
+
+//! Sink implementation for encoding typed structures.
+//!
+//! Typed structures such as EAT claims are encoded by walking their fields once. A
+//! `Sink` lets the same walk either count the encoded bytes, to find the encoded size,
+//! or write them to a buffer.
+
+use super::{Cursor, encode_header, encode_value, encoded_size, write_header_with_extras};
+use crate::{Value, encode::major_type::MajorType, error::Error, result::Result};
+
+/// Where encoded bytes go: either counted, to find their size, or written to a buffer.
+#[derive(Debug)]
+pub(crate) enum Sink<'c, 'b> {
+    /// Counts the encoded bytes.
+    Size(usize),
+    /// Writes the encoded bytes.
+    Buffer(&'c mut Cursor<'b>),
+}
+
+impl Sink<'_, '_> {
+    /// Counts the bytes written by `write`.
+    ///
+    /// Errors are ignored, so the size of a structure that fails to encode is the size of
+    /// the part before the failure.
+    pub(crate) fn size_of(write: impl FnOnce(&mut Sink) -> Result<()>) -> usize {
+        let mut sink = Sink::Size(0);
+        let _ = write(&mut sink);
+        match sink {
+            Sink::Size(size) => size,
+            Sink::Buffer(cursor) => cursor.pos,
+        }
+    }
+
+    /// Counts the bytes written by `write`, or returns the error `write` fails with.
+    pub(crate) fn checked_size_of(write: impl FnOnce(&mut Sink) -> Result<()>) -> Result<usize> {
+        let mut sink = Sink::Size(0);
+        write(&mut sink)?;
+        match sink {
+            Sink::Size(size) => Ok(size),
+            Sink::Buffer(cursor) => Ok(cursor.pos),
+        }
+    }
+
+    /// Writes the bytes written by `write` to `buf`, returning how many were written.
+    ///
+    /// This is the [`Encode`](super::Encode) counterpart of [`Sink::size_of`].
+    pub(crate) fn write_to(
+        buf: &mut [u8],
+        write: impl FnOnce(&mut Sink) -> Result<()>,
+    ) -> Result<usize> {
+        let mut cursor = Cursor::new(buf);
+        write(&mut Sink::Buffer(&mut cursor))?;
+        Ok(cursor.pos)
+    }
+
+    /// Writes a data item header.
+    ///
+    /// # Arguments
+    ///
+    /// * `major_type` - The major type of the data item.
+    /// * `value` - The argument of the header, such as the number of items.
+    pub(crate) fn header(&mut self, major_type: MajorType, value: u64) -> Result<()> {
+        match self {
+            Self::Size(size) => {
+                let (_, _, extra) = encode_header(major_type, value);
+                *size += 1 + extra;
+                Ok(())
+            }
+            Self::Buffer(cursor) => write_header_with_extras(cursor, major_type, value),
+        }
+    }
+
+    /// Writes a value.
+    pub(crate) fn value(&mut self, value: &Value) -> Result<()> {
+        match self {
+            Self::Size(size) => {
+                *size += encoded_size(value);
+                Ok(())
+            }
+            Self::Buffer(cursor) => encode_value(value, cursor),
+        }
+    }
+
+    /// Writes an item with its own encoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of bytes `encode` writes.
+    /// * `encode` - Encodes the item into the remaining buffer, returning the number of
+    ///   bytes written.
+    pub(crate) fn encoded(
+        &mut self,
+        size: usize,
+        encode: impl FnOnce(&mut [u8]) -> Result<usize>,
+    ) -> Result<()> {
+        match self {
+            Self::Size(total) => {
+                *total += size;
+                Ok(())
+            }
+            Self::Buffer(cursor) => {
+                let Some(rest) = cursor.data.get_mut(cursor.pos..) else {
+                    return Err(Error::BufferOverflow);
+                };
+                cursor.pos += encode(rest)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sink;
+    use crate::{
+        Value,
+        encode::{Cursor, encode, major_type},
+        error::Error,
+    };
+
+    #[test]
+    fn test_size_matches_buffer() {
+        let write = |sink: &mut Sink| {
+            sink.header(major_type::ARRAY, 2)?;
+            sink.value(&Value::unsigned(500))?;
+            sink.encoded(2, |buf| encode(&Value::text("a"), buf))
+        };
+
+        let mut buf = [0u8; 8];
+        assert_eq!(Sink::write_to(&mut buf, write), Ok(6));
+        assert_eq!(Sink::size_of(write), 6);
+        assert_eq!(&buf[..6], &[0x82, 0x19, 0x01, 0xF4, 0x61, b'a']);
+    }
+
+    #[test]
+    fn test_buffer_overflow() {
+        let mut buf = [0u8; 3];
+        let mut cursor = Cursor::new(&mut buf);
+        let mut sink = Sink::Buffer(&mut cursor);
+        assert_eq!(sink.value(&Value::unsigned(500)), Ok(()));
+        assert_eq!(
+            sink.encoded(2, |buf| encode(&Value::text("a"), buf)),
+            Err(Error::BufferOverflow)
+        );
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+
+//! Test helpers shared by the encoders built on [`Sink`](super::Sink).
+
+use super::encode;
+use crate::Value;
+
+/// Checks that `bytes` are exactly the encoding of `expected`.
+///
+/// Structures that write their own encoding are checked against the same data written
+/// as a [`Value`] tree, which is usually much easier to read than raw bytes.
+pub(crate) fn assert_encoding(bytes: &[u8], expected: &Value) {
+    let mut want = [0u8; 1024];
+    let size = encode(expected, &mut want).unwrap();
+    assert_eq!(bytes, &want[..size]);
+}
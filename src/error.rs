@@ -15,6 +15,50 @@ pub enum Error {
 
     /// The input contains an invalid or unsupported CBOR data type.
     InvalidType,
+
+    /// The input has the expected type, but its content is not valid for that type.
+    InvalidValue,
+
+    /// The input ended before a complete data item could be read.
+    UnexpectedEnd,
+
+    /// The input nests arrays, maps or tags more deeply than the decoder allows.
+    DepthExceeded,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let message = match self {
+            Self::BufferOverflow => "buffer too small",
+            Self::InvalidType => "invalid or unsupported CBOR type",
+            Self::InvalidValue => "invalid CBOR value",
+            Self::UnexpectedEnd => "unexpected end of input",
+            Self::DepthExceeded => "nesting depth limit exceeded",
+        };
+        f.write_str(message)
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    /// Reports a failure raised by a `Serialize` implementation as an invalid value.
+    fn custom<T: core::fmt::Display>(_msg: T) -> Self {
+        Self::InvalidValue
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    /// Reports a failure raised by a `Deserialize` implementation as an invalid value.
+    fn custom<T: core::fmt::Display>(_msg: T) -> Self {
+        Self::InvalidValue
+    }
+
+    fn invalid_type(_unexp: serde::de::Unexpected, _exp: &dyn serde::de::Expected) -> Self {
+        Self::InvalidType
+    }
 }
 
 #[cfg(test)]
@@ -45,7 +89,7 @@ mod tests {
     #[test]
     fn test_error_clone() {
         let err1 = Error::BufferOverflow;
-        let err2 = err1.clone();
+        let err2 = Clone::clone(&err1);
 
         assert_eq!(err1, err2);
     }
@@ -59,7 +103,7 @@ mod tests {
         assert!(ok_result.is_ok());
         assert!(err_result.is_err());
 
-        assert_eq!(ok_result.unwrap(), 42);
-        assert_eq!(err_result.unwrap_err(), Error::BufferOverflow);
+        assert_eq!(ok_result, Ok(42));
+        assert_eq!(err_result, Err(Error::BufferOverflow));
     }
 }
@@ -49,10 +49,49 @@
     variant_size_differences
 )]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// Returns early with the error of a failed `Result`, like `?` does outside `const fn`.
+macro_rules! const_try {
+    ($result:expr) => {
+        match $result {
+            Ok(value) => value,
+            Err(err) => return Err(err),
+        }
+    };
+}
+
+pub mod corim;
+pub mod cose;
+pub mod ctap2;
+pub mod cwt;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod decode;
+pub mod diag;
+pub mod dice;
+pub mod eat;
 pub mod encode;
 pub mod error;
+pub mod list;
+#[doc(hidden)]
+pub mod macros;
+pub mod path;
 pub mod result;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod suit;
+pub mod tag;
+pub mod webauthn;
 
+mod access;
+mod arena;
+#[cfg(feature = "alloc")]
+mod owned;
 mod value;
 
+pub use arena::*;
+#[cfg(feature = "alloc")]
+pub use owned::*;
 pub use value::*;
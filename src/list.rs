@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MIT
+
+//! Lists of typed items that are built from slices or decoded lazily.
+//!
+//! Typed structures such as CoMID triples contain lists of other typed structures.
+//! Without allocating, a decoded list cannot be converted up front, so a [`List`] either
+//! holds typed items given by the caller, or the decoded values, which are converted
+//! one by one as they are read.
+//!
+//! The fields of those structures are read with the helpers here, such as `text` and
+//! `required`, which treat a field of the wrong type as an error.
+
+use crate::{
+    Value,
+    encode::{Sink, major_type},
+    error::Error,
+    result::Result,
+    value::Map,
+};
+
+/// A list of items in a typed structure, such as the triples of a CoMID.
+///
+/// Built structures hold typed items. Decoded structures hold the decoded values, and
+/// each item is converted when it is read, so reading a decoded list can fail.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum List<'a, T> {
+    /// Typed items, as given when building a structure.
+    Items(&'a [T]),
+    /// Decoded items, as found when decoding a structure.
+    Values(&'a [Value<'a>]),
+}
+
+impl<T> Default for List<'_, T> {
+    fn default() -> Self {
+        Self::Items(&[])
+    }
+}
+
+impl<'a, T> List<'a, T>
+where
+    T: Copy + FromValue<'a>,
+{
+    /// Returns the item at `index`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Ok(T))` - The item.
+    /// * `Some(Err(Error))` - If a decoded item does not have the expected shape.
+    /// * `None` - If `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<Result<T>> {
+        match self {
+            Self::Items(items) => items.get(index).map(|item| Ok(*item)),
+            Self::Values(values) => values.get(index).map(T::from_value),
+        }
+    }
+
+    /// Returns an iterator over the items, converting decoded items as it goes.
+    pub fn iter(&self) -> impl Iterator<Item = Result<T>> + 'a {
+        let (items, values): (&[T], &[Value]) = match *self {
+            Self::Items(items) => (items, &[]),
+            Self::Values(values) => (&[], values),
+        };
+        items
+            .iter()
+            .map(|item| Ok(*item))
+            .chain(values.iter().map(T::from_value))
+    }
+}
+
+impl<'a, T> List<'a, T> {
+    /// Returns the number of items.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        match self {
+            Self::Items(items) => items.len(),
+            Self::Values(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if there are no items.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads a list from an optional field, which is empty if absent.
+    pub(crate) fn from_field(field: Option<&'a Value<'a>>) -> Result<Self> {
+        match field {
+            Some(value) => value.as_array().map(Self::Values).ok_or(Error::InvalidType),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Writes the list as an array.
+    pub(crate) fn write(&self, sink: &mut Sink) -> Result<()>
+    where
+        T: Item,
+    {
+        sink.header(major_type::ARRAY, self.len() as u64)?;
+        self.write_items(sink)
+    }
+
+    /// Writes the items without an array header, for arrays that start with other items.
+    pub(crate) fn write_items(&self, sink: &mut Sink) -> Result<()>
+    where
+        T: Item,
+    {
+        match self {
+            Self::Items(items) => items.iter().try_for_each(|item| item.write(sink)),
+            Self::Values(values) => values.iter().try_for_each(|value| sink.value(value)),
+        }
+    }
+}
+
+/// A typed item that is written to a [`Sink`] field by field.
+pub(crate) trait Item {
+    /// Writes the item.
+    fn write(&self, sink: &mut Sink) -> Result<()>;
+}
+
+impl Item for &[u8] {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.value(&Value::Bytes(self))
+    }
+}
+
+impl Item for &str {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.value(&Value::Text(self))
+    }
+}
+
+impl Item for u64 {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.value(&Value::Unsigned(*self))
+    }
+}
+
+mod private {
+    use crate::{Value, error::Error, result::Result};
+
+    /// Reads a list item from a decoded value.
+    ///
+    /// Typed structures are read with their `TryFrom` impl. Byte strings, text strings and
+    /// unsigned integers are read here instead, so that the crate does not add public
+    /// conversions from [`Value`] to types it does not own.
+    pub trait FromValue<'a>: Sized {
+        /// Reads the item, failing if the value does not have the expected shape.
+        fn from_value(value: &'a Value<'a>) -> Result<Self>;
+    }
+
+    impl<'a, T: TryFrom<&'a Value<'a>, Error = Error>> FromValue<'a> for T {
+        fn from_value(value: &'a Value<'a>) -> Result<Self> {
+            T::try_from(value)
+        }
+    }
+
+    impl<'a> FromValue<'a> for &'a [u8] {
+        /// Reads a byte string item, such as a segment of a SUIT component identifier.
+        fn from_value(value: &'a Value<'a>) -> Result<Self> {
+            value.as_bytes().ok_or(Error::InvalidType)
+        }
+    }
+
+    impl<'a> FromValue<'a> for &'a str {
+        /// Reads a text string item, such as a CTAP2 transport or version.
+        fn from_value(value: &'a Value<'a>) -> Result<Self> {
+            value.as_str().ok_or(Error::InvalidType)
+        }
+    }
+
+    impl<'a> FromValue<'a> for u64 {
+        /// Reads an unsigned integer item, such as a SUIT component index.
+        fn from_value(value: &'a Value<'a>) -> Result<Self> {
+            value.as_u64().ok_or(Error::InvalidType)
+        }
+    }
+}
+
+pub(crate) use private::FromValue;
+
+/// Returns a required field, or an error if it is missing.
+#[inline]
+pub(crate) fn required<T>(field: Option<T>) -> Result<T> {
+    field.ok_or(Error::InvalidType)
+}
+
+/// Returns the value if it is a map.
+pub(crate) fn fields<'v, 'a>(value: &'v Value<'a>) -> Result<&'v Value<'a>> {
+    match value {
+        Value::Map(_) => Ok(value),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Reads an optional byte string field.
+pub(crate) fn bytes<'a>(field: Option<&Value<'a>>) -> Result<Option<&'a [u8]>> {
+    match field {
+        Some(value) => value.as_bytes().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Reads an optional text string field.
+pub(crate) fn text<'a>(field: Option<&Value<'a>>) -> Result<Option<&'a str>> {
+    match field {
+        Some(value) => value.as_str().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Reads an optional unsigned integer field.
+pub(crate) fn unsigned(field: Option<&Value>) -> Result<Option<u64>> {
+    match field {
+        Some(value) => value.as_u64().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Reads an optional boolean field.
+pub(crate) fn boolean(field: Option<&Value>) -> Result<Option<bool>> {
+    match field {
+        Some(value) => value.as_bool().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Reads an optional map field.
+pub(crate) fn map<'a>(field: Option<&Value<'a>>) -> Result<Option<Map<'a>>> {
+    match field {
+        Some(value) => value.as_map().map(Some).ok_or(Error::InvalidType),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::List;
+    use crate::{Value, corim::TagId, error::Error};
+
+    #[test]
+    fn test_list() {
+        let items: List<TagId> = List::Items(&[TagId::Text("a"), TagId::Text("b")]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items.get(1), Some(Ok(TagId::Text("b"))));
+        assert_eq!(items.get(2), None);
+
+        static VALUES: [Value; 3] = [Value::text("a"), Value::unsigned(1), Value::bytes(&[0])];
+        let values: List<TagId> = List::Values(&VALUES);
+        let mut iter = values.iter();
+        assert_eq!(iter.next(), Some(Ok(TagId::Text("a"))));
+        assert_eq!(iter.next(), Some(Err(Error::InvalidType)));
+        assert_eq!(iter.next(), Some(Err(Error::InvalidValue)));
+        assert_eq!(iter.next(), None);
+        assert!(List::<TagId>::default().is_empty());
+    }
+
+    #[test]
+    fn test_primitive_items() {
+        static VALUES: [Value; 3] = [Value::bytes(&[1]), Value::unsigned(2), Value::text("c")];
+        let bytes: List<&[u8]> = List::Values(&VALUES);
+        let text: List<&str> = List::Values(&VALUES);
+        assert_eq!(text.get(2), Some(Ok("c")));
+        assert_eq!(text.get(0), Some(Err(Error::InvalidType)));
+        assert_eq!(bytes.get(0), Some(Ok(&[1][..])));
+        assert_eq!(bytes.get(1), Some(Err(Error::InvalidType)));
+
+        let unsigned: List<u64> = List::Values(&VALUES);
+        assert_eq!(unsigned.get(1), Some(Ok(2)));
+        assert_eq!(unsigned.get(2), Some(Err(Error::InvalidType)));
+    }
+}
@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: MIT
+
+//! Compile-time conversion of Rust literals for the [`cbor!`](crate::cbor) macro.
+//!
+//! A `macro_rules!` macro cannot tell a string literal from a number, so the macro passes
+//! each literal's source text through `stringify!` and these functions interpret it.
+//! They are only meant to be called from within `const` blocks, where any panic becomes a
+//! compile error pointing at the offending literal.
+
+use crate::Value;
+
+/// The largest integer that every `f64` below it can represent exactly.
+const MAX_EXACT_MANTISSA: u128 = 1 << 53;
+/// The largest power of ten that `f64` represents exactly.
+const MAX_EXACT_POWER: u32 = 22;
+
+/// Returns the number of bytes held by a string or byte string literal.
+///
+/// Other literals hold no bytes.
+pub const fn literal_len(source: &str) -> usize {
+    string_contents(source.as_bytes(), &mut [])
+}
+
+/// Returns the bytes held by a string or byte string literal, with escapes resolved.
+pub const fn literal_bytes<const N: usize>(source: &str) -> [u8; N] {
+    let mut out = [0u8; N];
+    let _ = string_contents(source.as_bytes(), &mut out);
+    out
+}
+
+/// Converts a literal into a `Value`.
+///
+/// # Arguments
+///
+/// * `source` - The literal's source text, as given by `stringify!`.
+/// * `bytes` - The result of [`literal_bytes`] for the same literal.
+pub const fn literal(source: &'static str, bytes: &'static [u8]) -> Value<'static> {
+    match source.as_bytes() {
+        [b'"' | b'r', ..] => match core::str::from_utf8(bytes) {
+            Ok(text) => Value::Text(text),
+            Err(_) => panic!("cbor!: text literal is not valid UTF-8"),
+        },
+        [b'b', b'"' | b'r', ..] => Value::Bytes(bytes),
+        [b'0'..=b'9', ..] => number(source, false),
+        _ => panic!("cbor!: unsupported literal"),
+    }
+}
+
+/// Converts the literal following a minus sign into a negative `Value`.
+pub const fn negative(source: &str) -> Value<'static> {
+    match source.as_bytes() {
+        [b'0'..=b'9', ..] => number(source, true),
+        _ => panic!("cbor!: only numbers can be negated"),
+    }
+}
+
+/// Converts a tag number literal.
+pub const fn tag(source: &str) -> u64 {
+    match source.as_bytes() {
+        [b'0'..=b'9', ..] => match number(source, false) {
+            Value::Unsigned(tag) => tag,
+            _ => panic!("cbor!: tag numbers must be unsigned integers"),
+        },
+        _ => panic!("cbor!: tag numbers must be unsigned integers"),
+    }
+}
+
+/// Checks a simple value number.
+pub const fn simple(value: u8) -> u8 {
+    if value >= 24 && value < 32 {
+        panic!("cbor!: simple values 24 to 31 are reserved");
+    }
+    value
+}
+
+/// Returns the number of bytes written by a hex string, which may contain whitespace.
+pub const fn hex_len(source: &str) -> usize {
+    hex_contents(source.as_bytes(), &mut [])
+}
+
+/// Returns the bytes written by a hex string.
+pub const fn hex<const N: usize>(source: &str) -> [u8; N] {
+    let mut out = [0u8; N];
+    let _ = hex_contents(source.as_bytes(), &mut out);
+    out
+}
+
+/// Decodes hex digits into `out`, or only counts them if `out` is too short.
+const fn hex_contents(source: &[u8], out: &mut [u8]) -> usize {
+    let mut len = 0;
+    let mut high = None;
+    let mut i = 0;
+    while i < source.len() {
+        let byte = source[i];
+        i += 1;
+        let digit = match byte {
+            b'0'..=b'9' => byte - b'0',
+            b'a'..=b'f' => byte - b'a' + 10,
+            b'A'..=b'F' => byte - b'A' + 10,
+            byte if byte.is_ascii_whitespace() => continue,
+            _ => panic!("cbor!: invalid hex digit"),
+        };
+        match high {
+            None => high = Some(digit),
+            Some(high_digit) => {
+                if len < out.len() {
+                    out[len] = (high_digit << 4) | digit;
+                }
+                len += 1;
+                high = None;
+            }
+        }
+    }
+    if high.is_some() {
+        panic!("cbor!: hex string has an odd number of digits");
+    }
+    len
+}
+
+/// Returns the range of a string literal's contents, and whether it is a raw string.
+const fn string_bounds(source: &[u8]) -> Option<(usize, usize, bool)> {
+    let mut start = match source {
+        [b'b', ..] => 1,
+        _ => 0,
+    };
+    let raw = start < source.len() && source[start] == b'r';
+    let mut hashes = 0;
+    if raw {
+        start += 1;
+        while start < source.len() && source[start] == b'#' {
+            hashes += 1;
+            start += 1;
+        }
+    }
+    if start >= source.len() || source[start] != b'"' {
+        return None;
+    }
+    Some((start + 1, source.len() - 1 - hashes, raw))
+}
+
+/// Resolves the contents of a string literal into `out`, or only counts them if `out` is
+/// too short.
+const fn string_contents(source: &[u8], out: &mut [u8]) -> usize {
+    let (mut i, end, raw) = match string_bounds(source) {
+        Some(bounds) => bounds,
+        None => return 0,
+    };
+
+    let mut len = 0;
+    while i < end {
+        let (bytes, count, next) = if raw || source[i] != b'\\' {
+            ([source[i], 0, 0, 0], 1, i + 1)
+        } else {
+            escape(source, i + 1)
+        };
+
+        let mut j = 0;
+        while j < count {
+            if len < out.len() {
+                out[len] = bytes[j];
+            }
+            len += 1;
+            j += 1;
+        }
+        i = next;
+    }
+    len
+}
+
+/// Resolves the escape sequence whose backslash precedes `i`.
+///
+/// Returns the UTF-8 bytes of the escaped character, how many of them are used, and the
+/// position after the sequence.
+const fn escape(source: &[u8], i: usize) -> ([u8; 4], usize, usize) {
+    let byte = match source[i] {
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'0' => 0,
+        b'\\' => b'\\',
+        b'\'' => b'\'',
+        b'"' => b'"',
+        b'x' => (hex_digit(source[i + 1]) << 4) | hex_digit(source[i + 2]),
+        b'u' => {
+            // \u{...} with up to six hex digits and optional underscores
+            let mut code = 0;
+            let mut j = i + 2;
+            while source[j] != b'}' {
+                if source[j] != b'_' {
+                    code = (code << 4) | hex_digit(source[j]) as u32;
+                }
+                j += 1;
+            }
+            let mut bytes = [0u8; 4];
+            let count = match char::from_u32(code) {
+                Some(c) => c.encode_utf8(&mut bytes).len(),
+                None => panic!("cbor!: invalid unicode escape"),
+            };
+            return (bytes, count, j + 1);
+        }
+        b'\n' | b'\r' => {
+            // A line continuation skips the line break and any leading whitespace
+            let mut j = i;
+            while j < source.len() && source[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            return ([0; 4], 0, j);
+        }
+        _ => panic!("cbor!: unsupported escape sequence"),
+    };
+    let next = if source[i] == b'x' { i + 3 } else { i + 1 };
+    ([byte, 0, 0, 0], 1, next)
+}
+
+/// Returns the value of a hex digit.
+const fn hex_digit(byte: u8) -> u8 {
+    match byte {
+        b'0'..=b'9' => byte - b'0',
+        b'a'..=b'f' => byte - b'a' + 10,
+        b'A'..=b'F' => byte - b'A' + 10,
+        _ => panic!("cbor!: invalid hex digit"),
+    }
+}
+
+/// Converts an integer or float literal, with an optional type suffix.
+///
+/// Floats are converted only when the result is exact, which holds for literals with up
+/// to 15 significant digits and a decimal exponent of at most 22 either way.
+const fn number(source: &str, negative: bool) -> Value<'static> {
+    let s = source.as_bytes();
+    let (radix, mut i) = match s {
+        [b'0', b'x', ..] => (16, 2),
+        [b'0', b'o', ..] => (8, 2),
+        [b'0', b'b', ..] => (2, 2),
+        _ => (10, 0),
+    };
+
+    let mut mantissa: u128 = 0;
+    let mut scale: i32 = 0;
+    let mut fraction = false;
+    let mut float = false;
+    while i < s.len() {
+        let byte = s[i];
+        let digit = match byte {
+            b'0'..=b'9' => (byte - b'0') as u32,
+            b'a'..=b'f' if radix == 16 => (byte - b'a' + 10) as u32,
+            b'A'..=b'F' if radix == 16 => (byte - b'A' + 10) as u32,
+            _ => u32::MAX,
+        };
+
+        if digit < radix {
+            mantissa = match mantissa.checked_mul(radix as u128) {
+                Some(shifted) => match shifted.checked_add(digit as u128) {
+                    Some(mantissa) => mantissa,
+                    None => panic!("cbor!: integer literal is too large"),
+                },
+                None => panic!("cbor!: integer literal is too large"),
+            };
+            if fraction {
+                scale -= 1;
+            }
+        } else if byte == b'.' && radix == 10 {
+            fraction = true;
+            float = true;
+        } else if byte != b'_' {
+            break;
+        }
+        i += 1;
+    }
+
+    if radix == 10 && i < s.len() && (s[i] == b'e' || s[i] == b'E') {
+        float = true;
+        i += 1;
+        let exponent_negative = s[i] == b'-';
+        if s[i] == b'-' || s[i] == b'+' {
+            i += 1;
+        }
+        let mut exponent: i32 = 0;
+        while i < s.len() && (s[i].is_ascii_digit() || s[i] == b'_') {
+            if s[i] != b'_' {
+                exponent = exponent
+                    .saturating_mul(10)
+                    .saturating_add((s[i] - b'0') as i32);
+            }
+            i += 1;
+        }
+        scale += if exponent_negative {
+            -exponent
+        } else {
+            exponent
+        };
+    }
+
+    // What remains is a type suffix such as `u8` or `f32`
+    let single = s.len() - i == 3 && s[i] == b'f' && s[i + 1] == b'3';
+    if i < s.len() && s[i] == b'f' {
+        float = true;
+    }
+
+    if float {
+        if mantissa > MAX_EXACT_MANTISSA || scale.unsigned_abs() > MAX_EXACT_POWER {
+            panic!("cbor!: float literal is not exactly representable; use (Value::float(..))");
+        }
+        let mut power = 1.0;
+        let mut k = 0;
+        while k < scale.unsigned_abs() {
+            power *= 10.0;
+            k += 1;
+        }
+        let mut value = if scale < 0 {
+            mantissa as f64 / power
+        } else {
+            mantissa as f64 * power
+        };
+        if single {
+            value = value as f32 as f64;
+        }
+        return Value::Float(if negative { -value } else { value });
+    }
+
+    if !negative {
+        if mantissa > u64::MAX as u128 {
+            panic!("cbor!: integer literal is too large");
+        }
+        Value::Unsigned(mantissa as u64)
+    } else if mantissa == 0 {
+        Value::Unsigned(0)
+    } else {
+        if mantissa - 1 > u64::MAX as u128 {
+            panic!("cbor!: integer literal is too small");
+        }
+        Value::Negative((mantissa - 1) as u64)
+    }
+}
@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: MIT
+
+//! Macros for writing CBOR data inline.
+//!
+//! The functions in this module are implementation details of the macros, and are only
+//! public so that macro expansions in other crates can reach them.
+
+use crate::{Value, encode::encode};
+
+mod literal;
+
+pub use literal::*;
+
+/// Encodes a value into an array of exactly its encoded size, for [`cbor_bytes!`](crate::cbor_bytes!).
+pub const fn encode_array<const N: usize>(value: &Value) -> [u8; N] {
+    let mut buf = [0u8; N];
+    match encode(value, &mut buf) {
+        Ok(len) if len == N => buf,
+        Ok(_) => panic!("cbor_bytes!: encoded size does not match encoded_size"),
+        Err(_) => panic!("cbor_bytes!: value cannot be encoded"),
+    }
+}
+
+/// Builds a [`Value`](crate::Value) tree from JSON-like syntax.
+///
+/// Every nested array, map and tagged item is placed in a `const` block, so the result
+/// borrows only `'static` data and can initialize `const` and `static` items without any
+/// named temporaries. For the same reason, every part of the input must be a constant.
+///
+/// The syntax follows CBOR diagnostic notation where Rust's tokenizer allows:
+///
+/// * `null`, `undefined`, `true`, `false` and `simple(n)`.
+/// * Integer and float literals, optionally negated. Floats must convert to `f64` exactly,
+///   which holds for up to 15 significant digits and a decimal exponent of at most 22.
+/// * String literals as text, and byte string literals such as `b"\x01\x02"` as bytes.
+/// * `h "0102"` for a byte string written in hex. The space is required, since Rust
+///   reserves prefixed literals such as `h"0102"`.
+/// * Arrays `[a, b]`, maps `{key: value}` with any item as a key, and tags such as
+///   `1(1700000000)`.
+/// * A parenthesized constant expression of type `Value`, such as `(Value::float(1e300))`
+///   or `(OTHER_VALUE)`, which is used as is.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, cbor, encode::encode};
+///
+/// static DEVICE: Value = cbor!({
+///     "name": "Alex",
+///     "tags": ["rust", "cbor"],
+///     1: h "0102",
+///     -1: [1.5, null, 0("2013-03-21T20:04:00Z")],
+/// });
+///
+/// let mut buf = [0u8; 128];
+/// assert!(encode(&DEVICE, &mut buf).is_ok());
+/// assert_eq!(
+///     DEVICE.to_string(),
+///     r#"{"name": "Alex", "tags": ["rust", "cbor"], 1: h'0102', -1: [1.5, null, 0("2013-03-21T20:04:00Z")]}"#
+/// );
+///
+/// // The same tree built by hand needs a named array for every level
+/// const TAGS: [Value; 2] = [Value::text("rust"), Value::text("cbor")];
+/// assert_eq!(cbor!(["rust", "cbor"]), Value::array(&TAGS));
+/// ```
+#[macro_export]
+macro_rules! cbor {
+    // Array items, accumulated one token at a time up to each comma
+    (@array [$($done:expr,)*] []) => {
+        [$($done,)*]
+    };
+    (@array [$($done:expr,)*] [$($item:tt)+]) => {
+        [$($done,)* $crate::cbor!($($item)+)]
+    };
+    (@array [$($done:expr,)*] [$($item:tt)+] , $($rest:tt)*) => {
+        $crate::cbor!(@array [$($done,)* $crate::cbor!($($item)+),] [] $($rest)*)
+    };
+    (@array [$($done:expr,)*] [$($item:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::cbor!(@array [$($done,)*] [$($item)* $next] $($rest)*)
+    };
+
+    // Map keys, accumulated up to each colon
+    (@key [$($done:tt)*] []) => {
+        [$($done)*]
+    };
+    (@key [$($done:tt)*] [$($key:tt)+]) => {
+        compile_error!("cbor!: expected `:` after a map key")
+    };
+    (@key [$($done:tt)*] [$($key:tt)+] : $($rest:tt)*) => {
+        $crate::cbor!(@value [$($done)*] [$($key)+] [] $($rest)*)
+    };
+    (@key [$($done:tt)*] [$($key:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::cbor!(@key [$($done)*] [$($key)* $next] $($rest)*)
+    };
+
+    // Map values, accumulated up to each comma
+    (@value [$($done:tt)*] [$($key:tt)+] []) => {
+        compile_error!("cbor!: expected a value after `:`")
+    };
+    (@value [$($done:tt)*] [$($key:tt)+] [$($value:tt)+]) => {
+        [$($done)* ($crate::cbor!($($key)+), $crate::cbor!($($value)+))]
+    };
+    (@value [$($done:tt)*] [$($key:tt)+] [$($value:tt)+] , $($rest:tt)*) => {
+        $crate::cbor!(
+            @key [$($done)* ($crate::cbor!($($key)+), $crate::cbor!($($value)+)),] [] $($rest)*
+        )
+    };
+    (@value [$($done:tt)*] [$($key:tt)+] [$($value:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::cbor!(@value [$($done)*] [$($key)+] [$($value)* $next] $($rest)*)
+    };
+
+    (null) => {
+        $crate::Value::Simple(22)
+    };
+    (undefined) => {
+        $crate::Value::Simple(23)
+    };
+    (true) => {
+        $crate::Value::Simple(21)
+    };
+    (false) => {
+        $crate::Value::Simple(20)
+    };
+    (simple($value:expr)) => {
+        $crate::Value::Simple(const { $crate::macros::simple($value) })
+    };
+    (h $hex:literal) => {
+        $crate::Value::Bytes(const {
+            const SOURCE: &str = $hex;
+            const LEN: usize = $crate::macros::hex_len(SOURCE);
+            const BYTES: [u8; LEN] = $crate::macros::hex::<LEN>(SOURCE);
+            &BYTES
+        })
+    };
+    ([$($items:tt)*]) => {
+        $crate::Value::Array(const { &$crate::cbor!(@array [] [] $($items)*) })
+    };
+    ({$($entries:tt)*}) => {
+        $crate::Value::Map(const { &$crate::cbor!(@key [] [] $($entries)*) })
+    };
+    (- $number:literal) => {
+        const { $crate::macros::negative(stringify!($number)) }
+    };
+    ($tag:literal ($($item:tt)+)) => {
+        $crate::Value::Tag(
+            const { $crate::macros::tag(stringify!($tag)) },
+            const { &$crate::cbor!($($item)+) },
+        )
+    };
+    (($value:expr)) => {
+        $value
+    };
+    ($literal:literal) => {
+        const {
+            const SOURCE: &str = stringify!($literal);
+            const LEN: usize = $crate::macros::literal_len(SOURCE);
+            const BYTES: [u8; LEN] = $crate::macros::literal_bytes::<LEN>(SOURCE);
+            $crate::macros::literal(SOURCE, &BYTES)
+        }
+    };
+}
+
+/// Encodes a [`cbor!`] tree at compile time, expanding to a `[u8; N]` of its bytes.
+///
+/// The input uses the same syntax as [`cbor!`]. The size comes from
+/// [`encoded_size`](crate::encode::encoded_size) and the bytes from
+/// [`encode`](crate::encode::encode), both evaluated in a `const` block, so invalid input
+/// fails to compile and nothing is left to run at runtime.
+///
+/// Since the length is only known after encoding, a `const` or `static` item can hold a
+/// reference to the array rather than naming its type.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::cbor_bytes;
+///
+/// static DESCRIPTOR: &[u8] = &cbor_bytes!({"id": 7, "name": "sensor", "data": h "0102"});
+///
+/// assert_eq!(DESCRIPTOR.len(), 25);
+/// assert_eq!(cbor_bytes!([1, -1, true]), [0x83, 0x01, 0x20, 0xF5]);
+/// ```
+#[macro_export]
+macro_rules! cbor_bytes {
+    ($($value:tt)+) => {
+        const {
+            const VALUE: $crate::Value<'static> = $crate::cbor!($($value)+);
+            const LEN: usize = $crate::encode::encoded_size(&VALUE);
+            const BYTES: [u8; LEN] = $crate::macros::encode_array::<LEN>(&VALUE);
+            BYTES
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Value, encode::encode};
+
+    const ITEMS: [Value; 3] = [Value::unsigned(1), Value::text("two"), Value::null()];
+    static TABLE: [Value; 2] = [cbor!([1, 2]), cbor!({"a": -1})];
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(cbor!(0), Value::unsigned(0));
+        assert_eq!(cbor!(18446744073709551615), Value::Unsigned(u64::MAX));
+        assert_eq!(cbor!(-18446744073709551616), Value::Negative(u64::MAX));
+        assert_eq!(cbor!(-1), Value::negative(-1));
+        assert_eq!(cbor!(-0), Value::unsigned(0));
+        assert_eq!(cbor!(0x1F), Value::unsigned(31));
+        assert_eq!(cbor!(0o17), Value::unsigned(15));
+        assert_eq!(cbor!(0b101u8), Value::unsigned(5));
+        assert_eq!(cbor!(1_000_000), Value::unsigned(1_000_000));
+
+        assert_eq!(cbor!(1.5), Value::float(1.5));
+        assert_eq!(cbor!(-0.1), Value::float(-0.1));
+        assert_eq!(cbor!(1e10), Value::float(1e10));
+        assert_eq!(cbor!(2.5E-3), Value::float(2.5e-3));
+        assert_eq!(cbor!(100_000.000_1), Value::float(100_000.000_1));
+        assert_eq!(cbor!(3f64), Value::float(3.0));
+        assert_eq!(cbor!(0.1f32), Value::float(0.1f32 as f64));
+        assert_eq!(cbor!((Value::float(1e300))), Value::float(1e300));
+
+        assert_eq!(cbor!(null), Value::null());
+        assert_eq!(cbor!(undefined), Value::Simple(23));
+        assert_eq!(cbor!(true), Value::bool(true));
+        assert_eq!(cbor!(false), Value::bool(false));
+        assert_eq!(cbor!(simple(16)), Value::Simple(16));
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(cbor!(""), Value::text(""));
+        assert_eq!(cbor!("hello"), Value::text("hello"));
+        assert_eq!(cbor!("a\"b\\c\n\t\0"), Value::text("a\"b\\c\n\t\0"));
+        assert_eq!(
+            cbor!("\x41\u{fc}\u{1_F600}"),
+            Value::text("A\u{fc}\u{1F600}")
+        );
+        assert_eq!(cbor!("ü"), Value::text("ü"));
+        assert_eq!(
+            cbor!(
+                "line \
+                 continued"
+            ),
+            Value::text("line continued")
+        );
+        assert_eq!(cbor!(r"raw\n"), Value::text("raw\\n"));
+        assert_eq!(cbor!(r#"say "hi""#), Value::text("say \"hi\""));
+
+        assert_eq!(cbor!(b"\x01\xff'"), Value::bytes(&[0x01, 0xFF, b'\'']));
+        assert_eq!(cbor!(br"\x01"), Value::bytes(b"\\x01"));
+        assert_eq!(cbor!(h ""), Value::bytes(&[]));
+        assert_eq!(cbor!(h "01 02 aB"), Value::bytes(&[0x01, 0x02, 0xAB]));
+    }
+
+    #[test]
+    fn test_nested() {
+        assert_eq!(cbor!([]), Value::array(&[]));
+        assert_eq!(cbor!({}), Value::map(&[]));
+        assert_eq!(cbor!([1, "two", null,]), Value::array(&ITEMS));
+        assert_eq!(cbor!([(ITEMS[0]), (ITEMS[1]), null]), Value::array(&ITEMS));
+
+        let inner = [Value::unsigned(3), Value::negative(-4)];
+        let pairs = [
+            (Value::negative(-1), Value::bytes(&[0x01])),
+            (Value::text("x"), Value::array(&inner)),
+        ];
+        assert_eq!(cbor!({-1: h "01", "x": [3, -4]}), Value::map(&pairs));
+
+        let time = Value::unsigned(1_700_000_000);
+        let tagged = Value::tag(1, &time);
+        assert_eq!(cbor!(1(1_700_000_000)), tagged);
+        assert_eq!(cbor!(55799(1(1700000000))), Value::tag(55799, &tagged));
+
+        assert_eq!(TABLE[0], cbor!([1, 2]));
+        let pairs = [(Value::text("a"), Value::negative(-1))];
+        assert_eq!(TABLE[1], Value::map(&pairs));
+    }
+
+    #[test]
+    fn test_bytes() {
+        const EMPTY: [u8; 1] = cbor_bytes!([]);
+        static TABLE: [&[u8]; 2] = [&cbor_bytes!(h "ff"), &cbor_bytes!({1: -2})];
+
+        assert_eq!(EMPTY, [0x80]);
+        assert_eq!(TABLE, [&[0x41, 0xFF][..], &[0xA1, 0x01, 0x21][..]]);
+        assert_eq!(cbor_bytes!(1.5), [0xFB, 0x3F, 0xF8, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            cbor_bytes!(0("2013-03-21T20:04:00Z")),
+            *b"\xC0\x742013-03-21T20:04:00Z"
+        );
+
+        let document = cbor!({"a": [1, 2], "b": b"\x00"});
+        let mut buf = [0u8; 16];
+        let size = encode(&document, &mut buf).unwrap();
+        assert_eq!(cbor_bytes!({"a": [1, 2], "b": b"\x00"}), buf[..size]);
+    }
+
+    #[test]
+    fn test_encode() {
+        const DOCUMENT: Value = cbor!({"a": [1, {"b": h "ff"}], 2: 24(b"")});
+
+        let mut buf = [0u8; 16];
+        let size = encode(&DOCUMENT, &mut buf).unwrap();
+        assert_eq!(
+            &buf[..size],
+            &[
+                0xA2, 0x61, 0x61, 0x82, 0x01, 0xA1, 0x61, 0x62, 0x41, 0xFF, 0x02, 0xD8, 0x18, 0x40
+            ]
+        );
+    }
+}
@@ -0,0 +1,422 @@
+// SPDX-License-Identifier: MIT
+
+//! Owned CBOR value representation.
+//!
+//! This module provides the `OwnedValue` enum, a counterpart to [`Value`] that owns its
+//! strings and child nodes through `Vec`, `String` and `Box`. It is available with the
+//! `alloc` feature, for host-side tools that build trees dynamically or need a decoded
+//! tree to outlive its input.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::{
+    Arena, Value,
+    encode::{self, Cursor, Encode, major_type},
+    result::Result,
+    tag,
+};
+
+/// Represents a CBOR value that owns all of its data.
+///
+/// Each variant mirrors the [`Value`] variant of the same name. An `OwnedValue` can be
+/// created from any `Value`, and borrowed back as a `Value` using an [`Arena`] for the
+/// child nodes.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Arena, OwnedValue, Value, decode::decode_owned, encode::Encode};
+///
+/// // Build a tree at runtime
+/// let mut items = Vec::new();
+/// for n in 1..=3 {
+///     items.push(OwnedValue::Unsigned(n));
+/// }
+/// let value = OwnedValue::Array(items);
+///
+/// let mut buf = [0u8; 8];
+/// let size = value.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..size], &[0x83, 0x01, 0x02, 0x03]);
+///
+/// // The decoded tree does not borrow from `buf`
+/// let decoded = decode_owned(&buf[..size]).unwrap();
+/// assert_eq!(decoded, value);
+///
+/// // Borrow it back as a `Value`
+/// let mut values = [Value::null(); 3];
+/// let mut arena = Arena::new(&mut values, &mut []);
+/// let borrowed = decoded.as_value(&mut arena).unwrap();
+/// assert_eq!(borrowed, Value::array(&[1, 2, 3].map(Value::unsigned)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedValue {
+    /// Unsigned integer (major type 0).
+    Unsigned(u64),
+
+    /// Negative integer (major type 1), stored as the absolute value minus one.
+    Negative(u64),
+
+    /// Byte string (major type 2).
+    Bytes(Vec<u8>),
+
+    /// UTF-8 text string (major type 3).
+    Text(String),
+
+    /// Array of CBOR data items (major type 4).
+    Array(Vec<OwnedValue>),
+
+    /// Map of pairs of CBOR data items (major type 5).
+    Map(Vec<(OwnedValue, OwnedValue)>),
+
+    /// Tagged value (major type 6), consisting of a tag number and the tagged item.
+    Tag(u64, Box<OwnedValue>),
+
+    /// Simple value (major type 7), including true, false, null, and undefined.
+    Simple(u8),
+
+    /// IEEE 754 Double-Precision Float (major type 7).
+    Float(f64),
+}
+
+impl OwnedValue {
+    /// Borrows this tree as a [`Value`].
+    ///
+    /// Strings are borrowed directly; every array item and tagged item takes one value
+    /// slot from `arena`, and every map entry takes one pair slot. [`arena_size`]
+    /// reports how many of each are needed.
+    ///
+    /// [`arena_size`]: OwnedValue::arena_size
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Value)` - The borrowed tree.
+    /// * `Err(Error::BufferOverflow)` - If the arena is too small.
+    pub fn as_value<'a>(&'a self, arena: &mut Arena<'a>) -> Result<Value<'a>> {
+        let value = match self {
+            Self::Array(items) => {
+                let slots = arena.alloc_values(items.len())?;
+                for (slot, item) in slots.iter_mut().zip(items) {
+                    *slot = item.as_value(arena)?;
+                }
+                Value::Array(slots)
+            }
+            Self::Map(pairs) => {
+                let slots = arena.alloc_pairs(pairs.len())?;
+                for (slot, (key, value)) in slots.iter_mut().zip(pairs) {
+                    *slot = (key.as_value(arena)?, value.as_value(arena)?);
+                }
+                Value::Map(slots)
+            }
+            Self::Tag(tag, item) => {
+                let item = item.as_value(arena)?;
+                Value::Tag(*tag, arena.alloc(item)?)
+            }
+            _ => self.leaf(),
+        };
+        Ok(value)
+    }
+
+    /// Returns the number of value slots and map entry slots needed by [`as_value`].
+    ///
+    /// [`as_value`]: OwnedValue::as_value
+    pub fn arena_size(&self) -> (usize, usize) {
+        match self {
+            Self::Array(items) => items.iter().fold((items.len(), 0), |acc, item| {
+                let (values, pairs) = item.arena_size();
+                (acc.0 + values, acc.1 + pairs)
+            }),
+            Self::Map(entries) => entries.iter().fold((0, entries.len()), |acc, (k, v)| {
+                let (key_values, key_pairs) = k.arena_size();
+                let (value_values, value_pairs) = v.arena_size();
+                (
+                    acc.0 + key_values + value_values,
+                    acc.1 + key_pairs + value_pairs,
+                )
+            }),
+            Self::Tag(_, item) => {
+                let (values, pairs) = item.arena_size();
+                (values + 1, pairs)
+            }
+            _ => (0, 0),
+        }
+    }
+
+    /// Converts this tree into a `Value<'static>` by leaking its allocations.
+    ///
+    /// The memory is never freed, so this is intended for long-lived trees such as
+    /// configuration loaded once at startup.
+    pub fn leak(self) -> Value<'static> {
+        match self {
+            Self::Bytes(bytes) => Value::Bytes(bytes.leak()),
+            Self::Text(text) => Value::Text(text.leak()),
+            Self::Array(items) => {
+                let items: Vec<Value<'static>> = items.into_iter().map(Self::leak).collect();
+                Value::Array(items.leak())
+            }
+            Self::Map(pairs) => {
+                let pairs: Vec<(Value<'static>, Value<'static>)> = pairs
+                    .into_iter()
+                    .map(|(key, value)| (key.leak(), value.leak()))
+                    .collect();
+                Value::Map(pairs.leak())
+            }
+            Self::Tag(tag, item) => Value::Tag(tag, Box::leak(Box::new(item.leak()))),
+            Self::Unsigned(n) => Value::Unsigned(n),
+            Self::Negative(n) => Value::Negative(n),
+            Self::Simple(s) => Value::Simple(s),
+            Self::Float(f) => Value::Float(f),
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode this value in CBOR format.
+    ///
+    /// This matches [`encode::encoded_size`] for the equivalent [`Value`].
+    pub fn encoded_size(&self) -> usize {
+        match self {
+            Self::Array(items) => {
+                let (_, _, extra) = encode::encode_header(major_type::ARRAY, items.len() as u64);
+                items.iter().map(Self::encoded_size).sum::<usize>() + 1 + extra
+            }
+            Self::Map(pairs) => {
+                let (_, _, extra) = encode::encode_header(major_type::MAP, pairs.len() as u64);
+                pairs
+                    .iter()
+                    .map(|(key, value)| key.encoded_size() + value.encoded_size())
+                    .sum::<usize>()
+                    + 1
+                    + extra
+            }
+            Self::Tag(tag, item) => {
+                let (_, _, extra) = encode::encode_header(major_type::TAG, *tag);
+                1 + extra + item.encoded_size()
+            }
+            _ => encode::encoded_size(&self.leaf()),
+        }
+    }
+
+    /// Encodes this value into a newly allocated byte vector.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = alloc::vec![0u8; self.encoded_size()];
+        // The buffer is sized exactly, so encoding cannot overflow
+        let size = self.as_cbor(&mut buf).unwrap_or_default();
+        debug_assert_eq!(size, buf.len());
+        buf
+    }
+
+    /// Writes this value to the cursor, recursing into child nodes.
+    fn encode(&self, cursor: &mut Cursor) -> Result<()> {
+        match self {
+            Self::Array(items) => {
+                encode::write_header_with_extras(cursor, major_type::ARRAY, items.len() as u64)?;
+                for item in items {
+                    item.encode(cursor)?;
+                }
+                Ok(())
+            }
+            Self::Map(pairs) => {
+                encode::write_header_with_extras(cursor, major_type::MAP, pairs.len() as u64)?;
+                for (key, value) in pairs {
+                    key.encode(cursor)?;
+                    value.encode(cursor)?;
+                }
+                Ok(())
+            }
+            Self::Tag(tag, item) => {
+                tag::check_content(*tag, &item.leaf())?;
+                encode::write_header_with_extras(cursor, major_type::TAG, *tag)?;
+                item.encode(cursor)
+            }
+            _ => encode::encode_value(&self.leaf(), cursor),
+        }
+    }
+
+    /// Borrows a value without child nodes as a [`Value`].
+    ///
+    /// Arrays, maps and tags have no borrowed form without an arena and map to null.
+    pub(crate) fn leaf(&self) -> Value<'_> {
+        match self {
+            Self::Unsigned(n) => Value::Unsigned(*n),
+            Self::Negative(n) => Value::Negative(*n),
+            Self::Bytes(bytes) => Value::Bytes(bytes),
+            Self::Text(text) => Value::Text(text),
+            Self::Simple(s) => Value::Simple(*s),
+            Self::Float(f) => Value::Float(*f),
+            Self::Array(_) | Self::Map(_) | Self::Tag(..) => Value::null(),
+        }
+    }
+}
+
+impl<'a> Encode<'a> for OwnedValue {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        let mut cursor = Cursor::new(buf);
+        self.encode(&mut cursor)?;
+        Ok(cursor.pos)
+    }
+}
+
+impl From<&Value<'_>> for OwnedValue {
+    fn from(value: &Value<'_>) -> Self {
+        match *value {
+            Value::Unsigned(n) => Self::Unsigned(n),
+            Value::Negative(n) => Self::Negative(n),
+            Value::Bytes(bytes) => Self::Bytes(bytes.into()),
+            Value::Text(text) => Self::Text(text.into()),
+            Value::Array(items) => Self::Array(items.iter().map(Self::from).collect()),
+            Value::Map(pairs) => Self::Map(
+                pairs
+                    .iter()
+                    .map(|(key, value)| (Self::from(key), Self::from(value)))
+                    .collect(),
+            ),
+            Value::Tag(tag, item) => Self::Tag(tag, Box::new(Self::from(item))),
+            Value::Simple(s) => Self::Simple(s),
+            Value::Float(f) => Self::Float(f),
+        }
+    }
+}
+
+impl From<Value<'_>> for OwnedValue {
+    fn from(value: Value<'_>) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl PartialEq<Value<'_>> for OwnedValue {
+    fn eq(&self, other: &Value<'_>) -> bool {
+        match (self, other) {
+            (Self::Array(items), Value::Array(others)) => {
+                items.len() == others.len() && items.iter().zip(others.iter()).all(|(a, b)| a == b)
+            }
+            (Self::Map(pairs), Value::Map(others)) => {
+                pairs.len() == others.len()
+                    && pairs
+                        .iter()
+                        .zip(others.iter())
+                        .all(|((k, v), (ok, ov))| k == ok && v == ov)
+            }
+            (Self::Tag(tag, item), Value::Tag(other_tag, other)) => {
+                tag == other_tag && **item == **other
+            }
+            (Self::Array(_) | Self::Map(_) | Self::Tag(..), _) => false,
+            _ => self.leaf() == *other,
+        }
+    }
+}
+
+impl PartialEq<OwnedValue> for Value<'_> {
+    fn eq(&self, other: &OwnedValue) -> bool {
+        other == self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OwnedValue;
+    use crate::{
+        Arena, Value,
+        decode::decode_owned,
+        encode::{Encode, encode, encoded_size},
+        error::Error,
+    };
+    use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+    /// A borrowed tree exercising every variant.
+    fn with_sample<R>(f: impl FnOnce(Value) -> R) -> R {
+        let blob = Value::bytes(&[0xDE, 0xAD]);
+        let colors = [Value::text("blue"), Value::text("green")];
+        let items = [
+            (Value::text("name"), Value::text("River")),
+            (Value::text("age"), Value::negative(-7)),
+            (Value::text("big"), Value::unsigned(u64::MAX)),
+            (Value::text("colors"), Value::array(&colors)),
+            (Value::text("blob"), Value::tag(24, &blob)),
+            (Value::text("ratio"), Value::float(0.25)),
+            (Value::text("admin"), Value::bool(false)),
+        ];
+        f(Value::map(&items))
+    }
+
+    #[test]
+    fn test_round_trip_value() {
+        with_sample(|value| {
+            let owned = OwnedValue::from(&value);
+            assert_eq!(owned, value);
+            assert_eq!(value, owned);
+            assert_eq!(owned.arena_size(), (3, 7));
+
+            let mut values = [Value::null(); 3];
+            let mut pairs = [(Value::null(), Value::null()); 7];
+            let mut arena = Arena::new(&mut values, &mut pairs);
+            assert_eq!(owned.as_value(&mut arena), Ok(value));
+            assert_eq!(arena.remaining_values(), 0);
+            assert_eq!(arena.remaining_pairs(), 0);
+        });
+    }
+
+    #[test]
+    fn test_as_value_overflow() {
+        let owned = OwnedValue::Tag(1, Box::new(OwnedValue::Unsigned(0)));
+        let mut arena = Arena::new(&mut [], &mut []);
+        assert_eq!(owned.as_value(&mut arena), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_encode_matches_value() {
+        with_sample(|value| {
+            let owned = OwnedValue::from(value);
+
+            let mut expected = [0u8; 96];
+            let size = encode(&value, &mut expected).unwrap();
+            assert_eq!(owned.encoded_size(), encoded_size(&value));
+            assert_eq!(owned.to_vec(), &expected[..size]);
+
+            let mut buf = [0u8; 96];
+            assert_eq!(owned.as_cbor(&mut buf), Ok(size));
+            assert_eq!(&buf[..size], &expected[..size]);
+
+            let mut small = [0u8; 4];
+            assert_eq!(owned.as_cbor(&mut small), Err(Error::BufferOverflow));
+        });
+    }
+
+    #[test]
+    fn test_decode_owned() {
+        let bytes = with_sample(|value| OwnedValue::from(value).to_vec());
+        let decoded = decode_owned(&bytes).unwrap();
+        drop(bytes);
+
+        with_sample(|value| assert_eq!(decoded, value));
+        assert_eq!(decode_owned(&[0x82, 0x01]), Err(Error::UnexpectedEnd));
+        assert_eq!(decode_owned(&[0x01, 0x02]), Err(Error::InvalidValue));
+
+        // 100(h'00') is not a valid date
+        assert_eq!(
+            decode_owned(&[0xD8, 0x64, 0x41, 0x00]),
+            Err(Error::InvalidType)
+        );
+        let owned = OwnedValue::Tag(100, Box::new(OwnedValue::Bytes(vec![0])));
+        assert_eq!(owned.as_cbor(&mut [0u8; 8]), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_leak() {
+        let owned = OwnedValue::Map(vec![(
+            OwnedValue::Text(String::from("key")),
+            OwnedValue::Tag(
+                2,
+                Box::new(OwnedValue::Array(vec![OwnedValue::Bytes(Vec::from([1u8]))])),
+            ),
+        )]);
+        let value: Value<'static> = owned.clone().leak();
+        assert_eq!(owned, value);
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let owned = OwnedValue::Array(vec![OwnedValue::Unsigned(1)]);
+        assert_ne!(owned, Value::array(&[]));
+        assert_ne!(owned, Value::array(&[Value::unsigned(2)]));
+        assert_ne!(owned, Value::null());
+        assert_ne!(OwnedValue::Simple(22), Value::array(&[]));
+    }
+}
@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: MIT
+
+//! Path queries in the style of JSON Pointer (RFC 6901).
+//!
+//! A pointer is a sequence of reference tokens, each preceded by `/`, such as
+//! `/address/city` or `/tags/1`. The empty pointer refers to the whole value. Within a
+//! token, `~1` stands for `/` and `~0` for `~`.
+//!
+//! Each token selects an item of an array by its index, or a value of a map by its key.
+//! A map key matches if it is a text string equal to the token, or an integer equal to
+//! the token read as a decimal number, so `/1/-7` reaches the value under the integer
+//! key `-7` inside the value under key `1`. Tags are looked through, so a pointer into a
+//! tagged array or map steps into the tagged item.
+//!
+//! Queries work on [`Value`] trees through [`Value::pointer`], and on encoded bytes
+//! through [`pointer()`], which skips past unrelated items without decoding them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{cbor, cbor_bytes, path::pointer};
+//!
+//! let value = cbor!({"address": {"city": "Techville"}, "tags": ["rust", "cbor"]});
+//! let city = value.pointer("/address/city").and_then(|city| city.as_str());
+//! assert_eq!(city, Some("Techville"));
+//! assert_eq!(value.pointer("/tags/2"), None);
+//!
+//! // The same query on the encoded form returns the bytes of the item
+//! let bytes = cbor_bytes!({1: {-7: 42}});
+//! assert_eq!(pointer(&bytes, "/1/-7"), Ok(Some(&[0x18, 0x2A][..])));
+//! ```
+
+use crate::{
+    Value,
+    decode::{Header, MAX_DEPTH, Reader, nested, read_header, skip, skip_contents},
+    result::Result,
+};
+
+/// A single reference token, with its `~0` and `~1` escapes still in place.
+#[derive(Debug, Clone, Copy)]
+struct Token<'p> {
+    text: &'p str,
+    /// The value of the token read as a decimal integer, if it is one.
+    integer: Option<i128>,
+}
+
+impl<'p> Token<'p> {
+    fn new(text: &'p str) -> Self {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        // Like array indices in RFC 6901, integers must not have leading zeros
+        let canonical = match digits.as_bytes() {
+            [b'0'] => digits.len() == text.len(),
+            [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+            _ => false,
+        };
+        Self {
+            text,
+            integer: canonical.then(|| text.parse().ok()).flatten(),
+        }
+    }
+
+    /// Returns the array index named by the token.
+    fn index(&self) -> Option<usize> {
+        usize::try_from(self.integer?).ok()
+    }
+
+    /// Returns `true` if the token names the given integer key.
+    fn matches_integer(&self, key: i128) -> bool {
+        self.integer == Some(key)
+    }
+
+    /// Returns `true` if the token, once unescaped, equals the given text key.
+    fn matches_text(&self, key: &str) -> bool {
+        let mut key = key.bytes();
+        let mut token = self.text.bytes();
+        while let Some(byte) = token.next() {
+            let byte = match byte {
+                b'~' => match token.next() {
+                    Some(b'0') => b'~',
+                    Some(b'1') => b'/',
+                    _ => return false,
+                },
+                byte => byte,
+            };
+            if key.next() != Some(byte) {
+                return false;
+            }
+        }
+        key.next().is_none()
+    }
+
+    /// Returns `true` if the token names the given map key.
+    fn matches(&self, key: &Value) -> bool {
+        match key {
+            Value::Text(text) => self.matches_text(text),
+            _ => key.as_i128().is_some_and(|n| self.matches_integer(n)),
+        }
+    }
+}
+
+/// Splits a pointer into its reference tokens.
+///
+/// Returns `None` if the pointer is neither empty nor starts with `/`.
+fn tokens(path: &str) -> Option<impl Iterator<Item = Token<'_>>> {
+    if !path.is_empty() && !path.starts_with('/') {
+        return None;
+    }
+    Some(path.split('/').skip(1).map(Token::new))
+}
+
+impl<'a> Value<'a> {
+    /// Looks up a nested value by a JSON Pointer-style path.
+    ///
+    /// See the [`path`](crate::path) module for the syntax.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Value)` - The value the path refers to.
+    /// * `None` - If the path is malformed or any step along it is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::Value;
+    ///
+    /// let items = [Value::text("rust"), Value::text("cbor")];
+    /// let pairs = [(Value::text("tags"), Value::array(&items))];
+    /// let map = Value::map(&pairs);
+    /// assert_eq!(map.pointer("/tags/1"), Some(&Value::text("cbor")));
+    /// assert_eq!(map.pointer(""), Some(&map));
+    /// assert_eq!(map.pointer("tags"), None);
+    /// ```
+    pub fn pointer(&self, path: &str) -> Option<&Value<'a>> {
+        let mut value = self;
+        for token in tokens(path)? {
+            value = match value.untagged() {
+                Value::Array(items) => items.get(token.index()?)?,
+                Value::Map(pairs) => pairs
+                    .iter()
+                    .find(|(key, _)| token.matches(key))
+                    .map(|(_, value)| value)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+/// Looks up a nested item in encoded bytes by a JSON Pointer-style path.
+///
+/// Only the items along the path, and those that precede them in their arrays and maps,
+/// are read; everything else is left unchecked. Items that are skipped over are not
+/// decoded, so no arena is needed. The returned bytes can be passed to
+/// [`decode`](crate::decode::decode).
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded data item.
+/// * `path` - The pointer, as described in the [module documentation](self).
+///
+/// # Returns
+///
+/// * `Ok(Some(&[u8]))` - The encoded item the path refers to.
+/// * `Ok(None)` - If the path is malformed or any step along it is missing.
+/// * `Err(Error)` - If an item that had to be read is malformed, as for
+///   [`decode`](crate::decode::decode).
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::path::pointer;
+///
+/// // {"a": [1, 2]}
+/// let bytes = [0xA1, 0x61, 0x61, 0x82, 0x01, 0x02];
+/// assert_eq!(pointer(&bytes, "/a/1"), Ok(Some(&[0x02][..])));
+/// assert_eq!(pointer(&bytes, "/b"), Ok(None));
+/// ```
+pub fn pointer<'a>(bytes: &'a [u8], path: &str) -> Result<Option<&'a [u8]>> {
+    let mut reader = Reader::new(bytes);
+    let mut depth = MAX_DEPTH;
+    let Some(tokens) = tokens(path) else {
+        return Ok(None);
+    };
+    for token in tokens {
+        depth = match step(&mut reader, &token, depth)? {
+            Some(depth) => depth,
+            None => return Ok(None),
+        };
+    }
+
+    let start = reader.pos;
+    skip(&mut reader, depth)?;
+    Ok(Some(&bytes[start..reader.pos]))
+}
+
+/// Moves the reader from the start of an item to the start of the child named by `token`.
+///
+/// # Returns
+///
+/// * `Ok(Some(usize))` - The depth remaining at the child.
+/// * `Ok(None)` - If the item has no such child.
+/// * `Err(Error)` - If the item is malformed.
+fn step(reader: &mut Reader, token: &Token, mut depth: usize) -> Result<Option<usize>> {
+    let mut header = read_header(reader)?;
+    while let Header::Tag(_) = header {
+        depth = nested(depth)?;
+        header = read_header(reader)?;
+    }
+
+    match header {
+        Header::Array(len) => {
+            let depth = nested(depth)?;
+            let index = match token.index() {
+                Some(index) if index < len => index,
+                _ => return Ok(None),
+            };
+            for _ in 0..index {
+                skip(reader, depth)?;
+            }
+            Ok(Some(depth))
+        }
+        Header::Map(len) => {
+            let depth = nested(depth)?;
+            for _ in 0..len {
+                let key = read_header(reader)?;
+                let found = match key {
+                    Header::Text(text) => token.matches_text(text),
+                    Header::Unsigned(n) => token.matches_integer(n as i128),
+                    Header::Negative(n) => token.matches_integer(-1 - n as i128),
+                    _ => false,
+                };
+                skip_contents(reader, key, depth)?;
+                if found {
+                    return Ok(Some(depth));
+                }
+                skip(reader, depth)?;
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Token, pointer};
+    use crate::{Arena, Value, cbor, cbor_bytes, decode::decode, error::Error};
+
+    static DOCUMENT: Value = cbor!({
+        "name": "Alex",
+        "address": {"city": "Techville"},
+        "tags": ["rust", "cbor"],
+        "a/b": 1,
+        "m~n": 2,
+        "": 3,
+        1: {-7: h "01", 4: 32(["x"])},
+        "10": "text ten",
+        10: "integer ten",
+    });
+
+    static ENCODED: &[u8] = &cbor_bytes!({
+        "name": "Alex",
+        "address": {"city": "Techville"},
+        "tags": ["rust", "cbor"],
+        "a/b": 1,
+        "m~n": 2,
+        "": 3,
+        1: {-7: h "01", 4: 32(["x"])},
+        "10": "text ten",
+        10: "integer ten",
+    });
+
+    /// Looks up `path` in both forms of the document and checks that they agree.
+    fn both(path: &str) -> Option<Value<'static>> {
+        let value = DOCUMENT.pointer(path).copied();
+        let bytes = pointer(ENCODED, path).unwrap();
+
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = bytes.map(|bytes| decode(bytes, &mut arena).unwrap());
+        assert_eq!(decoded, value, "{}", path);
+        value
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(Token::new("0").index(), Some(0));
+        assert_eq!(Token::new("12").index(), Some(12));
+        assert_eq!(Token::new("-7").integer, Some(-7));
+        assert_eq!(Token::new("-7").index(), None);
+        assert_eq!(Token::new("01").integer, None);
+        assert_eq!(Token::new("-0").integer, None);
+        assert_eq!(Token::new("1a").integer, None);
+        assert_eq!(Token::new("").integer, None);
+        assert!(Token::new("a~1b").matches_text("a/b"));
+        assert!(Token::new("m~0n").matches_text("m~n"));
+        assert!(!Token::new("a~2b").matches_text("a~2b"));
+        assert!(!Token::new("ab").matches_text("abc"));
+    }
+
+    #[test]
+    fn test_lookups() {
+        assert_eq!(both(""), Some(DOCUMENT));
+        assert_eq!(both("/name"), Some(Value::text("Alex")));
+        assert_eq!(both("/address/city"), Some(Value::text("Techville")));
+        assert_eq!(both("/tags/1"), Some(Value::text("cbor")));
+        assert_eq!(both("/a~1b"), Some(Value::unsigned(1)));
+        assert_eq!(both("/m~0n"), Some(Value::unsigned(2)));
+        assert_eq!(both("/"), Some(Value::unsigned(3)));
+        assert_eq!(both("/1/-7"), Some(Value::bytes(&[0x01])));
+
+        // Tags are looked through
+        assert_eq!(both("/1/4/0"), Some(Value::text("x")));
+
+        // The first key that matches wins, whether text or integer
+        assert_eq!(both("/10"), Some(Value::text("text ten")));
+    }
+
+    #[test]
+    fn test_missing() {
+        assert_eq!(both("name"), None);
+        assert_eq!(both("/nope"), None);
+        assert_eq!(both("/tags/2"), None);
+        assert_eq!(both("/tags/01"), None);
+        assert_eq!(both("/tags/-1"), None);
+        assert_eq!(both("/tags/x"), None);
+        assert_eq!(both("/name/0"), None);
+        assert_eq!(both("/1/-8"), None);
+        assert_eq!(both("/address/city/"), None);
+    }
+
+    #[test]
+    fn test_encoded_errors() {
+        // Items before the target must be well-formed, but those after it are not read
+        assert_eq!(pointer(&[0x82, 0x01, 0xFF], "/0"), Ok(Some(&[0x01][..])));
+        assert_eq!(pointer(&[0x82, 0xFC, 0x01], "/1"), Err(Error::InvalidValue));
+        assert_eq!(pointer(&[0x82, 0x01], "/1"), Err(Error::UnexpectedEnd));
+        assert_eq!(pointer(&[0x81, 0x5F, 0xFF], "/0"), Err(Error::InvalidType));
+
+        // Deeply nested items are rejected rather than overflowing the stack
+        let mut deep = [0x81; 200];
+        deep[199] = 0x00;
+        assert_eq!(pointer(&deep, ""), Err(Error::DepthExceeded));
+    }
+}
@@ -0,0 +1,676 @@
+// SPDX-License-Identifier: MIT
+
+//! Serde serialization into a fixed buffer.
+//!
+//! This module provides a `no_std` [`Serializer`] that writes CBOR directly into a
+//! caller-supplied byte slice, without building an intermediate [`Value`](crate::Value)
+//! tree. It is available with the `serde` feature.
+//!
+//! The serde data model is mapped onto CBOR as follows:
+//!
+//! * Booleans, integers, floats, strings and byte strings use the matching major type.
+//!   Integers beyond the 64-bit range are written as bignums (tags 2 and 3), and `f32`
+//!   values keep single precision.
+//! * `None`, `()` and unit structs are written as null, while `Some` and newtype structs
+//!   are written as the value they hold.
+//! * Sequences, tuples and tuple structs are arrays; maps are maps; structs are maps
+//!   keyed by field name.
+//! * Unit variants are written as the variant name. Other variants are single-entry maps
+//!   from the variant name to the content.
+//!
+//! Sequences and maps of unknown length are still written with a definite length, by
+//! shifting their content once the number of items is known.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::ser::to_slice;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Reading {
+//!     sensor: u8,
+//!     celsius: i16,
+//! }
+//!
+//! let mut buf = [0u8; 32];
+//! let size = to_slice(&Reading { sensor: 3, celsius: -4 }, &mut buf).unwrap();
+//! assert_eq!(
+//!     &buf[..size],
+//!     &[
+//!         0xA2, // Map with 2 pairs
+//!         0x66, 0x73, 0x65, 0x6E, 0x73, 0x6F, 0x72, 0x03, // "sensor": 3
+//!         0x67, 0x63, 0x65, 0x6C, 0x73, 0x69, 0x75, 0x73, 0x23, // "celsius": -4
+//!     ]
+//! );
+//! ```
+
+use serde::ser::{self, Serialize};
+
+use crate::{
+    encode::{
+        self, Cursor,
+        major_type::{self, MajorType},
+    },
+    error::Error,
+    result::Result,
+    tag,
+};
+
+/// Serializes a value into a byte buffer.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize.
+/// * `buf` - The buffer to write the encoded data into.
+///
+/// # Returns
+///
+/// * `Ok(usize)` - The number of bytes written to the buffer.
+/// * `Err(Error::BufferOverflow)` - If the buffer is too small to hold the encoded data.
+/// * `Err(Error::InvalidValue)` - If the `Serialize` implementation reports an error or
+///   produces a different number of items than it announced.
+#[inline]
+pub fn to_slice<T: ?Sized + Serialize>(value: &T, buf: &mut [u8]) -> Result<usize> {
+    let mut serializer = Serializer::new(buf);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.position())
+}
+
+/// A serde serializer that writes CBOR into a fixed buffer.
+///
+/// Several values can be written back to back, producing a CBOR sequence.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::ser::Serializer;
+/// use serde::Serialize;
+///
+/// let mut buf = [0u8; 8];
+/// let mut serializer = Serializer::new(&mut buf);
+/// (1u8, "a").serialize(&mut serializer).unwrap();
+/// assert_eq!(serializer.position(), 4);
+/// assert_eq!(&buf[..4], &[0x82, 0x01, 0x61, 0x61]);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Serializer<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl<'a> Serializer<'a> {
+    /// Creates a serializer that writes from the start of the provided buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The buffer to write the encoded data into.
+    #[inline]
+    pub const fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            cursor: Cursor::new(buf),
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.cursor.pos
+    }
+
+    /// Writes an item header with the given major type and argument.
+    #[inline]
+    fn header(&mut self, major: MajorType, value: u64) -> Result<()> {
+        encode::write_header_with_extras(&mut self.cursor, major, value)
+    }
+
+    /// Writes a signed integer as major type 0 or 1.
+    #[inline]
+    fn integer(&mut self, value: i64) -> Result<()> {
+        if value < 0 {
+            self.header(major_type::NEGATIVE, !value as u64)
+        } else {
+            self.header(major_type::UNSIGNED, value as u64)
+        }
+    }
+
+    /// Writes an unsigned magnitude, falling back to a bignum beyond 64 bits.
+    ///
+    /// `major` selects major type 0 or 1, and `bignum` the matching tag.
+    fn wide_integer(&mut self, major: MajorType, bignum: u64, value: u128) -> Result<()> {
+        if let Ok(value) = u64::try_from(value) {
+            return self.header(major, value);
+        }
+
+        let bytes = value.to_be_bytes();
+        let magnitude = &bytes[value.leading_zeros() as usize / 8..];
+        self.header(major_type::TAG, bignum)?;
+        self.header(major_type::BYTES, magnitude.len() as u64)?;
+        encode::write_all(&mut self.cursor, magnitude)
+    }
+
+    /// Writes a text string.
+    #[inline]
+    fn text(&mut self, value: &str) -> Result<()> {
+        self.header(major_type::TEXT, value.len() as u64)?;
+        encode::write_all(&mut self.cursor, value.as_bytes())
+    }
+
+    /// Writes the single-entry map header and key that introduce an enum variant's content.
+    #[inline]
+    fn variant(&mut self, variant: &str) -> Result<()> {
+        self.header(major_type::MAP, 1)?;
+        self.text(variant)
+    }
+
+    /// Starts an array or map, writing its header now if the length is known.
+    fn compound<'s>(
+        &'s mut self,
+        major: MajorType,
+        len: Option<usize>,
+    ) -> Result<Compound<'s, 'a>> {
+        let start = match len {
+            Some(len) => {
+                self.header(major, len as u64)?;
+                None
+            }
+            None => Some(self.cursor.pos),
+        };
+
+        Ok(Compound {
+            ser: self,
+            major,
+            start,
+            expected: len.map(|len| len as u64),
+            count: 0,
+        })
+    }
+
+    /// Inserts a header in front of content already written from `start`.
+    ///
+    /// This is used once the length of an item is known only after writing it.
+    fn insert_header(&mut self, start: usize, major: MajorType, value: u64) -> Result<()> {
+        let (header, extra, len) = encode::encode_header(major, value);
+        let end = self.cursor.pos;
+        let shift = 1 + len;
+
+        if end + shift > self.cursor.data.len() {
+            return Err(Error::BufferOverflow);
+        }
+
+        self.cursor.data.copy_within(start..end, start + shift);
+        self.cursor.data[start] = header;
+        self.cursor.data[start + 1..start + shift].copy_from_slice(&extra[..len]);
+        self.cursor.pos = end + shift;
+        Ok(())
+    }
+}
+
+/// Adapts the cursor to `core::fmt::Write` for [`ser::Serializer::collect_str`].
+struct TextWriter<'c, 'a>(&'c mut Cursor<'a>);
+
+impl core::fmt::Write for TextWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        encode::write_all(self.0, s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl<'s, 'a> ser::Serializer for &'s mut Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'s, 'a>;
+    type SerializeTuple = Compound<'s, 'a>;
+    type SerializeTupleStruct = Compound<'s, 'a>;
+    type SerializeTupleVariant = Compound<'s, 'a>;
+    type SerializeMap = Compound<'s, 'a>;
+    type SerializeStruct = Compound<'s, 'a>;
+    type SerializeStructVariant = Compound<'s, 'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.header(major_type::SIMPLE, if v { 21 } else { 20 })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.integer(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.integer(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.integer(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.integer(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        if v < 0 {
+            self.wide_integer(major_type::NEGATIVE, tag::NEGATIVE_BIGNUM, !v as u128)
+        } else {
+            self.wide_integer(major_type::UNSIGNED, tag::POSITIVE_BIGNUM, v as u128)
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.header(major_type::UNSIGNED, v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.header(major_type::UNSIGNED, v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.header(major_type::UNSIGNED, v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.header(major_type::UNSIGNED, v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.wide_integer(major_type::UNSIGNED, tag::POSITIVE_BIGNUM, v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.cursor.write_byte(major_type::SIMPLE << 5 | 26)?;
+        encode::write_all(&mut self.cursor, &v.to_bits().to_be_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.cursor.write_byte(major_type::SIMPLE << 5 | 27)?;
+        encode::write_all(&mut self.cursor, &v.to_bits().to_be_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.text(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.text(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.header(major_type::BYTES, v.len() as u64)?;
+        encode::write_all(&mut self.cursor, v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.header(major_type::SIMPLE, 22)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.text(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.variant(variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'s, 'a>> {
+        self.compound(major_type::ARRAY, len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'s, 'a>> {
+        self.compound(major_type::ARRAY, Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'s, 'a>> {
+        self.compound(major_type::ARRAY, Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'s, 'a>> {
+        self.variant(variant)?;
+        self.compound(major_type::ARRAY, Some(len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'s, 'a>> {
+        self.compound(major_type::MAP, len)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'s, 'a>> {
+        self.compound(major_type::MAP, Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'s, 'a>> {
+        self.variant(variant)?;
+        self.compound(major_type::MAP, Some(len))
+    }
+
+    fn collect_str<T: ?Sized + core::fmt::Display>(self, value: &T) -> Result<()> {
+        use core::fmt::Write;
+
+        let start = self.cursor.pos;
+        if write!(TextWriter(&mut self.cursor), "{}", value).is_err() {
+            return Err(Error::BufferOverflow);
+        }
+        let len = (self.cursor.pos - start) as u64;
+        self.insert_header(start, major_type::TEXT, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializes the items of an array or the entries of a map.
+///
+/// Returned by the [`Serializer`] for sequences, tuples, maps, structs and the content of
+/// non-unit enum variants.
+#[derive(Debug)]
+pub struct Compound<'s, 'a> {
+    ser: &'s mut Serializer<'a>,
+    major: MajorType,
+    /// Where the content starts, if the header is written only once the length is known.
+    start: Option<usize>,
+    /// The number of items or entries announced up front, if any.
+    expected: Option<u64>,
+    /// The number of items or entries written so far.
+    count: u64,
+}
+
+impl Compound<'_, '_> {
+    /// Writes the next array item or map value.
+    #[inline]
+    fn item<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut *self.ser)
+    }
+
+    /// Finishes the array or map, checking or inserting its length.
+    fn end(self) -> Result<()> {
+        match (self.start, self.expected) {
+            (Some(start), _) => self.ser.insert_header(start, self.major, self.count),
+            (None, Some(expected)) if expected == self.count => Ok(()),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeMap for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.count += 1;
+        self.item(key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.count += 1;
+        self.ser.text(key)?;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.count += 1;
+        self.ser.text(key)?;
+        self.item(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Serializer, to_slice};
+    use crate::error::Error;
+    use serde::{Serialize, Serializer as _, ser::SerializeSeq};
+
+    /// Serializes `value` and checks the output against `expected`.
+    fn check<T: Serialize>(value: T, expected: &[u8]) {
+        let mut buf = [0u8; 128];
+        let size = to_slice(&value, &mut buf).unwrap();
+        assert_eq!(&buf[..size], expected);
+    }
+
+    #[test]
+    fn test_primitives() {
+        check(true, &[0xF5]);
+        check(false, &[0xF4]);
+        check(0u8, &[0x00]);
+        check(500u16, &[0x19, 0x01, 0xF4]);
+        check(
+            u64::MAX,
+            &[0x1B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        );
+        check(-1i8, &[0x20]);
+        check(-500i32, &[0x39, 0x01, 0xF3]);
+        check(
+            i64::MIN,
+            &[0x3B, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        );
+        check(1.5f32, &[0xFA, 0x3F, 0xC0, 0x00, 0x00]);
+        check(
+            1.1f64,
+            &[0xFB, 0x3F, 0xF1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9A],
+        );
+        check('\u{00fc}', &[0x62, 0xC3, 0xBC]);
+        check("IETF", &[0x64, 0x49, 0x45, 0x54, 0x46]);
+    }
+
+    #[test]
+    fn test_wide_integers() {
+        check(42u128, &[0x18, 0x2A]);
+        check(-42i128, &[0x38, 0x29]);
+        check(
+            -(1i128 << 64),
+            &[0x3B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        );
+        check(
+            1u128 << 64,
+            &[
+                0xC2, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+        );
+        check(
+            -(1i128 << 64) - 1,
+            &[
+                0xC3, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unit_and_options() {
+        check((), &[0xF6]);
+        check(None::<u8>, &[0xF6]);
+        check(Some(7u8), &[0x07]);
+    }
+
+    #[test]
+    fn test_sequences() {
+        check([1u8, 2, 3], &[0x83, 0x01, 0x02, 0x03]);
+        check((1u8, "a"), &[0x82, 0x01, 0x61, 0x61]);
+        check::<&[u8]>(&[], &[0x80]);
+    }
+
+    #[test]
+    fn test_unknown_length() {
+        let mut buf = [0u8; 26];
+        let mut serializer = Serializer::new(&mut buf);
+        let mut seq = (&mut serializer).serialize_seq(None).unwrap();
+        for item in 0..24u8 {
+            seq.serialize_element(&item).unwrap();
+        }
+        seq.end().unwrap();
+        assert_eq!(serializer.position(), 26);
+        assert_eq!(&buf[..3], &[0x98, 24, 0x00]);
+        assert_eq!(buf[25], 23);
+
+        // The header no longer fits once the items are written
+        let mut buf = [0u8; 25];
+        let mut serializer = Serializer::new(&mut buf);
+        let mut seq = (&mut serializer).serialize_seq(None).unwrap();
+        for item in 0..24u8 {
+            seq.serialize_element(&item).unwrap();
+        }
+        assert_eq!(seq.end(), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let mut buf = [0u8; 8];
+        let mut serializer = Serializer::new(&mut buf);
+        let mut seq = (&mut serializer).serialize_seq(Some(2)).unwrap();
+        seq.serialize_element(&1u8).unwrap();
+        assert_eq!(seq.end(), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn test_collect_str() {
+        let mut buf = [0u8; 8];
+        let mut serializer = Serializer::new(&mut buf);
+        serializer
+            .collect_str(&format_args!("{}-{}", 12, 34))
+            .unwrap();
+        assert_eq!(&buf[..6], &[0x65, 0x31, 0x32, 0x2D, 0x33, 0x34]);
+
+        let mut buf = [0u8; 5];
+        let mut serializer = Serializer::new(&mut buf);
+        assert_eq!(
+            serializer.collect_str(&format_args!("{}-{}", 12, 34)),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut buf = [0u8; 4];
+        assert_eq!(to_slice("hello", &mut buf), Err(Error::BufferOverflow));
+        assert_eq!(to_slice(&[1u32; 4], &mut buf), Err(Error::BufferOverflow));
+    }
+}
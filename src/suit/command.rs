@@ -0,0 +1,784 @@
+// SPDX-License-Identifier: MIT
+
+//! SUIT command sequences and their parameters.
+
+use crate::{
+    Arena, Value,
+    decode::decode,
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    list::{List, bytes, fields, text, unsigned},
+    result::Result,
+};
+
+/// Condition 1: The vendor identifier parameter matches the device.
+pub const CONDITION_VENDOR_IDENTIFIER: i64 = 1;
+/// Condition 2: The class identifier parameter matches the device.
+pub const CONDITION_CLASS_IDENTIFIER: i64 = 2;
+/// Condition 3: The digest of the component matches the image digest parameter.
+pub const CONDITION_IMAGE_MATCH: i64 = 3;
+/// Condition 5: The component slot matches the component slot parameter.
+pub const CONDITION_COMPONENT_SLOT: i64 = 5;
+/// Condition 6: The content of the component matches the content parameter.
+pub const CONDITION_CHECK_CONTENT: i64 = 6;
+/// Condition 14: Always fails, aborting the sequence.
+pub const CONDITION_ABORT: i64 = 14;
+/// Condition 24: The device identifier parameter matches the device.
+pub const CONDITION_DEVICE_IDENTIFIER: i64 = 24;
+
+/// Directive 11: Processes the manifest of a dependency.
+pub const DIRECTIVE_PROCESS_DEPENDENCY: i64 = 11;
+/// Directive 12: Selects the components the following commands apply to.
+pub const DIRECTIVE_SET_COMPONENT_INDEX: i64 = 12;
+/// Directive 15: Runs sequences in turn until one succeeds.
+pub const DIRECTIVE_TRY_EACH: i64 = 15;
+/// Directive 18: Writes the content parameter to the component.
+pub const DIRECTIVE_WRITE: i64 = 18;
+/// Directive 19: Sets parameters that are not already set.
+pub const DIRECTIVE_SET_PARAMETERS: i64 = 19;
+/// Directive 20: Sets parameters, replacing those already set.
+pub const DIRECTIVE_OVERRIDE_PARAMETERS: i64 = 20;
+/// Directive 21: Fetches the payload at the URI parameter into the component.
+pub const DIRECTIVE_FETCH: i64 = 21;
+/// Directive 22: Copies the source component into the component.
+pub const DIRECTIVE_COPY: i64 = 22;
+/// Directive 23: Runs the component.
+pub const DIRECTIVE_INVOKE: i64 = 23;
+/// Directive 31: Swaps the source component and the component.
+pub const DIRECTIVE_SWAP: i64 = 31;
+/// Directive 32: Runs a sequence with the current components and parameters.
+pub const DIRECTIVE_RUN_SEQUENCE: i64 = 32;
+/// Directive 33: Removes the component.
+pub const DIRECTIVE_UNLINK: i64 = 33;
+
+/// Parameter 1: The vendor identifier, a UUID.
+pub const PARAMETER_VENDOR_IDENTIFIER: i64 = 1;
+/// Parameter 2: The class identifier, a UUID.
+pub const PARAMETER_CLASS_IDENTIFIER: i64 = 2;
+/// Parameter 3: The encoded digest of the image.
+pub const PARAMETER_IMAGE_DIGEST: i64 = 3;
+/// Parameter 5: The slot of the component.
+pub const PARAMETER_COMPONENT_SLOT: i64 = 5;
+/// Parameter 12: Whether the commands must run in order.
+pub const PARAMETER_STRICT_ORDER: i64 = 12;
+/// Parameter 13: Whether a failed condition skips the rest of the sequence instead of
+/// failing it.
+pub const PARAMETER_SOFT_FAILURE: i64 = 13;
+/// Parameter 14: The size of the image in bytes.
+pub const PARAMETER_IMAGE_SIZE: i64 = 14;
+/// Parameter 18: The content to write to the component.
+pub const PARAMETER_CONTENT: i64 = 18;
+/// Parameter 21: The URI to fetch the payload from.
+pub const PARAMETER_URI: i64 = 21;
+/// Parameter 22: The index of the source component for copy and swap.
+pub const PARAMETER_SOURCE_COMPONENT: i64 = 22;
+/// Parameter 23: The arguments to invoke the component with.
+pub const PARAMETER_INVOKE_ARGS: i64 = 23;
+/// Parameter 24: The device identifier, a UUID.
+pub const PARAMETER_DEVICE_IDENTIFIER: i64 = 24;
+
+/// Reporting policy bit: Report a record when the command succeeds.
+pub const SEND_RECORD_SUCCESS: u64 = 0x01;
+/// Reporting policy bit: Report a record when the command fails.
+pub const SEND_RECORD_FAILURE: u64 = 0x02;
+/// Reporting policy bit: Report system information when the command succeeds.
+pub const SEND_SYSINFO_SUCCESS: u64 = 0x04;
+/// Reporting policy bit: Report system information when the command fails.
+pub const SEND_SYSINFO_FAILURE: u64 = 0x08;
+
+/// The conditions, which take a reporting policy.
+const CONDITIONS: [i64; 7] = [
+    CONDITION_VENDOR_IDENTIFIER,
+    CONDITION_CLASS_IDENTIFIER,
+    CONDITION_IMAGE_MATCH,
+    CONDITION_COMPONENT_SLOT,
+    CONDITION_CHECK_CONTENT,
+    CONDITION_ABORT,
+    CONDITION_DEVICE_IDENTIFIER,
+];
+
+/// The directives that take a reporting policy.
+const DIRECTIVES: [i64; 7] = [
+    DIRECTIVE_PROCESS_DEPENDENCY,
+    DIRECTIVE_WRITE,
+    DIRECTIVE_FETCH,
+    DIRECTIVE_COPY,
+    DIRECTIVE_INVOKE,
+    DIRECTIVE_SWAP,
+    DIRECTIVE_UNLINK,
+];
+
+/// The components a command sequence applies to, as set by
+/// [`Command::SetComponentIndex`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentIndex<'a> {
+    /// One component, by its index in the common section.
+    Index(u64),
+    /// All components.
+    All,
+    /// Several components, by their indices in the common section.
+    Indices(List<'a, u64>),
+}
+
+impl ComponentIndex<'_> {
+    /// Writes the index, `true` for all components, or the array of indices.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match self {
+            Self::Index(index) => sink.value(&Value::Unsigned(*index)),
+            Self::All => sink.value(&Value::bool(true)),
+            Self::Indices(indices) => indices.write(sink),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value<'a>> for ComponentIndex<'a> {
+    type Error = Error;
+
+    /// Reads a component index.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ComponentIndex)` - The index.
+    /// * `Err(Error::InvalidType)` - If the value is not an unsigned integer, `true` or an
+    ///   array.
+    fn try_from(value: &'a Value<'a>) -> Result<Self> {
+        match value {
+            Value::Unsigned(index) => Ok(Self::Index(*index)),
+            Value::Array(indices) => Ok(Self::Indices(List::Values(indices))),
+            _ if value.as_bool() == Some(true) => Ok(Self::All),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The parameters of the selected components, as set by
+/// [`Command::SetParameters`] and [`Command::OverrideParameters`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Parameters<'a> {
+    vendor_identifier: Option<&'a [u8]>,
+    class_identifier: Option<&'a [u8]>,
+    image_digest: Option<&'a [u8]>,
+    component_slot: Option<u64>,
+    strict_order: Option<bool>,
+    soft_failure: Option<bool>,
+    image_size: Option<u64>,
+    content: Option<&'a [u8]>,
+    uri: Option<&'a str>,
+    source_component: Option<u64>,
+    invoke_args: Option<&'a [u8]>,
+    device_identifier: Option<&'a [u8]>,
+}
+
+impl<'a> Parameters<'a> {
+    /// Creates an empty set of parameters.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            vendor_identifier: None,
+            class_identifier: None,
+            image_digest: None,
+            component_slot: None,
+            strict_order: None,
+            soft_failure: None,
+            image_size: None,
+            content: None,
+            uri: None,
+            source_component: None,
+            invoke_args: None,
+            device_identifier: None,
+        }
+    }
+
+    /// Returns the parameters with the given vendor identifier.
+    #[inline]
+    pub const fn with_vendor_identifier(self, vendor_identifier: &'a [u8]) -> Self {
+        Self {
+            vendor_identifier: Some(vendor_identifier),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given class identifier.
+    #[inline]
+    pub const fn with_class_identifier(self, class_identifier: &'a [u8]) -> Self {
+        Self {
+            class_identifier: Some(class_identifier),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given encoded [`Digest`](super::Digest) of the
+    /// image.
+    #[inline]
+    pub const fn with_image_digest(self, image_digest: &'a [u8]) -> Self {
+        Self {
+            image_digest: Some(image_digest),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given component slot.
+    #[inline]
+    pub const fn with_component_slot(self, component_slot: u64) -> Self {
+        Self {
+            component_slot: Some(component_slot),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given strict order flag.
+    #[inline]
+    pub const fn with_strict_order(self, strict_order: bool) -> Self {
+        Self {
+            strict_order: Some(strict_order),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given soft failure flag.
+    #[inline]
+    pub const fn with_soft_failure(self, soft_failure: bool) -> Self {
+        Self {
+            soft_failure: Some(soft_failure),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given image size.
+    #[inline]
+    pub const fn with_image_size(self, image_size: u64) -> Self {
+        Self {
+            image_size: Some(image_size),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given content.
+    #[inline]
+    pub const fn with_content(self, content: &'a [u8]) -> Self {
+        Self {
+            content: Some(content),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given URI, which may refer to a payload
+    /// integrated in the envelope, such as `"#firmware"`.
+    #[inline]
+    pub const fn with_uri(self, uri: &'a str) -> Self {
+        Self {
+            uri: Some(uri),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given source component index.
+    #[inline]
+    pub const fn with_source_component(self, source_component: u64) -> Self {
+        Self {
+            source_component: Some(source_component),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given invoke arguments.
+    #[inline]
+    pub const fn with_invoke_args(self, invoke_args: &'a [u8]) -> Self {
+        Self {
+            invoke_args: Some(invoke_args),
+            ..self
+        }
+    }
+
+    /// Returns the parameters with the given device identifier.
+    #[inline]
+    pub const fn with_device_identifier(self, device_identifier: &'a [u8]) -> Self {
+        Self {
+            device_identifier: Some(device_identifier),
+            ..self
+        }
+    }
+
+    /// Returns the vendor identifier.
+    #[inline]
+    pub const fn vendor_identifier(&self) -> Option<&'a [u8]> {
+        self.vendor_identifier
+    }
+
+    /// Returns the class identifier.
+    #[inline]
+    pub const fn class_identifier(&self) -> Option<&'a [u8]> {
+        self.class_identifier
+    }
+
+    /// Returns the encoded digest of the image, which
+    /// [`Digest::decode`](super::Digest::decode) reads.
+    #[inline]
+    pub const fn image_digest(&self) -> Option<&'a [u8]> {
+        self.image_digest
+    }
+
+    /// Returns the component slot.
+    #[inline]
+    pub const fn component_slot(&self) -> Option<u64> {
+        self.component_slot
+    }
+
+    /// Returns the strict order flag.
+    #[inline]
+    pub const fn strict_order(&self) -> Option<bool> {
+        self.strict_order
+    }
+
+    /// Returns the soft failure flag.
+    #[inline]
+    pub const fn soft_failure(&self) -> Option<bool> {
+        self.soft_failure
+    }
+
+    /// Returns the image size.
+    #[inline]
+    pub const fn image_size(&self) -> Option<u64> {
+        self.image_size
+    }
+
+    /// Returns the content.
+    #[inline]
+    pub const fn content(&self) -> Option<&'a [u8]> {
+        self.content
+    }
+
+    /// Returns the URI.
+    #[inline]
+    pub const fn uri(&self) -> Option<&'a str> {
+        self.uri
+    }
+
+    /// Returns the source component index.
+    #[inline]
+    pub const fn source_component(&self) -> Option<u64> {
+        self.source_component
+    }
+
+    /// Returns the invoke arguments.
+    #[inline]
+    pub const fn invoke_args(&self) -> Option<&'a [u8]> {
+        self.invoke_args
+    }
+
+    /// Returns the device identifier.
+    #[inline]
+    pub const fn device_identifier(&self) -> Option<&'a [u8]> {
+        self.device_identifier
+    }
+
+    /// Returns the entries of the parameter map, in ascending key order.
+    fn entries(&self) -> [(i64, Option<Value<'a>>); 12] {
+        [
+            (
+                PARAMETER_VENDOR_IDENTIFIER,
+                self.vendor_identifier.map(Value::Bytes),
+            ),
+            (
+                PARAMETER_CLASS_IDENTIFIER,
+                self.class_identifier.map(Value::Bytes),
+            ),
+            (PARAMETER_IMAGE_DIGEST, self.image_digest.map(Value::Bytes)),
+            (
+                PARAMETER_COMPONENT_SLOT,
+                self.component_slot.map(Value::Unsigned),
+            ),
+            (PARAMETER_STRICT_ORDER, self.strict_order.map(Value::bool)),
+            (PARAMETER_SOFT_FAILURE, self.soft_failure.map(Value::bool)),
+            (PARAMETER_IMAGE_SIZE, self.image_size.map(Value::Unsigned)),
+            (PARAMETER_CONTENT, self.content.map(Value::Bytes)),
+            (PARAMETER_URI, self.uri.map(Value::Text)),
+            (
+                PARAMETER_SOURCE_COMPONENT,
+                self.source_component.map(Value::Unsigned),
+            ),
+            (PARAMETER_INVOKE_ARGS, self.invoke_args.map(Value::Bytes)),
+            (
+                PARAMETER_DEVICE_IDENTIFIER,
+                self.device_identifier.map(Value::Bytes),
+            ),
+        ]
+    }
+
+    /// Writes the parameter map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let entries = self.entries();
+        let len = entries.iter().filter(|(_, value)| value.is_some()).count();
+        sink.header(major_type::MAP, len as u64)?;
+        for (key, value) in entries {
+            if let Some(value) = value {
+                sink.value(&Value::integer(key))?;
+                sink.value(&value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Parameters<'a> {
+    type Error = Error;
+
+    /// Reads a parameter map. Parameters this module does not model are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Parameters)` - The parameters.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or a parameter has the
+    ///   wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let flag = |key| match map.get_int(key) {
+            Some(value) => value.as_bool().map(Some).ok_or(Error::InvalidType),
+            None => Ok(None),
+        };
+        let uri = text(map.get_int(PARAMETER_URI))?;
+
+        Ok(Self {
+            vendor_identifier: bytes(map.get_int(PARAMETER_VENDOR_IDENTIFIER))?,
+            class_identifier: bytes(map.get_int(PARAMETER_CLASS_IDENTIFIER))?,
+            image_digest: bytes(map.get_int(PARAMETER_IMAGE_DIGEST))?,
+            component_slot: unsigned(map.get_int(PARAMETER_COMPONENT_SLOT))?,
+            strict_order: flag(PARAMETER_STRICT_ORDER)?,
+            soft_failure: flag(PARAMETER_SOFT_FAILURE)?,
+            image_size: unsigned(map.get_int(PARAMETER_IMAGE_SIZE))?,
+            content: bytes(map.get_int(PARAMETER_CONTENT))?,
+            uri,
+            source_component: unsigned(map.get_int(PARAMETER_SOURCE_COMPONENT))?,
+            invoke_args: bytes(map.get_int(PARAMETER_INVOKE_ARGS))?,
+            device_identifier: bytes(map.get_int(PARAMETER_DEVICE_IDENTIFIER))?,
+        })
+    }
+}
+
+/// A command in a sequence: a condition to check or a directive to carry out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command<'a> {
+    /// A condition, such as [`CONDITION_IMAGE_MATCH`], with its reporting policy.
+    Condition(i64, u64),
+    /// A directive that takes a reporting policy, such as [`DIRECTIVE_INVOKE`].
+    Directive(i64, u64),
+    /// Selects the components the following commands apply to.
+    SetComponentIndex(ComponentIndex<'a>),
+    /// Sets parameters that are not already set.
+    SetParameters(Parameters<'a>),
+    /// Sets parameters, replacing those already set.
+    OverrideParameters(Parameters<'a>),
+    /// Runs the encoded sequences in turn until one succeeds.
+    ///
+    /// A decoded argument may end with null, which lets processing go on when every
+    /// sequence fails. The null is skipped, so it is not part of the list.
+    TryEach(List<'a, &'a [u8]>),
+    /// Runs the encoded sequence.
+    RunSequence(&'a [u8]),
+    /// A command this module does not model, with its argument.
+    Custom(i64, Value<'a>),
+}
+
+impl<'a> Command<'a> {
+    /// Returns the command identifier.
+    pub const fn id(&self) -> i64 {
+        match self {
+            Self::Condition(id, _) | Self::Directive(id, _) | Self::Custom(id, _) => *id,
+            Self::SetComponentIndex(_) => DIRECTIVE_SET_COMPONENT_INDEX,
+            Self::SetParameters(_) => DIRECTIVE_SET_PARAMETERS,
+            Self::OverrideParameters(_) => DIRECTIVE_OVERRIDE_PARAMETERS,
+            Self::TryEach(_) => DIRECTIVE_TRY_EACH,
+            Self::RunSequence(_) => DIRECTIVE_RUN_SEQUENCE,
+        }
+    }
+
+    /// Reads a command from its identifier and argument.
+    fn from_pair(id: &Value, argument: &'a Value<'a>) -> Result<Self> {
+        let id = match id.as_i128().map(i64::try_from) {
+            Some(Ok(id)) => id,
+            _ => return Err(Error::InvalidType),
+        };
+        let policy = || argument.as_u64().ok_or(Error::InvalidType);
+        match id {
+            _ if CONDITIONS.contains(&id) => Ok(Self::Condition(id, policy()?)),
+            _ if DIRECTIVES.contains(&id) => Ok(Self::Directive(id, policy()?)),
+            DIRECTIVE_SET_COMPONENT_INDEX => {
+                ComponentIndex::try_from(argument).map(Self::SetComponentIndex)
+            }
+            DIRECTIVE_SET_PARAMETERS => Parameters::try_from(argument).map(Self::SetParameters),
+            DIRECTIVE_OVERRIDE_PARAMETERS => {
+                Parameters::try_from(argument).map(Self::OverrideParameters)
+            }
+            DIRECTIVE_TRY_EACH => match argument {
+                Value::Array([sequences @ .., last]) if last.is_null() => {
+                    Ok(Self::TryEach(List::Values(sequences)))
+                }
+                Value::Array(sequences) => Ok(Self::TryEach(List::Values(sequences))),
+                _ => Err(Error::InvalidType),
+            },
+            DIRECTIVE_RUN_SEQUENCE => match argument {
+                Value::Bytes(sequence) => Ok(Self::RunSequence(sequence)),
+                _ => Err(Error::InvalidType),
+            },
+            _ => Ok(Self::Custom(id, *argument)),
+        }
+    }
+
+    /// Writes the command identifier and argument.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.value(&Value::integer(self.id()))?;
+        match self {
+            Self::Condition(_, policy) | Self::Directive(_, policy) => {
+                sink.value(&Value::Unsigned(*policy))
+            }
+            Self::SetComponentIndex(index) => index.write(sink),
+            Self::SetParameters(parameters) | Self::OverrideParameters(parameters) => {
+                parameters.write(sink)
+            }
+            Self::TryEach(sequences) => sequences.write(sink),
+            Self::RunSequence(sequence) => sink.value(&Value::Bytes(sequence)),
+            Self::Custom(_, argument) => sink.value(argument),
+        }
+    }
+}
+
+/// A command sequence, such as the validate or install sequence of a manifest.
+///
+/// A sequence is encoded as a flat array of command identifiers and arguments. Built
+/// sequences hold typed commands, while decoded sequences keep the decoded values and
+/// convert each command when it is read, like a [`List`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandSequence<'a> {
+    commands: &'a [Command<'a>],
+    values: &'a [Value<'a>],
+}
+
+impl<'a> CommandSequence<'a> {
+    /// Creates a sequence of commands.
+    #[inline]
+    pub const fn new(commands: &'a [Command<'a>]) -> Self {
+        Self {
+            commands,
+            values: &[],
+        }
+    }
+
+    /// Returns the number of commands.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.commands.len() + self.values.len() / 2
+    }
+
+    /// Returns `true` if there are no commands.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the command at `index`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Ok(Command))` - The command.
+    /// * `Some(Err(Error))` - If a decoded command does not have the expected shape.
+    /// * `None` - If `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<Result<Command<'a>>> {
+        self.iter().nth(index)
+    }
+
+    /// Returns an iterator over the commands, converting decoded commands as it goes.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Command<'a>>> + 'a {
+        let values: &'a [Value<'a>] = self.values;
+        self.commands.iter().map(|command| Ok(*command)).chain(
+            values
+                .chunks_exact(2)
+                .map(|pair| Command::from_pair(&pair[0], &pair[1])),
+        )
+    }
+
+    /// Decodes a sequence, as found in [`Manifest::validate`](super::Manifest::validate)
+    /// and the other sequences of a manifest.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CommandSequence)` - The sequence, whose commands are converted as they are
+    ///   read.
+    /// * `Err(Error)` - If the bytes are not a valid sequence, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        let value = decode(bytes, arena)?;
+        match value {
+            Value::Array(values) if values.len() % 2 == 0 => Ok(Self {
+                commands: &[],
+                values,
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    /// Calculates the number of bytes needed to encode the sequence.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the sequence array.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2 * self.len() as u64)?;
+        for command in self.commands {
+            command.write(sink)?;
+        }
+        for value in self.values {
+            sink.value(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for CommandSequence<'_> {
+    /// Encodes the sequence array.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CONDITION_IMAGE_MATCH, CONDITION_VENDOR_IDENTIFIER, Command, CommandSequence,
+        ComponentIndex, DIRECTIVE_FETCH, DIRECTIVE_INVOKE, Parameters, SEND_RECORD_FAILURE,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+        list::List,
+    };
+
+    #[test]
+    fn test_sequence_roundtrip() {
+        let parameters = Parameters::new()
+            .with_vendor_identifier(&[0xFA; 4])
+            .with_image_digest(&[0x82, 0x2F, 0x40])
+            .with_image_size(1024)
+            .with_soft_failure(true)
+            .with_uri("#firmware");
+        let try_each: [&[u8]; 2] = [&[0x80], &[0x82, 0x0E, 0x00]];
+        let commands = [
+            Command::SetComponentIndex(ComponentIndex::Index(0)),
+            Command::OverrideParameters(parameters),
+            Command::Condition(CONDITION_VENDOR_IDENTIFIER, SEND_RECORD_FAILURE),
+            Command::Directive(DIRECTIVE_FETCH, 0),
+            Command::TryEach(List::Items(&try_each)),
+            Command::Condition(CONDITION_IMAGE_MATCH, 0),
+            Command::SetComponentIndex(ComponentIndex::Indices(List::Items(&[0, 2]))),
+            Command::RunSequence(&[0x80]),
+            Command::SetComponentIndex(ComponentIndex::All),
+            Command::Directive(DIRECTIVE_INVOKE, 0),
+            Command::Custom(99, Value::text("x")),
+        ];
+        let sequence = CommandSequence::new(&commands);
+        assert_eq!(sequence.len(), 11);
+
+        static EXPECTED: Value = cbor!([
+            12, 0,
+            20, {1: h "FAFAFAFA", 3: h "822F40", 13: true, 14: 1024, 21: "#firmware"},
+            1, 2,
+            21, 0,
+            15, [h "80", h "820E00"],
+            3, 0,
+            12, [0, 2],
+            32, h "80",
+            12, true,
+            23, 0,
+            99, "x"
+        ]);
+        let mut buf = [0u8; 128];
+        let size = sequence.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, sequence.encoded_size());
+        assert_encoding(&buf[..size], &EXPECTED);
+
+        let mut values = [Value::null(); 32];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = CommandSequence::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded.len(), 11);
+        let mut iter = decoded.iter();
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Command::SetComponentIndex(ComponentIndex::Index(0))))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Command::OverrideParameters(parameters)))
+        );
+        assert_eq!(iter.next(), Some(Ok(commands[2])));
+        assert_eq!(iter.next(), Some(Ok(commands[3])));
+        let Some(Ok(Command::TryEach(sequences))) = iter.next() else {
+            panic!("expected try-each");
+        };
+        assert_eq!(sequences.get(1), Some(Ok(&[0x82, 0x0E, 0x00][..])));
+        assert_eq!(iter.next(), Some(Ok(commands[5])));
+        let Some(Ok(Command::SetComponentIndex(ComponentIndex::Indices(indices)))) = iter.next()
+        else {
+            panic!("expected component indices");
+        };
+        assert_eq!(indices.get(1), Some(Ok(2)));
+        assert_eq!(iter.next(), Some(Ok(commands[7])));
+        assert_eq!(iter.next(), Some(Ok(commands[8])));
+        assert_eq!(iter.next(), Some(Ok(commands[9])));
+        assert_eq!(iter.next(), Some(Ok(commands[10])));
+        assert_eq!(iter.next(), None);
+
+        // Re-encoding the decoded sequence gives the same bytes
+        let mut again = [0u8; 128];
+        assert_eq!(decoded.as_cbor(&mut again), Ok(size));
+        assert_eq!(&again[..size], &buf[..size]);
+    }
+
+    #[test]
+    fn test_try_each_null() {
+        static TRY_EACH: Value = cbor!([15, [h "80", h "820E00", null]]);
+        let Value::Array(values) = &TRY_EACH else {
+            unreachable!()
+        };
+        let sequence = CommandSequence {
+            commands: &[],
+            values,
+        };
+        let Some(Ok(Command::TryEach(sequences))) = sequence.get(0) else {
+            panic!("expected try-each");
+        };
+        assert_eq!(sequences.len(), 2);
+        assert_eq!(sequences.get(1), Some(Ok(&[0x82, 0x0E, 0x00][..])));
+        assert_eq!(sequences.get(2), None);
+    }
+
+    #[test]
+    fn test_sequence_errors() {
+        let mut values = [Value::null(); 4];
+        let mut arena = Arena::new(&mut values, &mut []);
+        // An odd number of items, and not an array
+        assert_eq!(
+            CommandSequence::decode(&[0x81, 0x01], &mut arena),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            CommandSequence::decode(&[0x01], &mut arena),
+            Err(Error::InvalidType)
+        );
+
+        static BAD_POLICY: Value = cbor!([1, "policy"]);
+        static BAD_INDEX: Value = cbor!([12, false]);
+        static BAD_PARAMETER: Value = cbor!([20, {14: "size"}]);
+        static TEXT_ID: Value = cbor!(["id", 0]);
+        for value in [&BAD_POLICY, &BAD_INDEX, &BAD_PARAMETER, &TEXT_ID] {
+            let Value::Array(values) = value else {
+                unreachable!()
+            };
+            let sequence = CommandSequence {
+                commands: &[],
+                values,
+            };
+            assert_eq!(sequence.get(0), Some(Err(Error::InvalidType)), "{value}");
+        }
+    }
+}
@@ -0,0 +1,1194 @@
+// SPDX-License-Identifier: MIT
+
+//! Software Updates for Internet of Things (SUIT) manifests, as defined by
+//! [RFC 9124](https://www.rfc-editor.org/rfc/rfc9124) and the IETF
+//! [SUIT manifest draft](https://datatracker.ietf.org/doc/draft-ietf-suit-manifest/).
+//!
+//! A SUIT envelope (tag 107) carries a firmware update: an authentication wrapper that
+//! signs the digest of the manifest, the manifest itself, and optionally the payloads.
+//! The manifest lists the components to update in its common section, and describes
+//! how to validate, load and invoke them as command sequences of conditions and
+//! directives.
+//!
+//! Most parts of a SUIT envelope are nested as byte strings holding encoded CBOR, so
+//! that digests and signatures can be computed over their exact bytes. The types in
+//! this module keep such parts as the encoded bytes and decode them on demand: the
+//! manifest of an [`Envelope`] is decoded with [`Manifest::decode`], its common section
+//! with [`Common::decode`] and its command sequences with [`CommandSequence::decode`].
+//! Decoding never copies, so a bootloader can process a manifest in place, for example
+//! from flash, with only an [`Arena`] of values in RAM. Building works the other way
+//! round: each part is encoded into a buffer and the encoded bytes are given to the
+//! part that contains it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Arena, Value,
+//!     encode::Encode,
+//!     suit::{
+//!         self, Command, CommandSequence, Common, ComponentId, Envelope, Manifest,
+//!     },
+//! };
+//!
+//! let segments: [&[u8]; 1] = [b"firmware"];
+//! let components = [ComponentId::new(&segments)];
+//! let mut common = [0u8; 32];
+//! let common_len = Common::new(&components).as_cbor(&mut common).unwrap();
+//!
+//! let commands = [Command::Directive(suit::DIRECTIVE_INVOKE, 0)];
+//! let mut invoke = [0u8; 8];
+//! let invoke_len = CommandSequence::new(&commands).as_cbor(&mut invoke).unwrap();
+//!
+//! let manifest = Manifest::new(1, &common[..common_len]).with_invoke(&invoke[..invoke_len]);
+//! let mut manifest_buf = [0u8; 64];
+//! let manifest_len = manifest.as_cbor(&mut manifest_buf).unwrap();
+//!
+//! // The authentication wrapper would hold the signed digest of the manifest
+//! let envelope = Envelope::new(&[0x81, 0x40], &manifest_buf[..manifest_len]);
+//! let mut buf = [0u8; 128];
+//! let size = envelope.as_cbor(&mut buf).unwrap();
+//!
+//! let mut values = [Value::null(); 8];
+//! let mut pairs = [(Value::null(), Value::null()); 8];
+//! let mut arena = Arena::new(&mut values, &mut pairs);
+//! let envelope = Envelope::decode(&buf[..size], &mut arena).unwrap();
+//! let manifest = Manifest::decode(envelope.manifest(), &mut arena).unwrap();
+//! assert_eq!(manifest.sequence_number(), 1);
+//!
+//! let invoke = CommandSequence::decode(manifest.invoke().unwrap(), &mut arena).unwrap();
+//! assert_eq!(invoke.get(0), Some(Ok(Command::Directive(suit::DIRECTIVE_INVOKE, 0))));
+//! ```
+
+mod command;
+
+pub use command::*;
+
+use crate::{
+    Arena, Value,
+    cose::{CoseSign1, Verifier},
+    decode::decode,
+    encode::{Encode, Sink, major_type},
+    error::Error,
+    list::{Item, List, bytes, fields, required, text, unsigned},
+    result::Result,
+    tag,
+    value::Map,
+};
+
+/// Envelope key 2: The authentication wrapper.
+pub const AUTHENTICATION_WRAPPER: i64 = 2;
+/// Envelope key 3: The manifest.
+pub const MANIFEST: i64 = 3;
+
+/// Manifest key 1: The manifest format version, which is 1.
+pub const MANIFEST_VERSION: i64 = 1;
+/// Manifest key 2: The sequence number, which increases with each update.
+pub const MANIFEST_SEQUENCE_NUMBER: i64 = 2;
+/// Manifest key 3: The common section.
+pub const COMMON: i64 = 3;
+/// Manifest key 4: A URI where the manifest can be found.
+pub const REFERENCE_URI: i64 = 4;
+/// Manifest key 7: The command sequence that validates the components.
+pub const VALIDATE: i64 = 7;
+/// Manifest key 8: The command sequence that loads the components to run them.
+pub const LOAD: i64 = 8;
+/// Manifest key 9: The command sequence that invokes the components.
+pub const INVOKE: i64 = 9;
+/// Manifest and envelope key 16: The command sequence that fetches the payloads.
+pub const PAYLOAD_FETCH: i64 = 16;
+/// Manifest and envelope key 20: The command sequence that installs the payloads.
+pub const INSTALL: i64 = 20;
+/// Manifest and envelope key 23: The text descriptions of the update.
+pub const TEXT: i64 = 23;
+
+/// Common section key 2: The identifiers of the components.
+pub const COMPONENTS: i64 = 2;
+/// Common section key 4: The command sequence that runs before every other sequence.
+pub const SHARED_SEQUENCE: i64 = 4;
+
+/// The manifest format version this module reads and writes.
+const VERSION: u64 = 1;
+
+/// A digest, as `[algorithm, bytes]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digest<'a> {
+    algorithm: i64,
+    digest: &'a [u8],
+}
+
+impl<'a> Digest<'a> {
+    /// Creates a digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The COSE hash algorithm, such as [`SHA_256`](crate::cose::SHA_256).
+    /// * `digest` - The digest.
+    #[inline]
+    pub const fn new(algorithm: i64, digest: &'a [u8]) -> Self {
+        Self { algorithm, digest }
+    }
+
+    /// Returns the COSE hash algorithm.
+    #[inline]
+    pub const fn algorithm(&self) -> i64 {
+        self.algorithm
+    }
+
+    /// Returns the digest.
+    #[inline]
+    pub const fn digest(&self) -> &'a [u8] {
+        self.digest
+    }
+
+    /// Returns `true` if a computed digest matches this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The COSE hash algorithm the digest was computed with.
+    /// * `digest` - The computed digest, such as that of [`Envelope::manifest`].
+    pub fn matches(&self, algorithm: i64, digest: &[u8]) -> bool {
+        self.algorithm == algorithm && self.digest == digest
+    }
+
+    /// Decodes a digest, as found in [`Authentication::digest`] or
+    /// [`Parameters::image_digest`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Digest)` - The digest.
+    /// * `Err(Error)` - If the bytes are not a valid digest, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the digest.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+}
+
+impl Item for Digest<'_> {
+    /// Writes the digest as `[algorithm, digest]`.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 2)?;
+        sink.value(&Value::integer(self.algorithm))?;
+        sink.value(&Value::Bytes(self.digest))
+    }
+}
+
+impl<'a> Encode<'a> for Digest<'_> {
+    /// Encodes the digest array.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Digest<'a> {
+    type Error = Error;
+
+    /// Reads a digest.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Digest)` - The digest.
+    /// * `Err(Error::InvalidType)` - If the value is not an `[int, bstr]` array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([algorithm, Value::Bytes(digest)]) => {
+                match algorithm.as_i128().map(i64::try_from) {
+                    Some(Ok(algorithm)) => Ok(Self::new(algorithm, digest)),
+                    _ => Err(Error::InvalidType),
+                }
+            }
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A SUIT envelope: the authentication wrapper, the manifest, and the members that were
+/// severed from the manifest or integrated into the envelope.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope<'a> {
+    authentication: &'a [u8],
+    manifest: &'a [u8],
+    payload_fetch: Option<&'a [u8]>,
+    install: Option<&'a [u8]>,
+    text: Option<&'a [u8]>,
+    payloads: Map<'a>,
+}
+
+impl<'a> Envelope<'a> {
+    /// Creates an envelope.
+    ///
+    /// # Arguments
+    ///
+    /// * `authentication` - The encoded [`Authentication`] wrapper.
+    /// * `manifest` - The encoded [`Manifest`].
+    #[inline]
+    pub const fn new(authentication: &'a [u8], manifest: &'a [u8]) -> Self {
+        Self {
+            authentication,
+            manifest,
+            payload_fetch: None,
+            install: None,
+            text: None,
+            payloads: &[],
+        }
+    }
+
+    /// Returns the envelope with the given payload fetch sequence, severed from the
+    /// manifest.
+    #[inline]
+    pub const fn with_payload_fetch(self, payload_fetch: &'a [u8]) -> Self {
+        Self {
+            payload_fetch: Some(payload_fetch),
+            ..self
+        }
+    }
+
+    /// Returns the envelope with the given install sequence, severed from the manifest.
+    #[inline]
+    pub const fn with_install(self, install: &'a [u8]) -> Self {
+        Self {
+            install: Some(install),
+            ..self
+        }
+    }
+
+    /// Returns the envelope with the given text descriptions, severed from the manifest.
+    #[inline]
+    pub const fn with_text(self, text: &'a [u8]) -> Self {
+        Self {
+            text: Some(text),
+            ..self
+        }
+    }
+
+    /// Returns the envelope with the given integrated payloads, as pairs of a text key,
+    /// which a URI parameter refers to, and the payload bytes.
+    #[inline]
+    pub const fn with_payloads(self, payloads: Map<'a>) -> Self {
+        Self { payloads, ..self }
+    }
+
+    /// Returns the encoded authentication wrapper, which [`Authentication::decode`]
+    /// reads.
+    #[inline]
+    pub const fn authentication(&self) -> &'a [u8] {
+        self.authentication
+    }
+
+    /// Returns the encoded manifest, which [`Manifest::decode`] reads. The digest in the
+    /// authentication wrapper is computed over these bytes.
+    #[inline]
+    pub const fn manifest(&self) -> &'a [u8] {
+        self.manifest
+    }
+
+    /// Returns the severed payload fetch sequence.
+    #[inline]
+    pub const fn payload_fetch(&self) -> Option<&'a [u8]> {
+        self.payload_fetch
+    }
+
+    /// Returns the severed install sequence.
+    #[inline]
+    pub const fn install(&self) -> Option<&'a [u8]> {
+        self.install
+    }
+
+    /// Returns the severed text descriptions.
+    #[inline]
+    pub const fn text(&self) -> Option<&'a [u8]> {
+        self.text
+    }
+
+    /// Returns the integrated payload with the given key.
+    pub fn payload(&self, key: &str) -> Option<&'a [u8]> {
+        self.payloads()
+            .find(|(name, _)| *name == key)
+            .map(|(_, payload)| payload)
+    }
+
+    /// Returns an iterator over the integrated payloads and their keys.
+    pub fn payloads(&self) -> impl Iterator<Item = (&'a str, &'a [u8])> + 'a {
+        self.payloads
+            .iter()
+            .filter_map(|(key, value)| match (key, value) {
+                (Value::Text(key), Value::Bytes(payload)) => Some((*key, *payload)),
+                _ => None,
+            })
+    }
+
+    /// Decodes an envelope, with or without tag 107.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Envelope)` - The envelope, borrowing its parts from `bytes`.
+    /// * `Err(Error)` - If the bytes are not a valid envelope, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the envelope, including tag 107.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the tagged envelope map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let entries = [
+            (AUTHENTICATION_WRAPPER, Some(self.authentication)),
+            (MANIFEST, Some(self.manifest)),
+            (PAYLOAD_FETCH, self.payload_fetch),
+            (INSTALL, self.install),
+            (TEXT, self.text),
+        ];
+        let len =
+            entries.iter().filter(|(_, bytes)| bytes.is_some()).count() + self.payloads().count();
+
+        sink.header(major_type::TAG, tag::SUIT_ENVELOPE)?;
+        sink.header(major_type::MAP, len as u64)?;
+        for (key, bytes) in entries {
+            if let Some(bytes) = bytes {
+                sink.value(&Value::integer(key))?;
+                sink.value(&Value::Bytes(bytes))?;
+            }
+        }
+        for (key, payload) in self.payloads() {
+            sink.value(&Value::Text(key))?;
+            sink.value(&Value::Bytes(payload))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for Envelope<'_> {
+    /// Encodes the envelope map, wrapped in tag 107.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Envelope<'a> {
+    type Error = Error;
+
+    /// Reads an envelope map, with or without tag 107. Entries with other integer keys,
+    /// such as delegation chains, are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Envelope)` - The envelope.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the authentication
+    ///   wrapper or the manifest is missing, or an entry has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = match value {
+            Value::Tag(tag::SUIT_ENVELOPE, map) => map,
+            _ => value,
+        };
+        let Some(payloads) = map.as_map() else {
+            return Err(Error::InvalidType);
+        };
+        if payloads
+            .iter()
+            .any(|(key, value)| matches!(key, Value::Text(_)) && value.as_bytes().is_none())
+        {
+            return Err(Error::InvalidType);
+        }
+
+        Ok(Self {
+            authentication: required(bytes(map.get_int(AUTHENTICATION_WRAPPER))?)?,
+            manifest: required(bytes(map.get_int(MANIFEST))?)?,
+            payload_fetch: bytes(map.get_int(PAYLOAD_FETCH))?,
+            install: bytes(map.get_int(INSTALL))?,
+            text: bytes(map.get_int(TEXT))?,
+            payloads,
+        })
+    }
+}
+
+/// The authentication wrapper: the digest of the manifest and the signatures over it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Authentication<'a> {
+    digest: &'a [u8],
+    blocks: List<'a, &'a [u8]>,
+}
+
+impl<'a> Authentication<'a> {
+    /// Creates an authentication wrapper.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest` - The encoded [`Digest`] of the manifest.
+    /// * `blocks` - The encoded `COSE_Sign1` or `COSE_Mac0` messages that authenticate
+    ///   the digest, with the digest as their detached payload.
+    #[inline]
+    pub const fn new(digest: &'a [u8], blocks: &'a [&'a [u8]]) -> Self {
+        Self {
+            digest,
+            blocks: List::Items(blocks),
+        }
+    }
+
+    /// Returns the encoded digest of the manifest, which [`Digest::decode`] reads.
+    #[inline]
+    pub const fn digest(&self) -> &'a [u8] {
+        self.digest
+    }
+
+    /// Returns the encoded authentication blocks.
+    #[inline]
+    pub const fn blocks(&self) -> List<'a, &'a [u8]> {
+        self.blocks
+    }
+
+    /// Checks that one of the blocks is a `COSE_Sign1` signature over the digest that
+    /// `verifier` accepts.
+    ///
+    /// A block normally carries a detached payload, which is taken to be the digest. A block
+    /// with an attached payload is only accepted if that payload is the digest itself.
+    ///
+    /// This only authenticates the digest: the caller must also check that the digest
+    /// [matches](Digest::matches) the one it computes over [`Envelope::manifest`].
+    ///
+    /// # Arguments
+    ///
+    /// * `arena` - Storage for the decoded blocks.
+    /// * `verifier` - The signature check.
+    /// * `scratch` - Space for the encoded `Sig_structure`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If a block has a valid signature.
+    /// * `Err(Error::InvalidType)` - If there are no blocks.
+    /// * `Err(Error)` - The error for the last block, if no block has a valid signature.
+    pub fn verify<V: Verifier + ?Sized>(
+        &self,
+        arena: &mut Arena<'a>,
+        verifier: &V,
+        scratch: &mut [u8],
+    ) -> Result<()> {
+        let mut result = Err(Error::InvalidType);
+        for block in self.blocks.iter() {
+            result = block
+                .and_then(|block| CoseSign1::try_from(&decode(block, arena)?))
+                .and_then(|message| match message.payload() {
+                    None => message
+                        .with_payload(self.digest)
+                        .verify(&[], verifier, scratch),
+                    Some(payload) if payload == self.digest => {
+                        message.verify(&[], verifier, scratch)
+                    }
+                    Some(_) => Err(Error::InvalidValue),
+                });
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Decodes an authentication wrapper, as found in [`Envelope::authentication`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Authentication)` - The wrapper, whose blocks are converted as they are read.
+    /// * `Err(Error)` - If the bytes are not a valid wrapper, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the wrapper.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the wrapper array.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        sink.header(major_type::ARRAY, 1 + self.blocks.len() as u64)?;
+        sink.value(&Value::Bytes(self.digest))?;
+        self.blocks.write_items(sink)
+    }
+}
+
+impl<'a> Encode<'a> for Authentication<'_> {
+    /// Encodes the wrapper array.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Authentication<'a> {
+    type Error = Error;
+
+    /// Reads an authentication wrapper.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Authentication)` - The wrapper.
+    /// * `Err(Error::InvalidType)` - If the value is not an array starting with the
+    ///   encoded digest.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some([Value::Bytes(digest), blocks @ ..]) => Ok(Self {
+                digest,
+                blocks: List::Values(blocks),
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A manifest member that can be severed: kept in the manifest, or moved to the
+/// envelope with only its digest left in the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severable<'a> {
+    /// The encoded member, kept in the manifest.
+    Present(&'a [u8]),
+    /// The digest of the member, which is carried in the envelope.
+    Severed(Digest<'a>),
+}
+
+impl Item for Severable<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        match self {
+            Self::Present(bytes) => sink.value(&Value::Bytes(bytes)),
+            Self::Severed(digest) => digest.write(sink),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Severable<'a> {
+    type Error = Error;
+
+    /// Reads a member, given as a byte string or as the digest of a severed member.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Severable)` - The member.
+    /// * `Err(Error::InvalidType)` - If the value is neither a byte string nor a digest.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Bytes(bytes) => Ok(Self::Present(bytes)),
+            _ => Digest::try_from(value).map(Self::Severed),
+        }
+    }
+}
+
+/// A SUIT manifest, describing which components an update affects and how to process
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Manifest<'a> {
+    sequence_number: u64,
+    common: &'a [u8],
+    reference_uri: Option<&'a str>,
+    validate: Option<&'a [u8]>,
+    load: Option<&'a [u8]>,
+    invoke: Option<&'a [u8]>,
+    payload_fetch: Option<Severable<'a>>,
+    install: Option<Severable<'a>>,
+    text: Option<Severable<'a>>,
+}
+
+impl<'a> Manifest<'a> {
+    /// Creates a manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence_number` - The sequence number, which must be greater than that of the
+    ///   installed manifest for the update to be accepted.
+    /// * `common` - The encoded [`Common`] section.
+    #[inline]
+    pub const fn new(sequence_number: u64, common: &'a [u8]) -> Self {
+        Self {
+            sequence_number,
+            common,
+            reference_uri: None,
+            validate: None,
+            load: None,
+            invoke: None,
+            payload_fetch: None,
+            install: None,
+            text: None,
+        }
+    }
+
+    /// Returns the manifest with the given reference URI.
+    #[inline]
+    pub const fn with_reference_uri(self, reference_uri: &'a str) -> Self {
+        Self {
+            reference_uri: Some(reference_uri),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given encoded validate sequence.
+    #[inline]
+    pub const fn with_validate(self, validate: &'a [u8]) -> Self {
+        Self {
+            validate: Some(validate),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given encoded load sequence.
+    #[inline]
+    pub const fn with_load(self, load: &'a [u8]) -> Self {
+        Self {
+            load: Some(load),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given encoded invoke sequence.
+    #[inline]
+    pub const fn with_invoke(self, invoke: &'a [u8]) -> Self {
+        Self {
+            invoke: Some(invoke),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given payload fetch sequence.
+    #[inline]
+    pub const fn with_payload_fetch(self, payload_fetch: Severable<'a>) -> Self {
+        Self {
+            payload_fetch: Some(payload_fetch),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given install sequence.
+    #[inline]
+    pub const fn with_install(self, install: Severable<'a>) -> Self {
+        Self {
+            install: Some(install),
+            ..self
+        }
+    }
+
+    /// Returns the manifest with the given text descriptions.
+    #[inline]
+    pub const fn with_text(self, text: Severable<'a>) -> Self {
+        Self {
+            text: Some(text),
+            ..self
+        }
+    }
+
+    /// Returns the sequence number.
+    #[inline]
+    pub const fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Returns the encoded common section, which [`Common::decode`] reads.
+    #[inline]
+    pub const fn common(&self) -> &'a [u8] {
+        self.common
+    }
+
+    /// Returns the reference URI.
+    #[inline]
+    pub const fn reference_uri(&self) -> Option<&'a str> {
+        self.reference_uri
+    }
+
+    /// Returns the encoded validate sequence, which [`CommandSequence::decode`] reads.
+    #[inline]
+    pub const fn validate(&self) -> Option<&'a [u8]> {
+        self.validate
+    }
+
+    /// Returns the encoded load sequence, which [`CommandSequence::decode`] reads.
+    #[inline]
+    pub const fn load(&self) -> Option<&'a [u8]> {
+        self.load
+    }
+
+    /// Returns the encoded invoke sequence, which [`CommandSequence::decode`] reads.
+    #[inline]
+    pub const fn invoke(&self) -> Option<&'a [u8]> {
+        self.invoke
+    }
+
+    /// Returns the payload fetch sequence.
+    #[inline]
+    pub const fn payload_fetch(&self) -> Option<Severable<'a>> {
+        self.payload_fetch
+    }
+
+    /// Returns the install sequence.
+    #[inline]
+    pub const fn install(&self) -> Option<Severable<'a>> {
+        self.install
+    }
+
+    /// Returns the text descriptions.
+    #[inline]
+    pub const fn text(&self) -> Option<Severable<'a>> {
+        self.text
+    }
+
+    /// Decodes a manifest, as found in [`Envelope::manifest`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Manifest)` - The manifest, borrowing its parts from `bytes`.
+    /// * `Err(Error)` - If the bytes are not a valid manifest, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the manifest.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the manifest map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let entries = [
+            (MANIFEST_VERSION, Some(Value::Unsigned(VERSION))),
+            (
+                MANIFEST_SEQUENCE_NUMBER,
+                Some(Value::Unsigned(self.sequence_number)),
+            ),
+            (COMMON, Some(Value::Bytes(self.common))),
+            (REFERENCE_URI, self.reference_uri.map(Value::Text)),
+            (VALIDATE, self.validate.map(Value::Bytes)),
+            (LOAD, self.load.map(Value::Bytes)),
+            (INVOKE, self.invoke.map(Value::Bytes)),
+        ];
+        let members = [
+            (PAYLOAD_FETCH, self.payload_fetch),
+            (INSTALL, self.install),
+            (TEXT, self.text),
+        ];
+        let len = entries.iter().filter(|(_, value)| value.is_some()).count()
+            + members
+                .iter()
+                .filter(|(_, member)| member.is_some())
+                .count();
+
+        sink.header(major_type::MAP, len as u64)?;
+        for (key, value) in entries {
+            if let Some(value) = value {
+                sink.value(&Value::integer(key))?;
+                sink.value(&value)?;
+            }
+        }
+        for (key, member) in members {
+            if let Some(member) = member {
+                sink.value(&Value::integer(key))?;
+                member.write(sink)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for Manifest<'_> {
+    /// Encodes the manifest map.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Manifest<'a> {
+    type Error = Error;
+
+    /// Reads a manifest map. Members this module does not model are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Manifest)` - The manifest.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, the version, sequence
+    ///   number or common section is missing, or a member has the wrong type.
+    /// * `Err(Error::InvalidValue)` - If the manifest version is not 1.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        if required(unsigned(map.get_int(MANIFEST_VERSION))?)? != VERSION {
+            return Err(Error::InvalidValue);
+        }
+        let sequence_number = required(unsigned(map.get_int(MANIFEST_SEQUENCE_NUMBER))?)?;
+        let common = required(bytes(map.get_int(COMMON))?)?;
+        let reference_uri = text(map.get_int(REFERENCE_URI))?;
+        let severable = |key| map.get_int(key).map(Severable::try_from).transpose();
+
+        Ok(Self {
+            sequence_number,
+            common,
+            reference_uri,
+            validate: bytes(map.get_int(VALIDATE))?,
+            load: bytes(map.get_int(LOAD))?,
+            invoke: bytes(map.get_int(INVOKE))?,
+            payload_fetch: severable(PAYLOAD_FETCH)?,
+            install: severable(INSTALL)?,
+            text: severable(TEXT)?,
+        })
+    }
+}
+
+/// The identifier of a component, such as a flash region or a file, as a list of byte
+/// string segments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComponentId<'a> {
+    segments: List<'a, &'a [u8]>,
+}
+
+impl<'a> ComponentId<'a> {
+    /// Creates a component identifier from its segments, of which there must be at
+    /// least one.
+    #[inline]
+    pub const fn new(segments: &'a [&'a [u8]]) -> Self {
+        Self {
+            segments: List::Items(segments),
+        }
+    }
+
+    /// Returns the segments of the identifier.
+    #[inline]
+    pub const fn segments(&self) -> List<'a, &'a [u8]> {
+        self.segments
+    }
+}
+
+impl Item for ComponentId<'_> {
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        self.segments.write(sink)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for ComponentId<'a> {
+    type Error = Error;
+
+    /// Reads a component identifier.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ComponentId)` - The identifier, whose segments are converted as they are
+    ///   read.
+    /// * `Err(Error::InvalidType)` - If the value is not a non-empty array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value.as_array() {
+            Some(segments) if !segments.is_empty() => Ok(Self {
+                segments: List::Values(segments),
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The common section of a manifest: the components and the sequence shared by all
+/// other sequences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Common<'a> {
+    components: List<'a, ComponentId<'a>>,
+    shared_sequence: Option<&'a [u8]>,
+}
+
+impl<'a> Common<'a> {
+    /// Creates a common section.
+    ///
+    /// # Arguments
+    ///
+    /// * `components` - The components the manifest affects. Commands refer to them by
+    ///   their index in this list.
+    #[inline]
+    pub const fn new(components: &'a [ComponentId<'a>]) -> Self {
+        Self {
+            components: List::Items(components),
+            shared_sequence: None,
+        }
+    }
+
+    /// Returns the section with the given encoded shared sequence.
+    #[inline]
+    pub const fn with_shared_sequence(self, shared_sequence: &'a [u8]) -> Self {
+        Self {
+            shared_sequence: Some(shared_sequence),
+            ..self
+        }
+    }
+
+    /// Returns the components.
+    #[inline]
+    pub const fn components(&self) -> List<'a, ComponentId<'a>> {
+        self.components
+    }
+
+    /// Returns the encoded shared sequence, which [`CommandSequence::decode`] reads.
+    #[inline]
+    pub const fn shared_sequence(&self) -> Option<&'a [u8]> {
+        self.shared_sequence
+    }
+
+    /// Decodes a common section, as found in [`Manifest::common`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Common)` - The section, whose components are converted as they are read.
+    /// * `Err(Error)` - If the bytes are not a valid section, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the section.
+    pub fn encoded_size(&self) -> usize {
+        Sink::size_of(|sink| self.write(sink))
+    }
+
+    /// Writes the section map.
+    fn write(&self, sink: &mut Sink) -> Result<()> {
+        let has_components = !self.components.is_empty();
+        let len = has_components as u64 + self.shared_sequence.is_some() as u64;
+        sink.header(major_type::MAP, len)?;
+        if has_components {
+            sink.value(&Value::integer(COMPONENTS))?;
+            self.components.write(sink)?;
+        }
+        if let Some(sequence) = self.shared_sequence {
+            sink.value(&Value::integer(SHARED_SEQUENCE))?;
+            sink.value(&Value::Bytes(sequence))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encode<'a> for Common<'_> {
+    /// Encodes the section map.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        Sink::write_to(buf, |sink| self.write(sink))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Common<'a> {
+    type Error = Error;
+
+    /// Reads a common section map. Dependencies are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Common)` - The section.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or an entry has the wrong
+    ///   type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        Ok(Self {
+            components: List::from_field(map.get_int(COMPONENTS))?,
+            shared_sequence: bytes(map.get_int(SHARED_SEQUENCE))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Authentication, Common, ComponentId, Digest, Envelope, Manifest, Severable};
+    use crate::{
+        Arena, Value, cbor,
+        cose::{self, CoseSign1, test_support::Checksum},
+        encode::{Encode, test_support::assert_encoding},
+        error::Error,
+    };
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let segments: [&[u8]; 2] = [b"flash", &[0x01]];
+        let components = [ComponentId::new(&segments)];
+        let common = Common::new(&components).with_shared_sequence(&[0x80]);
+        let mut common_buf = [0u8; 32];
+        let common_len = common.as_cbor(&mut common_buf).unwrap();
+        assert_eq!(common_len, common.encoded_size());
+
+        let install = Digest::new(cose::SHA_256, &[0x1A; 4]);
+        let manifest = Manifest::new(7, &common_buf[..common_len])
+            .with_reference_uri("https://example.com/fw")
+            .with_validate(&[0x80])
+            .with_invoke(&[0x82, 0x17, 0x00])
+            .with_install(Severable::Severed(install))
+            .with_text(Severable::Present(&[0xA0]));
+
+        static EXPECTED: Value = cbor!({
+            1: 1,
+            2: 7,
+            3: h "A202818245666C6173684101044180",
+            4: "https://example.com/fw",
+            7: h "80",
+            9: h "821700",
+            20: [-16, h "1A1A1A1A"],
+            23: h "A0"
+        });
+        let mut buf = [0u8; 128];
+        let size = manifest.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, manifest.encoded_size());
+        assert_encoding(&buf[..size], &EXPECTED);
+
+        let mut values = [Value::null(); 8];
+        let mut pairs = [(Value::null(), Value::null()); 12];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = Manifest::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, manifest);
+
+        let common = Common::decode(decoded.common(), &mut arena).unwrap();
+        let component = common.components().get(0).unwrap().unwrap();
+        assert_eq!(component.segments().get(0), Some(Ok(&b"flash"[..])));
+        assert_eq!(component.segments().len(), 2);
+        assert_eq!(common.shared_sequence(), Some(&[0x80][..]));
+
+        // Re-encoding the decoded section gives the same bytes
+        let mut again = [0u8; 32];
+        assert_eq!(common.as_cbor(&mut again), Ok(common_len));
+        assert_eq!(&again[..common_len], &common_buf[..common_len]);
+    }
+
+    #[test]
+    fn test_manifest_errors() {
+        static VERSION_2: Value = cbor!({1: 2, 2: 0, 3: h "A0"});
+        assert_eq!(Manifest::try_from(&VERSION_2), Err(Error::InvalidValue));
+
+        static NO_COMMON: Value = cbor!({1: 1, 2: 0});
+        static NO_VERSION: Value = cbor!({2: 0, 3: h "A0"});
+        static TEXT_VALIDATE: Value = cbor!({1: 1, 2: 0, 3: h "A0", 7: "seq"});
+        static BAD_SEVERED: Value = cbor!({1: 1, 2: 0, 3: h "A0", 20: [-16]});
+        static NOT_A_MAP: Value = cbor!([1, 0, h "A0"]);
+        for value in [
+            &NO_COMMON,
+            &NO_VERSION,
+            &TEXT_VALIDATE,
+            &BAD_SEVERED,
+            &NOT_A_MAP,
+        ] {
+            assert_eq!(
+                Manifest::try_from(value),
+                Err(Error::InvalidType),
+                "{value}"
+            );
+        }
+
+        static EMPTY_ID: Value = cbor!({2: [[]]});
+        let common = Common::try_from(&EMPTY_ID).unwrap();
+        assert_eq!(common.components().get(0), Some(Err(Error::InvalidType)));
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let payloads = [
+            (Value::text("#firmware"), Value::bytes(&[0xF0, 0x0D])),
+            (Value::integer(99), Value::bytes(&[])),
+        ];
+        let envelope = Envelope::new(&[0x81, 0x40], &[0xA0])
+            .with_install(&[0x80])
+            .with_payloads(&payloads);
+
+        static EXPECTED: Value = cbor!(107({
+            2: h "8140",
+            3: h "A0",
+            20: h "80",
+            "#firmware": h "F00D"
+        }));
+        let mut buf = [0u8; 64];
+        let size = envelope.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, envelope.encoded_size());
+        assert_encoding(&buf[..size], &EXPECTED);
+
+        let mut values = [Value::null(); 1];
+        let mut pairs = [(Value::null(), Value::null()); 4];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = Envelope::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded.authentication(), &[0x81, 0x40]);
+        assert_eq!(decoded.manifest(), &[0xA0]);
+        assert_eq!(decoded.install(), Some(&[0x80][..]));
+        assert_eq!(decoded.text(), None);
+        assert_eq!(decoded.payload("#firmware"), Some(&[0xF0, 0x0D][..]));
+        assert_eq!(decoded.payload("#other"), None);
+
+        // The decoded envelope borrows from the input bytes
+        assert!(buf.as_ptr_range().contains(&decoded.manifest().as_ptr()));
+
+        static UNTAGGED: Value = cbor!({2: h "8140", 3: h "A0", 1: h ""});
+        assert!(Envelope::try_from(&UNTAGGED).is_ok());
+        static NO_MANIFEST: Value = cbor!(107({2: h "8140"}));
+        static TEXT_PAYLOAD: Value = cbor!({2: h "8140", 3: h "A0", "#fw": "text"});
+        for value in [&NO_MANIFEST, &TEXT_PAYLOAD] {
+            assert_eq!(
+                Envelope::try_from(value),
+                Err(Error::InvalidType),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_authentication() {
+        let manifest_digest = Digest::new(cose::SHA_256, &[0x33; 32]);
+        let mut digest = [0u8; 48];
+        let digest_len = manifest_digest.as_cbor(&mut digest).unwrap();
+        assert_eq!(digest_len, manifest_digest.encoded_size());
+        let digest = &digest[..digest_len];
+
+        // Sign the digest, then detach it from the message
+        let protected = [0xA1, 0x01, 0x26];
+        let mut scratch = [0u8; 96];
+        let signed =
+            CoseSign1::sign(&protected, &[], digest, &[], &Checksum(5), &mut scratch).unwrap();
+        let detached = CoseSign1::new(&protected, &[], None, signed.signature());
+        let mut block = [0u8; 16];
+        let block_len = detached.as_cbor(&mut block).unwrap();
+        let blocks = [&block[..block_len]];
+        let authentication = Authentication::new(digest, &blocks);
+
+        let mut buf = [0u8; 96];
+        let size = authentication.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, authentication.encoded_size());
+
+        let mut values = [Value::null(); 16];
+        let mut pairs = [(Value::null(), Value::null()); 4];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = Authentication::decode(&buf[..size], &mut arena).unwrap();
+        let mut scratch = [0u8; 96];
+        assert_eq!(
+            decoded.verify(&mut arena, &Checksum(5), &mut scratch),
+            Ok(())
+        );
+        assert_eq!(
+            decoded.verify(&mut arena, &Checksum(6), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+
+        let decoded_digest = Digest::decode(decoded.digest(), &mut arena).unwrap();
+        assert!(decoded_digest.matches(cose::SHA_256, &[0x33; 32]));
+        assert!(!decoded_digest.matches(cose::SHA_384, &[0x33; 32]));
+
+        // An attached payload must be the digest, not some other signed content
+        let attached = signed.as_cbor(&mut buf).unwrap();
+        let blocks = [&buf[..attached]];
+        let mut values = [Value::null(); 16];
+        let mut pairs = [(Value::null(), Value::null()); 4];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        assert_eq!(
+            Authentication::new(digest, &blocks).verify(&mut arena, &Checksum(5), &mut scratch),
+            Ok(())
+        );
+
+        let mut other = [0u8; 96];
+        let other_signed =
+            CoseSign1::sign(&protected, &[], b"firmware", &[], &Checksum(5), &mut other).unwrap();
+        let mut block = [0u8; 32];
+        let block_len = other_signed.as_cbor(&mut block).unwrap();
+        let blocks = [&block[..block_len]];
+        let mut values = [Value::null(); 16];
+        let mut pairs = [(Value::null(), Value::null()); 4];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        assert_eq!(
+            Authentication::new(digest, &blocks).verify(&mut arena, &Checksum(5), &mut scratch),
+            Err(Error::InvalidValue)
+        );
+
+        let unsigned = Authentication::new(digest, &[]);
+        assert_eq!(
+            unsigned.verify(&mut arena, &Checksum(5), &mut scratch),
+            Err(Error::InvalidType)
+        );
+    }
+}
@@ -0,0 +1,519 @@
+// SPDX-License-Identifier: MIT
+
+//! Calendar dates (tags 100 and 1004).
+//!
+//! [RFC 8943](https://www.rfc-editor.org/rfc/rfc8943) defines two representations for
+//! dates without a time of day: tag 100 wraps the number of days since 1970-01-01 as an
+//! integer, and tag 1004 wraps an RFC 3339 `full-date` text string such as `1940-10-09`.
+//! Both are decoded into a [`Date`] in the proleptic Gregorian calendar.
+
+use crate::{
+    Value,
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    tag::{self, integer_from_value},
+};
+
+/// The first day representable by [`Date`].
+const MIN_DAYS: i64 = days_from_civil(i32::MIN as i64, 1, 1);
+/// The last day representable by [`Date`].
+const MAX_DAYS: i64 = days_from_civil(i32::MAX as i64, 12, 31);
+
+/// A date in the proleptic Gregorian calendar.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, tag::Date};
+///
+/// let date = Date::new(1940, 10, 9).unwrap();
+/// assert_eq!(date.to_days(), -10676);
+///
+/// // Both tag 100 and tag 1004 decode to the same date
+/// let days = Value::negative(-10676);
+/// assert_eq!(Date::try_from(&Value::tag(100, &days)), Ok(date));
+///
+/// let text = Value::text("1940-10-09");
+/// assert_eq!(Date::try_from(&Value::tag(1004, &text)), Ok(date));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// The Unix epoch date, 1970-01-01.
+    pub const UNIX_EPOCH: Date = Date {
+        year: 1970,
+        month: 1,
+        day: 1,
+    };
+
+    /// The length of an RFC 3339 `full-date` string, `YYYY-MM-DD`.
+    pub const FULL_DATE_LEN: usize = 10;
+
+    /// Creates a date from its year, month (1-12) and day of the month.
+    ///
+    /// Returns `None` if the month or day is out of range for that year.
+    #[inline]
+    pub const fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        if month >= 1 && month <= 12 && day >= 1 && day <= days_in_month(year as i64, month) {
+            Some(Self { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a date from the number of days since 1970-01-01.
+    ///
+    /// Returns `None` if the year would not fit in an `i32`.
+    #[inline]
+    pub const fn from_days(days: i64) -> Option<Self> {
+        if days < MIN_DAYS || days > MAX_DAYS {
+            return None;
+        }
+
+        let (year, month, day) = civil_from_days(days);
+        Some(Self {
+            year: year as i32,
+            month,
+            day,
+        })
+    }
+
+    /// Returns the number of days since 1970-01-01, negative for earlier dates.
+    #[inline]
+    pub const fn to_days(&self) -> i64 {
+        days_from_civil(self.year as i64, self.month, self.day)
+    }
+
+    /// Returns the year.
+    #[inline]
+    pub const fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Returns the month, from 1 (January) to 12 (December).
+    #[inline]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, starting at 1.
+    #[inline]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Parses an RFC 3339 `full-date` string such as `1940-10-09`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Date)` - The parsed date.
+    /// * `Err(Error::InvalidValue)` - If the string is not a valid full-date.
+    pub const fn parse_full_date(text: &str) -> Result<Self> {
+        let mut parser = Parser::new(text.as_bytes());
+        let date = const_try!(Self::parse(&mut parser));
+
+        if parser.is_empty() {
+            Ok(date)
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    /// Formats the date as an RFC 3339 `full-date` string into `buf`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&str)` - The formatted string, borrowed from `buf`.
+    /// * `Err(Error::BufferOverflow)` - If `buf` is shorter than [`Date::FULL_DATE_LEN`].
+    /// * `Err(Error::InvalidValue)` - If the year is outside 0000 to 9999.
+    pub fn format_full_date<'b>(&self, buf: &'b mut [u8]) -> Result<&'b str> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer)?;
+        writer.into_str()
+    }
+
+    /// Calculates the number of bytes needed to encode the date as tag 1004.
+    pub fn encoded_size(&self) -> Result<usize> {
+        let mut buf = [0u8; Self::FULL_DATE_LEN];
+        let text = Value::Text(self.format_full_date(&mut buf)?);
+        Ok(encode::encoded_size(&Value::Tag(tag::FULL_DATE, &text)))
+    }
+
+    /// Encodes the date as the number of days since the epoch (tag 100).
+    ///
+    /// Unlike the string form, this supports every representable year.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of bytes written to the buffer.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+    pub fn encode_days(&self, buf: &mut [u8]) -> Result<usize> {
+        let days = self.days_value();
+        encode::encode(&Value::Tag(tag::EPOCH_DATE, &days), buf)
+    }
+
+    /// Calculates the number of bytes needed to encode the date as tag 100.
+    pub fn days_encoded_size(&self) -> usize {
+        let days = self.days_value();
+        encode::encoded_size(&Value::Tag(tag::EPOCH_DATE, &days))
+    }
+
+    /// Returns the tag 100 payload for this date.
+    fn days_value(&self) -> Value<'static> {
+        let days = self.to_days();
+        if days < 0 {
+            Value::negative(days)
+        } else {
+            Value::Unsigned(days as u64)
+        }
+    }
+
+    /// Reads a `YYYY-MM-DD` date from the parser.
+    pub(super) const fn parse(parser: &mut Parser) -> Result<Self> {
+        let year = const_try!(parser.digits(4));
+        const_try!(parser.expect(b'-'));
+        let month = const_try!(parser.digits(2));
+        const_try!(parser.expect(b'-'));
+        let day = const_try!(parser.digits(2));
+
+        match Self::new(year as i32, month as u8, day as u8) {
+            Some(date) => Ok(date),
+            None => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Writes the date as `YYYY-MM-DD` to the writer.
+    pub(super) fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.year < 0 || self.year > 9999 {
+            return Err(Error::InvalidValue);
+        }
+
+        writer.digits(self.year as u32, 4)?;
+        writer.byte(b'-')?;
+        writer.digits(self.month as u32, 2)?;
+        writer.byte(b'-')?;
+        writer.digits(self.day as u32, 2)
+    }
+}
+
+impl<'a> Encode<'a> for Date {
+    /// Encodes the date as a full-date string (tag 1004).
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        let mut scratch = [0u8; Self::FULL_DATE_LEN];
+        let text = Value::Text(self.format_full_date(&mut scratch)?);
+        encode::encode(&Value::Tag(tag::FULL_DATE, &text), buf)
+    }
+}
+
+impl TryFrom<&Value<'_>> for Date {
+    type Error = Error;
+
+    /// Decodes a tag 100 day count or tag 1004 full-date string into a date.
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        match value {
+            Value::Tag(tag::FULL_DATE, Value::Text(text)) => Self::parse_full_date(text),
+            Value::Tag(tag::EPOCH_DATE, days) => match i64::try_from(integer_from_value(days)?) {
+                Ok(days) => Self::from_days(days).ok_or(Error::InvalidValue),
+                Err(_) => Err(Error::InvalidValue),
+            },
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// A byte-wise reader over an RFC 3339 string.
+pub(super) struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub(super) const fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub(super) const fn next(&mut self) -> Result<u8> {
+        if self.pos < self.bytes.len() {
+            let byte = self.bytes[self.pos];
+            self.pos += 1;
+            Ok(byte)
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    pub(super) const fn expect(&mut self, expected: u8) -> Result<()> {
+        if const_try!(self.next()) == expected {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    pub(super) const fn digits(&mut self, count: usize) -> Result<u32> {
+        let mut value = 0;
+        let mut i = 0;
+        while i < count {
+            let byte = const_try!(self.next());
+            if !byte.is_ascii_digit() {
+                return Err(Error::InvalidValue);
+            }
+            value = value * 10 + (byte - b'0') as u32;
+            i += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// A byte-wise writer of RFC 3339 strings into a fixed buffer.
+pub(super) struct Writer<'b> {
+    buf: &'b mut [u8],
+    pos: usize,
+}
+
+impl<'b> Writer<'b> {
+    pub(super) fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub(super) fn byte(&mut self, byte: u8) -> Result<()> {
+        match self.buf.get_mut(self.pos) {
+            Some(slot) => {
+                *slot = byte;
+                self.pos += 1;
+                Ok(())
+            }
+            None => Err(Error::BufferOverflow),
+        }
+    }
+
+    pub(super) fn digits(&mut self, value: u32, width: u32) -> Result<()> {
+        let mut divisor = 10u32.pow(width - 1);
+        while divisor > 0 {
+            self.byte(b'0' + (value / divisor % 10) as u8)?;
+            divisor /= 10;
+        }
+        Ok(())
+    }
+
+    /// Returns the written bytes as a string slice.
+    pub(super) fn into_str(self) -> Result<&'b str> {
+        match core::str::from_utf8(&self.buf[..self.pos]) {
+            Ok(text) => Ok(text),
+            Err(_) => Err(Error::InvalidValue),
+        }
+    }
+}
+
+/// Returns whether `year` is a leap year in the proleptic Gregorian calendar.
+const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days in the given month of the given year.
+const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days from 1970-01-01 to the given proleptic Gregorian date.
+const fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the proleptic Gregorian `(year, month, day)` for days since 1970-01-01.
+const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, civil_from_days, days_from_civil};
+    use crate::{
+        Value,
+        encode::{Encode, encode},
+        error::Error,
+    };
+
+    #[test]
+    fn test_construction() {
+        let date = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(date.year(), 2024);
+        assert_eq!(date.month(), 2);
+        assert_eq!(date.day(), 29);
+
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(1900, 2, 29), None);
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert_eq!(Date::new(2024, 4, 31), None);
+        assert_eq!(Date::new(2024, 0, 1), None);
+        assert_eq!(Date::new(2024, 13, 1), None);
+        assert_eq!(Date::new(2024, 1, 0), None);
+    }
+
+    #[test]
+    fn test_days_conversion() {
+        assert_eq!(Date::UNIX_EPOCH.to_days(), 0);
+        assert_eq!(Date::from_days(0), Some(Date::UNIX_EPOCH));
+        assert_eq!(Date::from_days(-1), Date::new(1969, 12, 31));
+        assert_eq!(Date::from_days(11017), Date::new(2000, 3, 1));
+
+        let min = Date::new(i32::MIN, 1, 1).unwrap();
+        let max = Date::new(i32::MAX, 12, 31).unwrap();
+        assert_eq!(Date::from_days(min.to_days()), Some(min));
+        assert_eq!(Date::from_days(max.to_days()), Some(max));
+        assert_eq!(Date::from_days(min.to_days() - 1), None);
+        assert_eq!(Date::from_days(max.to_days() + 1), None);
+        assert_eq!(Date::from_days(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let mut buf = [0u8; Date::FULL_DATE_LEN];
+
+        let date = Date::parse_full_date("1940-10-09").unwrap();
+        assert_eq!(date, Date::new(1940, 10, 9).unwrap());
+        assert_eq!(date.format_full_date(&mut buf), Ok("1940-10-09"));
+
+        let date = Date::new(5, 1, 2).unwrap();
+        assert_eq!(date.format_full_date(&mut buf), Ok("0005-01-02"));
+
+        let date = Date::new(10000, 1, 1).unwrap();
+        assert_eq!(date.format_full_date(&mut buf), Err(Error::InvalidValue));
+
+        let date = Date::new(-1, 1, 1).unwrap();
+        assert_eq!(date.format_full_date(&mut buf), Err(Error::InvalidValue));
+
+        let mut small = [0u8; 9];
+        assert_eq!(
+            Date::UNIX_EPOCH.format_full_date(&mut small),
+            Err(Error::BufferOverflow)
+        );
+
+        for text in ["", "1940-10-9", "1940-10-09T00", "1940-13-09", "1940/10/09"] {
+            assert_eq!(Date::parse_full_date(text), Err(Error::InvalidValue));
+        }
+    }
+
+    /// Test encoding of the RFC 8943 examples.
+    ///
+    /// The birth date 1940-10-09 is encoded as either:
+    /// - 0xD8 0x64 0x39 0x29 0xB3: Tag 100 wrapping -10676
+    /// - 0xD9 0x03 0xEC 0x6A ...: Tag 1004 wrapping "1940-10-09"
+    #[test]
+    fn test_encode() {
+        let date = Date::new(1940, 10, 9).unwrap();
+        let mut buf = [0u8; 16];
+
+        let size = date.encode_days(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[0xD8, 0x64, 0x39, 0x29, 0xB3]);
+        assert_eq!(date.days_encoded_size(), size);
+
+        let size = date.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..4], &[0xD9, 0x03, 0xEC, 0x6A]);
+        assert_eq!(&buf[4..size], b"1940-10-09");
+        assert_eq!(date.encoded_size(), Ok(size));
+
+        // Dates outside the full-date range can only use tag 100
+        let date = Date::new(-4713, 11, 24).unwrap();
+        assert_eq!(date.as_cbor(&mut buf), Err(Error::InvalidValue));
+        assert!(date.encode_days(&mut buf).is_ok());
+    }
+
+    #[test]
+    fn test_decode() {
+        let date = Date::new(2024, 5, 20).unwrap();
+
+        let days = Value::unsigned(19863);
+        assert_eq!(Date::try_from(&Value::tag(100, &days)), Ok(date));
+
+        let text = Value::text("2024-05-20");
+        assert_eq!(Date::try_from(&Value::tag(1004, &text)), Ok(date));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let text = Value::text("2024-02-30");
+        let value = Value::tag(1004, &text);
+        assert_eq!(Date::try_from(&value), Err(Error::InvalidValue));
+
+        let days = Value::Unsigned(u64::MAX);
+        let value = Value::tag(100, &days);
+        assert_eq!(Date::try_from(&value), Err(Error::InvalidValue));
+
+        let days = Value::text("19863");
+        let value = Value::tag(100, &days);
+        assert_eq!(Date::try_from(&value), Err(Error::InvalidType));
+
+        let text = Value::unsigned(19863);
+        let value = Value::tag(1004, &text);
+        assert_eq!(Date::try_from(&value), Err(Error::InvalidType));
+
+        let value = Value::tag(0, &text);
+        assert_eq!(Date::try_from(&value), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_encode_invalid() {
+        let mut buf = [0u8; 16];
+        let text = Value::text("garbage");
+        let value = Value::tag(1004, &text);
+        assert_eq!(encode(&value, &mut buf), Err(Error::InvalidValue));
+
+        let bytes = Value::bytes(&[0x00]);
+        let value = Value::tag(100, &bytes);
+        assert_eq!(encode(&value, &mut buf), Err(Error::InvalidType));
+
+        let days = Value::negative(-10676);
+        let value = Value::tag(100, &days);
+        assert_eq!(encode(&value, &mut buf), Ok(5));
+    }
+
+    #[test]
+    fn test_civil_conversions() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(0, 1, 1), -719528);
+
+        let mut days = -800_000;
+        while days < 3_000_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+            days += 97;
+        }
+    }
+}
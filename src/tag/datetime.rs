@@ -0,0 +1,587 @@
+// SPDX-License-Identifier: MIT
+
+//! Standard date/time strings (tag 0) and epoch-based date/time (tag 1).
+//!
+//! Tag 0 wraps an [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) `date-time` text
+//! string, while tag 1 wraps the number of seconds since 1970-01-01T00:00Z as an integer
+//! or floating-point number. Both are decoded into a [`Timestamp`].
+//!
+//! The parser and formatter work on fixed buffers and never allocate.
+
+use core::time::Duration;
+
+use crate::{
+    Value,
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    tag::{
+        self, Date,
+        date::{Parser, Writer},
+        integer_from_value,
+    },
+};
+
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// An instant in time, with the UTC offset it was expressed in.
+///
+/// The instant itself is stored as seconds and nanoseconds relative to the Unix epoch
+/// (1970-01-01T00:00:00Z). The offset only affects how the timestamp is formatted as
+/// an RFC 3339 string; two timestamps describing the same instant with different
+/// offsets compare as different values.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, tag::Timestamp};
+///
+/// let timestamp = Timestamp::parse_rfc3339("2024-05-20T10:30:00Z").unwrap();
+/// assert_eq!(timestamp.seconds(), 1716201000);
+///
+/// // Format into a fixed buffer and wrap it in tag 0
+/// let mut buf = [0u8; Timestamp::RFC3339_MAX_LEN];
+/// let text = Value::text(timestamp.format_rfc3339(&mut buf).unwrap());
+/// let value = Value::tag(0, &text);
+///
+/// assert_eq!(Timestamp::try_from(&value), Ok(timestamp));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    seconds: i64,
+    nanoseconds: u32,
+    offset_minutes: i16,
+}
+
+impl Timestamp {
+    /// The Unix epoch, 1970-01-01T00:00:00Z.
+    pub const UNIX_EPOCH: Timestamp = Timestamp {
+        seconds: 0,
+        nanoseconds: 0,
+        offset_minutes: 0,
+    };
+
+    /// The maximum length of an RFC 3339 string produced by [`Timestamp::format_rfc3339`].
+    ///
+    /// This corresponds to `YYYY-MM-DDTHH:MM:SS.nnnnnnnnn+HH:MM`.
+    pub const RFC3339_MAX_LEN: usize = 35;
+
+    /// Creates a UTC timestamp from seconds and nanoseconds since the Unix epoch.
+    ///
+    /// Returns `None` if `nanoseconds` is not less than one billion.
+    #[inline]
+    pub const fn new(seconds: i64, nanoseconds: u32) -> Option<Self> {
+        if nanoseconds < NANOS_PER_SECOND {
+            Some(Self {
+                seconds,
+                nanoseconds,
+                offset_minutes: 0,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the same instant expressed with the given UTC offset in minutes.
+    ///
+    /// Returns `None` if the offset is not between -23:59 and +23:59.
+    #[inline]
+    pub const fn with_offset(self, offset_minutes: i16) -> Option<Self> {
+        if offset_minutes > -1440 && offset_minutes < 1440 {
+            Some(Self {
+                offset_minutes,
+                ..self
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a UTC timestamp from a duration since the Unix epoch.
+    ///
+    /// Returns `None` if the duration does not fit in a signed 64-bit number of seconds.
+    #[inline]
+    pub const fn from_duration(duration: Duration) -> Option<Self> {
+        if duration.as_secs() > i64::MAX as u64 {
+            None
+        } else {
+            Self::new(duration.as_secs() as i64, duration.subsec_nanos())
+        }
+    }
+
+    /// Returns the duration since the Unix epoch, or `None` for instants before it.
+    #[inline]
+    pub const fn to_duration(&self) -> Option<Duration> {
+        if self.seconds < 0 {
+            None
+        } else {
+            Some(Duration::new(self.seconds as u64, self.nanoseconds))
+        }
+    }
+
+    /// Returns the whole seconds since the Unix epoch.
+    #[inline]
+    pub const fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// Returns the fractional part of the instant in nanoseconds.
+    #[inline]
+    pub const fn nanoseconds(&self) -> u32 {
+        self.nanoseconds
+    }
+
+    /// Returns the UTC offset in minutes used when formatting.
+    #[inline]
+    pub const fn offset_minutes(&self) -> i16 {
+        self.offset_minutes
+    }
+
+    /// Parses an RFC 3339 `date-time` string such as `2024-05-20T10:30:00.5+02:00`.
+    ///
+    /// Fractional seconds beyond nanosecond precision are truncated. Leap seconds
+    /// (a seconds field of 60) are not supported.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Timestamp)` - The parsed timestamp, keeping the offset of the input.
+    /// * `Err(Error::InvalidValue)` - If the string is not a valid RFC 3339 date-time.
+    pub fn parse_rfc3339(text: &str) -> Result<Self> {
+        let mut parser = Parser::new(text.as_bytes());
+
+        let date = Date::parse(&mut parser)?;
+        match parser.next()? {
+            b'T' | b't' => (),
+            _ => return Err(Error::InvalidValue),
+        }
+        let hour = parser.digits(2)?;
+        parser.expect(b':')?;
+        let minute = parser.digits(2)?;
+        parser.expect(b':')?;
+        let second = parser.digits(2)?;
+
+        let mut nanoseconds = 0;
+        let mut next = parser.next()?;
+        if next == b'.' {
+            let mut scale = NANOS_PER_SECOND;
+            let mut count = 0;
+            next = parser.next()?;
+            while next.is_ascii_digit() {
+                if scale > 1 {
+                    scale /= 10;
+                    nanoseconds += (next - b'0') as u32 * scale;
+                }
+                count += 1;
+                next = parser.next()?;
+            }
+            if count == 0 {
+                return Err(Error::InvalidValue);
+            }
+        }
+
+        let offset_minutes = match next {
+            b'Z' | b'z' => 0,
+            b'+' | b'-' => {
+                let hours = parser.digits(2)?;
+                parser.expect(b':')?;
+                let minutes = parser.digits(2)?;
+                if hours > 23 || minutes > 59 {
+                    return Err(Error::InvalidValue);
+                }
+                let offset = (hours * 60 + minutes) as i16;
+                if next == b'-' { -offset } else { offset }
+            }
+            _ => return Err(Error::InvalidValue),
+        };
+
+        if !parser.is_empty() || hour > 23 || minute > 59 || second > 59 {
+            return Err(Error::InvalidValue);
+        }
+
+        let local = date.to_days() * SECONDS_PER_DAY + (hour * 3600 + minute * 60 + second) as i64;
+
+        Ok(Self {
+            seconds: local - offset_minutes as i64 * 60,
+            nanoseconds,
+            offset_minutes,
+        })
+    }
+
+    /// Formats the timestamp as an RFC 3339 `date-time` string into `buf`.
+    ///
+    /// Fractional seconds are written with as few digits as needed and omitted when zero.
+    /// A zero offset is written as `Z`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&str)` - The formatted string, borrowed from `buf`.
+    /// * `Err(Error::BufferOverflow)` - If `buf` is too small; [`Timestamp::RFC3339_MAX_LEN`]
+    ///   bytes are always sufficient.
+    /// * `Err(Error::InvalidValue)` - If the local year is outside 0000 to 9999.
+    pub fn format_rfc3339<'b>(&self, buf: &'b mut [u8]) -> Result<&'b str> {
+        let local = self.seconds as i128 + self.offset_minutes as i128 * 60;
+        let days = local.div_euclid(SECONDS_PER_DAY as i128);
+        let time = local.rem_euclid(SECONDS_PER_DAY as i128) as u32;
+
+        let date = match i64::try_from(days).ok().and_then(Date::from_days) {
+            Some(date) => date,
+            None => return Err(Error::InvalidValue),
+        };
+
+        let mut writer = Writer::new(buf);
+        date.write(&mut writer)?;
+        writer.byte(b'T')?;
+        writer.digits(time / 3600, 2)?;
+        writer.byte(b':')?;
+        writer.digits(time / 60 % 60, 2)?;
+        writer.byte(b':')?;
+        writer.digits(time % 60, 2)?;
+
+        if self.nanoseconds != 0 {
+            let mut fraction = self.nanoseconds;
+            let mut width = 9;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+            writer.byte(b'.')?;
+            writer.digits(fraction, width)?;
+        }
+
+        if self.offset_minutes == 0 {
+            writer.byte(b'Z')?;
+        } else {
+            let offset = self.offset_minutes.unsigned_abs() as u32;
+            writer.byte(if self.offset_minutes < 0 { b'-' } else { b'+' })?;
+            writer.digits(offset / 60, 2)?;
+            writer.byte(b':')?;
+            writer.digits(offset % 60, 2)?;
+        }
+
+        writer.into_str()
+    }
+
+    /// Calculates the number of bytes needed to encode the timestamp as tag 0.
+    pub fn encoded_size(&self) -> Result<usize> {
+        let mut buf = [0u8; Self::RFC3339_MAX_LEN];
+        let text = Value::Text(self.format_rfc3339(&mut buf)?);
+        Ok(encode::encoded_size(&Value::Tag(tag::DATE_TIME, &text)))
+    }
+
+    /// Encodes the timestamp as epoch-based date/time (tag 1).
+    ///
+    /// Whole-second timestamps are written as integers; anything with a fractional part
+    /// is written as a floating-point number, which may lose sub-microsecond precision.
+    /// The UTC offset is not preserved.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of bytes written to the buffer.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+    pub fn encode_epoch(&self, buf: &mut [u8]) -> Result<usize> {
+        let seconds = self.epoch_value();
+        encode::encode(&Value::Tag(tag::EPOCH_DATE_TIME, &seconds), buf)
+    }
+
+    /// Calculates the number of bytes needed to encode the timestamp as tag 1.
+    pub fn epoch_encoded_size(&self) -> usize {
+        let seconds = self.epoch_value();
+        encode::encoded_size(&Value::Tag(tag::EPOCH_DATE_TIME, &seconds))
+    }
+
+    /// Returns the tag 1 payload for this timestamp.
+    fn epoch_value(&self) -> Value<'static> {
+        if self.nanoseconds != 0 {
+            Value::Float(self.seconds as f64 + self.nanoseconds as f64 / NANOS_PER_SECOND as f64)
+        } else if self.seconds < 0 {
+            Value::negative(self.seconds)
+        } else {
+            Value::Unsigned(self.seconds as u64)
+        }
+    }
+
+    /// Interprets a floating-point number of seconds since the epoch.
+    fn from_epoch_float(seconds: f64) -> Result<Self> {
+        // Bounds are chosen so that the whole part is exactly convertible to `i64`.
+        if !(-9.2e18..9.2e18).contains(&seconds) {
+            return Err(Error::InvalidValue);
+        }
+
+        let mut whole = seconds as i64;
+        if whole as f64 > seconds {
+            whole -= 1;
+        }
+
+        let nanoseconds = ((seconds - whole as f64) * NANOS_PER_SECOND as f64) as u32;
+        Ok(Self {
+            seconds: whole,
+            nanoseconds: nanoseconds.min(NANOS_PER_SECOND - 1),
+            offset_minutes: 0,
+        })
+    }
+}
+
+impl<'a> Encode<'a> for Timestamp {
+    /// Encodes the timestamp as a standard date/time string (tag 0).
+    ///
+    /// This form preserves both the offset and the full nanosecond precision.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        let mut scratch = [0u8; Self::RFC3339_MAX_LEN];
+        let text = Value::Text(self.format_rfc3339(&mut scratch)?);
+        encode::encode(&Value::Tag(tag::DATE_TIME, &text), buf)
+    }
+}
+
+impl TryFrom<&Value<'_>> for Timestamp {
+    type Error = Error;
+
+    /// Decodes a tag 0 string or tag 1 number into a timestamp.
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        match value {
+            Value::Tag(tag::DATE_TIME, Value::Text(text)) => Self::parse_rfc3339(text),
+            Value::Tag(tag::EPOCH_DATE_TIME, Value::Float(seconds)) => {
+                Self::from_epoch_float(*seconds)
+            }
+            Value::Tag(tag::EPOCH_DATE_TIME, seconds) => {
+                match i64::try_from(integer_from_value(seconds)?) {
+                    Ok(seconds) => Ok(Self {
+                        seconds,
+                        nanoseconds: 0,
+                        offset_minutes: 0,
+                    }),
+                    Err(_) => Err(Error::InvalidValue),
+                }
+            }
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::Timestamp;
+    use crate::{Value, encode::Encode, error::Error};
+
+    #[test]
+    fn test_parse_utc() {
+        let timestamp = Timestamp::parse_rfc3339("2013-03-21T20:04:00Z").unwrap();
+        assert_eq!(timestamp.seconds(), 1363896240);
+        assert_eq!(timestamp.nanoseconds(), 0);
+        assert_eq!(timestamp.offset_minutes(), 0);
+    }
+
+    #[test]
+    fn test_parse_fraction_and_offset() {
+        let timestamp = Timestamp::parse_rfc3339("2013-03-21T22:04:00.5+02:00").unwrap();
+        assert_eq!(timestamp.seconds(), 1363896240);
+        assert_eq!(timestamp.nanoseconds(), 500_000_000);
+        assert_eq!(timestamp.offset_minutes(), 120);
+
+        // Digits beyond nanosecond precision are truncated
+        let timestamp = Timestamp::parse_rfc3339("1969-12-31t23:59:59.1234567899-00:30").unwrap();
+        assert_eq!(timestamp.seconds(), 1799);
+        assert_eq!(timestamp.nanoseconds(), 123_456_789);
+        assert_eq!(timestamp.offset_minutes(), -30);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let invalid = [
+            "",
+            "2013-03-21",
+            "2013-03-21 20:04:00Z",
+            "2013-03-21T20:04:00",
+            "2013-03-21T20:04:00.Z",
+            "2013-13-21T20:04:00Z",
+            "2013-02-29T20:04:00Z",
+            "2013-03-21T24:00:00Z",
+            "2013-03-21T20:60:00Z",
+            "2013-03-21T20:04:60Z",
+            "2013-03-21T20:04:00+24:00",
+            "2013-03-21T20:04:00+0200",
+            "2013-03-21T20:04:00Zjunk",
+            "2013/03/21T20:04:00Z",
+        ];
+
+        for text in invalid {
+            assert_eq!(
+                Timestamp::parse_rfc3339(text),
+                Err(Error::InvalidValue),
+                "{text}"
+            );
+        }
+
+        // Leap days are accepted in leap years
+        assert!(Timestamp::parse_rfc3339("2024-02-29T00:00:00Z").is_ok());
+    }
+
+    #[test]
+    fn test_format() {
+        let mut buf = [0u8; Timestamp::RFC3339_MAX_LEN];
+
+        let timestamp = Timestamp::new(1363896240, 0).unwrap();
+        assert_eq!(
+            timestamp.format_rfc3339(&mut buf),
+            Ok("2013-03-21T20:04:00Z")
+        );
+
+        let timestamp = Timestamp::new(1363896240, 500_000_000)
+            .unwrap()
+            .with_offset(-90)
+            .unwrap();
+        assert_eq!(
+            timestamp.format_rfc3339(&mut buf),
+            Ok("2013-03-21T18:34:00.5-01:30")
+        );
+
+        let timestamp = Timestamp::new(253402300799, 999_999_999)
+            .unwrap()
+            .with_offset(1439)
+            .unwrap();
+        // The local time falls in the year 10000, which RFC 3339 cannot represent
+        assert_eq!(timestamp.format_rfc3339(&mut buf), Err(Error::InvalidValue));
+
+        let timestamp = Timestamp::new(253402300799 - 1439 * 60, 999_999_999)
+            .unwrap()
+            .with_offset(1439)
+            .unwrap();
+        let text = timestamp.format_rfc3339(&mut buf).unwrap();
+        assert_eq!(text, "9999-12-31T23:59:59.999999999+23:59");
+        assert_eq!(text.len(), Timestamp::RFC3339_MAX_LEN);
+    }
+
+    #[test]
+    fn test_format_buffer_overflow() {
+        let mut buf = [0u8; 19];
+        let timestamp = Timestamp::UNIX_EPOCH;
+        assert_eq!(
+            timestamp.format_rfc3339(&mut buf),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_rfc3339() {
+        let mut buf = [0u8; Timestamp::RFC3339_MAX_LEN];
+        let texts = [
+            "0000-01-01T00:00:00Z",
+            "1970-01-01T00:00:00Z",
+            "1969-12-31T23:59:59.999999999Z",
+            "2000-02-29T12:00:00.000001+05:45",
+            "2024-05-20T10:30:00-08:00",
+        ];
+
+        for text in texts {
+            let timestamp = Timestamp::parse_rfc3339(text).unwrap();
+            assert_eq!(timestamp.format_rfc3339(&mut buf), Ok(text));
+        }
+    }
+
+    /// Test encoding of the RFC 8949 tag 0 example.
+    #[test]
+    fn test_encode_date_time() {
+        let timestamp = Timestamp::parse_rfc3339("2013-03-21T20:04:00Z").unwrap();
+        let mut buf = [0u8; 64];
+        let size = timestamp.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(size, 22);
+        assert_eq!(buf[0], 0xC0); // tag 0
+        assert_eq!(buf[1], 0x74); // text string of length 20
+        assert_eq!(&buf[2..size], b"2013-03-21T20:04:00Z");
+        assert_eq!(timestamp.encoded_size(), Ok(size));
+    }
+
+    /// Test encoding of the RFC 8949 tag 1 examples.
+    #[test]
+    fn test_encode_epoch() {
+        let mut buf = [0u8; 16];
+
+        let timestamp = Timestamp::new(1363896240, 0).unwrap();
+        let size = timestamp.encode_epoch(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[0xC1, 0x1A, 0x51, 0x4B, 0x67, 0xB0]);
+        assert_eq!(timestamp.epoch_encoded_size(), size);
+
+        let timestamp = Timestamp::new(1363896240, 500_000_000).unwrap();
+        let size = timestamp.encode_epoch(&mut buf).unwrap();
+        assert_eq!(buf[0], 0xC1);
+        assert_eq!(&buf[2..size], &1363896240.5f64.to_bits().to_be_bytes());
+
+        let timestamp = Timestamp::new(-1, 0).unwrap();
+        let size = timestamp.encode_epoch(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[0xC1, 0x20]);
+    }
+
+    #[test]
+    fn test_decode() {
+        let text = Value::text("2013-03-21T20:04:00Z");
+        let value = Value::tag(0, &text);
+        let expected = Timestamp::new(1363896240, 0).unwrap();
+        assert_eq!(Timestamp::try_from(&value), Ok(expected));
+
+        let seconds = Value::unsigned(1363896240);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(Timestamp::try_from(&value), Ok(expected));
+
+        let seconds = Value::float(1363896240.5);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(
+            Timestamp::try_from(&value),
+            Timestamp::new(1363896240, 500_000_000).ok_or(Error::InvalidValue)
+        );
+
+        let seconds = Value::float(-1.25);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(
+            Timestamp::try_from(&value),
+            Timestamp::new(-2, 750_000_000).ok_or(Error::InvalidValue)
+        );
+
+        let seconds = Value::negative(-1);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(
+            Timestamp::try_from(&value),
+            Timestamp::new(-1, 0).ok_or(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let seconds = Value::float(f64::NAN);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(Timestamp::try_from(&value), Err(Error::InvalidValue));
+
+        let seconds = Value::Unsigned(u64::MAX);
+        let value = Value::tag(1, &seconds);
+        assert_eq!(Timestamp::try_from(&value), Err(Error::InvalidValue));
+
+        let seconds = Value::text("1363896240");
+        let value = Value::tag(1, &seconds);
+        assert_eq!(Timestamp::try_from(&value), Err(Error::InvalidType));
+
+        let text = Value::unsigned(0);
+        let value = Value::tag(0, &text);
+        assert_eq!(Timestamp::try_from(&value), Err(Error::InvalidType));
+
+        let text = Value::text("2013-03-21T20:04:00Z");
+        assert_eq!(Timestamp::try_from(&text), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_duration() {
+        let timestamp = Timestamp::new(1363896240, 5).unwrap();
+        assert_eq!(timestamp.to_duration(), Some(Duration::new(1363896240, 5)));
+        assert_eq!(
+            Timestamp::from_duration(Duration::new(1363896240, 5)),
+            Some(timestamp)
+        );
+
+        let before_epoch = Timestamp::new(-1, 0).unwrap();
+        assert_eq!(before_epoch.to_duration(), None);
+        assert_eq!(Timestamp::from_duration(Duration::MAX), None);
+        assert_eq!(Timestamp::new(0, 1_000_000_000), None);
+        assert_eq!(Timestamp::UNIX_EPOCH.with_offset(1440), None);
+    }
+}
@@ -0,0 +1,590 @@
+// SPDX-License-Identifier: MIT
+
+//! Decimal fractions (tag 4) and bigfloats (tag 5).
+//!
+//! Both tags wrap a two-element array `[exponent, mantissa]`, as defined in
+//! [RFC 8949 §3.4.4](https://www.rfc-editor.org/rfc/rfc8949#section-3.4.4). The exponent
+//! is always a CBOR integer, while the mantissa may be either an integer or a bignum
+//! (tag 2 or 3). Unlike `Value::Float`, these representations are exact, which makes them
+//! suitable for values such as monetary amounts.
+
+use crate::{
+    Value,
+    encode::{self, Encode},
+    error::Error,
+    result::Result,
+    tag::{self, integer_from_value, integer_value},
+};
+
+/// An arbitrary-precision integer stored as a big-endian magnitude (tag 2 or 3).
+///
+/// Following CBOR's integer encoding, a negative bignum represents `-1 - n`, where `n`
+/// is the unsigned magnitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bignum<'a> {
+    negative: bool,
+    magnitude: &'a [u8],
+}
+
+impl<'a> Bignum<'a> {
+    /// Creates a bignum from its sign and big-endian magnitude.
+    ///
+    /// # Arguments
+    ///
+    /// * `negative` - Whether the bignum is negative (tag 3) rather than positive (tag 2).
+    /// * `magnitude` - The big-endian bytes of `n`, where the value is `n` or `-1 - n`.
+    #[inline]
+    pub const fn new(negative: bool, magnitude: &'a [u8]) -> Self {
+        Self {
+            negative,
+            magnitude,
+        }
+    }
+
+    /// Returns `true` if the bignum is negative (tag 3).
+    #[inline]
+    pub const fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the big-endian magnitude bytes.
+    #[inline]
+    pub const fn magnitude(&self) -> &'a [u8] {
+        self.magnitude
+    }
+
+    /// Returns the tag number used to encode this bignum.
+    #[inline]
+    pub const fn tag(&self) -> u64 {
+        if self.negative {
+            tag::NEGATIVE_BIGNUM
+        } else {
+            tag::POSITIVE_BIGNUM
+        }
+    }
+
+    /// Converts the bignum into an `i128`, if it fits.
+    ///
+    /// Leading zero bytes in the magnitude are ignored.
+    pub const fn to_i128(&self) -> Option<i128> {
+        let n = match self.magnitude_u128() {
+            Some(n) => n,
+            None => return None,
+        };
+
+        if n > i128::MAX as u128 {
+            None
+        } else if self.negative {
+            Some(-1 - n as i128)
+        } else {
+            Some(n as i128)
+        }
+    }
+
+    /// Converts a positive bignum into a `u128`, if it fits.
+    ///
+    /// Leading zero bytes in the magnitude are ignored.
+    pub const fn to_u128(&self) -> Option<u128> {
+        if self.negative {
+            None
+        } else {
+            self.magnitude_u128()
+        }
+    }
+
+    /// Returns the magnitude as a `u128`, if it fits.
+    const fn magnitude_u128(&self) -> Option<u128> {
+        let mut n: u128 = 0;
+        let mut i = 0;
+
+        while i < self.magnitude.len() {
+            if n >> 120 != 0 {
+                return None;
+            }
+            n = (n << 8) | self.magnitude[i] as u128;
+            i += 1;
+        }
+
+        Some(n)
+    }
+
+    /// Writes the minimal bignum representation of `value` into `scratch`.
+    fn from_i128(value: i128, scratch: &mut [u8; 16]) -> Bignum<'_> {
+        let (negative, n) = if value < 0 {
+            (true, !value as u128)
+        } else {
+            (false, value as u128)
+        };
+
+        *scratch = n.to_be_bytes();
+        let start = (n.leading_zeros() / 8) as usize;
+
+        Bignum {
+            negative,
+            magnitude: &scratch[start..],
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Bignum<'a> {
+    type Error = Error;
+
+    /// Interprets a tag 2 or tag 3 value wrapping a byte string as a bignum.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Tag(tag::POSITIVE_BIGNUM, Value::Bytes(magnitude)) => {
+                Ok(Self::new(false, magnitude))
+            }
+            Value::Tag(tag::NEGATIVE_BIGNUM, Value::Bytes(magnitude)) => {
+                Ok(Self::new(true, magnitude))
+            }
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// The mantissa of a decimal fraction or bigfloat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mantissa<'a> {
+    /// An integer mantissa.
+    ///
+    /// When encoding, values within the CBOR integer range (-2^64 to 2^64-1) are written
+    /// as plain integers and anything larger is written as a bignum.
+    Integer(i128),
+
+    /// A bignum mantissa borrowed from its big-endian magnitude.
+    ///
+    /// This is always encoded as a bignum, even if its value would fit in an integer.
+    Bignum(Bignum<'a>),
+}
+
+impl<'a> Mantissa<'a> {
+    /// Converts the mantissa into an `i128`, if it fits.
+    #[inline]
+    pub const fn to_i128(&self) -> Option<i128> {
+        match self {
+            Mantissa::Integer(n) => Some(*n),
+            Mantissa::Bignum(b) => b.to_i128(),
+        }
+    }
+
+    /// Returns the bignum form of the mantissa, using `scratch` for integer mantissas.
+    fn bignum<'s>(&self, scratch: &'s mut [u8; 16]) -> Bignum<'s>
+    where
+        'a: 's,
+    {
+        match *self {
+            Mantissa::Integer(n) => Bignum::from_i128(n, scratch),
+            Mantissa::Bignum(b) => b,
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Mantissa<'a> {
+    type Error = Error;
+
+    /// Interprets an integer or bignum value as a mantissa.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Unsigned(_) | Value::Negative(_) => {
+                Ok(Mantissa::Integer(integer_from_value(value)?))
+            }
+            _ => Ok(Mantissa::Bignum(Bignum::try_from(value)?)),
+        }
+    }
+}
+
+/// A decimal fraction (tag 4), representing the exact value `mantissa * 10^exponent`.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{Value, tag::{DecimalFraction, Mantissa}};
+///
+/// // 1.10 as an exact amount
+/// let price = DecimalFraction::from_pair((110, -2));
+/// assert_eq!(price.exponent(), -2);
+/// assert_eq!(price.mantissa(), Mantissa::Integer(110));
+///
+/// // Recover the fraction from a value tree
+/// let items = [Value::negative(-2), Value::unsigned(110)];
+/// let array = Value::array(&items);
+/// let value = Value::tag(4, &array);
+/// assert_eq!(DecimalFraction::try_from(&value), Ok(price));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalFraction<'a> {
+    exponent: i64,
+    mantissa: Mantissa<'a>,
+}
+
+impl<'a> DecimalFraction<'a> {
+    /// Creates a decimal fraction from its base-10 exponent and mantissa.
+    #[inline]
+    pub const fn new(exponent: i64, mantissa: Mantissa<'a>) -> Self {
+        Self { exponent, mantissa }
+    }
+
+    /// Creates a decimal fraction from a `(mantissa, exponent)` pair.
+    #[inline]
+    pub const fn from_pair((mantissa, exponent): (i128, i64)) -> Self {
+        Self::new(exponent, Mantissa::Integer(mantissa))
+    }
+
+    /// Returns the `(mantissa, exponent)` pair, if the mantissa fits in an `i128`.
+    #[inline]
+    pub const fn to_pair(&self) -> Option<(i128, i64)> {
+        match self.mantissa.to_i128() {
+            Some(mantissa) => Some((mantissa, self.exponent)),
+            None => None,
+        }
+    }
+
+    /// Returns the base-10 exponent.
+    #[inline]
+    pub const fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    /// Returns the mantissa.
+    #[inline]
+    pub const fn mantissa(&self) -> Mantissa<'a> {
+        self.mantissa
+    }
+
+    /// Calculates the number of bytes needed to encode the decimal fraction.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        with_value(
+            tag::DECIMAL_FRACTION,
+            self.exponent,
+            &self.mantissa,
+            encode::encoded_size,
+        )
+    }
+}
+
+impl<'a> Encode<'a> for DecimalFraction<'_> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        with_value(
+            tag::DECIMAL_FRACTION,
+            self.exponent,
+            &self.mantissa,
+            |value| encode::encode(value, buf),
+        )
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for DecimalFraction<'a> {
+    type Error = Error;
+
+    /// Validates that the value is tag 4 wrapping an `[exponent, mantissa]` array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let (exponent, mantissa) = parse(tag::DECIMAL_FRACTION, value)?;
+        Ok(Self::new(exponent, mantissa))
+    }
+}
+
+/// A bigfloat (tag 5), representing the exact value `mantissa * 2^exponent`.
+///
+/// # Examples
+///
+/// ```
+/// use const_cbor::{encode::Encode, tag::Bigfloat};
+///
+/// // 1.5 is 3 * 2^-1
+/// let value = Bigfloat::from_pair((3, -1));
+///
+/// let mut buf = [0u8; 8];
+/// let size = value.as_cbor(&mut buf).unwrap();
+/// assert_eq!(&buf[..size], &[0xC5, 0x82, 0x20, 0x03]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bigfloat<'a> {
+    exponent: i64,
+    mantissa: Mantissa<'a>,
+}
+
+impl<'a> Bigfloat<'a> {
+    /// Creates a bigfloat from its base-2 exponent and mantissa.
+    #[inline]
+    pub const fn new(exponent: i64, mantissa: Mantissa<'a>) -> Self {
+        Self { exponent, mantissa }
+    }
+
+    /// Creates a bigfloat from a `(mantissa, exponent)` pair.
+    #[inline]
+    pub const fn from_pair((mantissa, exponent): (i128, i64)) -> Self {
+        Self::new(exponent, Mantissa::Integer(mantissa))
+    }
+
+    /// Returns the `(mantissa, exponent)` pair, if the mantissa fits in an `i128`.
+    #[inline]
+    pub const fn to_pair(&self) -> Option<(i128, i64)> {
+        match self.mantissa.to_i128() {
+            Some(mantissa) => Some((mantissa, self.exponent)),
+            None => None,
+        }
+    }
+
+    /// Returns the base-2 exponent.
+    #[inline]
+    pub const fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    /// Returns the mantissa.
+    #[inline]
+    pub const fn mantissa(&self) -> Mantissa<'a> {
+        self.mantissa
+    }
+
+    /// Calculates the number of bytes needed to encode the bigfloat.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        with_value(
+            tag::BIGFLOAT,
+            self.exponent,
+            &self.mantissa,
+            encode::encoded_size,
+        )
+    }
+}
+
+impl<'a> Encode<'a> for Bigfloat<'_> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        with_value(tag::BIGFLOAT, self.exponent, &self.mantissa, |value| {
+            encode::encode(value, buf)
+        })
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for Bigfloat<'a> {
+    type Error = Error;
+
+    /// Validates that the value is tag 5 wrapping an `[exponent, mantissa]` array.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let (exponent, mantissa) = parse(tag::BIGFLOAT, value)?;
+        Ok(Self::new(exponent, mantissa))
+    }
+}
+
+/// Builds the `tag([exponent, mantissa])` value on the stack and passes it to `f`.
+fn with_value<R>(tag: u64, exponent: i64, mantissa: &Mantissa, f: impl FnOnce(&Value) -> R) -> R {
+    let mut scratch = [0u8; 16];
+    let magnitude;
+
+    let integer = match mantissa {
+        Mantissa::Integer(n) => integer_value(*n),
+        Mantissa::Bignum(_) => None,
+    };
+
+    let mantissa = match integer {
+        Some(value) => value,
+        None => {
+            let bignum = mantissa.bignum(&mut scratch);
+            magnitude = Value::Bytes(bignum.magnitude());
+            Value::Tag(bignum.tag(), &magnitude)
+        }
+    };
+
+    let exponent = if exponent < 0 {
+        Value::negative(exponent)
+    } else {
+        Value::Unsigned(exponent as u64)
+    };
+
+    let items = [exponent, mantissa];
+    let array = Value::Array(&items);
+    f(&Value::Tag(tag, &array))
+}
+
+/// Validates the `tag([exponent, mantissa])` shape and extracts its parts.
+fn parse<'a>(expected: u64, value: &Value<'a>) -> Result<(i64, Mantissa<'a>)> {
+    match value {
+        Value::Tag(tag, Value::Array([exponent, mantissa])) if *tag == expected => {
+            let exponent = match i64::try_from(integer_from_value(exponent)?) {
+                Ok(exponent) => exponent,
+                Err(_) => return Err(Error::InvalidType),
+            };
+            Ok((exponent, Mantissa::try_from(mantissa)?))
+        }
+        _ => Err(Error::InvalidType),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bigfloat, Bignum, DecimalFraction, Mantissa};
+    use crate::{Value, encode::Encode, error::Error};
+
+    /// Test encoding of the RFC 8949 decimal fraction example.
+    ///
+    /// The value 273.15 is encoded as tag 4 wrapping `[-2, 27315]`:
+    /// - 0xC4: Tag 4
+    /// - 0x82: Array of 2 items
+    /// - 0x21: Negative integer -2
+    /// - 0x19 0x6A 0xB3: Unsigned integer 27315
+    #[test]
+    fn test_encode_decimal_fraction() {
+        let value = DecimalFraction::from_pair((27315, -2));
+        let mut buf = [0u8; 16];
+        let size = value.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(size, 6);
+        assert_eq!(&buf[..size], &[0xC4, 0x82, 0x21, 0x19, 0x6A, 0xB3]);
+        assert_eq!(value.encoded_size(), 6);
+    }
+
+    /// Test encoding of the RFC 8949 bigfloat example (1.5 as `3 * 2^-1`).
+    #[test]
+    fn test_encode_bigfloat() {
+        let value = Bigfloat::new(-1, Mantissa::Integer(3));
+        let mut buf = [0u8; 16];
+        let size = value.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(size, 4);
+        assert_eq!(&buf[..size], &[0xC5, 0x82, 0x20, 0x03]);
+        assert_eq!(value.encoded_size(), 4);
+    }
+
+    #[test]
+    fn test_encode_large_mantissa_as_bignum() {
+        // 2^64 does not fit in a CBOR integer and must become a positive bignum
+        let value = DecimalFraction::from_pair((1 << 64, 0));
+        let mut buf = [0u8; 32];
+        let size = value.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(
+            &buf[..size],
+            &[0xC4, 0x82, 0x00, 0xC2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(value.encoded_size(), size);
+
+        // -2^64 - 1 becomes a negative bignum with magnitude 2^64
+        let value = DecimalFraction::from_pair((-(1 << 64) - 1, 0));
+        let size = value.as_cbor(&mut buf).unwrap();
+        assert_eq!(
+            &buf[..size],
+            &[0xC4, 0x82, 0x00, 0xC3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_encode_borrowed_bignum() {
+        let magnitude = [0x01, 0x00];
+        let value = Bigfloat::new(2, Mantissa::Bignum(Bignum::new(true, &magnitude)));
+        let mut buf = [0u8; 16];
+        let size = value.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(&buf[..size], &[0xC5, 0x82, 0x02, 0xC3, 0x42, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_encode_buffer_overflow() {
+        let value = DecimalFraction::from_pair((27315, -2));
+        let mut buf = [0u8; 5];
+        assert_eq!(value.as_cbor(&mut buf), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_decode_decimal_fraction() {
+        let items = [Value::negative(-2), Value::unsigned(27315)];
+        let array = Value::array(&items);
+        let value = Value::tag(4, &array);
+
+        let fraction = DecimalFraction::try_from(&value).unwrap();
+        assert_eq!(fraction.exponent(), -2);
+        assert_eq!(fraction.mantissa(), Mantissa::Integer(27315));
+        assert_eq!(fraction.to_pair(), Some((27315, -2)));
+    }
+
+    #[test]
+    fn test_decode_bignum_mantissa() {
+        let magnitude = Value::bytes(&[0x01, 0x00]);
+        let items = [Value::unsigned(3), Value::tag(3, &magnitude)];
+        let array = Value::array(&items);
+        let value = Value::tag(5, &array);
+
+        let bigfloat = Bigfloat::try_from(&value).unwrap();
+        assert_eq!(bigfloat.exponent(), 3);
+        assert_eq!(
+            bigfloat.mantissa(),
+            Mantissa::Bignum(Bignum::new(true, &[0x01, 0x00]))
+        );
+        assert_eq!(bigfloat.to_pair(), Some((-257, 3)));
+    }
+
+    #[test]
+    fn test_decode_invalid_shapes() {
+        // Wrong tag number
+        let items = [Value::negative(-2), Value::unsigned(27315)];
+        let array = Value::array(&items);
+        let value = Value::tag(5, &array);
+        assert_eq!(DecimalFraction::try_from(&value), Err(Error::InvalidType));
+
+        // Wrong number of items
+        let items = [Value::negative(-2)];
+        let array = Value::array(&items);
+        let value = Value::tag(4, &array);
+        assert_eq!(DecimalFraction::try_from(&value), Err(Error::InvalidType));
+
+        // Exponent must be an integer
+        let magnitude = Value::bytes(&[0x01]);
+        let items = [Value::tag(2, &magnitude), Value::unsigned(1)];
+        let array = Value::array(&items);
+        let value = Value::tag(4, &array);
+        assert_eq!(DecimalFraction::try_from(&value), Err(Error::InvalidType));
+
+        // Mantissa must be an integer or bignum
+        let items = [Value::unsigned(1), Value::float(1.5)];
+        let array = Value::array(&items);
+        let value = Value::tag(4, &array);
+        assert_eq!(DecimalFraction::try_from(&value), Err(Error::InvalidType));
+
+        // The payload must be an array
+        let value = Value::tag(4, &magnitude);
+        assert_eq!(DecimalFraction::try_from(&value), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_bignum_to_i128() {
+        assert_eq!(Bignum::new(false, &[]).to_i128(), Some(0));
+        assert_eq!(Bignum::new(true, &[]).to_i128(), Some(-1));
+        assert_eq!(Bignum::new(false, &[0x00, 0x00, 0x01]).to_i128(), Some(1));
+        assert_eq!(Bignum::new(false, &[0xFF; 17]).to_i128(), None);
+        assert_eq!(Bignum::new(false, &[0x80; 16]).to_i128(), None);
+
+        let mut max = [0xFF; 16];
+        max[0] = 0x7F;
+        assert_eq!(Bignum::new(false, &max).to_i128(), Some(i128::MAX));
+        assert_eq!(Bignum::new(true, &max).to_i128(), Some(i128::MIN));
+    }
+
+    #[test]
+    fn test_bignum_to_u128() {
+        assert_eq!(Bignum::new(false, &[0x00, 0x01]).to_u128(), Some(1));
+        assert_eq!(Bignum::new(false, &[0xFF; 16]).to_u128(), Some(u128::MAX));
+        assert_eq!(Bignum::new(false, &[0xFF; 17]).to_u128(), None);
+        assert_eq!(Bignum::new(true, &[0x01]).to_u128(), None);
+    }
+
+    #[test]
+    fn test_round_trip_through_value() {
+        let original = DecimalFraction::from_pair((i128::MIN, i64::MIN));
+        let mut buf = [0u8; 64];
+        let size = original.as_cbor(&mut buf).unwrap();
+        assert_eq!(original.encoded_size(), size);
+
+        // Rebuild the value tree that the encoded bytes describe
+        let magnitude = (-1 - i128::MIN) as u128;
+        let magnitude = magnitude.to_be_bytes();
+        let bignum = Value::bytes(&magnitude);
+        let items = [Value::negative(i64::MIN), Value::tag(3, &bignum)];
+        let array = Value::array(&items);
+        let value = Value::tag(4, &array);
+
+        let decoded = DecimalFraction::try_from(&value).unwrap();
+        assert_eq!(decoded.to_pair(), original.to_pair());
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT
+
+//! Typed support for registered CBOR tags.
+//!
+//! This module provides the tag numbers registered for the semantic types this crate
+//! understands, along with typed representations that can be encoded directly and
+//! recovered from a decoded [`Value`] tree.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{encode::Encode, tag::{DecimalFraction, Mantissa}};
+//!
+//! // 273.15 is represented as 27315 * 10^-2
+//! let fraction = DecimalFraction::new(-2, Mantissa::Integer(27315));
+//!
+//! let mut buf = [0u8; 16];
+//! let size = fraction.as_cbor(&mut buf).unwrap();
+//! assert_eq!(&buf[..size], &[0xC4, 0x82, 0x21, 0x19, 0x6A, 0xB3]);
+//! ```
+
+mod date;
+mod datetime;
+mod decimal;
+mod multi_dim;
+mod typed_array;
+
+pub use date::*;
+pub use datetime::*;
+pub use decimal::*;
+pub use multi_dim::*;
+pub use typed_array::*;
+
+use crate::{Value, error::Error, result::Result};
+
+/// Tag 0: Standard date/time string in RFC 3339 format.
+pub const DATE_TIME: u64 = 0;
+/// Tag 1: Epoch-based date/time, as seconds since 1970-01-01T00:00Z.
+pub const EPOCH_DATE_TIME: u64 = 1;
+/// Tag 2: Unsigned bignum, a byte string holding a big-endian magnitude.
+pub const POSITIVE_BIGNUM: u64 = 2;
+/// Tag 3: Negative bignum, a byte string holding `-1 - n` as a big-endian magnitude.
+pub const NEGATIVE_BIGNUM: u64 = 3;
+/// Tag 4: Decimal fraction, an array of `[exponent, mantissa]` meaning `m * 10^e`.
+pub const DECIMAL_FRACTION: u64 = 4;
+/// Tag 5: Bigfloat, an array of `[exponent, mantissa]` meaning `m * 2^e`.
+pub const BIGFLOAT: u64 = 5;
+/// Tag 37: Binary UUID, a 16-byte byte string as defined in RFC 9562.
+pub const UUID: u64 = 37;
+/// Tag 40: Multi-dimensional array in row-major order.
+pub const MULTI_DIM_ARRAY: u64 = 40;
+/// Tag 61: CBOR Web Token, wrapping the COSE message that carries the claims.
+pub const CWT: u64 = 61;
+/// Tag 100: Number of days since the epoch date 1970-01-01.
+pub const EPOCH_DATE: u64 = 100;
+/// Tag 107: SUIT envelope, a firmware update manifest with its authentication.
+pub const SUIT_ENVELOPE: u64 = 107;
+/// Tag 111: Object identifier, the BER encoding of the OID's contents as a byte string.
+pub const OID: u64 = 111;
+/// Tag 501: CoRIM, an unsigned Concise Reference Integrity Manifest.
+pub const CORIM: u64 = 501;
+/// Tag 506: CoMID, a Concise Module Identifier wrapped in a byte string.
+pub const COMID: u64 = 506;
+/// Tag 1004: Calendar date as an RFC 3339 `full-date` string.
+pub const FULL_DATE: u64 = 1004;
+/// Tag 1040: Multi-dimensional array in column-major order.
+pub const MULTI_DIM_ARRAY_COLUMN_MAJOR: u64 = 1040;
+
+/// Checks the content of a tag whose content this crate validates, such as the day count
+/// of a tag 100 date. The content of other tags is accepted as it is.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the content is valid for the tag.
+/// * `Err(Error::InvalidType)` - If the content has the wrong type for the tag.
+/// * `Err(Error::InvalidValue)` - If the content has the right type but is out of range or
+///   malformed, such as a full-date string naming February 30.
+pub(crate) const fn check_content(tag: u64, item: &Value) -> Result<()> {
+    match (tag, item) {
+        (FULL_DATE, Value::Text(text)) => match Date::parse_full_date(text) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        },
+        (FULL_DATE, _) => Err(Error::InvalidType),
+        (EPOCH_DATE, _) => match integer_from_value(item) {
+            Ok(days) if days < i64::MIN as i128 || days > i64::MAX as i128 => {
+                Err(Error::InvalidValue)
+            }
+            Ok(days) => match Date::from_days(days as i64) {
+                Some(_) => Ok(()),
+                None => Err(Error::InvalidValue),
+            },
+            Err(err) => Err(err),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Converts a signed integer into the CBOR integer value representing it, if it fits.
+///
+/// CBOR integers cover the range -2^64 to 2^64-1; values outside that range must be
+/// encoded as bignums instead.
+#[inline]
+pub(crate) const fn integer_value<'a>(value: i128) -> Option<Value<'a>> {
+    if value >= 0 {
+        if value <= u64::MAX as i128 {
+            Some(Value::Unsigned(value as u64))
+        } else {
+            None
+        }
+    } else {
+        // -1 - value is non-negative here, and `!value` computes it without overflow.
+        let n = !value;
+        if n <= u64::MAX as i128 {
+            Some(Value::Negative(n as u64))
+        } else {
+            None
+        }
+    }
+}
+
+/// Extracts the signed value of a CBOR integer (major type 0 or 1).
+///
+/// # Returns
+///
+/// * `Ok(i128)` - The integer value.
+/// * `Err(Error::InvalidType)` - If the value is not an integer.
+#[inline]
+pub(crate) const fn integer_from_value(value: &Value) -> Result<i128> {
+    match value {
+        Value::Unsigned(n) => Ok(*n as i128),
+        Value::Negative(n) => Ok(-1 - *n as i128),
+        _ => Err(Error::InvalidType),
+    }
+}
@@ -0,0 +1,500 @@
+// SPDX-License-Identifier: MIT
+
+//! Multi-dimensional arrays (tags 40 and 1040).
+//!
+//! [RFC 8746 §3.1](https://www.rfc-editor.org/rfc/rfc8746#section-3.1) represents a
+//! multi-dimensional array as a two-element array `[dimensions, elements]`. The
+//! dimensions are an array of unsigned integers, and the elements are a flat array,
+//! either a plain array of data items or a typed array. Tag 40 lays the elements out in
+//! row-major order, and tag 1040 in column-major order.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Value,
+//!     encode::Encode,
+//!     tag::{ArrayOrder, BigEndian, MultiDimArray, MultiDimArrayView, TypedArray},
+//! };
+//!
+//! // A 2x3 matrix of 16-bit samples
+//! let samples = [1u16, 2, 3, 4, 5, 6];
+//! let matrix = MultiDimArray::new(
+//!     ArrayOrder::RowMajor,
+//!     &[2, 3],
+//!     TypedArray::<u16, BigEndian>::new(&samples),
+//! )
+//! .unwrap();
+//!
+//! let mut buf = [0u8; 32];
+//! let size = matrix.as_cbor(&mut buf).unwrap();
+//! assert_eq!(&buf[..5], &[0xD8, 0x28, 0x82, 0x82, 0x02]);
+//! assert_eq!(size, matrix.encoded_size());
+//!
+//! // The same structure as a value tree, with a plain array of elements
+//! let dimensions = [Value::unsigned(2), Value::unsigned(1)];
+//! let elements = [Value::text("a"), Value::text("b")];
+//! let items = [Value::array(&dimensions), Value::array(&elements)];
+//! let array = Value::array(&items);
+//! let value = Value::tag(40, &array);
+//!
+//! let view = MultiDimArrayView::try_from(&value).unwrap();
+//! assert!(view.dimensions().eq([2, 1]));
+//! assert_eq!(view.len(), 2);
+//! ```
+
+use crate::{
+    Value,
+    encode::{
+        self, Cursor, Encode, encode_header, encode_value, major_type, write_header_with_extras,
+    },
+    error::Error,
+    result::Result,
+    tag::{self, Element, Endian, TypedArray, TypedArrayView},
+};
+
+/// The order in which the elements of a multi-dimensional array are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrayOrder {
+    /// The last index varies fastest, as in C (tag 40).
+    RowMajor,
+
+    /// The first index varies fastest, as in Fortran (tag 1040).
+    ColumnMajor,
+}
+
+impl ArrayOrder {
+    /// Returns the tag number used for this order.
+    #[inline]
+    pub const fn tag(&self) -> u64 {
+        match self {
+            ArrayOrder::RowMajor => tag::MULTI_DIM_ARRAY,
+            ArrayOrder::ColumnMajor => tag::MULTI_DIM_ARRAY_COLUMN_MAJOR,
+        }
+    }
+
+    /// Returns the order described by a tag number, if it is tag 40 or 1040.
+    #[inline]
+    pub const fn from_tag(tag: u64) -> Option<Self> {
+        match tag {
+            tag::MULTI_DIM_ARRAY => Some(ArrayOrder::RowMajor),
+            tag::MULTI_DIM_ARRAY_COLUMN_MAJOR => Some(ArrayOrder::ColumnMajor),
+            _ => None,
+        }
+    }
+}
+
+/// A multi-dimensional array with a typed array payload, ready to be encoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultiDimArray<'a, T: Element, E: Endian> {
+    order: ArrayOrder,
+    dimensions: &'a [u64],
+    elements: TypedArray<'a, T, E>,
+}
+
+impl<'a, T: Element, E: Endian> MultiDimArray<'a, T, E> {
+    /// Creates a multi-dimensional array from its dimensions and flat elements.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MultiDimArray)` - If the number of elements equals the product of the
+    ///   dimensions.
+    /// * `Err(Error::InvalidValue)` - If it does not, or the product overflows `usize`.
+    #[inline]
+    pub const fn new(
+        order: ArrayOrder,
+        dimensions: &'a [u64],
+        elements: TypedArray<'a, T, E>,
+    ) -> Result<Self> {
+        match product(dimensions) {
+            Some(count) if count == elements.elements().len() => Ok(Self {
+                order,
+                dimensions,
+                elements,
+            }),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Returns the element order.
+    #[inline]
+    pub const fn order(&self) -> ArrayOrder {
+        self.order
+    }
+
+    /// Returns the dimensions, outermost first.
+    #[inline]
+    pub const fn dimensions(&self) -> &'a [u64] {
+        self.dimensions
+    }
+
+    /// Returns the flat typed array of elements.
+    #[inline]
+    pub const fn elements(&self) -> TypedArray<'a, T, E> {
+        self.elements
+    }
+
+    /// Calculates the number of bytes needed to encode the array.
+    #[inline]
+    pub const fn encoded_size(&self) -> usize {
+        let (_, _, tag_extra) = encode_header(major_type::TAG, self.order.tag());
+        let (_, _, dims_extra) = encode_header(major_type::ARRAY, self.dimensions.len() as u64);
+        let mut size = 1 + tag_extra + 1 + 1 + dims_extra;
+
+        let mut i = 0;
+        while i < self.dimensions.len() {
+            size += encode::encoded_size(&Value::Unsigned(self.dimensions[i]));
+            i += 1;
+        }
+
+        size + self.elements.encoded_size()
+    }
+}
+
+impl<'a, T: Element, E: Endian> Encode<'a> for MultiDimArray<'_, T, E> {
+    /// Encodes the array, packing the elements straight into the buffer.
+    ///
+    /// The packed elements have no [`Value`] of their own, so the headers of the tag and
+    /// of the enclosing arrays are written directly around them.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        let mut cursor = Cursor::new(buf);
+        write_header_with_extras(&mut cursor, major_type::TAG, self.order.tag())?;
+        write_header_with_extras(&mut cursor, major_type::ARRAY, 2)?;
+        write_header_with_extras(&mut cursor, major_type::ARRAY, self.dimensions.len() as u64)?;
+        for &dimension in self.dimensions {
+            encode_value(&Value::Unsigned(dimension), &mut cursor)?;
+        }
+
+        let pos = cursor.pos;
+        Ok(pos + self.elements.as_cbor(&mut cursor.data[pos..])?)
+    }
+}
+
+/// A borrowed view of a decoded multi-dimensional array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultiDimArrayView<'a> {
+    order: ArrayOrder,
+    dimensions: &'a [Value<'a>],
+    elements: &'a Value<'a>,
+    len: usize,
+}
+
+impl<'a> MultiDimArrayView<'a> {
+    /// Returns the element order.
+    #[inline]
+    pub const fn order(&self) -> ArrayOrder {
+        self.order
+    }
+
+    /// Returns an iterator over the dimensions, outermost first.
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions<'a> {
+        Dimensions {
+            items: self.dimensions.iter(),
+        }
+    }
+
+    /// Returns the number of dimensions.
+    #[inline]
+    pub const fn rank(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    /// Returns the total number of elements.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if any dimension is zero.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the flat elements, either a plain array or a typed array.
+    #[inline]
+    pub const fn elements(&self) -> &'a Value<'a> {
+        self.elements
+    }
+
+    /// Returns the flat elements as a typed array view.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TypedArrayView)` - The typed array of elements.
+    /// * `Err(Error::InvalidType)` - If the elements are a plain array.
+    #[inline]
+    pub fn typed_elements(&self) -> Result<TypedArrayView<'a>> {
+        TypedArrayView::try_from(self.elements)
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for MultiDimArrayView<'a> {
+    type Error = Error;
+
+    /// Validates the `[dimensions, elements]` shape of a tag 40 or tag 1040 value.
+    ///
+    /// Every dimension must be an unsigned integer, and the number of elements must
+    /// equal their product.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let (order, dimensions, elements) = match value {
+            Value::Tag(tag, Value::Array([Value::Array(dimensions), elements])) => {
+                match ArrayOrder::from_tag(*tag) {
+                    Some(order) => (order, *dimensions, elements),
+                    None => return Err(Error::InvalidType),
+                }
+            }
+            _ => return Err(Error::InvalidType),
+        };
+
+        if !dimensions
+            .iter()
+            .all(|dimension| matches!(dimension, Value::Unsigned(_)))
+        {
+            return Err(Error::InvalidType);
+        }
+
+        let count = match elements {
+            Value::Array(items) => items.len(),
+            Value::Tag(..) => TypedArrayView::try_from(elements)?.len(),
+            _ => return Err(Error::InvalidType),
+        };
+
+        let len = element_count(Dimensions {
+            items: dimensions.iter(),
+        });
+        if len != Some(count) {
+            return Err(Error::InvalidValue);
+        }
+
+        Ok(Self {
+            order,
+            dimensions,
+            elements,
+            len: count,
+        })
+    }
+}
+
+/// An iterator over the dimensions of a [`MultiDimArrayView`].
+///
+/// Iteration stops at the first dimension that is not an unsigned integer; views only
+/// hold dimensions that have already been validated.
+#[derive(Debug, Clone)]
+pub struct Dimensions<'a> {
+    items: core::slice::Iter<'a, Value<'a>>,
+}
+
+impl Iterator for Dimensions<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        match self.items.next() {
+            Some(Value::Unsigned(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.items.len()))
+    }
+}
+
+/// Returns the product of the dimensions, or `None` if it overflows `usize`.
+const fn product(dimensions: &[u64]) -> Option<usize> {
+    let mut count = 1usize;
+    let mut i = 0;
+    while i < dimensions.len() {
+        if dimensions[i] > usize::MAX as u64 {
+            return None;
+        }
+        count = match count.checked_mul(dimensions[i] as usize) {
+            Some(count) => count,
+            None => return None,
+        };
+        i += 1;
+    }
+    Some(count)
+}
+
+/// Returns the product of the dimensions, or `None` if it overflows `usize`.
+fn element_count(mut dimensions: impl Iterator<Item = u64>) -> Option<usize> {
+    dimensions.try_fold(1usize, |count, dimension| {
+        count.checked_mul(usize::try_from(dimension).ok()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayOrder, MultiDimArray, MultiDimArrayView};
+    use crate::{
+        Value,
+        encode::Encode,
+        error::Error,
+        tag::{BigEndian, ElementType, LittleEndian, TypedArray},
+    };
+
+    /// Test encoding of a 2x3 row-major matrix of little-endian u16 samples.
+    ///
+    /// - 0xD8 0x28: Tag 40
+    /// - 0x82: Array of 2 items
+    /// - 0x82 0x02 0x03: Dimensions [2, 3]
+    /// - 0xD8 0x45: Tag 69 (u16, little endian)
+    /// - 0x4C: Byte string of length 12
+    #[test]
+    fn test_encode_row_major() {
+        let samples = [2u16, 4, 8, 4, 2, 8];
+        let matrix = MultiDimArray::new(
+            ArrayOrder::RowMajor,
+            &[2, 3],
+            TypedArray::<u16, LittleEndian>::new(&samples),
+        )
+        .unwrap();
+
+        let mut buf = [0u8; 32];
+        let size = matrix.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(
+            &buf[..size],
+            &[
+                0xD8, 0x28, 0x82, 0x82, 0x02, 0x03, 0xD8, 0x45, 0x4C, 0x02, 0x00, 0x04, 0x00, 0x08,
+                0x00, 0x04, 0x00, 0x02, 0x00, 0x08, 0x00,
+            ]
+        );
+        assert_eq!(matrix.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_encode_column_major() {
+        let samples = [1.0f32; 4];
+        let elements = TypedArray::<f32, BigEndian>::new(&samples);
+        let matrix = MultiDimArray::new(ArrayOrder::ColumnMajor, &[4], elements).unwrap();
+
+        let mut buf = [0u8; 64];
+        let size = matrix.as_cbor(&mut buf).unwrap();
+        assert_eq!(&buf[..6], &[0xD9, 0x04, 0x10, 0x82, 0x81, 0x04]);
+        assert_eq!(matrix.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_new_invalid() {
+        let samples = [1.0f32; 4];
+        let elements = TypedArray::<f32, BigEndian>::new(&samples);
+
+        // The element count does not match the dimensions
+        assert_eq!(
+            MultiDimArray::new(ArrayOrder::ColumnMajor, &[2, 2, 1000], elements),
+            Err(Error::InvalidValue)
+        );
+
+        // The product of the dimensions overflows
+        assert_eq!(
+            MultiDimArray::new(ArrayOrder::RowMajor, &[u64::MAX, 2], elements),
+            Err(Error::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_encode_buffer_overflow() {
+        let samples = [1u8, 2];
+        let matrix = MultiDimArray::new(
+            ArrayOrder::RowMajor,
+            &[2],
+            TypedArray::<u8, BigEndian>::new(&samples),
+        )
+        .unwrap();
+
+        let mut buf = [0u8; 9];
+        assert_eq!(matrix.encoded_size(), 10);
+        assert_eq!(matrix.as_cbor(&mut buf), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_decode_typed_payload() {
+        let dimensions = [Value::unsigned(2), Value::unsigned(2)];
+        let bytes = Value::bytes(&[0, 1, 0, 2, 0, 3, 0, 4]);
+        let items = [Value::array(&dimensions), Value::tag(65, &bytes)];
+        let array = Value::array(&items);
+        let value = Value::tag(1040, &array);
+
+        let view = MultiDimArrayView::try_from(&value).unwrap();
+        assert_eq!(view.order(), ArrayOrder::ColumnMajor);
+        assert_eq!(view.rank(), 2);
+        assert!(view.dimensions().eq([2, 2]));
+        assert_eq!(view.len(), 4);
+        assert!(!view.is_empty());
+
+        let elements = view.typed_elements().unwrap();
+        assert_eq!(elements.element_type(), ElementType::U16);
+        assert!(elements.iter::<u16>().unwrap().eq([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_decode_plain_payload() {
+        let dimensions = [Value::unsigned(3), Value::unsigned(0)];
+        let items = [Value::array(&dimensions), Value::array(&[])];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+
+        let view = MultiDimArrayView::try_from(&value).unwrap();
+        assert_eq!(view.order(), ArrayOrder::RowMajor);
+        assert!(view.is_empty());
+        assert_eq!(view.elements(), &Value::array(&[]));
+        assert_eq!(view.typed_elements(), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let elements = [Value::null(), Value::null()];
+
+        // Element count does not match the dimensions
+        let dimensions = [Value::unsigned(3)];
+        let items = [Value::array(&dimensions), Value::array(&elements)];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+        assert_eq!(
+            MultiDimArrayView::try_from(&value),
+            Err(Error::InvalidValue)
+        );
+
+        // Dimensions overflow
+        let dimensions = [Value::Unsigned(u64::MAX), Value::unsigned(2)];
+        let items = [Value::array(&dimensions), Value::array(&elements)];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+        assert_eq!(
+            MultiDimArrayView::try_from(&value),
+            Err(Error::InvalidValue)
+        );
+
+        // Dimensions must be unsigned integers
+        let dimensions = [Value::negative(-2)];
+        let items = [Value::array(&dimensions), Value::array(&elements)];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+        assert_eq!(MultiDimArrayView::try_from(&value), Err(Error::InvalidType));
+
+        // Wrong tag
+        let dimensions = [Value::unsigned(2)];
+        let items = [Value::array(&dimensions), Value::array(&elements)];
+        let array = Value::array(&items);
+        let value = Value::tag(41, &array);
+        assert_eq!(MultiDimArrayView::try_from(&value), Err(Error::InvalidType));
+
+        // Elements must be an array or typed array
+        let text = Value::text("ab");
+        let items = [Value::array(&dimensions), text];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+        assert_eq!(MultiDimArrayView::try_from(&value), Err(Error::InvalidType));
+
+        // Missing the elements entry
+        let items = [Value::array(&dimensions)];
+        let array = Value::array(&items);
+        let value = Value::tag(40, &array);
+        assert_eq!(MultiDimArrayView::try_from(&value), Err(Error::InvalidType));
+    }
+}
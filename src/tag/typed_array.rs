@@ -0,0 +1,697 @@
+// SPDX-License-Identifier: MIT
+
+//! Typed arrays (tags 64 to 87).
+//!
+//! [RFC 8746](https://www.rfc-editor.org/rfc/rfc8746) encodes homogeneous numeric arrays
+//! as a single byte string holding the packed elements, with the tag number describing
+//! the element type and byte order. This is far more compact than an array of individual
+//! data items, which makes it a good fit for large sample buffers.
+//!
+//! Encoding goes through [`TypedArray`], created with [`Value::as_typed_array`] or
+//! [`TypedArray::new`], which packs borrowed elements straight into the output buffer.
+//! Decoding produces a [`TypedArrayView`] over the borrowed byte string, whose elements
+//! can be read back with [`TypedArrayView::iter`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Value,
+//!     encode::Encode,
+//!     tag::{ByteOrder, ElementType, LittleEndian, TypedArrayView},
+//! };
+//!
+//! let samples = [1u16, 2, 0x0300];
+//! let array = Value::as_typed_array::<u16, LittleEndian>(&samples);
+//!
+//! let mut buf = [0u8; 16];
+//! let size = array.as_cbor(&mut buf).unwrap();
+//! assert_eq!(&buf[..size], &[0xD8, 0x45, 0x46, 0x01, 0x00, 0x02, 0x00, 0x00, 0x03]);
+//!
+//! // A decoded tag 69 value yields a view over the packed bytes
+//! let bytes = Value::bytes(&buf[3..size]);
+//! let value = Value::tag(69, &bytes);
+//! let view = TypedArrayView::try_from(&value).unwrap();
+//! assert_eq!(view.element_type(), ElementType::U16);
+//! assert_eq!(view.byte_order(), ByteOrder::Little);
+//! assert!(view.iter::<u16>().unwrap().eq(samples));
+//! ```
+
+use core::{marker::PhantomData, slice::ChunksExact};
+
+use crate::{
+    Value,
+    encode::{Cursor, Encode, encode_header, major_type, write_all, write_header_with_extras},
+    error::Error,
+    result::Result,
+};
+
+/// The first tag number assigned to typed arrays.
+const FIRST_TAG: u64 = 64;
+/// The last tag number assigned to typed arrays.
+const LAST_TAG: u64 = 87;
+/// The reserved tag number for little-endian signed 8-bit integers.
+const RESERVED_TAG: u64 = 76;
+
+/// The byte order of the elements in a typed array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Most significant byte first (network byte order).
+    Big,
+
+    /// Least significant byte first.
+    Little,
+}
+
+/// The element type of a typed array, as described by its tag number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementType {
+    /// Unsigned 8-bit integers (tag 64).
+    U8,
+    /// Unsigned 8-bit integers with clamped arithmetic (tag 68).
+    U8Clamped,
+    /// Unsigned 16-bit integers (tags 65 and 69).
+    U16,
+    /// Unsigned 32-bit integers (tags 66 and 70).
+    U32,
+    /// Unsigned 64-bit integers (tags 67 and 71).
+    U64,
+    /// Signed 8-bit integers (tag 72).
+    I8,
+    /// Signed 16-bit integers (tags 73 and 77).
+    I16,
+    /// Signed 32-bit integers (tags 74 and 78).
+    I32,
+    /// Signed 64-bit integers (tags 75 and 79).
+    I64,
+    /// IEEE 754 binary16 floats (tags 80 and 84).
+    F16,
+    /// IEEE 754 binary32 floats (tags 81 and 85).
+    F32,
+    /// IEEE 754 binary64 floats (tags 82 and 86).
+    F64,
+    /// IEEE 754 binary128 floats (tags 83 and 87).
+    F128,
+}
+
+impl ElementType {
+    /// Returns the size of one element in bytes.
+    #[inline]
+    pub const fn size(&self) -> usize {
+        match self {
+            ElementType::U8 | ElementType::U8Clamped | ElementType::I8 => 1,
+            ElementType::U16 | ElementType::I16 | ElementType::F16 => 2,
+            ElementType::U32 | ElementType::I32 | ElementType::F32 => 4,
+            ElementType::U64 | ElementType::I64 | ElementType::F64 => 8,
+            ElementType::F128 => 16,
+        }
+    }
+
+    /// Returns the typed array tag number for this element type and byte order.
+    ///
+    /// Single-byte element types have no byte order, so `order` is ignored for them.
+    #[inline]
+    pub const fn tag(&self, order: ByteOrder) -> u64 {
+        // RFC 8746 §2.1: the tag is 0b010_f_s_e_ll, where f marks floats, s marks
+        // signed integers, e marks little endian and ll is the log2 of the size.
+        let (float, signed, log2) = match self {
+            ElementType::U8 => (0, 0, 0),
+            ElementType::U8Clamped => return FIRST_TAG | 0b100,
+            ElementType::U16 => (0, 0, 1),
+            ElementType::U32 => (0, 0, 2),
+            ElementType::U64 => (0, 0, 3),
+            ElementType::I8 => (0, 1, 0),
+            ElementType::I16 => (0, 1, 1),
+            ElementType::I32 => (0, 1, 2),
+            ElementType::I64 => (0, 1, 3),
+            ElementType::F16 => (1, 0, 0),
+            ElementType::F32 => (1, 0, 1),
+            ElementType::F64 => (1, 0, 2),
+            ElementType::F128 => (1, 0, 3),
+        };
+
+        let little = match order {
+            ByteOrder::Little if self.size() > 1 => 1,
+            _ => 0,
+        };
+
+        FIRST_TAG | float << 4 | signed << 3 | little << 2 | log2
+    }
+
+    /// Returns the element type and byte order described by a typed array tag number.
+    ///
+    /// Returns `None` for tags outside 64 to 87 and for the reserved tag 76.
+    #[inline]
+    pub const fn from_tag(tag: u64) -> Option<(ElementType, ByteOrder)> {
+        if tag < FIRST_TAG || tag > LAST_TAG || tag == RESERVED_TAG {
+            return None;
+        }
+
+        let order = if tag & 0b100 != 0 {
+            ByteOrder::Little
+        } else {
+            ByteOrder::Big
+        };
+
+        let element = match (tag & 0b1_1000, tag & 0b11) {
+            (0b0_0000, 0) if tag & 0b100 != 0 => {
+                return Some((ElementType::U8Clamped, ByteOrder::Big));
+            }
+            (0b0_0000, 0) => ElementType::U8,
+            (0b0_0000, 1) => ElementType::U16,
+            (0b0_0000, 2) => ElementType::U32,
+            (0b0_0000, _) => ElementType::U64,
+            (0b0_1000, 0) => ElementType::I8,
+            (0b0_1000, 1) => ElementType::I16,
+            (0b0_1000, 2) => ElementType::I32,
+            (0b0_1000, _) => ElementType::I64,
+            (_, 0) => ElementType::F16,
+            (_, 1) => ElementType::F32,
+            (_, 2) => ElementType::F64,
+            (_, _) => ElementType::F128,
+        };
+
+        Some((element, order))
+    }
+}
+
+mod private {
+    use super::ByteOrder;
+
+    /// Prevents [`Element`](super::Element) from being implemented outside this crate,
+    /// and hides the byte conversions.
+    pub trait Sealed: Copy {
+        /// Writes the element into `out`, which is exactly the element size.
+        fn write(self, order: ByteOrder, out: &mut [u8]);
+
+        /// Reads an element from `bytes`, which is exactly the element size.
+        fn read(order: ByteOrder, bytes: &[u8]) -> Self;
+    }
+}
+
+/// A Rust type that can be stored in a typed array.
+///
+/// This is implemented for the fixed-size integer types and for `f32` and `f64`.
+pub trait Element: private::Sealed {
+    /// The element type this Rust type corresponds to.
+    const TYPE: ElementType;
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $element:ident),* $(,)?) => {
+        $(
+            impl private::Sealed for $ty {
+                #[inline]
+                fn write(self, order: ByteOrder, out: &mut [u8]) {
+                    out.copy_from_slice(&match order {
+                        ByteOrder::Big => self.to_be_bytes(),
+                        ByteOrder::Little => self.to_le_bytes(),
+                    });
+                }
+
+                #[inline]
+                fn read(order: ByteOrder, bytes: &[u8]) -> Self {
+                    let mut raw = [0u8; core::mem::size_of::<$ty>()];
+                    raw.copy_from_slice(bytes);
+                    match order {
+                        ByteOrder::Big => <$ty>::from_be_bytes(raw),
+                        ByteOrder::Little => <$ty>::from_le_bytes(raw),
+                    }
+                }
+            }
+
+            impl Element for $ty {
+                const TYPE: ElementType = ElementType::$element;
+            }
+        )*
+    };
+}
+
+impl_element! {
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    f32 => F32,
+    f64 => F64,
+}
+
+/// A compile-time byte order for [`TypedArray`].
+pub trait Endian: Copy {
+    /// The byte order this marker selects.
+    const ORDER: ByteOrder;
+}
+
+/// Marker selecting big-endian element encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BigEndian {}
+
+/// Marker selecting little-endian element encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LittleEndian {}
+
+impl Endian for BigEndian {
+    const ORDER: ByteOrder = ByteOrder::Big;
+}
+
+impl Endian for LittleEndian {
+    const ORDER: ByteOrder = ByteOrder::Little;
+}
+
+/// A borrowed slice of elements to be encoded as a typed array.
+///
+/// The element type `T` and byte order `E` select the tag number. Elements are packed
+/// directly into the output buffer while encoding, so no intermediate byte copy of the
+/// slice is needed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypedArray<'a, T: Element, E: Endian> {
+    elements: &'a [T],
+    order: PhantomData<E>,
+}
+
+impl<'a, T: Element, E: Endian> TypedArray<'a, T, E> {
+    /// Creates a typed array over the given elements.
+    #[inline]
+    pub const fn new(elements: &'a [T]) -> Self {
+        Self {
+            elements,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns the borrowed elements.
+    #[inline]
+    pub const fn elements(&self) -> &'a [T] {
+        self.elements
+    }
+
+    /// Returns the tag number this typed array is encoded with.
+    #[inline]
+    pub const fn tag(&self) -> u64 {
+        T::TYPE.tag(E::ORDER)
+    }
+
+    /// Returns the length in bytes of the packed byte string.
+    #[inline]
+    pub const fn byte_len(&self) -> usize {
+        self.elements.len() * T::TYPE.size()
+    }
+
+    /// Calculates the number of bytes needed to encode the typed array.
+    #[inline]
+    pub const fn encoded_size(&self) -> usize {
+        let (_, _, tag_extra) = encode_header(major_type::TAG, self.tag());
+        let (_, _, len_extra) = encode_header(major_type::BYTES, self.byte_len() as u64);
+        2 + tag_extra + len_extra + self.byte_len()
+    }
+}
+
+impl<'a> Value<'a> {
+    /// Creates a typed array over the given elements, ready to be encoded.
+    ///
+    /// This is shorthand for [`TypedArray::new`]. The elements stay borrowed and are
+    /// packed into the output buffer as the array is encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use const_cbor::{Value, encode::Encode, tag::LittleEndian};
+    ///
+    /// let samples = [1u16, 2];
+    /// let array = Value::as_typed_array::<u16, LittleEndian>(&samples);
+    /// let mut buf = [0u8; 8];
+    /// let size = array.as_cbor(&mut buf).unwrap();
+    /// assert_eq!(&buf[..size], &[0xD8, 0x45, 0x44, 0x01, 0x00, 0x02, 0x00]);
+    /// ```
+    #[inline]
+    pub const fn as_typed_array<T: Element, E: Endian>(elements: &'a [T]) -> TypedArray<'a, T, E> {
+        TypedArray::new(elements)
+    }
+}
+
+impl<'a, T: Element, E: Endian> Encode<'a> for TypedArray<'_, T, E> {
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        if buf.len() < self.encoded_size() {
+            return Err(Error::BufferOverflow);
+        }
+
+        let mut cursor = Cursor::new(buf);
+        write_header_with_extras(&mut cursor, major_type::TAG, self.tag())?;
+        write_header_with_extras(&mut cursor, major_type::BYTES, self.byte_len() as u64)?;
+
+        let mut scratch = [0u8; 8];
+        let scratch = &mut scratch[..T::TYPE.size()];
+        for element in self.elements {
+            element.write(E::ORDER, scratch);
+            write_all(&mut cursor, scratch)?;
+        }
+
+        Ok(cursor.pos)
+    }
+}
+
+/// A borrowed view of a decoded typed array.
+///
+/// The view keeps the packed bytes as they appeared in the input, together with the
+/// element type and byte order from the tag number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypedArrayView<'a> {
+    element_type: ElementType,
+    byte_order: ByteOrder,
+    bytes: &'a [u8],
+}
+
+impl<'a> TypedArrayView<'a> {
+    /// Creates a view over packed bytes for the given typed array tag number.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TypedArrayView)` - The view over `bytes`.
+    /// * `Err(Error::InvalidType)` - If `tag` is not a typed array tag.
+    /// * `Err(Error::InvalidValue)` - If the length of `bytes` is not a multiple of the
+    ///   element size.
+    #[inline]
+    pub const fn new(tag: u64, bytes: &'a [u8]) -> Result<Self> {
+        let Some((element_type, byte_order)) = ElementType::from_tag(tag) else {
+            return Err(Error::InvalidType);
+        };
+
+        if bytes.len() % element_type.size() != 0 {
+            return Err(Error::InvalidValue);
+        }
+
+        Ok(Self {
+            element_type,
+            byte_order,
+            bytes,
+        })
+    }
+
+    /// Returns the element type.
+    #[inline]
+    pub const fn element_type(&self) -> ElementType {
+        self.element_type
+    }
+
+    /// Returns the byte order of the elements.
+    #[inline]
+    pub const fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the packed element bytes.
+    #[inline]
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the tag number of the typed array.
+    #[inline]
+    pub const fn tag(&self) -> u64 {
+        self.element_type.tag(self.byte_order)
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.bytes.len() / self.element_type.size()
+    }
+
+    /// Returns `true` if the array has no elements.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns an iterator over the elements, decoded as `T`.
+    ///
+    /// Clamped `u8` arrays may be read as `u8`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Elements)` - An iterator producing each element in order.
+    /// * `Err(Error::InvalidType)` - If `T` does not match the element type.
+    #[inline]
+    pub fn iter<T: Element>(&self) -> Result<Elements<'a, T>> {
+        match (T::TYPE, self.element_type) {
+            (ElementType::U8, ElementType::U8Clamped) => (),
+            (expected, actual) if expected == actual => (),
+            _ => return Err(Error::InvalidType),
+        }
+
+        Ok(Elements {
+            chunks: self.bytes.chunks_exact(T::TYPE.size()),
+            byte_order: self.byte_order,
+            element: PhantomData,
+        })
+    }
+
+    /// Returns the element at `index`, decoded as `T`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(T))` - The element, if `index` is in range.
+    /// * `Ok(None)` - If `index` is out of range.
+    /// * `Err(Error::InvalidType)` - If `T` does not match the element type.
+    #[inline]
+    pub fn get<T: Element>(&self, index: usize) -> Result<Option<T>> {
+        Ok(self.iter::<T>()?.nth(index))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for TypedArrayView<'a> {
+    type Error = Error;
+
+    /// Interprets a typed array tag wrapping a byte string as a view.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        match value {
+            Value::Tag(tag, Value::Bytes(bytes)) => Self::new(*tag, bytes),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+/// An iterator over the elements of a [`TypedArrayView`].
+#[derive(Debug, Clone)]
+pub struct Elements<'a, T> {
+    chunks: ChunksExact<'a, u8>,
+    byte_order: ByteOrder,
+    element: PhantomData<T>,
+}
+
+impl<T: Element> Iterator for Elements<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.chunks
+            .next()
+            .map(|bytes| T::read(self.byte_order, bytes))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<T> {
+        self.chunks
+            .nth(n)
+            .map(|bytes| T::read(self.byte_order, bytes))
+    }
+}
+
+impl<T: Element> DoubleEndedIterator for Elements<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.chunks
+            .next_back()
+            .map(|bytes| T::read(self.byte_order, bytes))
+    }
+}
+
+impl<T: Element> ExactSizeIterator for Elements<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{BigEndian, ByteOrder, ElementType, LittleEndian, TypedArray, TypedArrayView};
+    use crate::{Value, encode::Encode, error::Error};
+
+    #[test]
+    fn test_tag_numbers() {
+        let cases = [
+            (ElementType::U8, ByteOrder::Big, 64),
+            (ElementType::U16, ByteOrder::Big, 65),
+            (ElementType::U32, ByteOrder::Big, 66),
+            (ElementType::U64, ByteOrder::Big, 67),
+            (ElementType::U8Clamped, ByteOrder::Big, 68),
+            (ElementType::U16, ByteOrder::Little, 69),
+            (ElementType::U32, ByteOrder::Little, 70),
+            (ElementType::U64, ByteOrder::Little, 71),
+            (ElementType::I8, ByteOrder::Big, 72),
+            (ElementType::I16, ByteOrder::Big, 73),
+            (ElementType::I32, ByteOrder::Big, 74),
+            (ElementType::I64, ByteOrder::Big, 75),
+            (ElementType::I16, ByteOrder::Little, 77),
+            (ElementType::I32, ByteOrder::Little, 78),
+            (ElementType::I64, ByteOrder::Little, 79),
+            (ElementType::F16, ByteOrder::Big, 80),
+            (ElementType::F32, ByteOrder::Big, 81),
+            (ElementType::F64, ByteOrder::Big, 82),
+            (ElementType::F128, ByteOrder::Big, 83),
+            (ElementType::F16, ByteOrder::Little, 84),
+            (ElementType::F32, ByteOrder::Little, 85),
+            (ElementType::F64, ByteOrder::Little, 86),
+            (ElementType::F128, ByteOrder::Little, 87),
+        ];
+
+        for (element, order, tag) in cases {
+            assert_eq!(element.tag(order), tag);
+            assert_eq!(ElementType::from_tag(tag), Some((element, order)));
+        }
+
+        // Single-byte elements ignore the byte order
+        assert_eq!(ElementType::U8.tag(ByteOrder::Little), 64);
+        assert_eq!(ElementType::I8.tag(ByteOrder::Little), 72);
+
+        assert_eq!(ElementType::from_tag(63), None);
+        assert_eq!(ElementType::from_tag(76), None);
+        assert_eq!(ElementType::from_tag(88), None);
+    }
+
+    #[test]
+    fn test_encode_little_endian() {
+        let samples = [0x0102u16, 0x0304];
+        let array = TypedArray::<u16, LittleEndian>::new(&samples);
+        let mut buf = [0u8; 16];
+        let size = array.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(size, 7);
+        assert_eq!(Value::as_typed_array::<u16, LittleEndian>(&samples), array);
+        assert_eq!(
+            &buf[..size],
+            &[
+                0xD8, 0x45, // tag 69
+                0x44, // byte string of length 4
+                0x02, 0x01, 0x04, 0x03,
+            ]
+        );
+        assert_eq!(array.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_encode_big_endian() {
+        let samples = [-2i32, 1];
+        let array = TypedArray::<i32, BigEndian>::new(&samples);
+        let mut buf = [0u8; 16];
+        let size = array.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(
+            &buf[..size],
+            &[
+                0xD8, 0x4A, // tag 74
+                0x48, // byte string of length 8
+                0xFF, 0xFF, 0xFF, 0xFE, 0x00, 0x00, 0x00, 0x01,
+            ]
+        );
+        assert_eq!(array.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_encode_large_array() {
+        let samples = [0x1234u16; 200];
+        let array = TypedArray::<u16, BigEndian>::new(&samples);
+        let mut buf = [0u8; 512];
+        let size = array.as_cbor(&mut buf).unwrap();
+
+        assert_eq!(size, 2 + 3 + 400);
+        assert_eq!(&buf[..5], &[0xD8, 0x41, 0x59, 0x01, 0x90]);
+        assert!(buf[5..size].chunks(2).all(|c| c == [0x12, 0x34]));
+        assert_eq!(array.encoded_size(), size);
+    }
+
+    #[test]
+    fn test_encode_buffer_overflow() {
+        let samples = [1.5f64];
+        let array = TypedArray::<f64, LittleEndian>::new(&samples);
+        let mut buf = [0u8; 10];
+        assert_eq!(array.as_cbor(&mut buf), Err(Error::BufferOverflow));
+    }
+
+    #[test]
+    fn test_view_iteration() {
+        let bytes = Value::bytes(&[0x00, 0x00, 0xC0, 0x3F, 0x00, 0x00, 0x80, 0xBF]);
+        let value = Value::tag(85, &bytes);
+        let view = TypedArrayView::try_from(&value).unwrap();
+
+        assert_eq!(view.element_type(), ElementType::F32);
+        assert_eq!(view.byte_order(), ByteOrder::Little);
+        assert_eq!(view.tag(), 85);
+        assert_eq!(view.len(), 2);
+        assert!(!view.is_empty());
+
+        let mut iter = view.iter::<f32>().unwrap();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some(1.5));
+        assert_eq!(iter.next(), Some(-1.0));
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(view.get::<f32>(1), Ok(Some(-1.0)));
+        assert_eq!(view.get::<f32>(2), Ok(None));
+        assert_eq!(view.iter::<u32>().err(), Some(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_view_clamped() {
+        let bytes = Value::bytes(&[0, 128, 255]);
+        let value = Value::tag(68, &bytes);
+        let view = TypedArrayView::try_from(&value).unwrap();
+
+        assert_eq!(view.element_type(), ElementType::U8Clamped);
+        assert!(view.iter::<u8>().unwrap().eq([0, 128, 255]));
+        assert_eq!(view.iter::<i8>().err(), Some(Error::InvalidType));
+    }
+
+    #[test]
+    fn test_view_round_trip() {
+        let samples = [u64::MAX, 0, 42];
+        let array = TypedArray::<u64, LittleEndian>::new(&samples);
+        let mut buf = [0u8; 32];
+        let size = array.as_cbor(&mut buf).unwrap();
+
+        let bytes = Value::bytes(&buf[4..size]);
+        let value = Value::tag(array.tag(), &bytes);
+        let view = TypedArrayView::try_from(&value).unwrap();
+        assert!(view.iter::<u64>().unwrap().eq(samples));
+        assert!(
+            view.iter::<u64>()
+                .unwrap()
+                .rev()
+                .eq(samples.into_iter().rev())
+        );
+    }
+
+    #[test]
+    fn test_view_invalid() {
+        // Length is not a multiple of the element size
+        let bytes = Value::bytes(&[0x00, 0x01, 0x02]);
+        let value = Value::tag(65, &bytes);
+        assert_eq!(TypedArrayView::try_from(&value), Err(Error::InvalidValue));
+
+        // Not a typed array tag
+        let value = Value::tag(76, &bytes);
+        assert_eq!(TypedArrayView::try_from(&value), Err(Error::InvalidType));
+        let value = Value::tag(2, &bytes);
+        assert_eq!(TypedArrayView::try_from(&value), Err(Error::InvalidType));
+
+        // Payload must be a byte string
+        let text = Value::text("abc");
+        let value = Value::tag(64, &text);
+        assert_eq!(TypedArrayView::try_from(&value), Err(Error::InvalidType));
+    }
+}
@@ -13,7 +13,7 @@
 //! structures in a memory-efficient and type-safe way.
 
 type Array<'a> = &'a [Value<'a>];
-type Map<'a> = &'a [(Value<'a>, Value<'a>)];
+pub(crate) type Map<'a> = &'a [(Value<'a>, Value<'a>)];
 
 /// Represents a CBOR value that can be encoded or decoded.
 ///
@@ -181,6 +181,16 @@ impl<'a> Value<'a> {
         Self::Negative((-(value + 1)) as u64)
     }
 
+    /// Creates a CBOR integer value of either sign, as used for integer labels.
+    #[inline]
+    pub(crate) const fn integer(value: i64) -> Self {
+        if value < 0 {
+            Self::negative(value)
+        } else {
+            Self::Unsigned(value as u64)
+        }
+    }
+
     /// Creates a CBOR byte string value (major type 2).
     ///
     /// Byte strings in CBOR can contain any sequence of bytes and are designed
@@ -427,10 +437,10 @@ mod tests {
 
     #[test]
     fn test_float_construction() {
-        let value = Value::float(3.14159);
+        let value = Value::float(1.1);
         match value {
             Value::Float(f) => {
-                assert!((f - 3.14159).abs() < f64::EPSILON);
+                assert!((f - 1.1).abs() < f64::EPSILON);
             }
             _ => panic!("Expected Value::Float, got {:?}", value),
         }
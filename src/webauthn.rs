@@ -0,0 +1,900 @@
+// SPDX-License-Identifier: MIT
+
+//! WebAuthn attestation objects and authenticator data, as defined by the
+//! [Web Authentication](https://www.w3.org/TR/webauthn-3/) specification.
+//!
+//! When a credential is registered, the authenticator returns an attestation object: a
+//! map of the attestation statement format (`fmt`), the attestation statement
+//! (`attStmt`) and the authenticator data (`authData`). [`AttestationObject`] reads and
+//! writes this map.
+//!
+//! The authenticator data is a byte string with a binary layout: the SHA-256 hash of the
+//! relying party ID, the flags, the signature counter, and optionally the attested
+//! credential data, whose public key is an embedded `COSE_Key`, and a CBOR map of
+//! extension outputs. [`AuthenticatorData`] parses this layout in place.
+//!
+//! [`AttestationObject::statement`] checks the shape of the `packed`, `tpm` and `none`
+//! attestation statements. Verifying the attestation signatures and certificates is
+//! left to the relying party.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use const_cbor::{
+//!     Arena, Value,
+//!     cose::key::{CoseKey, ED25519},
+//!     encode::Encode,
+//!     webauthn::{self, AttestationObject, AttestedCredential, AuthenticatorData, Statement},
+//! };
+//!
+//! let key = CoseKey::okp(ED25519, &[0x5A; 32]);
+//! let credential = AttestedCredential::new(&[0; 16], &[0xC1, 0xC2], key);
+//! let auth_data = AuthenticatorData::new(&[0xAB; 32], webauthn::FLAG_UP, 0)
+//!     .with_attested_credential(credential);
+//! let mut auth_buf = [0u8; 128];
+//! let auth_len = auth_data.write(&mut auth_buf).unwrap();
+//!
+//! let object = AttestationObject::new(webauthn::FMT_NONE, &[], &auth_buf[..auth_len]);
+//! let mut buf = [0u8; 160];
+//! let size = object.as_cbor(&mut buf).unwrap();
+//!
+//! let mut pairs = [(Value::null(), Value::null()); 8];
+//! let mut arena = Arena::new(&mut [], &mut pairs);
+//! let object = AttestationObject::decode(&buf[..size], &mut arena).unwrap();
+//! assert_eq!(object.statement(), Ok(Statement::None));
+//!
+//! let auth_data = AuthenticatorData::decode(object.auth_data(), &mut arena).unwrap();
+//! assert!(auth_data.user_present());
+//! let credential = auth_data.attested_credential().unwrap();
+//! assert_eq!(credential.credential_id(), &[0xC1, 0xC2]);
+//! assert_eq!(credential.public_key(), key);
+//! ```
+
+use crate::{
+    Arena, Value,
+    cose::key::CoseKey,
+    decode::{self, MAX_DEPTH, Reader},
+    encode::{self, Cursor, Encode, encode_value, write_all},
+    error::Error,
+    list::{List, bytes, fields, required, text},
+    result::Result,
+    value::Map,
+};
+
+/// Flag bit 0: The user was present.
+pub const FLAG_UP: u8 = 0x01;
+/// Flag bit 2: The user was verified, such as by a PIN or biometric.
+pub const FLAG_UV: u8 = 0x04;
+/// Flag bit 3: The credential may be backed up.
+pub const FLAG_BE: u8 = 0x08;
+/// Flag bit 4: The credential is backed up.
+pub const FLAG_BS: u8 = 0x10;
+/// Flag bit 6: Attested credential data follows the signature counter.
+pub const FLAG_AT: u8 = 0x40;
+/// Flag bit 7: Extension outputs follow the other authenticator data.
+pub const FLAG_ED: u8 = 0x80;
+
+/// Attestation statement format `packed`.
+pub const FMT_PACKED: &str = "packed";
+/// Attestation statement format `tpm`.
+pub const FMT_TPM: &str = "tpm";
+/// Attestation statement format `none`, for credentials without attestation.
+pub const FMT_NONE: &str = "none";
+
+/// The TPM version that `tpm` attestation statements are defined for.
+pub const TPM_VERSION: &str = "2.0";
+
+/// The longest credential ID that authenticator data may carry.
+pub const MAX_CREDENTIAL_ID_LEN: usize = 1023;
+
+/// The length of the relying party ID hash, flags and signature counter.
+const HEADER_LEN: usize = 37;
+
+/// The attested credential data: the credential created by a registration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttestedCredential<'a> {
+    aaguid: &'a [u8; 16],
+    credential_id: &'a [u8],
+    public_key: CoseKey<'a>,
+}
+
+impl<'a> AttestedCredential<'a> {
+    /// Creates attested credential data.
+    ///
+    /// # Arguments
+    ///
+    /// * `aaguid` - The AAGUID, identifying the authenticator model.
+    /// * `credential_id` - The credential ID, of at most [`MAX_CREDENTIAL_ID_LEN`] bytes.
+    /// * `public_key` - The public key of the credential.
+    #[inline]
+    pub const fn new(
+        aaguid: &'a [u8; 16],
+        credential_id: &'a [u8],
+        public_key: CoseKey<'a>,
+    ) -> Self {
+        Self {
+            aaguid,
+            credential_id,
+            public_key,
+        }
+    }
+
+    /// Returns the AAGUID.
+    #[inline]
+    pub const fn aaguid(&self) -> &'a [u8; 16] {
+        self.aaguid
+    }
+
+    /// Returns the credential ID.
+    #[inline]
+    pub const fn credential_id(&self) -> &'a [u8] {
+        self.credential_id
+    }
+
+    /// Returns the public key of the credential.
+    #[inline]
+    pub const fn public_key(&self) -> CoseKey<'a> {
+        self.public_key
+    }
+
+    /// Calculates the number of bytes needed to encode the attested credential data.
+    fn encoded_size(&self) -> usize {
+        16 + 2 + self.credential_id.len() + self.public_key.encoded_size()
+    }
+
+    /// Writes the AAGUID, the length-prefixed credential ID and the encoded public key.
+    fn write(&self, cursor: &mut Cursor) -> Result<()> {
+        if self.credential_id.len() > MAX_CREDENTIAL_ID_LEN {
+            return Err(Error::InvalidValue);
+        }
+        write_all(cursor, self.aaguid)?;
+        write_all(cursor, &(self.credential_id.len() as u16).to_be_bytes())?;
+        write_all(cursor, self.credential_id)?;
+        let Some(rest) = cursor.data.get_mut(cursor.pos..) else {
+            return Err(Error::BufferOverflow);
+        };
+        cursor.pos += self.public_key.as_cbor(rest)?;
+        Ok(())
+    }
+
+    /// Reads attested credential data, leaving `reader` after the public key.
+    fn read(reader: &mut Reader<'a>, arena: &mut Arena<'a>) -> Result<Self> {
+        let aaguid = reader.read_bytes(16)?;
+        let len = u16::from_be_bytes(reader.read_be()?);
+        if len as usize > MAX_CREDENTIAL_ID_LEN {
+            return Err(Error::InvalidValue);
+        }
+        let credential_id = reader.read_bytes(len as u64)?;
+        let public_key = CoseKey::try_from(&decode_item(reader, arena)?)?;
+
+        Ok(Self {
+            aaguid: aaguid.try_into().map_err(|_| Error::UnexpectedEnd)?,
+            credential_id,
+            public_key,
+        })
+    }
+}
+
+/// Authenticator data, as signed by the authenticator for registrations and assertions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8; 32],
+    flags: u8,
+    sign_count: u32,
+    attested_credential: Option<AttestedCredential<'a>>,
+    extensions: Option<Value<'a>>,
+}
+
+impl<'a> AuthenticatorData<'a> {
+    /// Creates authenticator data.
+    ///
+    /// # Arguments
+    ///
+    /// * `rp_id_hash` - The SHA-256 hash of the relying party ID.
+    /// * `flags` - The flags, such as [`FLAG_UP`]. [`FLAG_AT`] and [`FLAG_ED`] are set
+    ///   when attested credential data and extensions are added.
+    /// * `sign_count` - The signature counter.
+    #[inline]
+    pub const fn new(rp_id_hash: &'a [u8; 32], flags: u8, sign_count: u32) -> Self {
+        Self {
+            rp_id_hash,
+            flags: flags & !(FLAG_AT | FLAG_ED),
+            sign_count,
+            attested_credential: None,
+            extensions: None,
+        }
+    }
+
+    /// Returns the data with the given attested credential data, setting [`FLAG_AT`].
+    #[inline]
+    pub const fn with_attested_credential(self, credential: AttestedCredential<'a>) -> Self {
+        Self {
+            flags: self.flags | FLAG_AT,
+            attested_credential: Some(credential),
+            ..self
+        }
+    }
+
+    /// Returns the data with the given map of extension outputs, setting [`FLAG_ED`].
+    #[inline]
+    pub const fn with_extensions(self, extensions: Value<'a>) -> Self {
+        Self {
+            flags: self.flags | FLAG_ED,
+            extensions: Some(extensions),
+            ..self
+        }
+    }
+
+    /// Returns the SHA-256 hash of the relying party ID.
+    #[inline]
+    pub const fn rp_id_hash(&self) -> &'a [u8; 32] {
+        self.rp_id_hash
+    }
+
+    /// Returns the flags.
+    #[inline]
+    pub const fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns `true` if the user was present.
+    #[inline]
+    pub const fn user_present(&self) -> bool {
+        self.flags & FLAG_UP != 0
+    }
+
+    /// Returns `true` if the user was verified.
+    #[inline]
+    pub const fn user_verified(&self) -> bool {
+        self.flags & FLAG_UV != 0
+    }
+
+    /// Returns `true` if the credential may be backed up.
+    #[inline]
+    pub const fn backup_eligible(&self) -> bool {
+        self.flags & FLAG_BE != 0
+    }
+
+    /// Returns `true` if the credential is backed up.
+    #[inline]
+    pub const fn backed_up(&self) -> bool {
+        self.flags & FLAG_BS != 0
+    }
+
+    /// Returns the signature counter, or 0 if the authenticator does not keep one.
+    #[inline]
+    pub const fn sign_count(&self) -> u32 {
+        self.sign_count
+    }
+
+    /// Returns the attested credential data, present for registrations.
+    #[inline]
+    pub const fn attested_credential(&self) -> Option<AttestedCredential<'a>> {
+        self.attested_credential
+    }
+
+    /// Returns the map of extension outputs.
+    #[inline]
+    pub const fn extensions(&self) -> Option<Value<'a>> {
+        self.extensions
+    }
+
+    /// Parses authenticator data, as found in [`AttestationObject::auth_data`] or in an
+    /// assertion response. The public key and the extensions are decoded into `arena`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AuthenticatorData)` - The data, borrowing from `bytes`.
+    /// * `Err(Error::UnexpectedEnd)` - If the data ends before the parts its flags
+    ///   announce.
+    /// * `Err(Error::InvalidType)` - If the public key is not a `COSE_Key`, or the
+    ///   extensions are not a map.
+    /// * `Err(Error::InvalidValue)` - If the credential ID is too long, or bytes follow
+    ///   the parts the flags announce.
+    /// * `Err(Error)` - If the embedded CBOR is malformed, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let rp_id_hash = reader.read_bytes(32)?;
+        let flags = reader.read_byte()?;
+        let sign_count = u32::from_be_bytes(reader.read_be()?);
+        let attested_credential = match flags & FLAG_AT {
+            0 => None,
+            _ => Some(AttestedCredential::read(&mut reader, arena)?),
+        };
+        let extensions = match flags & FLAG_ED {
+            0 => None,
+            _ => match decode_item(&mut reader, arena)? {
+                extensions @ Value::Map(_) => Some(extensions),
+                _ => return Err(Error::InvalidType),
+            },
+        };
+        if !reader.is_empty() {
+            return Err(Error::InvalidValue);
+        }
+
+        Ok(Self {
+            rp_id_hash: rp_id_hash.try_into().map_err(|_| Error::UnexpectedEnd)?,
+            flags,
+            sign_count,
+            attested_credential,
+            extensions,
+        })
+    }
+
+    /// Calculates the number of bytes needed to encode the data.
+    pub fn encoded_size(&self) -> usize {
+        HEADER_LEN
+            + self
+                .attested_credential
+                .map_or(0, |credential| credential.encoded_size())
+            + self.extensions.as_ref().map_or(0, encode::encoded_size)
+    }
+
+    /// Writes the data in its binary layout. The result is not a CBOR data item, but is
+    /// carried as a byte string in attestation objects.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The number of bytes written.
+    /// * `Err(Error::BufferOverflow)` - If the buffer is too small.
+    /// * `Err(Error::InvalidValue)` - If the credential ID is too long.
+    pub fn write(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut cursor = Cursor::new(buf);
+        write_all(&mut cursor, self.rp_id_hash)?;
+        cursor.write_byte(self.flags)?;
+        write_all(&mut cursor, &self.sign_count.to_be_bytes())?;
+        if let Some(credential) = &self.attested_credential {
+            credential.write(&mut cursor)?;
+        }
+        if let Some(extensions) = &self.extensions {
+            encode_value(extensions, &mut cursor)?;
+        }
+        Ok(cursor.pos)
+    }
+}
+
+/// An attestation object, as returned when a credential is registered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttestationObject<'a> {
+    fmt: &'a str,
+    att_stmt: Map<'a>,
+    auth_data: &'a [u8],
+}
+
+impl<'a> AttestationObject<'a> {
+    /// Creates an attestation object.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmt` - The attestation statement format, such as [`FMT_PACKED`].
+    /// * `att_stmt` - The entries of the attestation statement map.
+    /// * `auth_data` - The encoded [`AuthenticatorData`].
+    #[inline]
+    pub const fn new(fmt: &'a str, att_stmt: Map<'a>, auth_data: &'a [u8]) -> Self {
+        Self {
+            fmt,
+            att_stmt,
+            auth_data,
+        }
+    }
+
+    /// Returns the attestation statement format.
+    #[inline]
+    pub const fn fmt(&self) -> &'a str {
+        self.fmt
+    }
+
+    /// Returns the entries of the attestation statement map.
+    #[inline]
+    pub const fn att_stmt(&self) -> Map<'a> {
+        self.att_stmt
+    }
+
+    /// Returns the encoded authenticator data, which [`AuthenticatorData::decode`]
+    /// parses. Attestation signatures are computed over these bytes followed by the
+    /// hash of the client data.
+    #[inline]
+    pub const fn auth_data(&self) -> &'a [u8] {
+        self.auth_data
+    }
+
+    /// Checks the shape of the attestation statement for its format.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Statement)` - The statement, or [`Statement::Other`] for formats other than
+    ///   `packed`, `tpm` and `none`.
+    /// * `Err(Error::InvalidType)` - If a required field is missing or has the wrong
+    ///   type.
+    /// * `Err(Error::InvalidValue)` - If a `none` statement is not empty, a certificate
+    ///   chain is empty, or a `tpm` statement is not for TPM 2.0.
+    pub fn statement(&self) -> Result<Statement<'a>> {
+        let statement = Value::Map(self.att_stmt);
+        match self.fmt {
+            FMT_NONE if self.att_stmt.is_empty() => Ok(Statement::None),
+            FMT_NONE => Err(Error::InvalidValue),
+            FMT_PACKED => PackedStatement::try_from(&statement).map(Statement::Packed),
+            FMT_TPM => TpmStatement::try_from(&statement).map(Statement::Tpm),
+            _ => Ok(Statement::Other(self.att_stmt)),
+        }
+    }
+
+    /// Decodes an attestation object.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AttestationObject)` - The object, borrowing from `bytes`.
+    /// * `Err(Error)` - If the bytes are not a valid object, or the arena is too small.
+    pub fn decode(bytes: &'a [u8], arena: &mut Arena<'a>) -> Result<Self> {
+        Self::try_from(&decode::decode(bytes, arena)?)
+    }
+
+    /// Calculates the number of bytes needed to encode the object.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        self.with_value(encode::encoded_size)
+    }
+
+    /// Calls `f` with the object as a `Value`, its keys in canonical CTAP2 order.
+    fn with_value<R>(&self, f: impl FnOnce(&Value) -> R) -> R {
+        let entries = [
+            (Value::Text("fmt"), Value::Text(self.fmt)),
+            (Value::Text("attStmt"), Value::Map(self.att_stmt)),
+            (Value::Text("authData"), Value::Bytes(self.auth_data)),
+        ];
+        f(&Value::Map(&entries))
+    }
+}
+
+impl<'a> Encode<'a> for AttestationObject<'_> {
+    /// Encodes the attestation object map.
+    fn as_cbor(&'a self, buf: &'a mut [u8]) -> Result<usize> {
+        self.with_value(|value| encode::encode(value, buf))
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for AttestationObject<'a> {
+    type Error = Error;
+
+    /// Reads an attestation object map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AttestationObject)` - The object.
+    /// * `Err(Error::InvalidType)` - If the value is not a map, or `fmt`, `attStmt` or
+    ///   `authData` is missing or has the wrong type.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let map = fields(value)?;
+        let att_stmt = map.get_str("attStmt").and_then(Value::as_map);
+
+        Ok(Self {
+            fmt: required(text(map.get_str("fmt"))?)?,
+            att_stmt: required(att_stmt)?,
+            auth_data: required(bytes(map.get_str("authData"))?)?,
+        })
+    }
+}
+
+/// An attestation statement, checked for the shape its format requires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statement<'a> {
+    /// A `packed` statement.
+    Packed(PackedStatement<'a>),
+    /// A `tpm` statement.
+    Tpm(TpmStatement<'a>),
+    /// A `none` statement, which is empty.
+    None,
+    /// A statement in another format, whose shape is not checked.
+    Other(Map<'a>),
+}
+
+/// A `packed` attestation statement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PackedStatement<'a> {
+    alg: i64,
+    sig: &'a [u8],
+    x5c: List<'a, &'a [u8]>,
+}
+
+impl<'a> PackedStatement<'a> {
+    /// Returns the COSE algorithm of the signature.
+    #[inline]
+    pub const fn alg(&self) -> i64 {
+        self.alg
+    }
+
+    /// Returns the signature over the authenticator data and the client data hash.
+    #[inline]
+    pub const fn sig(&self) -> &'a [u8] {
+        self.sig
+    }
+
+    /// Returns the DER-encoded attestation certificate followed by its chain, empty for
+    /// self attestation.
+    #[inline]
+    pub const fn x5c(&self) -> List<'a, &'a [u8]> {
+        self.x5c
+    }
+
+    /// Returns `true` for self attestation, signed with the credential's own key.
+    #[inline]
+    pub const fn is_self_attestation(&self) -> bool {
+        self.x5c.is_empty()
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for PackedStatement<'a> {
+    type Error = Error;
+
+    /// Reads a `packed` statement map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PackedStatement)` - The statement.
+    /// * `Err(Error::InvalidType)` - If `alg` or `sig` is missing, or a field has the
+    ///   wrong type.
+    /// * `Err(Error::InvalidValue)` - If `x5c` is present but empty.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let x5c = match value.get_str("x5c") {
+            Some(x5c) => certificates(x5c)?,
+            None => List::default(),
+        };
+
+        Ok(Self {
+            alg: algorithm(value.get_str("alg"))?,
+            sig: required(bytes(value.get_str("sig"))?)?,
+            x5c,
+        })
+    }
+}
+
+/// A `tpm` attestation statement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TpmStatement<'a> {
+    alg: i64,
+    x5c: List<'a, &'a [u8]>,
+    sig: &'a [u8],
+    cert_info: &'a [u8],
+    pub_area: &'a [u8],
+}
+
+impl<'a> TpmStatement<'a> {
+    /// Returns the COSE algorithm of the signature.
+    #[inline]
+    pub const fn alg(&self) -> i64 {
+        self.alg
+    }
+
+    /// Returns the DER-encoded attestation identity key certificate followed by its
+    /// chain.
+    #[inline]
+    pub const fn x5c(&self) -> List<'a, &'a [u8]> {
+        self.x5c
+    }
+
+    /// Returns the signature over `certInfo`.
+    #[inline]
+    pub const fn sig(&self) -> &'a [u8] {
+        self.sig
+    }
+
+    /// Returns the `TPMS_ATTEST` structure that was signed.
+    #[inline]
+    pub const fn cert_info(&self) -> &'a [u8] {
+        self.cert_info
+    }
+
+    /// Returns the `TPMT_PUBLIC` structure of the credential key.
+    #[inline]
+    pub const fn pub_area(&self) -> &'a [u8] {
+        self.pub_area
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for TpmStatement<'a> {
+    type Error = Error;
+
+    /// Reads a `tpm` statement map.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TpmStatement)` - The statement.
+    /// * `Err(Error::InvalidType)` - If a field is missing or has the wrong type.
+    /// * `Err(Error::InvalidValue)` - If `ver` is not `"2.0"`, or `x5c` is empty.
+    fn try_from(value: &Value<'a>) -> Result<Self> {
+        let version = value.get_str("ver").and_then(Value::as_str);
+        match version {
+            Some(TPM_VERSION) => {}
+            Some(_) => return Err(Error::InvalidValue),
+            None => return Err(Error::InvalidType),
+        }
+
+        Ok(Self {
+            alg: algorithm(value.get_str("alg"))?,
+            x5c: certificates(required(value.get_str("x5c"))?)?,
+            sig: required(bytes(value.get_str("sig"))?)?,
+            cert_info: required(bytes(value.get_str("certInfo"))?)?,
+            pub_area: required(bytes(value.get_str("pubArea"))?)?,
+        })
+    }
+}
+
+/// Decodes the data item at the position of `reader` and advances past it.
+fn decode_item<'a>(reader: &mut Reader<'a>, arena: &mut Arena<'a>) -> Result<Value<'a>> {
+    let start = reader.pos;
+    decode::skip(reader, MAX_DEPTH)?;
+    decode::decode(&reader.data[start..reader.pos], arena)
+}
+
+/// Reads a required COSE algorithm field.
+fn algorithm(field: Option<&Value>) -> Result<i64> {
+    match field.and_then(Value::as_i128).map(i64::try_from) {
+        Some(Ok(alg)) => Ok(alg),
+        _ => Err(Error::InvalidType),
+    }
+}
+
+/// Reads a non-empty certificate chain.
+fn certificates<'a>(value: &'a Value<'a>) -> Result<List<'a, &'a [u8]>> {
+    match value.as_array() {
+        Some([]) => Err(Error::InvalidValue),
+        Some(certificates) => Ok(List::Values(certificates)),
+        None => Err(Error::InvalidType),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AttestationObject, AttestedCredential, AuthenticatorData, FLAG_AT, FLAG_BE, FLAG_ED,
+        FLAG_UP, FLAG_UV, FMT_PACKED, FMT_TPM, Statement,
+    };
+    use crate::{
+        Arena, Value, cbor,
+        cose::{
+            self,
+            key::{CoseKey, KeyParameters, Label, P256},
+        },
+        encode::Encode,
+        error::Error,
+    };
+
+    #[test]
+    fn test_authenticator_data() {
+        let key = CoseKey::ec2(P256, &[0x11; 32], &[0x22; 32]).with_alg(cose::ES256);
+        let credential = AttestedCredential::new(&[0xAA; 16], &[0xC0; 4], key);
+        static EXTENSIONS: Value = cbor!({"credProtect": 2});
+        let auth_data = AuthenticatorData::new(&[0xAB; 32], FLAG_UP | FLAG_UV | FLAG_AT, 7)
+            .with_attested_credential(credential)
+            .with_extensions(EXTENSIONS);
+        assert_eq!(auth_data.flags(), FLAG_UP | FLAG_UV | FLAG_AT | FLAG_ED);
+
+        let mut buf = [0u8; 192];
+        let size = auth_data.write(&mut buf).unwrap();
+        assert_eq!(size, auth_data.encoded_size());
+        assert_eq!(&buf[32..37], &[0xC5, 0, 0, 0, 7]);
+        assert_eq!(&buf[37 + 16..37 + 18], &[0, 4]);
+
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = AuthenticatorData::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, auth_data);
+        assert!(decoded.user_present());
+        assert!(decoded.user_verified());
+        assert!(!decoded.backup_eligible());
+        assert!(!decoded.backed_up());
+        assert_eq!(decoded.sign_count(), 7);
+        let credential = decoded.attested_credential().unwrap();
+        assert_eq!(credential.aaguid(), &[0xAA; 16]);
+        assert_eq!(credential.public_key().alg(), Some(Label::Int(cose::ES256)));
+        assert_eq!(
+            decoded.extensions().unwrap().get_str("credProtect"),
+            Some(&Value::unsigned(2))
+        );
+
+        // An assertion carries only the header
+        let assertion = AuthenticatorData::new(&[0xAB; 32], FLAG_UP | FLAG_BE, 8);
+        let mut assertion_buf = [0u8; 37];
+        assert_eq!(assertion.write(&mut assertion_buf), Ok(37));
+        let decoded = AuthenticatorData::decode(&assertion_buf, &mut arena).unwrap();
+        assert!(decoded.backup_eligible());
+        assert_eq!(decoded.attested_credential(), None);
+    }
+
+    #[test]
+    fn test_authenticator_data_errors() {
+        let key = CoseKey::okp(cose::key::ED25519, &[0x33; 32]);
+        let credential = AttestedCredential::new(&[0; 16], &[1], key);
+        let auth_data =
+            AuthenticatorData::new(&[0; 32], FLAG_UP, 0).with_attested_credential(credential);
+        let mut buf = [0u8; 128];
+        let size = auth_data.write(&mut buf).unwrap();
+
+        // Trailing bytes that the flags do not announce
+        let mut trailing = buf;
+        trailing[size] = 0xA0;
+        // Extensions that are not a map
+        let mut not_a_map = buf;
+        not_a_map[32] |= FLAG_ED;
+        not_a_map[size] = 0x01;
+        // A credential ID longer than allowed
+        let mut long_id = [0u8; 55];
+        long_id[32] = FLAG_AT;
+        long_id[53..55].copy_from_slice(&1024u16.to_be_bytes());
+
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        // Truncated before the end of the header, and inside the public key
+        assert_eq!(
+            AuthenticatorData::decode(&buf[..36], &mut arena),
+            Err(Error::UnexpectedEnd)
+        );
+        assert_eq!(
+            AuthenticatorData::decode(&buf[..size - 1], &mut arena),
+            Err(Error::UnexpectedEnd)
+        );
+        assert_eq!(
+            AuthenticatorData::decode(&trailing[..size + 1], &mut arena),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            AuthenticatorData::decode(&not_a_map[..size + 1], &mut arena),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            AuthenticatorData::decode(&long_id, &mut arena),
+            Err(Error::InvalidValue)
+        );
+
+        let long_id = [0u8; 1024];
+        let long = AuthenticatorData::new(&[0; 32], 0, 0)
+            .with_attested_credential(AttestedCredential::new(&[0; 16], &long_id, key));
+        let mut long_buf = [0u8; 1200];
+        assert_eq!(long.write(&mut long_buf), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn test_attestation_object() {
+        let statement = [
+            (Value::text("alg"), Value::integer(cose::ES256)),
+            (Value::text("sig"), Value::bytes(&[0x30, 0x44])),
+        ];
+        let object = AttestationObject::new(FMT_PACKED, &statement, &[0xAB; 37]);
+        let mut buf = [0u8; 96];
+        let size = object.as_cbor(&mut buf).unwrap();
+        assert_eq!(size, object.encoded_size());
+        assert_eq!(&buf[..5], &[0xA3, 0x63, b'f', b'm', b't']);
+
+        let mut pairs = [(Value::null(), Value::null()); 5];
+        let mut arena = Arena::new(&mut [], &mut pairs);
+        let decoded = AttestationObject::decode(&buf[..size], &mut arena).unwrap();
+        assert_eq!(decoded, object);
+        let Ok(Statement::Packed(packed)) = decoded.statement() else {
+            panic!("expected a packed statement");
+        };
+        assert_eq!(packed.alg(), cose::ES256);
+        assert_eq!(packed.sig(), &[0x30, 0x44]);
+        assert!(packed.is_self_attestation());
+
+        static NOT_A_MAP: Value = cbor!(["packed", {}, h ""]);
+        static NO_AUTH_DATA: Value = cbor!({"fmt": "none", "attStmt": {}});
+        static TEXT_AUTH_DATA: Value = cbor!({"fmt": "none", "attStmt": {}, "authData": ""});
+        for value in [&NOT_A_MAP, &NO_AUTH_DATA, &TEXT_AUTH_DATA] {
+            assert_eq!(
+                AttestationObject::try_from(value),
+                Err(Error::InvalidType),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tpm_rsa_credential() {
+        // TPM authenticators commonly register RS256 credentials
+        let modulus = [0xC5; 256];
+        let key = CoseKey::rsa(&modulus, &[0x01, 0x00, 0x01]).with_alg(cose::RS256);
+        let credential = AttestedCredential::new(&[0x08; 16], &[0xC0; 32], key);
+        let auth_data = AuthenticatorData::new(&[0xAB; 32], FLAG_UP | FLAG_UV | FLAG_AT, 0)
+            .with_attested_credential(credential);
+        let mut auth_buf = [0u8; 384];
+        let auth_len = auth_data.write(&mut auth_buf).unwrap();
+
+        static TPM: Value = cbor!({
+            "ver": "2.0",
+            "alg": -257,
+            "x5c": [h "3082"],
+            "sig": h "0102",
+            "certInfo": h "FF544347",
+            "pubArea": h "0001"
+        });
+        let object = AttestationObject::new(FMT_TPM, TPM.as_map().unwrap(), &auth_buf[..auth_len]);
+        let mut buf = [0u8; 512];
+        let size = object.as_cbor(&mut buf).unwrap();
+
+        let mut values = [Value::null(); 4];
+        let mut pairs = [(Value::null(), Value::null()); 16];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let decoded = AttestationObject::decode(&buf[..size], &mut arena).unwrap();
+        let Ok(Statement::Tpm(tpm)) = decoded.statement() else {
+            panic!("expected a tpm statement");
+        };
+        assert_eq!(tpm.alg(), cose::RS256);
+
+        let auth_data = AuthenticatorData::decode(decoded.auth_data(), &mut arena).unwrap();
+        let credential = auth_data.attested_credential().unwrap();
+        assert_eq!(credential.public_key(), key);
+        assert_eq!(
+            credential.public_key().parameters(),
+            KeyParameters::Rsa {
+                n: &modulus,
+                e: &[0x01, 0x00, 0x01]
+            }
+        );
+    }
+
+    #[test]
+    fn test_statements() {
+        static PACKED: Value = cbor!({"alg": -7, "sig": h "3044", "x5c": [h "3082", h "3083"]});
+        static TPM: Value = cbor!({
+            "ver": "2.0",
+            "alg": -257,
+            "x5c": [h "3082"],
+            "sig": h "0102",
+            "certInfo": h "FF544347",
+            "pubArea": h "0023"
+        });
+        static FIDO_U2F: Value = cbor!({"sig": h "01", "x5c": [h "3082"]});
+        static NONE: Value = cbor!({});
+        let statement = |fmt, value: &'static Value<'static>| {
+            AttestationObject::new(fmt, value.as_map().unwrap(), &[]).statement()
+        };
+
+        let Ok(Statement::Packed(packed)) = statement(FMT_PACKED, &PACKED) else {
+            panic!("expected a packed statement");
+        };
+        assert!(!packed.is_self_attestation());
+        assert_eq!(packed.x5c().get(1), Some(Ok(&[0x30, 0x83][..])));
+
+        let Ok(Statement::Tpm(tpm)) = statement(FMT_TPM, &TPM) else {
+            panic!("expected a tpm statement");
+        };
+        assert_eq!(tpm.alg(), -257);
+        assert_eq!(tpm.x5c().len(), 1);
+        assert_eq!(tpm.sig(), &[0x01, 0x02]);
+        assert_eq!(tpm.cert_info(), &[0xFF, 0x54, 0x43, 0x47]);
+        assert_eq!(tpm.pub_area(), &[0x00, 0x23]);
+
+        assert_eq!(statement("none", &NONE), Ok(Statement::None));
+        assert_eq!(
+            statement("fido-u2f", &FIDO_U2F),
+            Ok(Statement::Other(FIDO_U2F.as_map().unwrap()))
+        );
+
+        static PACKED_NO_SIG: Value = cbor!({"alg": -7});
+        static PACKED_TEXT_ALG: Value = cbor!({"alg": "ES256", "sig": h "30"});
+        static TPM_NO_X5C: Value = cbor!({
+            "ver": "2.0", "alg": -257, "sig": h "01", "certInfo": h "02", "pubArea": h "03"
+        });
+        assert_eq!(
+            statement(FMT_PACKED, &PACKED_NO_SIG),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            statement(FMT_PACKED, &PACKED_TEXT_ALG),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(statement(FMT_TPM, &TPM_NO_X5C), Err(Error::InvalidType));
+
+        static PACKED_EMPTY_X5C: Value = cbor!({"alg": -7, "sig": h "30", "x5c": []});
+        static TPM_VERSION_1: Value = cbor!({
+            "ver": "1.2", "alg": -257, "x5c": [h "30"], "sig": h "01", "certInfo": h "02",
+            "pubArea": h "03"
+        });
+        assert_eq!(
+            statement(FMT_PACKED, &PACKED_EMPTY_X5C),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(statement(FMT_TPM, &TPM_VERSION_1), Err(Error::InvalidValue));
+        assert_eq!(statement("none", &PACKED), Err(Error::InvalidValue));
+    }
+}
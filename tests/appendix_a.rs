@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MIT
+
+//! Conformance tests against the examples in RFC 8949 Appendix A.
+//!
+//! The vectors in `vectors/appendix_a.json` are vendored from
+//! <https://github.com/cbor/test-vectors>. Each one holds the encoded bytes along with
+//! either the decoded value as JSON or, where JSON cannot express it, the expected
+//! diagnostic notation, which is also parsed back.
+
+use const_cbor::{
+    Arena, Value,
+    decode::decode,
+    diag::{parse, write_encoded},
+    encode::{encode, encoded_size},
+    error::Error,
+    tag::Bignum,
+};
+use serde_json::Value as Json;
+
+const VECTORS: &str = include_str!("vectors/appendix_a.json");
+
+/// Vectors using indefinite-length items, which the decoder does not support yet.
+///
+/// These are valid CBOR, so decoding them is skipped as a known gap rather than checked.
+/// They are still printed through [`write_encoded`], which accepts them.
+const INDEFINITE_LENGTH: &[&str] = &[
+    "5f42010243030405ff",
+    "7f657374726561646d696e67ff",
+    "9fff",
+    "9f018202039f0405ffff",
+    "9f01820203820405ff",
+    "83018202039f0405ff",
+    "83019f0203ff820405",
+    "9f0102030405060708090a0b0c0d0e0f101112131415161718181819ff",
+    "bf61610161629f0203ffff",
+    "826161bf61626163ff",
+    "bf6346756ef563416d7421ff",
+];
+
+/// Vectors that RFC 8949 no longer considers well-formed.
+///
+/// The vendored file predates RFC 8949, which requires simple values below 32 to use the
+/// one-byte encoding (section 3.3).
+const NOT_WELL_FORMED: &[&str] = &["f818"];
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Returns the value of an integer or bignum as decimal text.
+fn integer_text(value: &Value) -> Option<String> {
+    match *value {
+        Value::Unsigned(n) => Some(n.to_string()),
+        Value::Negative(n) => Some((-1 - n as i128).to_string()),
+        Value::Tag(..) => Bignum::try_from(value)
+            .ok()?
+            .to_i128()
+            .map(|n| n.to_string()),
+        _ => None,
+    }
+}
+
+/// Checks a decoded value against the JSON form of the expected value.
+fn matches_json(value: &Value, expected: &Json) -> bool {
+    match (value, expected) {
+        (Value::Simple(20), Json::Bool(false)) => true,
+        (Value::Simple(21), Json::Bool(true)) => true,
+        (Value::Simple(22), Json::Null) => true,
+        (Value::Text(text), Json::String(expected)) => text == expected,
+        (Value::Float(f), Json::Number(n)) => {
+            n.as_f64().map(f64::to_bits) == Some(f.to_bits())
+                && n.to_string().contains(['.', 'e', 'E'])
+        }
+        (_, Json::Number(n)) => integer_text(value) == Some(n.to_string()),
+        (Value::Array(items), Json::Array(expected)) => {
+            items.len() == expected.len()
+                && items.iter().zip(expected).all(|(v, e)| matches_json(v, e))
+        }
+        (Value::Map(pairs), Json::Object(expected)) => {
+            pairs.len() == expected.len()
+                && pairs.iter().all(|(key, value)| match key {
+                    Value::Text(key) => expected
+                        .get(*key)
+                        .is_some_and(|expected| matches_json(value, expected)),
+                    _ => false,
+                })
+        }
+        _ => false,
+    }
+}
+
+fn contains_float(value: &Value) -> bool {
+    match value {
+        Value::Float(_) => true,
+        Value::Array(items) => items.iter().any(contains_float),
+        Value::Map(pairs) => pairs
+            .iter()
+            .any(|(key, value)| contains_float(key) || contains_float(value)),
+        Value::Tag(_, item) => contains_float(item),
+        _ => false,
+    }
+}
+
+/// Runs one vector, returning a description of the first mismatch.
+fn check_vector(vector: &Json) -> Result<(), String> {
+    let hex = vector["hex"].as_str().unwrap();
+    let bytes = from_hex(hex);
+
+    if INDEFINITE_LENGTH.contains(&hex) {
+        let mut text = String::new();
+        write_encoded(&bytes, &mut text).map_err(|err| format!("print failed: {:?}", err))?;
+        return match vector.get("diagnostic").and_then(Json::as_str) {
+            Some(expected) if text != expected => {
+                Err(format!("printed {} but expected {}", text, expected))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let mut values = [Value::null(); 32];
+    let mut pairs = [(Value::null(), Value::null()); 8];
+    let mut arena = Arena::new(&mut values, &mut pairs);
+    let result = decode(&bytes, &mut arena);
+
+    if NOT_WELL_FORMED.contains(&hex) {
+        let printed = write_encoded(&bytes, &mut String::new());
+        return match (result, printed) {
+            (Err(Error::InvalidValue), Err(Error::InvalidValue)) => Ok(()),
+            other => Err(format!("expected rejection, got {:?}", other)),
+        };
+    }
+
+    let value = result.map_err(|err| format!("decode failed: {:?}", err))?;
+
+    if let Some(expected) = vector.get("decoded") {
+        if !matches_json(&value, expected) {
+            return Err(format!("decoded {} but expected {}", value, expected));
+        }
+    }
+    if let Some(expected) = vector.get("diagnostic").and_then(Json::as_str) {
+        let actual = value.to_string();
+        if actual != expected {
+            return Err(format!("diagnostic {} but expected {}", actual, expected));
+        }
+
+        let mut storage = [0u8; 64];
+        let mut values = [Value::null(); 32];
+        let mut pairs = [(Value::null(), Value::null()); 8];
+        let mut arena = Arena::new(&mut values, &mut pairs);
+        let parsed = parse(expected, &mut storage, &mut arena)
+            .map_err(|err| format!("parse failed: {:?}", err))?;
+        if parsed.to_string() != actual {
+            return Err(format!("parsed {} but decoded {}", parsed, actual));
+        }
+    }
+
+    // Floats are always encoded in double precision, so only their value survives
+    let mut buf = [0u8; 64];
+    let size = encode(&value, &mut buf).map_err(|err| format!("encode failed: {:?}", err))?;
+    if size != encoded_size(&value) {
+        return Err(format!(
+            "encoded {} bytes but encoded_size is {}",
+            size,
+            encoded_size(&value)
+        ));
+    }
+    if contains_float(&value) {
+        let mut values = [Value::null(); 32];
+        let mut arena = Arena::new(&mut values, &mut []);
+        let again = decode(&buf[..size], &mut arena)
+            .map_err(|err| format!("re-decode failed: {:?}", err))?;
+        if again.to_string() != value.to_string() {
+            return Err(format!("re-decoded {}", again));
+        }
+    } else if vector["roundtrip"] == Json::Bool(true) && buf[..size] != bytes[..] {
+        return Err(format!("encoded as {:02x?}", &buf[..size]));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_appendix_a() {
+    let vectors: Vec<Json> = serde_json::from_str(VECTORS).unwrap();
+    assert_eq!(vectors.len(), 82);
+
+    let failures: Vec<String> = vectors
+        .iter()
+        .filter_map(|vector| {
+            check_vector(vector)
+                .err()
+                .map(|err| format!("{}: {}", vector["hex"], err))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} failing vectors:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_edge_cases() {
+    // -2^64 is the most negative integer that fits in major type 1
+    let value = Value::Negative(u64::MAX);
+    assert_eq!(integer_text(&value).unwrap(), "-18446744073709551616");
+
+    let mut buf = [0u8; 9];
+    assert_eq!(encode(&value, &mut buf), Ok(9));
+    assert_eq!(buf, [0x3B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+
+    // The smallest half-precision subnormal and the largest half-precision normal
+    let mut arena = Arena::new(&mut [], &mut []);
+    assert_eq!(
+        decode(&[0xF9, 0x00, 0x01], &mut arena),
+        Ok(Value::float(2f64.powi(-24)))
+    );
+    let mut arena = Arena::new(&mut [], &mut []);
+    assert_eq!(
+        decode(&[0xF9, 0x7B, 0xFF], &mut arena),
+        Ok(Value::float(65504.0))
+    );
+}
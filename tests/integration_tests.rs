@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MIT
 
-use const_cbor::{Value, encode};
+use const_cbor::{Value, cbor, encode};
 
 // Integration test that creates a complex nested structure and encodes it
 #[test]
@@ -52,6 +52,24 @@ fn test_complex_document() {
 
     // We don't check the exact bytes here since that would be very verbose,
     // but in a real application you might decode it back and verify the content
+
+    // The same document written inline, without the temporaries
+    static WRITTEN: Value = cbor!({
+        "name": "Alex Smith",
+        "age": 42,
+        "active": true,
+        "address": {
+            "street": "123 Main St",
+            "city": "Techville",
+            "postal_code": "12345",
+        },
+        "tags": ["rust", "cbor", "const"],
+        "notes": null,
+        "scores": [],
+        "balance": 123.45,
+        "created_at": 0("2024-05-20T10:30:00Z"),
+    });
+    assert_eq!(WRITTEN, document);
 }
 
 // Test that encoding and size calculation handle empty/edge cases correctly
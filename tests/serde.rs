@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT
+
+#![cfg(feature = "serde")]
+
+use const_cbor::{Value, de::from_slice, encode::encode, error::Error, ser::to_slice};
+use serde::{Deserialize, Serialize};
+
+/// Serializes `value` and checks the output against the encoding of `expected`.
+fn check<T: Serialize>(value: T, expected: Value) {
+    let mut expected_buf = [0u8; 128];
+    let size = encode(&expected, &mut expected_buf).unwrap();
+
+    let mut buf = [0u8; 128];
+    assert_eq!(to_slice(&value, &mut buf), Ok(size));
+    assert_eq!(&buf[..size], &expected_buf[..size]);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Meters(u32);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Point(i8, i8);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Device<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    serial: Option<u32>,
+    ports: [u16; 2],
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Command {
+    Reset,
+    Sleep(u16),
+    Move(i8, i8),
+    Configure { id: u8 },
+}
+
+#[test]
+fn test_serialize_structs() {
+    check(Unit, Value::null());
+    check(Meters(10), Value::unsigned(10));
+
+    let coordinates = [Value::unsigned(1), Value::negative(-1)];
+    check(Point(1, -1), Value::array(&coordinates));
+
+    let ports = [Value::unsigned(80), Value::unsigned(443)];
+    let pairs = [
+        (Value::text("name"), Value::text("eth0")),
+        (Value::text("ports"), Value::array(&ports)),
+    ];
+    let device = Device {
+        name: "eth0",
+        serial: None,
+        ports: [80, 443],
+    };
+    check(device, Value::map(&pairs));
+
+    let pairs = [
+        (Value::text("name"), Value::text("eth0")),
+        (Value::text("serial"), Value::unsigned(9)),
+        (Value::text("ports"), Value::array(&ports)),
+    ];
+    let device = Device {
+        name: "eth0",
+        serial: Some(9),
+        ports: [80, 443],
+    };
+    check(device, Value::map(&pairs));
+}
+
+#[test]
+fn test_serialize_enums() {
+    check(Command::Reset, Value::text("Reset"));
+
+    let sleep = [(Value::text("Sleep"), Value::unsigned(100))];
+    check(Command::Sleep(100), Value::map(&sleep));
+
+    let offsets = [Value::unsigned(1), Value::negative(-2)];
+    let moved = [(Value::text("Move"), Value::array(&offsets))];
+    check(Command::Move(1, -2), Value::map(&moved));
+
+    let fields = [(Value::text("id"), Value::unsigned(4))];
+    let content = Value::map(&fields);
+    let configure = [(Value::text("Configure"), content)];
+    check(Command::Configure { id: 4 }, Value::map(&configure));
+}
+
+/// Serializes `value`, then checks that it deserializes back to an equal value.
+fn round_trip<'de, T>(value: &T, buf: &'de mut [u8]) -> T
+where
+    T: Serialize + Deserialize<'de> + core::fmt::Debug + PartialEq,
+{
+    let size = to_slice(value, buf).unwrap();
+    let decoded: T = from_slice(&buf[..size]).unwrap();
+    assert_eq!(&decoded, value);
+    decoded
+}
+
+#[test]
+fn test_round_trip() {
+    let mut buf = [0u8; 64];
+    let _ = round_trip(&Unit, &mut buf);
+    let _ = round_trip(&Meters(70_000), &mut buf);
+    let _ = round_trip(&Point(-128, 127), &mut buf);
+
+    for command in [
+        Command::Reset,
+        Command::Sleep(100),
+        Command::Move(1, -2),
+        Command::Configure { id: 4 },
+    ] {
+        let _ = round_trip(&command, &mut buf);
+    }
+
+    let device = Device {
+        name: "eth0",
+        serial: None,
+        ports: [80, 443],
+    };
+    let _ = round_trip(&device, &mut buf);
+}
+
+#[test]
+fn test_deserialize_mismatch() {
+    // A unit variant written as a map, and a newtype variant written as text
+    let mut buf = [0u8; 32];
+    let reset = [(Value::text("Reset"), Value::unsigned(1))];
+    let size = encode(&Value::map(&reset), &mut buf).unwrap();
+    assert_eq!(from_slice::<Command>(&buf[..size]), Err(Error::InvalidType));
+    assert_eq!(
+        from_slice::<Command>(&[0x65, b'S', b'l', b'e', b'e', b'p']),
+        Err(Error::InvalidType)
+    );
+
+    // Unknown fields are skipped, missing ones are rejected
+    let pairs = [
+        (Value::text("name"), Value::text("eth0")),
+        (Value::text("extra"), Value::bool(true)),
+    ];
+    let size = encode(&Value::map(&pairs), &mut buf).unwrap();
+    assert_eq!(from_slice::<Device>(&buf[..size]), Err(Error::InvalidValue));
+}